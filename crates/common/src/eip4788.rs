@@ -0,0 +1,112 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers for the EIP-4788 beacon roots contract, which destinations verify beacon commitments
+//! against.
+//!
+//! The contract stores the parent beacon block root of each execution block under the timestamp of
+//! that block, in a ring buffer of [HISTORY_BUFFER_LENGTH] entries. The beacon root of a block is
+//! therefore keyed by the timestamp of its child, and a commitment to it can only be verified
+//! until the entry of that timestamp is overwritten.
+
+use alloy_primitives::{Address, U256, address};
+use risc0_steel::Commitment;
+
+/// Address of the beacon roots contract, the same on every chain supporting EIP-4788.
+pub const BEACON_ROOTS_ADDRESS: Address = address!("0x000F3df6D732807Ef1319fB7B8bB8522d0Beac02");
+
+/// Number of entries in the ring buffer of the beacon roots contract.
+pub const HISTORY_BUFFER_LENGTH: u64 = 8191;
+
+/// Time between beacon chain slots in seconds.
+pub const SECONDS_PER_SLOT: u64 = 12;
+
+/// Version of Steel commitments to a block by its beacon block root.
+const BEACON_COMMITMENT_VERSION: u16 = 1;
+
+/// The timestamp the beacon root of a block with `block_timestamp` is keyed by, which is the
+/// timestamp of its child. Predicted as the next slot, so it is off if that slot is missed.
+pub fn timestamp_key(block_timestamp: u64) -> u64 {
+    block_timestamp + SECONDS_PER_SLOT
+}
+
+/// Index of the ring buffer entry holding the beacon root keyed by `timestamp`.
+pub fn ring_index(timestamp: u64) -> u64 {
+    timestamp % HISTORY_BUFFER_LENGTH
+}
+
+/// Storage slots of the timestamp and of the beacon root of the ring buffer entry of `timestamp`.
+pub fn storage_slots(timestamp: u64) -> (U256, U256) {
+    let index = ring_index(timestamp);
+    (U256::from(index), U256::from(index + HISTORY_BUFFER_LENGTH))
+}
+
+/// The time from which the beacon root keyed by `timestamp` may be overwritten, which is when the
+/// next slot with the same ring buffer index begins.
+pub fn expires_at(timestamp: u64) -> u64 {
+    timestamp + HISTORY_BUFFER_LENGTH * SECONDS_PER_SLOT
+}
+
+/// Whether the beacon root keyed by `timestamp` may have been overwritten at `now`.
+pub fn is_expired(timestamp: u64, now: u64) -> bool {
+    now >= expires_at(timestamp)
+}
+
+/// The timestamp key of `commitment` if it is a beacon commitment.
+pub fn commitment_timestamp(commitment: &Commitment) -> Option<u64> {
+    let (claim, version) = commitment.decode_id();
+    if version != BEACON_COMMITMENT_VERSION {
+        return None;
+    }
+    u64::try_from(claim).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::B256;
+
+    #[test]
+    fn maps_timestamps_to_ring_buffer() {
+        let key = timestamp_key(1_741_083_119);
+        assert_eq!(key, 1_741_083_131);
+        assert_eq!(ring_index(key), key % 8191);
+        assert_eq!(
+            storage_slots(key),
+            (
+                U256::from(ring_index(key)),
+                U256::from(ring_index(key) + 8191)
+            )
+        );
+
+        // The entry is reused after a full turn of the ring buffer, and not before
+        assert_eq!(ring_index(expires_at(key)), ring_index(key));
+        assert!(!is_expired(key, expires_at(key) - 1));
+        assert!(is_expired(key, expires_at(key)));
+    }
+
+    #[test]
+    fn decodes_beacon_commitments() {
+        let commitment = |version: u16, claim: u64| Commitment {
+            id: (U256::from(version) << 240) | U256::from(claim),
+            digest: B256::ZERO,
+            configID: B256::ZERO,
+        };
+        assert_eq!(
+            commitment_timestamp(&commitment(1, 1_741_083_131)),
+            Some(1_741_083_131)
+        );
+        assert_eq!(commitment_timestamp(&commitment(0, 22_000_000)), None);
+    }
+}
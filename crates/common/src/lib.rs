@@ -12,38 +12,261 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloy_primitives::{Address, B256, Bytes};
-use alloy_sol_types::sol;
+use alloy_primitives::{Address, B256, Bytes, keccak256};
+use alloy_sol_types::{SolValue, sol};
 use risc0_steel::{Commitment, ethereum::EthEvmInput};
 
+/// Expands to `sol!`, generating contract instances for interfaces when the `rpc` feature is
+/// enabled. Guests build without it since the instances pull in `alloy-contract`.
+macro_rules! rpc_sol {
+    ($($body:tt)*) => {
+        #[cfg(feature = "rpc")]
+        ::alloy_sol_types::sol! {
+            #![sol(rpc)]
+            $($body)*
+        }
+        #[cfg(not(feature = "rpc"))]
+        ::alloy_sol_types::sol! {
+            $($body)*
+        }
+    };
+}
+
+pub mod eip4788;
+pub mod merkle;
+pub mod ntt;
+
+/// Maximum size in bytes of an encoded TransceiverMessage that the guest will attest to.
+/// NTT transfers are a few hundred bytes; this bound stops adversarially large messages from
+/// inflating proving cost or destination calldata.
+pub const MAX_ENCODED_MESSAGE_SIZE: usize = 8192;
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct GuestInput {
     pub commitment: EthEvmInput,
+    pub claims: Vec<MessageClaim>,
+    /// Chain ID of the source chain. Required by the multichain guest, which selects its chain
+    /// spec from it, and ignored by guests built for a fixed chain.
+    pub chain_id: Option<u64>,
+}
+
+/// A message the guest must find in the logs of the committed block.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MessageClaim {
     pub encoded_message: Bytes,
     pub contract_addr: B256,
 }
 
 impl GuestInput {
+    /// Builds an input claiming a single message. The guest commits a plain [Journal] for this
+    /// shape, which is what the BoundlessTransceiver contract accepts. Inputs with several claims
+    /// commit a [BatchJournal] instead.
+    pub fn single(commitment: EthEvmInput, encoded_message: Bytes, contract_addr: B256) -> Self {
+        Self {
+            commitment,
+            claims: vec![MessageClaim {
+                encoded_message,
+                contract_addr,
+            }],
+            chain_id: None,
+        }
+    }
+
+    pub fn with_chain_id(mut self, chain_id: u64) -> Self {
+        self.chain_id = Some(chain_id);
+        self
+    }
+
+    /// Frames the input for the guest: the bincode encoded commitment, which Steel only supports
+    /// through serde, prefixed with its length, then the claims and the chain ID laid out directly
+    /// so the guest reads them by slicing the frame rather than through bincode.
     pub fn serialize(&self) -> Result<Vec<u8>, String> {
-        bincode::serialize(self).map_err(|e| format!("Failed to serialize GuestInput: {e}"))
+        let commitment = bincode::serialize(&self.commitment)
+            .map_err(|e| format!("Failed to serialize GuestInput: {e}"))?;
+        let claims_len: usize = self
+            .claims
+            .iter()
+            .map(|claim| 4 + claim.encoded_message.len() + 32)
+            .sum();
+        let mut data = Vec::with_capacity(4 + commitment.len() + 4 + claims_len + 9);
+        put_len(&mut data, commitment.len())?;
+        data.extend_from_slice(&commitment);
+        put_len(&mut data, self.claims.len())?;
+        for claim in &self.claims {
+            put_len(&mut data, claim.encoded_message.len())?;
+            data.extend_from_slice(&claim.encoded_message);
+            data.extend_from_slice(claim.contract_addr.as_slice());
+        }
+        match self.chain_id {
+            Some(chain_id) => {
+                data.push(1);
+                data.extend_from_slice(&chain_id.to_le_bytes());
+            }
+            None => data.push(0),
+        }
+        Ok(data)
     }
 
+    /// Reads an input framed by [GuestInput::serialize].
     pub fn deserialize(data: &[u8]) -> Result<Self, String> {
-        bincode::deserialize(data).map_err(|e| format!("Failed to deserialize GuestInput: {e}"))
+        let mut frame = Frame(data);
+        let commitment_len = frame.len()?;
+        let commitment = bincode::deserialize(frame.take(commitment_len)?)
+            .map_err(|e| format!("Failed to deserialize GuestInput: {e}"))?;
+        let claims = (0..frame.len()?)
+            .map(|_| {
+                let message_len = frame.len()?;
+                Ok(MessageClaim {
+                    encoded_message: Bytes::copy_from_slice(frame.take(message_len)?),
+                    contract_addr: B256::from_slice(frame.take(32)?),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        let chain_id = match frame.take(1)? {
+            [0] => None,
+            [1] => Some(u64::from_le_bytes(frame.take(8)?.try_into().unwrap())),
+            _ => return Err("Failed to deserialize GuestInput: invalid chain ID tag".to_string()),
+        };
+        if !frame.0.is_empty() {
+            return Err("Failed to deserialize GuestInput: trailing bytes".to_string());
+        }
+        Ok(Self {
+            commitment,
+            claims,
+            chain_id,
+        })
     }
 }
 
-sol! {
+/// Appends `len` as the little-endian `u32` prefixing a field of a framed [GuestInput].
+fn put_len(data: &mut Vec<u8>, len: usize) -> Result<(), String> {
+    let len = u32::try_from(len).map_err(|_| format!("GuestInput field of {len} bytes"))?;
+    data.extend_from_slice(&len.to_le_bytes());
+    Ok(())
+}
+
+/// The unread rest of a framed [GuestInput].
+struct Frame<'a>(&'a [u8]);
+
+impl<'a> Frame<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("Failed to deserialize GuestInput: unexpected end of input".to_string());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let len = self.take(4)?.try_into().unwrap();
+        Ok(u32::from_le_bytes(len) as usize)
+    }
+}
+
+/// Input of the generic event inclusion guest, claiming an event with the given signature and
+/// ABI encoded data was emitted by a contract in the committed block.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct EventInput {
+    pub commitment: EthEvmInput,
+    pub emitter_contract: B256,
+    /// topic0 of the event, which the guest checks against the event type it was built for.
+    pub event_signature: B256,
+    /// The non-indexed fields of the event as they appear in the log data.
+    pub data: Bytes,
+}
+
+impl EventInput {
+    pub fn serialize(&self) -> Result<Vec<u8>, String> {
+        bincode::serialize(self).map_err(|e| format!("Failed to serialize EventInput: {e}"))
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, String> {
+        bincode::deserialize(data).map_err(|e| format!("Failed to deserialize EventInput: {e}"))
+    }
+}
+
+/// Input of the block range guest, claiming a message was emitted in one of a contiguous range of
+/// blocks, for when the exact block is unknown or only an upper bound matters.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct RangeInput {
+    /// The newest block of the range, whose commitment the guest commits.
+    pub commitment: EthEvmInput,
+    /// The older blocks of the range, newest first, each the parent of the block before it.
+    pub ancestors: Vec<EthEvmInput>,
+    pub claim: MessageClaim,
+}
+
+impl RangeInput {
+    pub fn serialize(&self) -> Result<Vec<u8>, String> {
+        bincode::serialize(self).map_err(|e| format!("Failed to serialize RangeInput: {e}"))
+    }
+
+    pub fn deserialize(data: &[u8]) -> Result<Self, String> {
+        bincode::deserialize(data).map_err(|e| format!("Failed to deserialize RangeInput: {e}"))
+    }
+}
+
+rpc_sol! {
+    /// Bindings for the BoundlessTransceiver contract, shared by the guest, the host library and
+    /// the relay.
     interface IBoundlessTransceiver {
+      /// @notice Configuration of a source chain the transceiver accepts messages from.
+      struct AuthorizedSource {
+          bytes32 transceiverContract;
+          address commitmentValidator;
+          bytes32 imageID;
+      }
+
       /// @notice Emitted when a message is sent from this transceiver.
       /// @param recipientChain The chain ID of the recipient.
       /// @param encoded_message The encoded TransceiverMessage.
       event SendTransceiverMessage(
-          uint16 recipientChain, bytes encodedMessage
+          uint16 indexed recipientChain, bytes encodedMessage
       );
+
+      error UnsupportedSourceChain(uint256 chainId);
+      error InvalidCommitment();
+      error InvalidEmitter();
+
+      /// @notice Process a message along with its ZK proof of inclusion in the origin chain
+      /// @param journalData The journal data that the proof commits to
+      /// @param seal The opaque ZK proof seal that allows it to be verified on-chain
+      /// @dev This function verifies the ZK proof, checks the commitments, then forwards the message to the NTT Manager.
+      function receiveMessage(
+          bytes calldata journalData, bytes calldata seal
+      ) external;
+
+      function setAuthorizedSource(
+          uint16 chainId, bytes32 transceiverContract, address validator, bytes32 imageID
+      ) external;
+
+      function getTransceiverType() external view returns (string memory);
+
+      function authorizedSources(uint16 chainId) external view returns (AuthorizedSource memory);
+
+      function nttManager() external view returns (address);
+
+      address public verifier;
+    }
+}
+
+rpc_sol! {
+    /// Bindings for the contracts a BoundlessTransceiver validates the commitments of a source
+    /// chain with, such as the BlockRootOracle.
+    interface ICommitmentValidator {
+      /// @notice Validates a Steel commitment. Only supports v2 commitments which identify the
+      /// beacon block root by its slot
+      function validateCommitment(
+          Commitment commitment, uint16 confirmationLevel
+      ) external view returns (bool);
     }
 }
 
+/// Confirmation level requiring a block root to be confirmed by both a Boundless proof and
+/// Wormhole, which the BoundlessTransceiver validates commitments with.
+pub const TWO_OF_TWO_FLAG: u16 = 0b11;
+
 sol! {
     /// @notice Journal that is committed to by the guest.
     struct Journal {
@@ -59,6 +282,414 @@ sol! {
     }
 }
 
+sol! {
+    /// @notice Journal of a single message in a versioned format. Its encoding is prefixed with
+    /// @notice [JOURNAL_V2_TAG], telling it apart from the unversioned v1 [Journal], and then
+    /// @notice starts with the version of its format, telling it apart from later versions.
+    struct JournalV2 {
+        // Version of the journal format, always 2
+        uint16 version;
+
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // Number of the execution block the message event was emitted in, which the commitment
+        // only identifies indirectly, e.g. through the beacon block root
+        uint64 blockNumber;
+
+        // Hash of the execution block the message event was emitted in
+        bytes32 blockHash;
+
+        // The encoded TransceiverMessage that this proof commits to
+        bytes encodedMessage;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+    }
+}
+
+/// Version of [JournalV2]. The unversioned [Journal] is version 1.
+pub const JOURNAL_V2: u16 = 2;
+
+/// Tag the encoding of a [JournalV2] starts with, `bytes4(keccak256("JournalV2"))`. The ABI
+/// encoding of an unversioned [Journal] starts with the offset of its struct, whose first four
+/// bytes are zero, so the tag tells the two apart whatever they commit to.
+pub const JOURNAL_V2_TAG: [u8; 4] = [0x00, 0xff, 0xb5, 0xc4];
+
+sol! {
+    /// @notice A single message attested to by a batched guest execution.
+    struct BatchedMessage {
+        // The encoded TransceiverMessage that this proof commits to
+        bytes encodedMessage;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+    }
+
+    /// @notice Journal committed to by the guest when attesting to several messages at once.
+    struct BatchJournal {
+        // Commitment shared by every message in the batch
+        Commitment commitment;
+
+        // The messages that this proof commits to, in input order
+        BatchedMessage[] messages;
+    }
+}
+
+sol! {
+    /// @notice Journal committed to by the guest in hash commit mode, where only the digest of
+    /// @notice the message is committed and the message itself is supplied alongside the proof.
+    struct DigestJournal {
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // keccak256 of the encoded TransceiverMessage that this proof commits to
+        bytes32 messageDigest;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+    }
+}
+
+sol! {
+    /// @notice Journal committed to by the multichain guest, which serves several source chains
+    /// @notice with a single image ID and therefore commits to the chain it proved against.
+    struct ChainJournal {
+        // Chain ID of the source chain whose chain spec was used to verify the commitment
+        uint64 chainId;
+
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // The encoded TransceiverMessage that this proof commits to
+        bytes encodedMessage;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+    }
+}
+
+sol! {
+    /// @notice Journal committed to by the generic event inclusion guest, attesting that an
+    /// @notice event was emitted by a contract regardless of the protocol it belongs to.
+    struct EventJournal {
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // The contract that emitted the event
+        bytes32 emitterContract;
+
+        // topic0 of the event, i.e. the keccak256 hash of its signature
+        bytes32 eventSignature;
+
+        // The ABI encoded non-indexed fields of the event
+        bytes data;
+    }
+}
+
+sol! {
+    /// @notice State of the NTT manager that sent a message, read in the block the message was
+    /// @notice emitted in.
+    struct ManagerState {
+        // The NTT manager named as the source of the message
+        address nttManager;
+
+        // Sequence number the next message sent by the manager will carry
+        uint64 nextMessageSequence;
+
+        // Amount the manager could still send before hitting its outbound rate limit
+        uint256 currentOutboundCapacity;
+    }
+
+    /// @notice Journal committed to by the manager state guest, attesting to a message along with
+    /// @notice the state of the NTT manager that sent it.
+    struct StateJournal {
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // The encoded TransceiverMessage that this proof commits to
+        bytes encodedMessage;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+
+        // State of the source NTT manager of the message at the end of the block
+        ManagerState managerState;
+    }
+}
+
+sol! {
+    /// @notice The token holdings backing a transfer, read at the end of the block the transfer
+    /// @notice was sent in.
+    struct TokenBacking {
+        // The token of the NTT manager that sent the transfer
+        address token;
+
+        // Mode of the NTT manager: 0 when it locks the tokens it sends, 1 when it burns them
+        uint8 mode;
+
+        // Token balance of the NTT manager, which holds the tokens locked in locking mode
+        uint256 managerBalance;
+
+        // Total supply of the token, which the tokens burned in burning mode are taken out of
+        uint256 totalSupply;
+    }
+
+    /// @notice Journal committed to by the token backing guest, attesting to a token transfer
+    /// @notice along with the holdings of its token that back it.
+    struct BackingJournal {
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // The encoded TransceiverMessage that this proof commits to
+        bytes encodedMessage;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+
+        // Holdings of the transferred token at the end of the block
+        TokenBacking tokenBacking;
+    }
+}
+
+sol! {
+    /// @notice The registration of the NTT managers a message was sent between, as read from the
+    /// @notice source manager at the end of the block the message was emitted in.
+    struct PeerRegistration {
+        // The source NTT manager, on which the emitting transceiver is enabled
+        address nttManager;
+
+        // Wormhole chain ID the message was sent to
+        uint16 recipientChain;
+
+        // The peer of the source manager on the recipient chain, the recipient of the message
+        bytes32 peerAddress;
+
+        // Token decimals the source manager has registered for the peer
+        uint8 peerDecimals;
+    }
+
+    /// @notice Journal committed to by the peer registration guest, attesting to a message sent
+    /// @notice through a registered transceiver of its NTT manager to a registered peer.
+    struct PeerJournal {
+        // Commitment locks this proof to a specific block root
+        Commitment commitment;
+
+        // The encoded TransceiverMessage that this proof commits to
+        bytes encodedMessage;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+
+        // The registration the message was checked against
+        PeerRegistration peerRegistration;
+    }
+}
+
+sol! {
+    /// @notice Leaf of a message in the Merkle tree committed to by the Merkle batch guest.
+    struct MessageLeaf {
+        // keccak256 of the encoded TransceiverMessage
+        bytes32 messageDigest;
+
+        // The contract that emitted the message event
+        bytes32 emitterContract;
+
+        // Wormhole chain ID the message was sent to
+        uint16 recipientChain;
+    }
+
+    /// @notice Journal committed to by the Merkle batch guest, attesting to every message whose
+    /// @notice leaf is in the Merkle tree with the committed root. Each message is verified
+    /// @notice against the root with its branch, which the relayer supplies alongside it.
+    struct MerkleBatchJournal {
+        // Commitment shared by every message in the batch
+        Commitment commitment;
+
+        // Root of the Merkle tree over the leaves of the messages, in input order
+        bytes32 messagesRoot;
+
+        // Number of messages in the tree
+        uint32 messageCount;
+    }
+}
+
+impl Journal {
+    /// Decodes a single message journal of any version into the unversioned [Journal], telling
+    /// them apart by the [JOURNAL_V2_TAG] of versioned journals.
+    pub fn decode_any(data: &[u8]) -> Result<Self, String> {
+        if data.starts_with(&JOURNAL_V2_TAG) {
+            JournalV2::decode(data).map(Self::from)
+        } else {
+            Self::abi_decode(data).map_err(|e| format!("Failed to decode Journal: {e}"))
+        }
+    }
+
+    /// Converts this journal of a message emitted in the execution block with `block_number` and
+    /// `block_hash` into the versioned format.
+    pub fn to_v2(&self, block_number: u64, block_hash: B256) -> JournalV2 {
+        JournalV2 {
+            version: JOURNAL_V2,
+            commitment: self.commitment.clone(),
+            blockNumber: block_number,
+            blockHash: block_hash,
+            encodedMessage: self.encodedMessage.clone(),
+            emitterContract: self.emitterContract,
+        }
+    }
+
+    /// Extends this journal with the state of the NTT manager that sent the message.
+    pub fn with_manager_state(&self, manager_state: ManagerState) -> StateJournal {
+        StateJournal {
+            commitment: self.commitment.clone(),
+            encodedMessage: self.encodedMessage.clone(),
+            emitterContract: self.emitterContract,
+            managerState: manager_state,
+        }
+    }
+
+    /// Extends this journal with the token holdings backing the transfer it carries.
+    pub fn with_token_backing(&self, token_backing: TokenBacking) -> BackingJournal {
+        BackingJournal {
+            commitment: self.commitment.clone(),
+            encodedMessage: self.encodedMessage.clone(),
+            emitterContract: self.emitterContract,
+            tokenBacking: token_backing,
+        }
+    }
+
+    /// Extends this journal with the peer registration the message was sent under.
+    pub fn with_peer_registration(&self, peer_registration: PeerRegistration) -> PeerJournal {
+        PeerJournal {
+            commitment: self.commitment.clone(),
+            encodedMessage: self.encodedMessage.clone(),
+            emitterContract: self.emitterContract,
+            peerRegistration: peer_registration,
+        }
+    }
+
+    /// Converts this journal into its hash commit mode counterpart.
+    pub fn to_digest_journal(&self) -> DigestJournal {
+        DigestJournal {
+            commitment: self.commitment.clone(),
+            messageDigest: keccak256(&self.encodedMessage),
+            emitterContract: self.emitterContract,
+        }
+    }
+
+    /// Converts this journal into the multichain guest's journal for the given source chain.
+    pub fn to_chain_journal(&self, chain_id: u64) -> ChainJournal {
+        ChainJournal {
+            chainId: chain_id,
+            commitment: self.commitment.clone(),
+            encodedMessage: self.encodedMessage.clone(),
+            emitterContract: self.emitterContract,
+        }
+    }
+}
+
+impl JournalV2 {
+    /// Encodes the journal as the guest commits it: [JOURNAL_V2_TAG] followed by its ABI encoding.
+    pub fn encode(&self) -> Vec<u8> {
+        [JOURNAL_V2_TAG.as_slice(), &self.abi_encode()].concat()
+    }
+
+    /// Decodes a journal encoded by [JournalV2::encode].
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        let encoded = data
+            .strip_prefix(JOURNAL_V2_TAG.as_slice())
+            .ok_or("Failed to decode JournalV2: missing tag")?;
+        Self::abi_decode(encoded).map_err(|e| format!("Failed to decode JournalV2: {e}"))
+    }
+}
+
+impl From<JournalV2> for Journal {
+    fn from(journal: JournalV2) -> Self {
+        Self {
+            commitment: journal.commitment,
+            encodedMessage: journal.encodedMessage,
+            emitterContract: journal.emitterContract,
+        }
+    }
+}
+
+impl From<StateJournal> for Journal {
+    fn from(journal: StateJournal) -> Self {
+        Self {
+            commitment: journal.commitment,
+            encodedMessage: journal.encodedMessage,
+            emitterContract: journal.emitterContract,
+        }
+    }
+}
+
+impl From<BackingJournal> for Journal {
+    fn from(journal: BackingJournal) -> Self {
+        Self {
+            commitment: journal.commitment,
+            encodedMessage: journal.encodedMessage,
+            emitterContract: journal.emitterContract,
+        }
+    }
+}
+
+impl From<PeerJournal> for Journal {
+    fn from(journal: PeerJournal) -> Self {
+        Self {
+            commitment: journal.commitment,
+            encodedMessage: journal.encodedMessage,
+            emitterContract: journal.emitterContract,
+        }
+    }
+}
+
+impl BatchJournal {
+    /// Combines per-message journals into a batch. All journals must share the same commitment.
+    pub fn from_journals(journals: Vec<Journal>) -> Result<Self, String> {
+        let commitment = journals
+            .first()
+            .ok_or("Cannot build a batch from zero journals")?
+            .commitment
+            .clone();
+        let messages = journals
+            .into_iter()
+            .map(|journal| {
+                if journal.commitment != commitment {
+                    return Err("Journals in a batch must share the same commitment".to_string());
+                }
+                Ok(BatchedMessage {
+                    encodedMessage: journal.encodedMessage,
+                    emitterContract: journal.emitterContract,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            commitment,
+            messages,
+        })
+    }
+
+    /// Splits the batch into the per-message journals accepted by the BoundlessTransceiver.
+    pub fn journals(&self) -> Vec<Journal> {
+        self.messages
+            .iter()
+            .map(|message| Journal {
+                commitment: self.commitment.clone(),
+                encodedMessage: message.encodedMessage.clone(),
+                emitterContract: message.emitterContract,
+            })
+            .collect()
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        self.abi_encode()
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Self, String> {
+        Self::abi_decode(data).map_err(|e| format!("Failed to decode BatchJournal: {e}"))
+    }
+}
+
 /// Converts a Wormhole format B256 address to an Ethereum Address.
 pub fn from_wormhole_address(wormhole_addr: B256) -> Address {
     // Extract the last 20 bytes from the 32-byte B256
@@ -74,3 +705,213 @@ pub fn to_wormhole_address(address: Address) -> B256 {
     bytes[12..].copy_from_slice(address.as_slice());
     B256::from(bytes)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::LogData;
+    use alloy_primitives::U256;
+    use alloy_sol_types::SolEvent;
+
+    fn journal(commitment_id: u64, message: &'static str) -> Journal {
+        Journal {
+            commitment: Commitment {
+                id: U256::from(commitment_id),
+                digest: B256::repeat_byte(0x11),
+                configID: B256::repeat_byte(0x22),
+            },
+            encodedMessage: Bytes::from(message),
+            emitterContract: to_wormhole_address(Address::repeat_byte(0x33)),
+        }
+    }
+
+    #[test]
+    fn decodes_transceiver_logs() {
+        // A message for chain 30 laid out as the BoundlessTransceiver emits it: the recipient chain
+        // is indexed, so only the message is in the data
+        let message = Bytes::from_static(b"message");
+        let data = [
+            B256::from(U256::from(32)).0,
+            B256::from(U256::from(message.len())).0,
+            B256::right_padding_from(&message).0,
+        ]
+        .concat();
+        let log = LogData::new(
+            vec![
+                IBoundlessTransceiver::SendTransceiverMessage::SIGNATURE_HASH,
+                B256::left_padding_from(&30u16.to_be_bytes()),
+            ],
+            data.into(),
+        )
+        .unwrap();
+
+        let event = IBoundlessTransceiver::SendTransceiverMessage::decode_log_data(&log).unwrap();
+        assert_eq!(event.recipientChain, 30);
+        assert_eq!(event.encodedMessage, message);
+    }
+
+    #[test]
+    fn batch_journal_roundtrip() {
+        let journals = vec![journal(1, "first"), journal(1, "second")];
+        let batch = BatchJournal::from_journals(journals.clone()).unwrap();
+
+        let decoded = BatchJournal::decode(&batch.encode()).unwrap();
+        assert_eq!(decoded.commitment, journals[0].commitment);
+        assert_eq!(decoded.messages.len(), 2);
+
+        let split = decoded.journals();
+        for (actual, expected) in split.iter().zip(&journals) {
+            assert_eq!(actual.abi_encode(), expected.abi_encode());
+        }
+    }
+
+    /// Expected ABI encodings of the journals, one 32 byte word per line.
+    const GOLDEN_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden");
+
+    /// Compares `encoded` with the golden file `name`, or overwrites the file when
+    /// `UPDATE_GOLDEN` is set.
+    fn assert_golden(name: &str, encoded: &[u8]) {
+        let path = std::path::Path::new(GOLDEN_DIR).join(format!("{name}.hex"));
+        // A tag ahead of the ABI encoding goes on a line of its own
+        let (tag, encoded) = encoded.split_at(encoded.len() % 32);
+        let words: String = std::iter::once(tag)
+            .filter(|tag| !tag.is_empty())
+            .chain(encoded.chunks(32))
+            .map(|word| alloy_primitives::hex::encode(word) + "\n")
+            .collect();
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, words).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {e}", path.display()));
+        assert_eq!(
+            words, expected,
+            "ABI encoding of {name} changed, deployed contracts would no longer decode it"
+        );
+    }
+
+    #[test]
+    fn journal_encodings_match_golden_files() {
+        let block_commitment = Commitment {
+            id: U256::from(22_000_000),
+            digest: B256::repeat_byte(0x11),
+            configID: B256::repeat_byte(0x22),
+        };
+        let beacon_commitment = Commitment {
+            id: (U256::from(2) << 240) | U256::from(1_234_567_890),
+            digest: B256::repeat_byte(0xaa),
+            configID: B256::repeat_byte(0xbb),
+        };
+        let emitter = to_wormhole_address(Address::repeat_byte(0x33));
+        let block = Journal {
+            commitment: block_commitment.clone(),
+            encodedMessage: (0..77u8).collect(),
+            emitterContract: emitter,
+        };
+        let beacon = Journal {
+            commitment: beacon_commitment.clone(),
+            encodedMessage: Bytes::new(),
+            emitterContract: emitter,
+        };
+        let batch = BatchJournal {
+            commitment: beacon_commitment.clone(),
+            messages: vec![
+                BatchedMessage {
+                    encodedMessage: block.encodedMessage.clone(),
+                    emitterContract: emitter,
+                },
+                BatchedMessage {
+                    encodedMessage: Bytes::from("second"),
+                    emitterContract: to_wormhole_address(Address::repeat_byte(0x44)),
+                },
+            ],
+        };
+        let event = EventJournal {
+            commitment: block_commitment,
+            emitterContract: emitter,
+            eventSignature: keccak256("Transfer(address,address,uint256)"),
+            data: U256::from(1_234_567).abi_encode().into(),
+        };
+        let chain = ChainJournal {
+            commitment: beacon_commitment,
+            ..block.to_chain_journal(8453)
+        };
+
+        assert_golden("journal_block", &block.abi_encode());
+        assert_golden("journal_beacon", &beacon.abi_encode());
+        assert_golden(
+            "journal_v2",
+            &block.to_v2(22_000_000, B256::repeat_byte(0xcc)).encode(),
+        );
+        assert_golden("digest_journal", &block.to_digest_journal().abi_encode());
+        assert_golden("chain_journal", &chain.abi_encode());
+        assert_golden("batch_journal", &batch.encode());
+        assert_golden("event_journal", &event.abi_encode());
+        assert_golden(
+            "state_journal",
+            &block
+                .with_manager_state(ManagerState {
+                    nttManager: Address::repeat_byte(0x55),
+                    nextMessageSequence: 42,
+                    currentOutboundCapacity: U256::from(10).pow(U256::from(24)),
+                })
+                .abi_encode(),
+        );
+        assert_golden(
+            "backing_journal",
+            &block
+                .with_token_backing(TokenBacking {
+                    token: Address::repeat_byte(0x66),
+                    mode: 1,
+                    managerBalance: U256::ZERO,
+                    totalSupply: U256::from(21_000_000) * U256::from(10).pow(U256::from(18)),
+                })
+                .abi_encode(),
+        );
+        assert_golden(
+            "merkle_batch_journal",
+            &MerkleBatchJournal {
+                commitment: block.commitment.clone(),
+                messagesRoot: B256::repeat_byte(0x66),
+                messageCount: 3,
+            }
+            .abi_encode(),
+        );
+        assert_golden(
+            "peer_journal",
+            &block
+                .with_peer_registration(PeerRegistration {
+                    nttManager: Address::repeat_byte(0x55),
+                    recipientChain: 30,
+                    peerAddress: B256::repeat_byte(0x77),
+                    peerDecimals: 18,
+                })
+                .abi_encode(),
+        );
+    }
+
+    #[test]
+    fn decodes_journals_of_any_version() {
+        // The commitment ID of a journal committing to block 2 equals the version of a JournalV2
+        let block_2 = journal(2, "message");
+        let journal = journal(22_000_000, "message");
+        let versioned = journal.to_v2(22_000_000, B256::repeat_byte(0xcc));
+        for encoded in [journal.abi_encode(), versioned.encode()] {
+            let decoded = Journal::decode_any(&encoded).unwrap();
+            assert_eq!(decoded.abi_encode(), journal.abi_encode());
+        }
+        let decoded = Journal::decode_any(&block_2.abi_encode()).unwrap();
+        assert_eq!(decoded.abi_encode(), block_2.abi_encode());
+        assert!(Journal::decode_any(&[0; 64]).is_err());
+        assert!(JournalV2::decode(&versioned.abi_encode()).is_err());
+        assert_eq!(JOURNAL_V2_TAG, keccak256("JournalV2")[..4]);
+    }
+
+    #[test]
+    fn batch_journal_rejects_mixed_commitments() {
+        let journals = vec![journal(1, "first"), journal(2, "second")];
+        assert!(BatchJournal::from_journals(journals).is_err());
+        assert!(BatchJournal::from_journals(vec![]).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Merkle trees over the messages of a batch, whose root the Merkle batch guest commits to
+//! instead of the messages themselves, so its journal stays small however large the batch.
+//!
+//! Leaves are the keccak256 of the ABI encoded [MessageLeaf] of each message, in input order.
+//! Pairs of nodes are hashed in sorted order, as OpenZeppelin's `MerkleProof` does, so a message
+//! is verified with its leaf and the sibling hashes on the path to the root alone. A node without
+//! a sibling is carried up to the next level unchanged.
+
+use alloy_primitives::{B256, keccak256};
+use alloy_sol_types::SolValue;
+
+use crate::MessageLeaf;
+
+impl MessageLeaf {
+    pub fn new(encoded_message: &[u8], emitter_contract: B256, recipient_chain: u16) -> Self {
+        Self {
+            messageDigest: keccak256(encoded_message),
+            emitterContract: emitter_contract,
+            recipientChain: recipient_chain,
+        }
+    }
+
+    /// The node of the leaf in the tree, `keccak256(abi.encode(leaf))`.
+    pub fn hash(&self) -> B256 {
+        keccak256(self.abi_encode())
+    }
+}
+
+/// The root of the tree over `leaves`, or zero if there are none.
+pub fn root(leaves: &[B256]) -> B256 {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// The sibling hashes on the path from leaf `index` to the root, bottom up.
+pub fn branch(leaves: &[B256], mut index: usize) -> Vec<B256> {
+    let mut level = leaves.to_vec();
+    let mut branch = Vec::new();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            branch.push(*sibling);
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    branch
+}
+
+/// Whether `leaf` is in the tree with `root`, given its `branch`.
+pub fn verify(leaf: B256, branch: &[B256], root: B256) -> bool {
+    branch
+        .iter()
+        .fold(leaf, |node, sibling| hash_pair(node, *sibling))
+        == root
+}
+
+fn next_level(level: &[B256]) -> Vec<B256> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => hash_pair(*left, *right),
+            [node] => *node,
+            _ => unreachable!("chunks hold one or two nodes"),
+        })
+        .collect()
+}
+
+fn hash_pair(a: B256, b: B256) -> B256 {
+    let (low, high) = if a <= b { (a, b) } else { (b, a) };
+    let mut preimage = [0; 64];
+    preimage[..32].copy_from_slice(low.as_slice());
+    preimage[32..].copy_from_slice(high.as_slice());
+    keccak256(preimage)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_branch_of_every_leaf() {
+        for count in 1..=9u8 {
+            let leaves: Vec<_> = (0..count)
+                .map(|i| MessageLeaf::new(&[i], B256::repeat_byte(0x33), u16::from(i)).hash())
+                .collect();
+            let tree_root = root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let leaf_branch = branch(&leaves, index);
+                assert!(
+                    verify(*leaf, &leaf_branch, tree_root),
+                    "leaf {index} of {count}"
+                );
+                assert!(!verify(B256::repeat_byte(0x01), &leaf_branch, tree_root));
+            }
+        }
+        assert_eq!(root(&[]), B256::ZERO);
+    }
+
+    #[test]
+    fn hashes_sorted_pairs() {
+        let (a, b) = (B256::repeat_byte(0x01), B256::repeat_byte(0x02));
+        assert_eq!(root(&[a, b]), root(&[b, a]));
+        assert_eq!(
+            root(&[a, b]),
+            keccak256([a.as_slice(), b.as_slice()].concat())
+        );
+        // The odd node is carried up rather than hashed with itself
+        assert_eq!(root(&[a, b, a]), hash_pair(hash_pair(a, b), a));
+    }
+}
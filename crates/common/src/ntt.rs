@@ -0,0 +1,491 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parsing of the Wormhole NTT transceiver messages carried by `SendTransceiverMessage` events.
+//!
+//! Transceivers encode their messages with `TransceiverStructs.encodeTransceiverMessage`:
+//!
+//! ```text
+//! prefix (4) | source manager (32) | recipient manager (32)
+//!   | manager payload length (2) | manager payload | transceiver payload length (2) | transceiver payload
+//! ```
+//!
+//! The BoundlessTransceiver emits the encoded message, while the standard WormholeTransceiver
+//! emits the message struct and leaves the encoding to the core bridge. [MessageEvent] maps both
+//! to the encoded form, which is what the guests attest to.
+//!
+//! The manager payload is an `NttManagerMessage`, whose own payload is the `NativeTokenTransfer`
+//! of a token transfer:
+//!
+//! ```text
+//! id (32) | sender (32) | payload length (2) | payload
+//! NTT prefix (4) | decimals (1) | amount (8) | source token (32) | recipient (32) | recipient chain (2)
+//! ```
+
+use alloy_primitives::{B256, Bytes, FixedBytes, keccak256};
+use alloy_sol_types::{SolEvent, sol};
+
+use crate::IBoundlessTransceiver;
+
+/// Prefix identifying messages of the BoundlessTransceiver.
+pub const BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX: FixedBytes<4> =
+    FixedBytes([0x1d, 0x49, 0xa4, 0x5d]);
+
+/// Prefix identifying messages of the standard WormholeTransceiver.
+pub const WORMHOLE_TRANSCEIVER_PAYLOAD_PREFIX: FixedBytes<4> = FixedBytes([0x99, 0x45, 0xff, 0x10]);
+
+/// Prefix identifying `NativeTokenTransfer` payloads.
+pub const NTT_PREFIX: FixedBytes<4> = FixedBytes([0x99, 0x4e, 0x54, 0x54]);
+
+sol! {
+    interface IWormholeTransceiver {
+        struct TransceiverMessage {
+            bytes32 sourceNttManagerAddress;
+            bytes32 recipientNttManagerAddress;
+            bytes nttManagerPayload;
+            bytes transceiverPayload;
+        }
+
+        /// @notice Emitted when a message is sent from the standard WormholeTransceiver.
+        event SendTransceiverMessage(uint16 recipientChain, TransceiverMessage message);
+    }
+}
+
+rpc_sol! {
+    /// The NTT Manager a transceiver sends messages for and forwards received messages to. The
+    /// manager records an attestation for every transceiver that delivers a message and executes
+    /// it once the threshold is met. On the sending side, its sequence counter and outbound rate
+    /// limit can be proven along with a message.
+    interface INttManager {
+        struct TransceiverInfo {
+            bool registered;
+            bool enabled;
+            uint8 index;
+        }
+
+        struct NttManagerPeer {
+            bytes32 peerAddress;
+            uint8 tokenDecimals;
+        }
+
+        /// @notice Emitted when a transceiver delivers a message to the manager.
+        event MessageAttestedTo(bytes32 digest, address transceiver, uint8 index);
+
+        /// @notice Emitted when a transfer has been redeemed on this chain.
+        event TransferRedeemed(bytes32 indexed digest);
+
+        function isMessageExecuted(bytes32 digest) external view returns (bool);
+
+        function messageAttestations(bytes32 digest) external view returns (uint8 count);
+
+        function transceiverAttestedToMessage(bytes32 digest, uint8 index) external view returns (bool);
+
+        /// @notice The enabled transceivers, in the order of `getTransceiverInfo`.
+        function getTransceivers() external view returns (address[] memory);
+
+        function getTransceiverInfo() external view returns (TransceiverInfo[] memory);
+
+        /// @notice The Wormhole chain ID of the chain of this manager, which its transceivers stamp
+        /// on the messages they send.
+        function chainId() external view returns (uint16);
+
+        /// @notice The manager registered as the peer of this manager on chain `chainId`.
+        function getPeer(uint16 chainId) external view returns (NttManagerPeer memory);
+
+        /// @notice Sequence number the next message sent by the manager will carry.
+        function nextMessageSequence() external view returns (uint64);
+
+        /// @notice Amount the manager can currently send before hitting its outbound rate limit.
+        function getCurrentOutboundCapacity() external view returns (uint256);
+
+        /// @notice The token the manager transfers.
+        function token() external view returns (address);
+
+        /// @notice Whether the manager locks (0) or burns (1) the tokens it sends.
+        function getMode() external view returns (uint8);
+    }
+}
+
+sol! {
+    /// The views of an ERC-20 token that show the tokens an NTT manager locked or burned.
+    interface IERC20 {
+        function balanceOf(address account) external view returns (uint256);
+
+        function totalSupply() external view returns (uint256);
+    }
+}
+
+/// A `SendTransceiverMessage` event of a transceiver, carrying a transceiver message.
+pub trait MessageEvent: SolEvent {
+    /// Wormhole chain ID the message is sent to.
+    fn recipient_chain(&self) -> u16;
+
+    /// The message in its encoded form, as emitted by the BoundlessTransceiver.
+    fn encoded_message(&self) -> Bytes;
+}
+
+impl MessageEvent for IBoundlessTransceiver::SendTransceiverMessage {
+    fn recipient_chain(&self) -> u16 {
+        self.recipientChain
+    }
+
+    fn encoded_message(&self) -> Bytes {
+        self.encodedMessage.clone()
+    }
+}
+
+impl MessageEvent for IWormholeTransceiver::SendTransceiverMessage {
+    fn recipient_chain(&self) -> u16 {
+        self.recipientChain
+    }
+
+    fn encoded_message(&self) -> Bytes {
+        TransceiverMessage {
+            source_ntt_manager: self.message.sourceNttManagerAddress,
+            recipient_ntt_manager: self.message.recipientNttManagerAddress,
+            ntt_manager_payload: self.message.nttManagerPayload.clone(),
+            transceiver_payload: self.message.transceiverPayload.clone(),
+        }
+        .encode(WORMHOLE_TRANSCEIVER_PAYLOAD_PREFIX)
+        // The transceiver could not have emitted payloads that do not fit their length prefix
+        .expect("transceiver message payload too long")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TransceiverMessage {
+    /// Wormhole format address of the NTT manager that sent the message.
+    pub source_ntt_manager: B256,
+    /// Wormhole format address of the NTT manager the message is for.
+    pub recipient_ntt_manager: B256,
+    /// The encoded `NttManagerMessage`.
+    pub ntt_manager_payload: Bytes,
+    /// Transceiver specific data, the Wormhole chain ID of the source chain for this transceiver.
+    pub transceiver_payload: Bytes,
+}
+
+impl TransceiverMessage {
+    /// Parses an encoded transceiver message, checking it carries the prefix of the
+    /// BoundlessTransceiver or the standard WormholeTransceiver.
+    pub fn parse(encoded: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader(encoded);
+        let prefix = reader.take(4)?;
+        if prefix != BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX.as_slice()
+            && prefix != WORMHOLE_TRANSCEIVER_PAYLOAD_PREFIX.as_slice()
+        {
+            return Err(format!(
+                "unexpected transceiver message prefix {}",
+                FixedBytes::<4>::from_slice(prefix)
+            ));
+        }
+        let source_ntt_manager = B256::from_slice(reader.take(32)?);
+        let recipient_ntt_manager = B256::from_slice(reader.take(32)?);
+        let ntt_manager_payload = reader.take_prefixed()?;
+        let transceiver_payload = reader.take_prefixed()?;
+        if !reader.0.is_empty() {
+            return Err(format!(
+                "{} trailing bytes after transceiver message",
+                reader.0.len()
+            ));
+        }
+        Ok(Self {
+            source_ntt_manager,
+            recipient_ntt_manager,
+            ntt_manager_payload,
+            transceiver_payload,
+        })
+    }
+
+    /// The Wormhole chain ID of the source chain, which the BoundlessTransceiver puts in its
+    /// transceiver payload.
+    pub fn source_chain(&self) -> Result<u16, String> {
+        let payload: [u8; 2] = self.transceiver_payload.as_ref().try_into().map_err(|_| {
+            format!(
+                "expected a 2 byte transceiver payload, got {} bytes",
+                self.transceiver_payload.len()
+            )
+        })?;
+        Ok(u16::from_be_bytes(payload))
+    }
+
+    /// Digest the NTT manager on the destination identifies the message by, as attested to in its
+    /// `MessageAttestedTo` events.
+    pub fn digest(&self, source_chain: u16) -> B256 {
+        let mut preimage = source_chain.to_be_bytes().to_vec();
+        preimage.extend_from_slice(&self.ntt_manager_payload);
+        keccak256(preimage)
+    }
+
+    /// Parses the token transfer carried by the message, failing for other manager messages.
+    pub fn transfer(&self) -> Result<NativeTokenTransfer, String> {
+        let message = NttManagerMessage::parse(&self.ntt_manager_payload)?;
+        NativeTokenTransfer::parse(&message.payload)
+    }
+
+    /// Encodes the message with the given transceiver prefix, failing if a payload does not fit
+    /// its `u16` length prefix.
+    pub fn encode(&self, prefix: FixedBytes<4>) -> Result<Bytes, String> {
+        let mut encoded = prefix.to_vec();
+        encoded.extend_from_slice(self.source_ntt_manager.as_slice());
+        encoded.extend_from_slice(self.recipient_ntt_manager.as_slice());
+        for payload in [&self.ntt_manager_payload, &self.transceiver_payload] {
+            let len = u16::try_from(payload.len())
+                .map_err(|_| format!("payload of {} bytes is too long", payload.len()))?;
+            encoded.extend_from_slice(&len.to_be_bytes());
+            encoded.extend_from_slice(payload);
+        }
+        Ok(encoded.into())
+    }
+}
+
+/// The message an NTT manager sends to its peers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NttManagerMessage {
+    /// Sequence number assigned by the sending manager.
+    pub id: B256,
+    /// Wormhole format address of the account that initiated the transfer.
+    pub sender: B256,
+    pub payload: Bytes,
+}
+
+impl NttManagerMessage {
+    pub fn parse(encoded: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader(encoded);
+        let id = B256::from_slice(reader.take(32)?);
+        let sender = B256::from_slice(reader.take(32)?);
+        let payload = reader.take_prefixed()?;
+        Ok(Self {
+            id,
+            sender,
+            payload,
+        })
+    }
+
+    /// Encodes the message, failing if the payload does not fit its `u16` length prefix.
+    pub fn encode(&self) -> Result<Bytes, String> {
+        let len = u16::try_from(self.payload.len())
+            .map_err(|_| format!("payload of {} bytes is too long", self.payload.len()))?;
+        let mut encoded = self.id.to_vec();
+        encoded.extend_from_slice(self.sender.as_slice());
+        encoded.extend_from_slice(&len.to_be_bytes());
+        encoded.extend_from_slice(&self.payload);
+        Ok(encoded.into())
+    }
+}
+
+/// A token transfer, the payload of the manager messages of token transfers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeTokenTransfer {
+    /// Amount in units of `10^-decimals` tokens, trimmed to at most 8 decimals.
+    pub amount: u64,
+    pub decimals: u8,
+    /// Wormhole format address of the token on the source chain.
+    pub source_token: B256,
+    /// Wormhole format address of the recipient.
+    pub to: B256,
+    /// Wormhole chain ID of the recipient.
+    pub to_chain: u16,
+}
+
+impl NativeTokenTransfer {
+    /// Parses a transfer, ignoring any additional payload that follows it.
+    pub fn parse(encoded: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader(encoded);
+        let prefix = reader.take(4)?;
+        if prefix != NTT_PREFIX.as_slice() {
+            return Err(format!(
+                "unexpected transfer prefix {}",
+                FixedBytes::<4>::from_slice(prefix)
+            ));
+        }
+        let decimals = reader.take(1)?[0];
+        let amount = u64::from_be_bytes(reader.take(8)?.try_into().unwrap());
+        let source_token = B256::from_slice(reader.take(32)?);
+        let to = B256::from_slice(reader.take(32)?);
+        let to_chain = u16::from_be_bytes(reader.take(2)?.try_into().unwrap());
+        Ok(Self {
+            amount,
+            decimals,
+            source_token,
+            to,
+            to_chain,
+        })
+    }
+
+    /// The amount in whole tokens.
+    pub fn tokens(&self) -> f64 {
+        self.amount as f64 / 10f64.powi(self.decimals.into())
+    }
+
+    /// Encodes the transfer without an additional payload.
+    pub fn encode(&self) -> Bytes {
+        let mut encoded = NTT_PREFIX.to_vec();
+        encoded.push(self.decimals);
+        encoded.extend_from_slice(&self.amount.to_be_bytes());
+        encoded.extend_from_slice(self.source_token.as_slice());
+        encoded.extend_from_slice(self.to.as_slice());
+        encoded.extend_from_slice(&self.to_chain.to_be_bytes());
+        encoded.into()
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err(format!(
+                "transceiver message truncated, expected {len} more bytes, got {}",
+                self.0.len()
+            ));
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    /// Takes a field prefixed with its big endian `u16` length.
+    fn take_prefixed(&mut self) -> Result<Bytes, String> {
+        let len = u16::from_be_bytes(self.take(2)?.try_into().unwrap());
+        Ok(Bytes::copy_from_slice(self.take(len.into())?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(prefix: &[u8], manager_payload: &[u8], transceiver_payload: &[u8]) -> Vec<u8> {
+        let mut encoded = prefix.to_vec();
+        encoded.extend_from_slice(&[0x11; 32]);
+        encoded.extend_from_slice(&[0x22; 32]);
+        encoded.extend_from_slice(&(manager_payload.len() as u16).to_be_bytes());
+        encoded.extend_from_slice(manager_payload);
+        encoded.extend_from_slice(&(transceiver_payload.len() as u16).to_be_bytes());
+        encoded.extend_from_slice(transceiver_payload);
+        encoded
+    }
+
+    #[test]
+    fn parses_transceiver_message() {
+        let encoded = encode(
+            BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX.as_slice(),
+            b"manager payload",
+            &2u16.to_be_bytes(),
+        );
+        let message = TransceiverMessage::parse(&encoded).unwrap();
+        assert_eq!(message.source_ntt_manager, B256::repeat_byte(0x11));
+        assert_eq!(message.recipient_ntt_manager, B256::repeat_byte(0x22));
+        assert_eq!(
+            message.ntt_manager_payload,
+            Bytes::from_static(b"manager payload")
+        );
+        assert_eq!(message.transceiver_payload, Bytes::from_static(&[0, 2]));
+        assert_eq!(message.source_chain(), Ok(2));
+        assert_eq!(
+            message.digest(2),
+            keccak256([&[0, 2], b"manager payload".as_slice()].concat())
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_messages() {
+        let valid = encode(
+            BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX.as_slice(),
+            b"payload",
+            &[],
+        );
+        assert!(TransceiverMessage::parse(&valid[..valid.len() - 1]).is_err());
+        assert!(TransceiverMessage::parse(&[valid.clone(), vec![0]].concat()).is_err());
+        assert!(TransceiverMessage::parse(&encode(&[0; 4], b"payload", &[])).is_err());
+    }
+
+    #[test]
+    fn parses_token_transfers() {
+        let mut transfer = NTT_PREFIX.to_vec();
+        transfer.push(6);
+        transfer.extend_from_slice(&2_500_000u64.to_be_bytes());
+        transfer.extend_from_slice(&[0x33; 32]);
+        transfer.extend_from_slice(&[0x44; 32]);
+        transfer.extend_from_slice(&30u16.to_be_bytes());
+        let mut manager_payload = [[0x55; 32], [0x66; 32]].concat();
+        manager_payload.extend_from_slice(&(transfer.len() as u16).to_be_bytes());
+        manager_payload.extend_from_slice(&transfer);
+
+        let encoded = encode(
+            BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX.as_slice(),
+            &manager_payload,
+            &2u16.to_be_bytes(),
+        );
+        let transfer = TransceiverMessage::parse(&encoded)
+            .unwrap()
+            .transfer()
+            .unwrap();
+        assert_eq!(transfer.amount, 2_500_000);
+        assert_eq!(transfer.decimals, 6);
+        assert_eq!(transfer.source_token, B256::repeat_byte(0x33));
+        assert_eq!(transfer.to, B256::repeat_byte(0x44));
+        assert_eq!(transfer.to_chain, 30);
+        assert_eq!(transfer.tokens(), 2.5);
+
+        assert!(NativeTokenTransfer::parse(&[0; 78]).is_err());
+    }
+
+    #[test]
+    fn encodes_token_transfers() {
+        let transfer = NativeTokenTransfer {
+            amount: 2_500_000,
+            decimals: 6,
+            source_token: B256::repeat_byte(0x33),
+            to: B256::repeat_byte(0x44),
+            to_chain: 30,
+        };
+        let message = NttManagerMessage {
+            id: B256::repeat_byte(0x55),
+            sender: B256::repeat_byte(0x66),
+            payload: transfer.encode(),
+        };
+        let encoded = message.encode().unwrap();
+        assert_eq!(NttManagerMessage::parse(&encoded), Ok(message.clone()));
+        assert_eq!(NativeTokenTransfer::parse(&message.payload), Ok(transfer));
+    }
+
+    #[test]
+    fn encodes_wormhole_transceiver_events() {
+        let event = IWormholeTransceiver::SendTransceiverMessage {
+            recipientChain: 30,
+            message: IWormholeTransceiver::TransceiverMessage {
+                sourceNttManagerAddress: B256::repeat_byte(0x11),
+                recipientNttManagerAddress: B256::repeat_byte(0x22),
+                nttManagerPayload: Bytes::from_static(b"manager payload"),
+                transceiverPayload: Bytes::new(),
+            },
+        };
+        let expected = encode(
+            WORMHOLE_TRANSCEIVER_PAYLOAD_PREFIX.as_slice(),
+            b"manager payload",
+            &[],
+        );
+        assert_eq!(event.encoded_message().as_ref(), expected.as_slice());
+
+        let message = TransceiverMessage::parse(&expected).unwrap();
+        assert_eq!(
+            message
+                .encode(WORMHOLE_TRANSCEIVER_PAYLOAD_PREFIX)
+                .unwrap()
+                .as_ref(),
+            expected.as_slice()
+        );
+    }
+}
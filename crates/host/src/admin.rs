@@ -0,0 +1,289 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Runtime control of the relay daemon through the admin API, so operational changes do not
+//! require a restart. Every admin request must carry the configured bearer token.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use alloy_primitives::Address;
+use axum::{
+    Json, Router,
+    extract::{Path, Request, State},
+    http::{StatusCode, header::AUTHORIZATION},
+    middleware::{self, Next},
+    response::Response,
+    routing::{delete, get, post},
+};
+use serde::Serialize;
+use tokio::sync::mpsc;
+use tracing::log;
+
+use crate::breaker::CircuitBreaker;
+use crate::config::RouteConfig;
+use crate::fees::UnpaidMessage;
+use crate::job::MessageId;
+
+/// State of the daemon shared with the admin API.
+#[derive(Default)]
+pub struct DaemonControl {
+    paused: AtomicBool,
+    in_flight: Mutex<HashSet<MessageId>>,
+    /// One past the last source chain block scanned for messages, or 0 before the first scan.
+    scanned_to: AtomicU64,
+    unpaid: Mutex<HashMap<MessageId, UnpaidMessage>>,
+}
+
+impl DaemonControl {
+    /// Whether the daemon has stopped picking up new messages.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// The messages currently being relayed.
+    pub fn in_flight(&self) -> Vec<MessageId> {
+        self.in_flight.lock().unwrap().iter().copied().collect()
+    }
+
+    pub fn is_in_flight(&self, id: &MessageId) -> bool {
+        self.in_flight.lock().unwrap().contains(id)
+    }
+
+    /// Marks the message `id` as being relayed, returning false if it already was.
+    pub fn start(&self, id: MessageId) -> bool {
+        self.in_flight.lock().unwrap().insert(id)
+    }
+
+    pub fn finish(&self, id: &MessageId) {
+        self.in_flight.lock().unwrap().remove(id);
+    }
+
+    /// The last source chain block scanned for messages, if any was yet.
+    pub fn last_scanned_block(&self) -> Option<u64> {
+        self.scanned_to.load(Ordering::Relaxed).checked_sub(1)
+    }
+
+    /// Records that the blocks before `next_block` were scanned.
+    pub fn set_next_block(&self, next_block: u64) {
+        self.scanned_to.store(next_block, Ordering::Relaxed);
+    }
+
+    /// The messages waiting for their relay to be paid for, oldest first.
+    pub fn unpaid(&self) -> Vec<UnpaidMessage> {
+        let mut unpaid: Vec<_> = self.unpaid.lock().unwrap().values().cloned().collect();
+        unpaid.sort_by_key(|unpaid| unpaid.message.block_number);
+        unpaid
+    }
+
+    /// The message `id`, if it is parked.
+    pub fn parked(&self, id: &MessageId) -> Option<UnpaidMessage> {
+        self.unpaid.lock().unwrap().get(id).cloned()
+    }
+
+    /// Parks a message until its relay is paid for.
+    pub fn park(&self, message: UnpaidMessage) {
+        self.unpaid.lock().unwrap().insert(message.id(), message);
+    }
+
+    pub fn unpark(&self, id: &MessageId) {
+        self.unpaid.lock().unwrap().remove(id);
+    }
+}
+
+/// Changes to the daemon requested through the admin API, applied by the daemon in order.
+#[derive(Debug)]
+pub enum AdminCommand {
+    /// Relay the message again, e.g. after it failed.
+    Retry(MessageId),
+    /// Take the message out of the dead-letter queue and relay it again.
+    Replay(MessageId),
+    AddRoute(RouteConfig),
+    RemoveRoute {
+        manager: Address,
+        recipient_chain: u16,
+    },
+}
+
+/// The daemon side of the admin API.
+pub struct AdminLink {
+    pub control: Arc<DaemonControl>,
+    pub commands: mpsc::UnboundedReceiver<AdminCommand>,
+}
+
+/// The API side of the admin API.
+pub struct Admin {
+    token: String,
+    control: Arc<DaemonControl>,
+    breaker: Arc<CircuitBreaker>,
+    commands: mpsc::UnboundedSender<AdminCommand>,
+}
+
+impl Admin {
+    /// Creates an admin API accepting requests authenticated with `token`, along with the link
+    /// the daemon is controlled through. Resuming also resets `breaker`.
+    pub fn new(token: String, breaker: Arc<CircuitBreaker>) -> (Arc<Self>, AdminLink) {
+        let control = Arc::new(DaemonControl::default());
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let admin = Arc::new(Self {
+            token,
+            control: control.clone(),
+            breaker,
+            commands,
+        });
+        (
+            admin,
+            AdminLink {
+                control,
+                commands: receiver,
+            },
+        )
+    }
+
+    fn send(&self, command: AdminCommand) -> StatusCode {
+        log::info!("Admin request: {command:?}");
+        match self.commands.send(command) {
+            Ok(()) => StatusCode::ACCEPTED,
+            // The daemon is not running, e.g. for one-shot commands
+            Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AdminStatus {
+    paused: bool,
+    /// The anomaly that tripped the circuit breaker, if it is tripped.
+    breaker_tripped: Option<String>,
+    in_flight: Vec<MessageId>,
+    /// Messages parked until their relay is paid for.
+    unpaid: Vec<UnpaidMessage>,
+}
+
+pub fn router(admin: Arc<Admin>) -> Router {
+    Router::new()
+        .route("/status", get(status))
+        .route("/pause", post(pause))
+        .route("/resume", post(resume))
+        .route("/drain", post(drain))
+        .route("/retry/{id}", post(retry))
+        .route("/dead-letters/{id}/replay", post(replay))
+        .route("/routes", post(add_route))
+        .route("/routes/{manager}/{recipient_chain}", delete(remove_route))
+        .layer(middleware::from_fn_with_state(admin.clone(), authenticate))
+        .with_state(admin)
+}
+
+async fn authenticate(
+    State(admin): State<Arc<Admin>>,
+    request: Request,
+    next: Next,
+) -> Result<Response, StatusCode> {
+    let authorized = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), admin.token.as_bytes()));
+    if !authorized {
+        return Err(StatusCode::UNAUTHORIZED);
+    }
+    Ok(next.run(request).await)
+}
+
+/// Compares the tokens without leaking the length of their common prefix through timing.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn admin_status(admin: &Admin) -> Json<AdminStatus> {
+    Json(AdminStatus {
+        paused: admin.control.is_paused(),
+        breaker_tripped: admin.breaker.tripped().map(|anomaly| anomaly.to_string()),
+        in_flight: admin.control.in_flight(),
+        unpaid: admin.control.unpaid(),
+    })
+}
+
+async fn status(State(admin): State<Arc<Admin>>) -> Json<AdminStatus> {
+    admin_status(&admin)
+}
+
+/// Stops picking up new messages. Messages emitted meanwhile are picked up on resume.
+async fn pause(State(admin): State<Arc<Admin>>) -> Json<AdminStatus> {
+    log::info!("Admin request: pause");
+    admin.control.set_paused(true);
+    admin_status(&admin)
+}
+
+/// Picks up new messages again, resetting the circuit breaker if it tripped.
+async fn resume(State(admin): State<Arc<Admin>>) -> Json<AdminStatus> {
+    log::info!("Admin request: resume");
+    admin.breaker.reset();
+    admin.control.set_paused(false);
+    admin_status(&admin)
+}
+
+/// Pauses intake so the messages in flight can finish, e.g. before a restart. Drained once the
+/// status lists no messages in flight.
+async fn drain(State(admin): State<Arc<Admin>>) -> Json<AdminStatus> {
+    log::info!("Admin request: drain");
+    admin.control.set_paused(true);
+    admin_status(&admin)
+}
+
+async fn retry(State(admin): State<Arc<Admin>>, Path(id): Path<String>) -> StatusCode {
+    match id.parse() {
+        Ok(id) => admin.send(AdminCommand::Retry(id)),
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+async fn replay(State(admin): State<Arc<Admin>>, Path(id): Path<String>) -> StatusCode {
+    match id.parse() {
+        Ok(id) => admin.send(AdminCommand::Replay(id)),
+        Err(_) => StatusCode::BAD_REQUEST,
+    }
+}
+
+async fn add_route(State(admin): State<Arc<Admin>>, Json(route): Json<RouteConfig>) -> StatusCode {
+    admin.send(AdminCommand::AddRoute(route))
+}
+
+async fn remove_route(
+    State(admin): State<Arc<Admin>>,
+    Path((manager, recipient_chain)): Path<(Address, u16)>,
+) -> StatusCode {
+    admin.send(AdminCommand::RemoveRoute {
+        manager,
+        recipient_chain,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_tokens() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+    }
+}
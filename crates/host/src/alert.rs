@@ -0,0 +1,61 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Operator alerts, logged and optionally posted as JSON to a webhook.
+
+use reqwest::{Client, Url};
+use serde::Serialize;
+use tracing::log;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Alert {
+    /// Short machine readable identifier of the alert, e.g. `sla_breach`.
+    pub kind: &'static str,
+    pub message: String,
+}
+
+#[derive(Clone, Default)]
+pub struct Alerter {
+    webhook_url: Option<Url>,
+    client: Client,
+}
+
+impl Alerter {
+    pub fn new(webhook_url: Option<Url>) -> Self {
+        Self {
+            webhook_url,
+            client: Client::new(),
+        }
+    }
+
+    /// Logs the alert and posts it to the webhook, if configured. Failing to deliver an alert is
+    /// logged rather than returned, so alerting never interrupts relaying.
+    pub async fn fire(&self, alert: Alert) {
+        log::error!("[{}] {}", alert.kind, alert.message);
+
+        let Some(url) = &self.webhook_url else {
+            return;
+        };
+        let result = self
+            .client
+            .post(url.clone())
+            .json(&alert)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(e) = result {
+            log::warn!("Failed to deliver alert to webhook: {e}");
+        }
+    }
+}
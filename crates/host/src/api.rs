@@ -0,0 +1,195 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! HTTP server exposing the status dashboard, the metrics endpoint, the status API, the cost
+//! quote API and the admin API of the relay.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use alloy_primitives::Address;
+use anyhow::{Context, Result};
+use axum::{
+    Json, Router,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Html,
+    routing::get,
+};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tracing::log;
+use zkvm::{GUEST_PROGRAMS, GuestProgram};
+
+use crate::admin::{self, Admin};
+use crate::config::{DaemonConfig, Finality};
+use crate::dashboard;
+use crate::job::{Job, MessageId};
+use crate::metrics::{
+    EndpointStats, LatencyPercentiles, Metrics, ProofMetrics, RelayError, WalletBalance,
+};
+use crate::quote::CostQuote;
+use crate::relayer::Relayer;
+use crate::store::JobStore;
+use crate::watcher::finalized_block;
+
+/// State shared with the request handlers.
+#[derive(Default)]
+pub struct ApiState {
+    pub metrics: Arc<Metrics>,
+    pub store: Option<Arc<dyn JobStore>>,
+    /// Serves the admin API under `/admin`, if set.
+    pub admin: Option<Arc<Admin>>,
+    /// The configuration the daemon was started with, shown on the dashboard.
+    pub config: Option<DaemonConfig>,
+    /// Quotes what proving messages costs under `/quote`, if set.
+    pub relayer: Option<Arc<Relayer>>,
+}
+
+/// Response of the status API.
+#[derive(Serialize, Deserialize)]
+pub struct Status {
+    pub recent_proofs: Vec<ProofMetrics>,
+    pub delivery_latency: LatencyPercentiles,
+    /// Number of messages in the dead-letter queue, if jobs are persisted.
+    pub dead_letters: Option<usize>,
+    pub breaker_tripped: bool,
+    pub wallets: Vec<WalletBalance>,
+    pub recent_errors: Vec<RelayError>,
+    /// Requests to the source chain endpoints.
+    #[serde(default)]
+    pub endpoints: Vec<EndpointStats>,
+}
+
+pub fn router(state: Arc<ApiState>) -> Router {
+    let router = Router::new()
+        .route("/", get(index))
+        .route("/metrics", get(metrics))
+        .route("/status", get(status))
+        .route("/jobs", get(jobs))
+        .route("/jobs/{id}", get(job))
+        .route("/dead-letters", get(dead_letters))
+        .route("/quote/{id}", get(quote))
+        .with_state(state.clone());
+    match &state.admin {
+        Some(admin) => router.nest("/admin", admin::router(admin.clone())),
+        None => router,
+    }
+}
+
+/// Serves the API on `addr` until the process exits.
+pub async fn serve(addr: SocketAddr, state: Arc<ApiState>) -> Result<()> {
+    let listener = TcpListener::bind(addr)
+        .await
+        .with_context(|| format!("failed to bind API listener to {addr}"))?;
+    log::info!("Serving API on {addr}");
+    axum::serve(listener, router(state)).await?;
+    Ok(())
+}
+
+async fn index(State(state): State<Arc<ApiState>>) -> Result<Html<String>, StatusCode> {
+    dashboard::render(&state).map(Html).map_err(internal_error)
+}
+
+async fn metrics(State(state): State<Arc<ApiState>>) -> String {
+    state.metrics.render_prometheus()
+}
+
+async fn status(State(state): State<Arc<ApiState>>) -> Result<Json<Status>, StatusCode> {
+    let dead_letters = match &state.store {
+        Some(store) => Some(
+            store
+                .list()
+                .map_err(internal_error)?
+                .iter()
+                .filter(|job| job.dead_lettered)
+                .count(),
+        ),
+        None => None,
+    };
+    Ok(Json(Status {
+        recent_proofs: state.metrics.recent_proofs(),
+        delivery_latency: state.metrics.latency_percentiles(),
+        dead_letters,
+        breaker_tripped: state.metrics.breaker_tripped(),
+        wallets: state.metrics.wallet_balances(),
+        recent_errors: state.metrics.recent_errors(),
+        endpoints: state.metrics.endpoint_stats(),
+    }))
+}
+
+async fn jobs(State(state): State<Arc<ApiState>>) -> Result<Json<Vec<Job>>, StatusCode> {
+    let store = state.store.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let jobs = store.list().map_err(internal_error)?;
+    Ok(Json(jobs))
+}
+
+/// The messages in the dead-letter queue, with the failures of each attempt.
+async fn dead_letters(State(state): State<Arc<ApiState>>) -> Result<Json<Vec<Job>>, StatusCode> {
+    let store = state.store.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let mut jobs = store.list().map_err(internal_error)?;
+    jobs.retain(|job| job.dead_lettered);
+    Ok(Json(jobs))
+}
+
+async fn job(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+) -> Result<Json<Job>, StatusCode> {
+    let store = state.store.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let id: MessageId = id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let job = store.load(&id).map_err(internal_error)?;
+    job.map(Json).ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Parameters of a cost quote.
+#[derive(Deserialize)]
+struct QuoteQuery {
+    /// The source transceiver that emitted the message.
+    source: Address,
+    /// Defaults to the latest finalized block.
+    commitment_block: Option<u64>,
+    /// Name of the embedded guest program. Defaults to the first one.
+    guest: Option<String>,
+}
+
+/// What proving a message costs on each backend, from a dry run of the guest.
+async fn quote(
+    State(state): State<Arc<ApiState>>,
+    Path(id): Path<String>,
+    Query(query): Query<QuoteQuery>,
+) -> Result<Json<CostQuote>, StatusCode> {
+    let relayer = state.relayer.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    let id: MessageId = id.parse().map_err(|_| StatusCode::BAD_REQUEST)?;
+    let guest = match &query.guest {
+        Some(name) => GuestProgram::by_name(name).ok_or(StatusCode::BAD_REQUEST)?,
+        None => &GUEST_PROGRAMS[0],
+    };
+    let commitment_block = match query.commitment_block {
+        Some(block) => block,
+        None => finalized_block(&relayer.rpc, Finality::Finalized)
+            .await
+            .map_err(internal_error)?,
+    };
+    let quote = relayer
+        .quote(id, query.source, guest, commitment_block)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(quote))
+}
+
+fn internal_error(e: anyhow::Error) -> StatusCode {
+    log::error!("API request failed: {e:#}");
+    StatusCode::INTERNAL_SERVER_ERROR
+}
@@ -0,0 +1,98 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Files carrying the work of one step of relaying to another, so that steps needing network
+//! access and steps needing a prover can run on different machines.
+
+use std::fs;
+use std::path::Path;
+
+use alloy_primitives::Address;
+use anyhow::{Context, Result, ensure};
+use risc0_zkvm::{Digest, Receipt};
+use serde::{Deserialize, Serialize};
+use zkvm::GuestProgram;
+
+use crate::job::MessageId;
+
+/// The input of a message, exported for proving on a machine without access to the source chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct InputArtifact {
+    pub id: MessageId,
+    /// The source transceiver contract that emitted the message.
+    pub source: Address,
+    pub commitment_block: u64,
+    /// Name of the embedded guest program the input was built for.
+    pub guest: String,
+    /// Image ID of the guest, so the input is not proven with a different build of it.
+    pub image_id: Digest,
+    /// The framed guest input.
+    pub input: Vec<u8>,
+}
+
+impl InputArtifact {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        read(path.as_ref())
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        write(path.as_ref(), self)
+    }
+
+    /// Returns the embedded guest the input was built for, failing if its image ID changed.
+    pub fn guest_program(&self) -> Result<&'static GuestProgram> {
+        let guest = GuestProgram::by_name(&self.guest)
+            .with_context(|| format!("Unknown guest program: {}", self.guest))?;
+        let image_id = Digest::from(guest.image_id);
+        ensure!(
+            image_id == self.image_id,
+            "Input was built for image ID {} of guest {}, but the embedded guest has {image_id}",
+            self.image_id,
+            self.guest
+        );
+        Ok(guest)
+    }
+}
+
+/// The proof of a message produced from an [InputArtifact], ready to be delivered.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofArtifact {
+    pub id: MessageId,
+    /// The source transceiver contract that emitted the message.
+    pub source: Address,
+    pub commitment_block: u64,
+    /// Name of the embedded guest program that produced the proof.
+    pub guest: String,
+    pub receipt: Receipt,
+}
+
+impl ProofArtifact {
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        read(path.as_ref())
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<()> {
+        write(path.as_ref(), self)
+    }
+}
+
+pub(crate) fn read<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T> {
+    let bytes = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+    bincode::deserialize(&bytes).with_context(|| format!("invalid artifact {}", path.display()))
+}
+
+pub(crate) fn write<T: Serialize>(path: &Path, artifact: &T) -> Result<()> {
+    let bytes = bincode::serialize(artifact)?;
+    fs::write(path, bytes).with_context(|| format!("failed to write {}", path.display()))
+}
@@ -0,0 +1,175 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Audit bundles: everything needed to check the relay of a message end to end, in a single JSON
+//! file that an auditor can verify without access to the relayer's infrastructure.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy::rpc::types::TransactionReceipt;
+use alloy_primitives::{Address, B256, Bytes};
+use anyhow::{Context, Result, ensure};
+use common::Journal;
+use risc0_ethereum_contracts::encode_seal;
+use risc0_zkvm::{Digest, Receipt};
+use serde::{Deserialize, Serialize};
+
+use crate::job::MessageId;
+use crate::watcher::decode_message;
+
+/// Version of the bundle format, bumped on incompatible changes.
+const VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AuditBundle {
+    pub version: u32,
+    pub id: MessageId,
+    /// The source transceiver that emitted the message.
+    pub source: Address,
+    /// The destination transceiver the message was delivered to.
+    pub destination: Address,
+    /// Name of the guest program that proved the message.
+    pub guest: String,
+    pub image_id: Digest,
+    /// SHA-256 digest of the guest input the proof was produced from.
+    pub input_digest: Digest,
+    pub journal: Bytes,
+    /// The seal as submitted to the destination.
+    pub seal: Bytes,
+    /// The Groth16 receipt, verifiable with the RISC Zero verifier.
+    pub receipt: Receipt,
+    /// Receipt of the source transaction emitting the message.
+    pub source_receipt: TransactionReceipt,
+    /// Receipt of the destination transaction delivering the proof.
+    pub destination_receipt: TransactionReceipt,
+}
+
+impl AuditBundle {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        id: MessageId,
+        source: Address,
+        destination: Address,
+        guest: &str,
+        image_id: Digest,
+        input_digest: Digest,
+        receipt: Receipt,
+        source_receipt: TransactionReceipt,
+        destination_receipt: TransactionReceipt,
+    ) -> Result<Self> {
+        Ok(Self {
+            version: VERSION,
+            id,
+            source,
+            destination,
+            guest: guest.to_string(),
+            image_id,
+            input_digest,
+            journal: receipt.journal.bytes.clone().into(),
+            seal: encode_seal(&receipt).context("invalid receipt")?.into(),
+            receipt,
+            source_receipt,
+            destination_receipt,
+        })
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let json = fs::read(path).with_context(|| format!("failed to read {}", path.display()))?;
+        serde_json::from_slice(&json)
+            .with_context(|| format!("invalid audit bundle {}", path.display()))
+    }
+
+    /// Writes the bundle to `<dir>/<message ID>.json`, returning its path.
+    pub fn write(&self, dir: impl AsRef<Path>) -> Result<PathBuf> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", self.id));
+        fs::write(&path, serde_json::to_vec_pretty(self)?)
+            .with_context(|| format!("failed to write {}", path.display()))?;
+        Ok(path)
+    }
+
+    /// Checks everything that can be checked without network access: that the receipt is valid
+    /// for the image ID and matches the journal and seal, that the journal commits to the message
+    /// the source transaction emitted, and that the destination transaction succeeded. Returns
+    /// the journal, whose commitment remains to be checked against the source chain.
+    pub fn verify(&self) -> Result<Journal> {
+        ensure!(
+            self.version == VERSION,
+            "unsupported audit bundle version {}",
+            self.version
+        );
+        self.receipt
+            .verify(self.image_id)
+            .context("receipt does not verify against the image ID")?;
+        ensure!(
+            self.receipt.journal.bytes == self.journal,
+            "journal does not match the receipt"
+        );
+        ensure!(
+            encode_seal(&self.receipt)? == self.seal.to_vec(),
+            "seal does not match the receipt"
+        );
+
+        let journal = Journal::decode_any(&self.journal)
+            .map_err(anyhow::Error::msg)
+            .context("invalid journal")?;
+        ensure!(
+            journal.emitterContract == B256::left_padding_from(self.source.as_slice()),
+            "journal commits to a message of {} instead of {}",
+            journal.emitterContract,
+            self.source
+        );
+
+        ensure!(
+            self.source_receipt.transaction_hash == self.id.tx_hash,
+            "source receipt is for transaction {} instead of {}",
+            self.source_receipt.transaction_hash,
+            self.id.tx_hash
+        );
+        ensure!(
+            self.source_receipt.status(),
+            "source transaction {} failed",
+            self.id.tx_hash
+        );
+        // Numbered among the messages of the source in the transaction, as the watcher does
+        let (_, encoded_message) = self
+            .source_receipt
+            .logs()
+            .iter()
+            .filter(|log| log.address() == self.source)
+            .filter_map(|log| decode_message(&log.inner).ok())
+            .nth(self.id.index)
+            .with_context(|| format!("source transaction has no message {}", self.id))?;
+        ensure!(
+            encoded_message == journal.encodedMessage,
+            "journal commits to another message than the source transaction emitted"
+        );
+
+        ensure!(
+            self.destination_receipt.status(),
+            "destination transaction {} failed",
+            self.destination_receipt.transaction_hash
+        );
+        ensure!(
+            self.destination_receipt.to == Some(self.destination),
+            "destination transaction {} was not sent to {}",
+            self.destination_receipt.transaction_hash,
+            self.destination
+        );
+        Ok(journal)
+    }
+}
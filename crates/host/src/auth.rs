@@ -0,0 +1,153 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Authentication of source chain endpoints requiring more than a key in their URL.
+//!
+//! Custom headers and basic auth credentials are configured per endpoint in a TOML file, matched
+//! by URL prefix, and sent with every request to the endpoint.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result, bail};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::Url;
+use reqwest::header::{self, HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+
+/// Credentials of the source chain endpoints.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub endpoints: Vec<EndpointAuth>,
+}
+
+/// Credentials sent to the endpoints under a URL prefix. Values may reference environment
+/// variables as `${NAME}`, keeping secrets out of the file.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndpointAuth {
+    /// Prefix of the URLs of the endpoints, e.g. `https://rpc.example.com/`.
+    pub url: String,
+    /// Headers added to every request.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    pub basic_auth: Option<BasicAuth>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BasicAuth {
+    pub username: String,
+    pub password: Option<String>,
+}
+
+impl AuthConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read endpoint auth {}", path.display()))?;
+        toml::from_str(&contents)
+            .with_context(|| format!("invalid endpoint auth {}", path.display()))
+    }
+
+    /// The headers to send to the endpoint at `url`, from the first entry whose prefix matches.
+    pub fn headers(&self, url: &Url) -> Result<HeaderMap> {
+        let mut headers = HeaderMap::new();
+        let Some(auth) = self
+            .endpoints
+            .iter()
+            .find(|auth| url.as_str().starts_with(&auth.url))
+        else {
+            return Ok(headers);
+        };
+        for (name, value) in &auth.headers {
+            let name = HeaderName::try_from(name.as_str())
+                .with_context(|| format!("invalid header name {name}"))?;
+            let value = HeaderValue::try_from(expand(value)?)
+                .with_context(|| format!("invalid value of header {name}"))?;
+            headers.insert(name, value);
+        }
+        if let Some(basic) = &auth.basic_auth {
+            let password = basic.password.as_deref().map(expand).transpose()?;
+            let credentials = format!(
+                "{}:{}",
+                expand(&basic.username)?,
+                password.unwrap_or_default()
+            );
+            let mut value =
+                HeaderValue::try_from(format!("Basic {}", STANDARD.encode(credentials)))?;
+            value.set_sensitive(true);
+            headers.insert(header::AUTHORIZATION, value);
+        }
+        Ok(headers)
+    }
+}
+
+/// Replaces each `${NAME}` in `value` with the environment variable `NAME`.
+fn expand(value: &str) -> Result<String> {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            bail!("unterminated variable reference in {value}");
+        };
+        let name = &rest[start + 2..start + len];
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&std::env::var(name).with_context(|| format!("{name} is not set"))?);
+        rest = &rest[start + len + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_headers_of_matching_endpoint() {
+        let config: AuthConfig = toml::from_str(
+            r#"
+            [[endpoints]]
+            url = "https://rpc.example.com/"
+            headers = { "x-api-key" = "secret" }
+            basic_auth = { username = "relayer", password = "hunter2" }
+            "#,
+        )
+        .unwrap();
+        let headers = config
+            .headers(&"https://rpc.example.com/v1".parse().unwrap())
+            .unwrap();
+        assert_eq!(headers["x-api-key"], "secret");
+        assert_eq!(
+            headers[header::AUTHORIZATION],
+            format!("Basic {}", STANDARD.encode("relayer:hunter2"))
+        );
+        let headers = config
+            .headers(&"https://other.example.com/".parse().unwrap())
+            .unwrap();
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn expands_variables() {
+        assert_eq!(expand("no variables").unwrap(), "no variables");
+        assert!(expand("${BOUNDLESS_UNSET_TEST_VARIABLE}").is_err());
+        assert!(expand("${UNTERMINATED").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Cycle counts of the embedded guests compared with previously released builds of them on the
+//! same inputs, so regressions of proving cost are caught before a release.
+//!
+//! Released builds are read from an artifacts directory holding the ELF of each guest as
+//! `<guest name>.elf`.
+
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use risc0_zkvm::{ExecutorEnv, SessionInfo, default_executor};
+use serde::Serialize;
+use zkvm::GuestProgram;
+
+use crate::error::HostError;
+
+/// A framed guest input to benchmark the guest it is for on.
+pub struct BenchInput {
+    /// Name of the snapshot or path of the input artifact the input was read from.
+    pub name: String,
+    pub guest: &'static GuestProgram,
+    pub input: Vec<u8>,
+}
+
+/// User cycles of the released and the embedded build of a guest on one input.
+#[derive(Clone, Debug, Serialize)]
+pub struct CycleComparison {
+    pub input: String,
+    pub guest: String,
+    pub baseline_cycles: u64,
+    pub current_cycles: u64,
+    /// Change from the released build, negative for an improvement.
+    pub delta: i64,
+    pub delta_percent: f64,
+}
+
+impl CycleComparison {
+    fn new(input: &BenchInput, baseline_cycles: u64, current_cycles: u64) -> Self {
+        let delta = current_cycles as i64 - baseline_cycles as i64;
+        Self {
+            input: input.name.clone(),
+            guest: input.guest.name.to_string(),
+            baseline_cycles,
+            current_cycles,
+            delta,
+            delta_percent: delta as f64 * 100.0 / baseline_cycles.max(1) as f64,
+        }
+    }
+}
+
+/// Executes the embedded guest of `input` and its released build in `artifacts_dir` on the input.
+/// Returns `None` if the directory holds no release of the guest, e.g. as it is new.
+pub fn compare(input: &BenchInput, artifacts_dir: &Path) -> Result<Option<CycleComparison>> {
+    let path = artifacts_dir.join(format!("{}.elf", input.guest.name));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let baseline_elf =
+        fs::read(&path).with_context(|| format!("failed to read {}", path.display()))?;
+    let baseline = user_cycles(&execute(&input.input, &baseline_elf).with_context(|| {
+        format!(
+            "released guest {} rejected {}",
+            input.guest.name, input.name
+        )
+    })?);
+    let current = user_cycles(
+        &execute(&input.input, input.guest.elf)
+            .with_context(|| format!("guest {} rejected {}", input.guest.name, input.name))?,
+    );
+    Ok(Some(CycleComparison::new(input, baseline, current)))
+}
+
+fn execute(input: &[u8], elf: &[u8]) -> Result<SessionInfo> {
+    let env = ExecutorEnv::builder().write_slice(input).build()?;
+    default_executor()
+        .execute(env, elf)
+        .map_err(HostError::classify)
+}
+
+/// Cycles spent executing the guest, excluding the padding of segments, which only changes when a
+/// segment crosses a power of two and would hide smaller regressions.
+fn user_cycles(session: &SessionInfo) -> u64 {
+    session
+        .segments
+        .iter()
+        .map(|segment| u64::from(segment.cycles))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn computes_relative_delta() {
+        let input = BenchInput {
+            name: "transfer".to_string(),
+            guest: &zkvm::NTT_MESSAGE_INCLUSION,
+            input: Vec::new(),
+        };
+        let comparison = CycleComparison::new(&input, 1_000_000, 1_050_000);
+        assert_eq!(comparison.delta, 50_000);
+        assert_eq!(comparison.delta_percent, 5.0);
+
+        let comparison = CycleComparison::new(&input, 1_000_000, 900_000);
+        assert_eq!(comparison.delta, -100_000);
+        assert_eq!(comparison.delta_percent, -10.0);
+    }
+}
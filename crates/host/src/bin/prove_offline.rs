@@ -0,0 +1,152 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proves an input exported by `relay build-input` without any network access, writing the proof
+//! to a file to be delivered by a machine with access to the destination chain.
+//!
+//! With `--queue`, runs as a prover worker instead, proving the inputs a relay pushes to a
+//! shared queue so that the relay, which holds the keys, does no proving itself.
+
+use anyhow::{Result, ensure};
+use clap::{Parser, ValueEnum};
+use proof_builder::{
+    artifact::{InputArtifact, ProofArtifact},
+    metrics::ProofMetrics,
+    prove, prove_groth16, prove_succinct,
+    queue::{self, ProofOutcome, ProofQueue},
+    snark::SnarkBackend,
+};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::log;
+use tracing_subscriber::EnvFilter;
+
+/// Interval between checks of the queue for new inputs.
+const CLAIM_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The kind of receipt to produce.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ReceiptKind {
+    /// A Groth16 receipt that can be verified on-chain.
+    Groth16,
+    /// A succinct STARK receipt, to be compressed elsewhere.
+    Succinct,
+}
+
+#[derive(Parser)]
+struct Args {
+    /// Input file written by `relay build-input`
+    #[arg(long, required_unless_present = "queue", requires = "out")]
+    input: Option<PathBuf>,
+
+    /// Path to write the proof to
+    #[arg(long)]
+    out: Option<PathBuf>,
+
+    /// Kind of receipt to produce
+    #[arg(long, value_enum, default_value_t = ReceiptKind::Groth16)]
+    receipt_kind: ReceiptKind,
+
+    /// Run as a prover worker, proving the inputs a relay pushes to this queue (`relay
+    /// --proof-queue`) until stopped: a shared directory or a `redis://` URL
+    #[arg(long, env = "PROOF_QUEUE", conflicts_with = "input")]
+    queue: Option<String>,
+
+    /// Time in seconds after which a claimed input that was not proven is presumed abandoned by
+    /// a crashed worker and handed to another one
+    #[arg(long, env = "PROOF_LEASE_SECONDS", default_value_t = 3600)]
+    lease_seconds: u64,
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .init();
+
+    let args = Args::parse();
+    if let Some(queue) = &args.queue {
+        ensure!(
+            matches!(args.receipt_kind, ReceiptKind::Groth16),
+            "prover workers must produce Groth16 receipts"
+        );
+        let queue = queue::open(queue)?;
+        return work(queue.as_ref(), Duration::from_secs(args.lease_seconds)).await;
+    }
+
+    let (Some(input), Some(out)) = (&args.input, &args.out) else {
+        unreachable!("clap requires --input and --out without --queue");
+    };
+    let proof = prove_input(InputArtifact::read(input)?, args.receipt_kind).await?;
+    proof.write(out)?;
+    log::info!("Wrote proof of message {} to {}", proof.id, out.display());
+
+    Ok(())
+}
+
+/// Proves the inputs pushed to `queue` as they arrive.
+async fn work(queue: &dyn ProofQueue, lease: Duration) -> Result<()> {
+    log::info!("Waiting for inputs to prove");
+    loop {
+        let Some(input) = queue.claim(lease)? else {
+            tokio::time::sleep(CLAIM_POLL_INTERVAL).await;
+            continue;
+        };
+        let id = input.id;
+        let outcome = match prove_input(input, ReceiptKind::Groth16).await {
+            Ok(proof) => ProofOutcome::Proved(proof),
+            Err(e) => {
+                log::error!("Failed to prove message {id}: {e:#}");
+                ProofOutcome::Failed(format!("{e:#}"))
+            }
+        };
+        queue.complete(&id, &outcome)?;
+        log::info!("Completed message {id}");
+    }
+}
+
+async fn prove_input(input: InputArtifact, receipt_kind: ReceiptKind) -> Result<ProofArtifact> {
+    let guest = input.guest_program()?;
+    log::info!("Proving message {} with guest {}", input.id, guest.name);
+
+    let (prove_info, metrics) = match receipt_kind {
+        ReceiptKind::Succinct => prove_succinct(input.input, guest).await?,
+        ReceiptKind::Groth16 => match SnarkBackend::detect()? {
+            SnarkBackend::Local => prove(input.input, guest).await?,
+            SnarkBackend::Bonsai => prove_groth16(input.input, guest).await?,
+        },
+    };
+    log_metrics(&metrics);
+
+    Ok(ProofArtifact {
+        id: input.id,
+        source: input.source,
+        commitment_block: input.commitment_block,
+        guest: input.guest,
+        receipt: prove_info.receipt,
+    })
+}
+
+fn log_metrics(metrics: &ProofMetrics) {
+    log::info!(
+        "Proved {} cycles in {} segments using {} in {:?}",
+        metrics.total_cycles,
+        metrics.segments,
+        metrics.backend,
+        metrics.proving_time,
+    );
+    if let Some(compression_time) = metrics.compression_time {
+        log::info!("Compressed proof in {compression_time:?}");
+    }
+}
@@ -12,61 +12,737 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloy_primitives::{Address, TxHash};
-use anyhow::{Context, Result, ensure};
-use clap::Parser;
-use common::Journal;
-use proof_builder::build_proof;
-use risc0_ethereum_contracts::encode_seal;
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionRequest;
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, TxHash, U256};
+use anyhow::{Context, Result, anyhow, ensure};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use common::{Journal, MessageClaim, eip4788, to_wormhole_address};
+use proof_builder::{
+    InputOptions, SentMessage,
+    admin::{Admin, AdminLink},
+    alert::Alerter,
+    api::{self, ApiState},
+    artifact::{InputArtifact, ProofArtifact},
+    audit::AuditBundle,
+    auth::AuthConfig,
+    bench::{self, BenchInput},
+    breaker::{BreakerLimits, CircuitBreaker},
+    budget::{BudgetLimits, ProvingBudget},
+    cache::InputCache,
+    checkup::Checkup,
+    compressor::Compressor,
+    config::{CommitmentStrategy, DaemonConfig, Finality},
+    daemon::Daemon,
+    destination::{Destination, broadcast, sign_transaction, source_image_id},
+    export::{MessageRecord, write_csv, write_parquet},
+    fetch_input,
+    guests::Registry,
+    heartbeat::Heartbeater,
+    job::MessageId,
+    market::MarketProver,
+    merkle_branches,
+    metrics::Metrics,
+    queue,
+    quote::{Throughput, quote},
+    reconcile::find_undelivered,
+    relayer::{Prover, Relayer},
+    rpc::{EndpointKind, EndpointPool, TransportOptions},
+    selftest,
+    sizes::SizeReport,
+    sla::SlaMonitor,
+    snapshot::{self, SNAPSHOT_DIR, Snapshot},
+    snark::SnarkBackend,
+    source_chain_id,
+    stale::StaleMonitor,
+    store::{FileJobStore, JobStore},
+    throttle::RpcBudget,
+    transaction_messages, tui,
+    vaa::{GuardianClient, HybridMode, VaaRoute},
+    vcr::Cassette,
+    vectors::{SyntheticMessage, TestVector},
+    version::VersionInfo,
+    watcher::finalized_block,
+};
 use risc0_steel::alloy::{
-    network::EthereumWallet,
-    providers::ProviderBuilder,
-    signers::local::PrivateKeySigner,
-    sol,
-    sol_types::{SolCall, SolValue},
-    transports::http::reqwest::Url,
+    providers::ProviderBuilder, signers::local::PrivateKeySigner, transports::http::reqwest::Url,
 };
 use risc0_zkvm::Digest;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::log;
 use tracing_subscriber::EnvFilter;
-use zkvm::NTT_MESSAGE_INCLUSION_ID;
+use zkvm::GuestProgram;
+
+#[derive(Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    #[command(flatten)]
+    proxy: ProxyArgs,
+
+    /// Image ID the embedded default guest must have. The relay refuses to run if it differs,
+    /// e.g. because the guest was rebuilt and no longer matches the deployed contracts
+    #[arg(long, global = true, env = "EXPECTED_IMAGE_ID")]
+    expected_image_id: Option<Digest>,
+
+    /// Directory or HTTP(S) URL of a guest artifact registry. Its guests are loaded at startup and
+    /// used alongside the embedded ones, e.g. to prove for contracts verifying older guest builds
+    #[arg(long, global = true, env = "GUEST_REGISTRY")]
+    guest_registry: Option<Registry>,
+}
 
-sol! {
-    #[sol(rpc)]
-    interface IBoundlessTransceiver {
-      /// @notice Process a message along with its ZK proof of inclusion in the origin chain
-      /// @param encodedMessage The Wormhole encoded message containing the NTT Manager message.
-      /// @param journalData The journal data that the proof commits to
-      /// @param seal The opaque ZK proof seal that allows it to be verified on-chain
-      /// @dev This function verifies the ZK proof, checks the commitments, then forwards the message to the NTT Manager.
-      function receiveMessage(
-          bytes calldata journalData, bytes calldata seal
-      ) external;
+/// The proxy outbound connections go through.
+#[derive(Args)]
+struct ProxyArgs {
+    /// HTTP(S) proxy for every outbound connection: source chain endpoints, destination chains,
+    /// remote provers, the Boundless market and webhooks. Defaults to the proxy set in the
+    /// HTTPS_PROXY and HTTP_PROXY environment variables, if any
+    #[arg(long, global = true)]
+    proxy: Option<Url>,
 
-      bytes32 public immutable imageID;
+    /// Hosts connected to directly rather than through the proxy, comma separated, in addition to
+    /// those in NO_PROXY
+    #[arg(long, global = true, value_delimiter = ',')]
+    no_proxy: Vec<String>,
+}
+
+impl ProxyArgs {
+    /// Routes the outbound connections through the proxy. Every HTTP client of the relay and of
+    /// the libraries it uses reads the proxy from the environment, so the settings are applied by
+    /// setting the standard variables. Local proxies, such as those of cassettes and endpoint
+    /// authentication, are always connected to directly.
+    ///
+    /// Must be called before any other thread is started.
+    fn apply(&self) {
+        let set = |name: &str, value: &str| {
+            // SAFETY: no other thread exists yet that could read the environment concurrently
+            unsafe { std::env::set_var(name, value) }
+        };
+        if let Some(proxy) = &self.proxy {
+            set("HTTP_PROXY", proxy.as_str());
+            set("HTTPS_PROXY", proxy.as_str());
+        }
+        let proxied = ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some());
+        if !proxied {
+            return;
+        }
+        let mut no_proxy: Vec<String> = std::env::var("NO_PROXY")
+            .or_else(|_| std::env::var("no_proxy"))
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .map(str::to_string)
+            .collect();
+        no_proxy.extend(self.no_proxy.iter().cloned());
+        for host in ["127.0.0.1", "localhost"] {
+            if !no_proxy.iter().any(|other| other == host) {
+                no_proxy.push(host.to_string());
+            }
+        }
+        set("NO_PROXY", &no_proxy.join(","));
+        if let Some(proxy) = &self.proxy {
+            log::info!(
+                "Connecting through proxy {}",
+                proxy.host_str().unwrap_or("-")
+            );
+        }
     }
 }
 
-/// Relay an event from the NTT Manager contract on the Source chain to the BoundlessTransceiver contract on the Destination chain.
-/// This will prove the inclusion of the event on the source chain using Steel and then send the proof to the destination chain.
-#[derive(Parser)]
-struct Args {
+#[derive(Subcommand)]
+enum Command {
+    /// Relay an event from the NTT Manager contract on the Source chain to the BoundlessTransceiver contract on the Destination chain.
+    /// This will prove the inclusion of the event on the source chain using Steel and then send the proof to the destination chain.
+    Send(SendArgs),
+
+    /// Watch the source transceivers listed in a config file and relay their messages as they finalize.
+    Watch(WatchArgs),
+
+    /// Compare the image ID of the embedded guest against the one destinations authorize for a chain.
+    CheckImageId(CheckImageIdArgs),
+
+    /// Report whether the commitment of a journal is currently verifiable on a destination chain
+    /// and how long until it expires, as JSON. Fails if it is not verifiable.
+    CheckCommitment(CheckCommitmentArgs),
+
+    /// Validate the setup of a daemon config before deploying it: the source chain endpoints,
+    /// the chain IDs of all chains, the contracts at the configured addresses, the image IDs of
+    /// the destinations and the keys and balances of the delivering wallets. Prints the outcome
+    /// of every check as JSON and fails if any failed.
+    CheckConfig(CheckConfigArgs),
+
+    /// Relay every message a source transaction sent once its block is final: wait for finality,
+    /// commit to a final block, prove and submit
+    RelayTx(RelayTxArgs),
+
+    /// Estimate the cost and latency of proving a message on each backend from a dry run of the
+    /// guest, as JSON
+    CompareBackends(CompareBackendsArgs),
+
+    /// Print the persisted state and transition history of relayed messages as JSON.
+    Jobs(JobsArgs),
+
+    /// Build the guest input of a message and write it to a file, to prove it on a machine without
+    /// access to the source chain.
+    BuildInput(BuildInputArgs),
+
+    /// Break down the size of a guest input written by `build-input`, or of a snapshot, by what it
+    /// holds: block headers, ancestors, receipts, state and storage trie nodes, contract code and
+    /// messages. Prints the breakdown as JSON.
+    InputSize(InputSizeArgs),
+
+    /// Build the guest input of a historical message and check it in as a snapshot, together with
+    /// the journal the guest commits for it, so `cargo test` detects changes of guest behavior.
+    Snapshot(SnapshotArgs),
+
+    /// Build the unsigned destination transaction delivering a proof written by `prove-offline`,
+    /// with its nonce and gas populated, so it can be signed on an offline machine.
+    PrepareTx(PrepareTxArgs),
+
+    /// Sign a transaction prepared by `prepare-tx` without network access, writing it as raw hex.
+    SignTx(SignTxArgs),
+
+    /// Broadcast a raw signed transaction to the destination chain.
+    Broadcast(BroadcastArgs),
+
+    /// Scan a range of source chain blocks for messages and print those that were not delivered to
+    /// their destination as JSON, along with their age.
+    Reconcile(ReconcileArgs),
+
+    /// List the messages a source transceiver sent in a transaction as JSON, with the index each
+    /// is proven by and the index of its log
+    Messages(MessagesArgs),
+
+    /// Print the Merkle branch of every message a source transceiver sent in a transaction as
+    /// JSON, from the journal the `ntt_message_inclusion_merkle` guest committed for them
+    MerkleBranches(MerkleBranchesArgs),
+
+    /// Write one record per persisted message, with the time it entered each stage, its proof
+    /// measurements, gas costs and outcome, for offline analysis and reporting.
+    Export(ExportArgs),
+
+    /// Verify an audit bundle written by `--audit-dir` without network access, printing the block
+    /// commitment that remains to be checked against the source chain.
+    VerifyBundle(VerifyBundleArgs),
+
+    /// Write the fixtures of a synthetic message delivered with a mock proof, for the Foundry
+    /// tests of the destination contract: the encoded message, journal, seal, image ID and
+    /// calldata.
+    TestVectors(TestVectorsArgs),
+
+    /// Execute the embedded guests and their released builds on the same inputs and report the
+    /// change in cycles as JSON, failing on regressions past a threshold.
+    BenchCycles(BenchCyclesArgs),
+
+    /// Show a live terminal dashboard of a relay running `watch` with `--api-addr`.
+    Tui(TuiArgs),
+
+    /// Print the version and commit the relay was built from and, with `--verbose`, the image IDs
+    /// of its embedded guests, the zkVM version and the supported chain specs.
+    Version(VersionArgs),
+}
+
+/// Access to the source chain.
+#[derive(Args)]
+struct SourceArgs {
+    /// Ethereum RPC endpoint URLs, comma separated. Requests fail over between them.
+    #[arg(
+        long,
+        env = "ETH_RPC_URL",
+        value_delimiter = ',',
+        required_unless_present = "vcr_replay"
+    )]
+    eth_rpc_url: Vec<Url>,
+
+    /// Beacon API endpoint URLs for source (ethereum) chain, comma separated. Requests fail over
+    /// between them.
+    #[arg(
+        long,
+        env = "BEACON_API_URL",
+        value_delimiter = ',',
+        required_unless_present = "vcr_replay"
+    )]
+    beacon_api_url: Vec<Url>,
+
+    /// Interval in seconds between health checks of the source chain endpoints
+    #[arg(long, env = "HEALTH_CHECK_SECONDS", default_value_t = 30)]
+    health_check_seconds: u64,
+
+    /// Maximum weighted requests per second sent to each source chain RPC endpoint
+    #[arg(long, env = "RPC_REQUESTS_PER_SECOND")]
+    rpc_requests_per_second: Option<u32>,
+
+    /// Maximum number of concurrent requests to each source chain RPC endpoint
+    #[arg(long, env = "RPC_MAX_CONCURRENT")]
+    rpc_max_concurrent: Option<usize>,
+
+    /// Weight of an RPC method against the request budget as `<method>=<weight>`, overriding the
+    /// defaults. Can be repeated.
+    #[arg(long = "rpc-method-weight", value_parser = parse_method_weight)]
+    rpc_method_weights: Vec<(String, u32)>,
+
+    /// Directory in which preflighted inputs are cached, so re-proving a message does not
+    /// require fetching its state from the RPC again
+    #[arg(long, env = "INPUT_CACHE_DIR")]
+    input_cache_dir: Option<PathBuf>,
+
+    /// Record every response of the source chain endpoints to this cassette, to be replayed with
+    /// `--vcr-replay`
+    #[arg(long, env = "VCR_RECORD", conflicts_with = "vcr_replay")]
+    vcr_record: Option<PathBuf>,
+
+    /// Answer source chain requests from this cassette recorded with `--vcr-record` instead of
+    /// the endpoints, which need not be given
+    #[arg(long, env = "VCR_REPLAY")]
+    vcr_replay: Option<PathBuf>,
+
+    /// TOML file of the headers and basic auth credentials to send to the source chain endpoints
+    #[arg(long, env = "ENDPOINT_AUTH", conflicts_with_all = ["vcr_record", "vcr_replay"])]
+    endpoint_auth: Option<PathBuf>,
+
+    /// Time in seconds allowed for connecting to a source chain endpoint
+    #[arg(long, env = "CONNECT_TIMEOUT_SECONDS", default_value_t = 10)]
+    connect_timeout_seconds: u64,
+
+    /// Time in seconds allowed for each read of a response of a source chain endpoint, after
+    /// which the request fails over to the next endpoint
+    #[arg(long, env = "READ_TIMEOUT_SECONDS", default_value_t = 60)]
+    read_timeout_seconds: u64,
+
+    /// Interval in seconds of TCP keep-alive probes on connections to source chain endpoints, or
+    /// 0 to disable them
+    #[arg(long, env = "TCP_KEEPALIVE_SECONDS", default_value_t = 60)]
+    tcp_keepalive_seconds: u64,
+
+    /// Time in seconds after which idle connections to source chain endpoints are closed
+    #[arg(long, env = "POOL_IDLE_TIMEOUT_SECONDS", default_value_t = 90)]
+    pool_idle_timeout_seconds: u64,
+
+    /// Maximum number of idle connections kept open to each source chain endpoint
+    #[arg(long, env = "POOL_MAX_IDLE_PER_HOST", default_value_t = 16)]
+    pool_max_idle_per_host: usize,
+}
+
+/// Observability and persistence of the relay.
+#[derive(Args)]
+struct ServiceArgs {
+    /// Address to serve the metrics endpoint and status API on while relaying
+    #[arg(long, env = "API_ADDR")]
+    api_addr: Option<SocketAddr>,
+
+    /// Maximum time in seconds from source finalization to destination confirmation before an
+    /// SLA breach alert is fired
+    #[arg(long, env = "SLA_SECONDS", default_value_t = 3600)]
+    sla_seconds: u64,
+
+    /// Time in seconds after source finalization after which an undelivered message is alerted
+    /// on as stale
+    #[arg(long, env = "STALE_AFTER_SECONDS", default_value_t = 7200)]
+    stale_after_seconds: u64,
+
+    /// Window in seconds over which reverted submissions and gas spend are counted by the circuit
+    /// breaker
+    #[arg(long, env = "BREAKER_WINDOW_SECONDS", default_value_t = 3600)]
+    breaker_window_seconds: u64,
+
+    /// Number of reverted submissions within the window past which all submissions are halted
+    #[arg(long, env = "BREAKER_MAX_REVERTS", default_value_t = 3)]
+    breaker_max_reverts: usize,
+
+    /// Gas spend in ETH within the window past which all submissions are halted. Unlimited if
+    /// unset
+    #[arg(long, env = "BREAKER_MAX_SPEND_ETH")]
+    breaker_max_spend_eth: Option<f64>,
+
+    /// Spend in ETH on proofs from the Boundless market per day past which proving waits for the
+    /// next day. Unlimited if unset
+    #[arg(long, env = "MAX_DAILY_MARKET_SPEND_ETH")]
+    max_daily_market_spend_eth: Option<f64>,
+
+    /// Spend in ETH on the market proof of a single message past which the message fails.
+    /// Unlimited if unset
+    #[arg(long, env = "MAX_MESSAGE_MARKET_SPEND_ETH")]
+    max_message_market_spend_eth: Option<f64>,
+
+    /// Spend in USD on proofs from Bonsai per day past which proving waits for the next day.
+    /// Unlimited if unset
+    #[arg(long, env = "MAX_DAILY_BONSAI_SPEND_USD")]
+    max_daily_bonsai_spend_usd: Option<f64>,
+
+    /// Spend in USD on the Bonsai proof of a single message past which the message fails.
+    /// Unlimited if unset
+    #[arg(long, env = "MAX_MESSAGE_BONSAI_SPEND_USD")]
+    max_message_bonsai_spend_usd: Option<f64>,
+
+    /// Number of failed attempts after which a message is moved to the dead-letter queue, where
+    /// it waits for an operator to replay it. Requires `--store-dir`
+    #[arg(long, env = "MAX_ATTEMPTS", default_value_t = 5)]
+    max_attempts: usize,
+
+    /// Webhook that alerts are posted to as JSON
+    #[arg(long, env = "ALERT_WEBHOOK_URL")]
+    alert_webhook_url: Option<Url>,
+
+    /// Directory in which the state of relayed messages is persisted
+    #[arg(long, env = "STORE_DIR")]
+    store_dir: Option<PathBuf>,
+
+    /// Hand proving to prover workers (`prove-offline --queue`) through this queue instead of
+    /// proving on this host: a shared directory or a `redis://` URL
+    #[arg(long, env = "PROOF_QUEUE")]
+    proof_queue: Option<String>,
+
+    /// Seconds to wait for the prover workers to prove a queued message before failing it. Should
+    /// exceed the `--lease-seconds` of the workers, so the claim of a crashed worker expires and
+    /// another worker proves the message in time
+    #[arg(long, env = "PROOF_QUEUE_TIMEOUT_SECONDS", default_value_t = 7200)]
+    proof_queue_timeout_seconds: u64,
+
+    /// Request proofs from the Boundless market on the chain of this RPC URL instead of proving
+    /// on this host. Messages are delivered with set-inclusion proofs of the aggregated proofs,
+    /// which the verifier router of each destination must route to a set verifier
+    #[arg(long, env = "BOUNDLESS_RPC_URL", conflicts_with = "proof_queue")]
+    boundless_rpc_url: Option<Url>,
+
+    /// Private key of the wallet paying for proofs requested from the Boundless market
+    #[arg(long, env = "BOUNDLESS_PRIVATE_KEY", requires = "boundless_rpc_url")]
+    boundless_private_key: Option<PrivateKeySigner>,
+
+    /// Backends to prove on, tried in order, when the Boundless market does not serve a request
+    /// in time, e.g. `bonsai,local`. Without any, proofs wait for the market
+    #[arg(
+        long,
+        env = "MARKET_FALLBACK",
+        value_delimiter = ',',
+        requires = "boundless_rpc_url"
+    )]
+    market_fallback: Vec<SnarkBackend>,
+
+    /// Execute the guest with the profiler before proving, writing a pprof profile of its
+    /// cycles to this path
+    #[arg(long, env = "PPROF_OUT")]
+    pprof_out: Option<PathBuf>,
+
+    /// Write an audit bundle of each delivered message to this directory, to be verified with
+    /// `relay verify-bundle`
+    #[arg(long, env = "AUDIT_DIR")]
+    audit_dir: Option<PathBuf>,
+}
+
+/// Delivery of the Wormhole VAA of messages alongside their proof.
+#[derive(Args)]
+struct WormholeArgs {
+    /// Guardian API serving signed VAAs. If set, the VAA of messages whose source and destination
+    /// WormholeTransceivers are known is delivered alongside the proof
+    #[arg(long, env = "GUARDIAN_API_URL")]
+    guardian_api_url: Option<Url>,
+
+    /// Whether to deliver `both` attestations, or stop at whichever delivers the message `first`
+    #[arg(long, env = "HYBRID_MODE", default_value = "both")]
+    hybrid_mode: HybridMode,
+
+    /// Interval in seconds between requests for a VAA the guardians have not signed yet
+    #[arg(long, env = "VAA_POLL_SECONDS", default_value_t = 10)]
+    vaa_poll_seconds: u64,
+
+    /// Maximum time in seconds to wait for the guardians to sign a VAA
+    #[arg(long, env = "VAA_TIMEOUT_SECONDS", default_value_t = 1800)]
+    vaa_timeout_seconds: u64,
+}
+
+#[derive(Args)]
+struct SendArgs {
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    dest_wallet_private_key: PrivateKeySigner,
+
+    /// Destination chain RPC endpoint URL
+    #[arg(long, env = "DEST_RPC_URL")]
+    dest_rpc_url: Url,
+
+    /// Ethereum block to use for the beacon block commitment.
+    /// This should be the first epoch boundary block after the send transaction was included.
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: u64,
+
+    /// Address of the BoundlessTransceiver contract on the source chain
+    #[arg(long, env = "SRC_TRANSCEIVER_ADDRESS")]
+    src_transceiver_addr: Address,
+
+    /// Address of the BoundlessTransceiver contract on the destination chain
+    #[arg(long, env = "DEST_TRANSCEIVER_ADDRESS")]
+    dst_transceiver_addr: Address,
+
+    /// Address of the WormholeTransceiver contract on the source chain, to deliver the VAA of the
+    /// message alongside the proof
+    #[arg(
+        long,
+        env = "SRC_WORMHOLE_TRANSCEIVER_ADDRESS",
+        requires = "dst_wormhole_transceiver_addr",
+        requires = "guardian_api_url"
+    )]
+    src_wormhole_transceiver_addr: Option<Address>,
+
+    /// Address of the WormholeTransceiver contract on the destination chain
+    #[arg(
+        long,
+        env = "DEST_WORMHOLE_TRANSCEIVER_ADDRESS",
+        requires = "src_wormhole_transceiver_addr"
+    )]
+    dst_wormhole_transceiver_addr: Option<Address>,
+
+    /// Instead of broadcasting the `receiveMessage` transaction, sign it and write it as raw hex to
+    /// this path, or to stdout if `-`, e.g. to send it through a private orderflow endpoint
+    #[arg(long, conflicts_with = "src_wormhole_transceiver_addr")]
+    raw_tx_out: Option<PathBuf>,
+
+    /// Transaction hash of the send transaction on the source chain
+    #[arg(long, env = "TX_HASH")]
+    tx_hash: TxHash,
+
+    /// Index of the message among those the transceiver sent in the transaction, as listed by
+    /// `relay messages`
+    #[arg(long, default_value_t = 0)]
+    index: usize,
+
+    /// Name of the embedded guest program to prove with. Defaults to the guest matching the image
+    /// ID of the destination contract
+    #[arg(long, env = "GUEST")]
+    guest: Option<String>,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    #[command(flatten)]
+    service: ServiceArgs,
+
+    #[command(flatten)]
+    wormhole: WormholeArgs,
+}
+
+#[derive(Args)]
+struct RelayTxArgs {
     /// Ethereum private key
     #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
     dest_wallet_private_key: PrivateKeySigner,
 
-    /// Ethereum RPC endpoint URL
-    #[arg(long, env = "ETH_RPC_URL")]
-    eth_rpc_url: Url,
+    /// Destination chain RPC endpoint URL
+    #[arg(long, env = "DEST_RPC_URL")]
+    dest_rpc_url: Url,
+
+    /// Address of the BoundlessTransceiver contract on the source chain
+    #[arg(long, env = "SRC_TRANSCEIVER_ADDRESS")]
+    src_transceiver_addr: Address,
+
+    /// Address of the BoundlessTransceiver contract on the destination chain
+    #[arg(long, env = "DEST_TRANSCEIVER_ADDRESS")]
+    dst_transceiver_addr: Address,
+
+    /// Transaction hash of the send transaction on the source chain
+    #[arg(long, env = "TX_HASH")]
+    tx_hash: TxHash,
+
+    /// When the block of the transaction is final enough to prove: `finalized`, `safe` or a
+    /// number of confirmations
+    #[arg(long, env = "FINALITY", default_value = "finalized")]
+    finality: Finality,
+
+    /// Which block to commit to: the final block at the time the transaction became final
+    /// (`finalized`), or the block of the transaction (`message_block`)
+    #[arg(long, default_value = "finalized")]
+    commitment: CommitmentStrategy,
+
+    /// Interval in seconds between checks of whether the transaction is final
+    #[arg(long, default_value_t = 12)]
+    poll_seconds: u64,
+
+    /// Name of the embedded guest program to prove with. Defaults to the guest matching the image
+    /// ID of the destination contract
+    #[arg(long, env = "GUEST")]
+    guest: Option<String>,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    #[command(flatten)]
+    service: ServiceArgs,
+
+    #[command(flatten)]
+    wormhole: WormholeArgs,
+}
+
+#[derive(Args)]
+struct WatchArgs {
+    /// Path of the TOML config listing the source transceivers to watch and their destinations
+    #[arg(long, env = "RELAY_CONFIG")]
+    config: PathBuf,
+
+    /// Ethereum private key paying for delivery on the destination chains
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    dest_wallet_private_key: PrivateKeySigner,
+
+    /// Maximum number of messages relayed at once
+    #[arg(long, env = "MAX_CONCURRENT_JOBS", default_value_t = 4)]
+    max_concurrent_jobs: usize,
+
+    /// Maximum number of receipts compressed in one batch
+    #[arg(long, env = "COMPRESS_BATCH_SIZE", default_value_t = 8)]
+    compress_batch_size: usize,
+
+    /// Maximum time in seconds to wait for more receipts before compressing a batch
+    #[arg(long, env = "COMPRESS_BATCH_WAIT_SECONDS", default_value_t = 10)]
+    compress_batch_wait_seconds: u64,
+
+    /// Bearer token authenticating requests to the admin API. The admin API is served under
+    /// `/admin` on the API address if set
+    #[arg(long, env = "ADMIN_TOKEN", requires = "api_addr")]
+    admin_token: Option<String>,
+
+    /// Endpoint to periodically post a heartbeat to, signed by the wallet of the daemon, so a
+    /// fleet operator notices relayers that died silently
+    #[arg(long, env = "HEARTBEAT_URL")]
+    heartbeat_url: Option<Url>,
+
+    /// Interval in seconds between heartbeats
+    #[arg(long, env = "HEARTBEAT_SECONDS", default_value_t = 60)]
+    heartbeat_seconds: u64,
+
+    /// Before watching, execute the guests of the config on a synthetic message and deliver its
+    /// mock proof to a simulated destination, refusing to start if either fails
+    #[arg(long, env = "SELF_TEST")]
+    self_test: bool,
+
+    #[command(flatten)]
+    source: SourceArgs,
+
+    #[command(flatten)]
+    service: ServiceArgs,
+
+    #[command(flatten)]
+    wormhole: WormholeArgs,
+}
+
+#[derive(Args)]
+struct CheckImageIdArgs {
+    /// Destination chain RPC endpoint URL
+    #[arg(long, env = "DEST_RPC_URL")]
+    dest_rpc_url: Url,
+
+    /// Addresses of the BoundlessTransceiver contracts on the destination chain
+    #[arg(
+        long,
+        env = "DEST_TRANSCEIVER_ADDRESS",
+        value_delimiter = ',',
+        required = true
+    )]
+    dst_transceiver_addr: Vec<Address>,
+
+    /// Wormhole chain ID of the source chain whose image ID is compared, as the contracts configure
+    /// an image ID per source chain
+    #[arg(long, env = "SOURCE_CHAIN")]
+    source_chain: u16,
+
+    /// Name of the embedded guest program whose image ID is expected
+    #[arg(long, env = "GUEST", default_value = "ntt_message_inclusion")]
+    guest: String,
+}
+
+#[derive(Args)]
+struct CheckCommitmentArgs {
+    /// The ABI encoded journal, as a file holding it in binary or hex, or as hex
+    #[arg(long)]
+    journal: String,
 
     /// Destination chain RPC endpoint URL
     #[arg(long, env = "DEST_RPC_URL")]
     dest_rpc_url: Url,
 
-    /// Beacon API endpoint URL for source (ethereum) chain
-    #[arg(long, env = "BEACON_API_URL")]
-    beacon_api_url: Url,
+    /// Address of the BoundlessTransceiver contract on the destination chain, whose validator
+    /// checks consensus commitments. Beacon commitments are checked against the EIP-4788 beacon
+    /// roots contract of the chain instead.
+    #[arg(long, env = "DEST_TRANSCEIVER_ADDRESS")]
+    dst_transceiver_addr: Option<Address>,
+}
+
+#[derive(Args)]
+struct CheckConfigArgs {
+    /// Path of the TOML config listing the source transceivers and their destinations
+    #[arg(long, env = "RELAY_CONFIG")]
+    config: PathBuf,
+
+    /// Ethereum private key paying for delivery on the destination chains
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    dest_wallet_private_key: PrivateKeySigner,
+
+    /// Balance in ETH each delivering wallet must at least hold on the chains it delivers to.
+    /// Wallets without any balance fail regardless
+    #[arg(long, env = "MIN_WALLET_BALANCE_ETH", default_value_t = 0.0)]
+    min_balance_eth: f64,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Args)]
+struct CompareBackendsArgs {
+    /// Ethereum block to use for the beacon block commitment.
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: u64,
+
+    /// Address of the BoundlessTransceiver contract on the source chain
+    #[arg(long, env = "SRC_TRANSCEIVER_ADDRESS")]
+    src_transceiver_addr: Address,
+
+    /// Transaction hash of the send transaction on the source chain
+    #[arg(long, env = "TX_HASH")]
+    tx_hash: TxHash,
+
+    /// Index of the message among those the transceiver sent in the transaction, as listed by
+    /// `relay messages`
+    #[arg(long, default_value_t = 0)]
+    index: usize,
+
+    /// Name of the embedded guest program to prove with
+    #[arg(long, env = "GUEST", default_value = "ntt_message_inclusion")]
+    guest: String,
+
+    /// Daemon config whose `[pricing]` and `[market]` tables price the backends. Defaults to the
+    /// default prices
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Cycles per second proven on this host without GPUs
+    #[arg(long, default_value_t = 100_000)]
+    cpu_hz: u64,
 
+    /// Cycles per second proven on this host with GPUs
+    #[arg(long, default_value_t = 1_000_000)]
+    gpu_hz: u64,
+
+    /// Cycles per second proven on Bonsai
+    #[arg(long, default_value_t = 2_000_000)]
+    bonsai_hz: u64,
+
+    /// Cycles per second proven by the prover fulfilling a market request
+    #[arg(long, default_value_t = 2_000_000)]
+    market_hz: u64,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Args)]
+struct BuildInputArgs {
     /// Ethereum block to use for the beacon block commitment.
     /// This should be the first epoch boundary block after the send transaction was included.
     #[arg(long, env = "COMMITMENT_BLOCK")]
@@ -76,76 +752,1309 @@ struct Args {
     #[arg(long, env = "SRC_TRANSCEIVER_ADDRESS")]
     src_transceiver_addr: Address,
 
+    /// Transaction hash of the send transaction on the source chain
+    #[arg(long, env = "TX_HASH")]
+    tx_hash: TxHash,
+
+    /// Index of the message among those the transceiver sent in the transaction, as listed by
+    /// `relay messages`
+    #[arg(long, default_value_t = 0)]
+    index: usize,
+
+    /// Name of the embedded guest program the input is for
+    #[arg(long, env = "GUEST", default_value = "ntt_message_inclusion")]
+    guest: String,
+
+    /// Path to write the input to
+    #[arg(long)]
+    out: PathBuf,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Args)]
+struct InputSizeArgs {
+    /// Input written by `build-input`
+    #[arg(
+        long,
+        required_unless_present = "snapshot",
+        conflicts_with = "snapshot"
+    )]
+    input: Option<PathBuf>,
+
+    /// Name of a snapshot whose input to break down instead
+    #[arg(long)]
+    snapshot: Option<String>,
+
+    /// Directory of the snapshots
+    #[arg(long, default_value = SNAPSHOT_DIR)]
+    snapshot_dir: PathBuf,
+
+    /// Number of levels of nested fields listed
+    #[arg(long, default_value_t = 4)]
+    depth: usize,
+}
+
+#[derive(Args)]
+struct SnapshotArgs {
+    /// Name of the snapshot, such as the source chain and what the message is
+    #[arg(long)]
+    name: String,
+
+    /// Directory to write the snapshot to
+    #[arg(long, default_value = SNAPSHOT_DIR)]
+    dir: PathBuf,
+
+    /// Ethereum block to use for the beacon block commitment.
+    #[arg(long, env = "COMMITMENT_BLOCK")]
+    commitment_block: u64,
+
+    /// Address of the BoundlessTransceiver contract on the source chain
+    #[arg(long, env = "SRC_TRANSCEIVER_ADDRESS")]
+    src_transceiver_addr: Address,
+
+    /// Transaction hash of the send transaction on the source chain
+    #[arg(long, env = "TX_HASH")]
+    tx_hash: TxHash,
+
+    /// Name of the embedded guest program the input is for
+    #[arg(long, env = "GUEST", default_value = "ntt_message_inclusion")]
+    guest: String,
+
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Args)]
+struct PrepareTxArgs {
+    /// Proof file written by `prove-offline`
+    #[arg(long)]
+    proof: PathBuf,
+
+    /// Destination chain RPC endpoint URL
+    #[arg(long, env = "DEST_RPC_URL")]
+    dest_rpc_url: Url,
+
     /// Address of the BoundlessTransceiver contract on the destination chain
     #[arg(long, env = "DEST_TRANSCEIVER_ADDRESS")]
     dst_transceiver_addr: Address,
 
+    /// Address of the account that will sign and pay for the transaction
+    #[arg(long)]
+    from: Address,
+
+    /// Path to write the unsigned transaction to, as JSON
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Args)]
+struct SignTxArgs {
+    /// Unsigned transaction written by `prepare-tx`
+    #[arg(long)]
+    tx: PathBuf,
+
+    /// Ethereum private key
+    #[arg(long, env = "ETH_WALLET_PRIVATE_KEY")]
+    dest_wallet_private_key: PrivateKeySigner,
+
+    /// Path to write the signed transaction to, as raw hex
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Args)]
+struct BroadcastArgs {
+    /// Signed transaction written by `sign-tx`, as raw hex
+    #[arg(long)]
+    raw_tx: PathBuf,
+
+    /// Destination chain RPC endpoint URL
+    #[arg(long, env = "DEST_RPC_URL")]
+    dest_rpc_url: Url,
+}
+
+#[derive(Args)]
+struct MessagesArgs {
+    /// Ethereum RPC endpoint URLs, comma separated. Requests fail over between them.
+    #[arg(long, env = "ETH_RPC_URL", value_delimiter = ',', required = true)]
+    eth_rpc_url: Vec<Url>,
+
+    /// Address of the transceiver contract on the source chain
+    #[arg(long, env = "SRC_TRANSCEIVER_ADDRESS")]
+    src_transceiver_addr: Address,
+
     /// Transaction hash of the send transaction on the source chain
     #[arg(long, env = "TX_HASH")]
     tx_hash: TxHash,
+
+    /// Name of the embedded guest program, selecting the kind of transceiver
+    #[arg(long, env = "GUEST", default_value = "ntt_message_inclusion")]
+    guest: String,
+}
+
+#[derive(Args)]
+struct MerkleBranchesArgs {
+    #[command(flatten)]
+    messages: MessagesArgs,
+
+    /// The ABI encoded Merkle batch journal, as a file holding it in binary or hex, or as hex
+    #[arg(long)]
+    journal: String,
+}
+
+#[derive(Args)]
+struct ReconcileArgs {
+    /// Path of the TOML config listing the source transceivers and their destinations
+    #[arg(long, env = "RELAY_CONFIG")]
+    config: PathBuf,
+
+    /// Ethereum RPC endpoint URLs, comma separated. Requests fail over between them.
+    #[arg(long, env = "ETH_RPC_URL", value_delimiter = ',', required = true)]
+    eth_rpc_url: Vec<Url>,
+
+    /// First source chain block to scan
+    #[arg(long)]
+    from_block: u64,
+
+    /// Last source chain block to scan. Defaults to the latest finalized block.
+    #[arg(long)]
+    to_block: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    /// Directory in which the state of relayed messages is persisted
+    #[arg(long, env = "STORE_DIR")]
+    store_dir: PathBuf,
+
+    /// Format of the export
+    #[arg(long, value_enum, default_value_t = ExportFormat::Csv)]
+    format: ExportFormat,
+
+    /// Path to write the export to
+    #[arg(long)]
+    out: PathBuf,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum VectorFormat {
+    Solidity,
+    Json,
+}
+
+#[derive(Args)]
+struct TestVectorsArgs {
+    /// Name of the embedded guest program whose image ID the proof is for
+    #[arg(long, env = "GUEST", default_value = "ntt_message_inclusion")]
+    guest: String,
+
+    /// Selector of the mock verifier, as in `new RiscZeroMockVerifier(selector)`
+    #[arg(long, default_value = "0x00000000")]
+    selector: FixedBytes<4>,
+
+    /// Wormhole chain ID of the source chain
+    #[arg(long, default_value_t = 666)]
+    source_chain: u16,
+
+    /// Wormhole chain ID of the destination chain
+    #[arg(long, default_value_t = 333)]
+    recipient_chain: u16,
+
+    /// Address of the NTT manager on the destination chain
+    #[arg(long, default_value_t = Address::ZERO)]
+    recipient_manager: Address,
+
+    /// Recipient of the transferred tokens
+    #[arg(long)]
+    recipient: Option<Address>,
+
+    /// Slot of the beacon block the message is committed to
+    #[arg(long, default_value_t = 1_234_567_890)]
+    slot: u64,
+
+    /// Format of the fixtures
+    #[arg(long, value_enum, default_value_t = VectorFormat::Solidity)]
+    format: VectorFormat,
+
+    /// Name of the generated Solidity library
+    #[arg(long, default_value = "TestVectors")]
+    library: String,
+
+    /// Path to write the fixtures to. Defaults to stdout
+    #[arg(long)]
+    out: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct VerifyBundleArgs {
+    /// Audit bundle to verify
+    #[arg(long)]
+    bundle: PathBuf,
+}
+
+#[derive(Args)]
+struct BenchCyclesArgs {
+    /// Directory holding the ELF of each released guest as `<guest name>.elf`
+    #[arg(long, env = "BASELINE_ARTIFACTS_DIR")]
+    artifacts_dir: PathBuf,
+
+    /// Directory of the snapshots whose inputs are executed
+    #[arg(long, default_value = SNAPSHOT_DIR)]
+    snapshot_dir: PathBuf,
+
+    /// Inputs written by `build-input` to execute in addition to the snapshots
+    #[arg(long)]
+    input: Vec<PathBuf>,
+
+    /// Increase in cycles, in percent of the released build, past which the command fails
+    #[arg(long)]
+    max_regression_percent: Option<f64>,
+}
+
+#[derive(Args)]
+struct TuiArgs {
+    /// Base URL of the API of the relay, e.g. `http://127.0.0.1:9090/`
+    #[arg(long, env = "RELAY_API_URL")]
+    api_url: Url,
+
+    /// Interval in seconds between refreshes of the dashboard
+    #[arg(long, default_value_t = 2)]
+    refresh_seconds: u64,
+}
+
+#[derive(Args)]
+struct VersionArgs {
+    /// Also print the embedded guests, the zkVM version and the supported chain specs
+    #[arg(long, short)]
+    verbose: bool,
+
+    /// Print as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Args)]
+struct JobsArgs {
+    /// Directory in which the state of relayed messages is persisted
+    #[arg(long, env = "STORE_DIR")]
+    store_dir: PathBuf,
+
+    /// Only print the message with this ID (`<tx hash>-<index>`)
+    #[arg(long)]
+    id: Option<MessageId>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
-    let args = Args::try_parse()?;
+    let cli = Cli::try_parse()?;
+    if let Some(expected) = cli.expected_image_id {
+        check_pinned_image_id(expected)?;
+    }
+    // Before the runtime starts its worker threads
+    cli.proxy.apply();
+    tokio::runtime::Runtime::new()?.block_on(async move {
+        if let Some(registry) = cli.guest_registry {
+            registry
+                .load()
+                .await
+                .with_context(|| format!("failed to load guests from {registry}"))?;
+        }
+        run(cli.command).await
+    })
+}
+
+async fn run(command: Command) -> Result<()> {
+    match command {
+        Command::Send(args) => send(args).await,
+        Command::Watch(args) => watch(args).await,
+        Command::CheckImageId(args) => check_image_id(args).await,
+        Command::CheckCommitment(args) => check_commitment(args).await,
+        Command::CheckConfig(args) => check_config(args).await,
+        Command::CompareBackends(args) => compare_backends(args).await,
+        Command::RelayTx(args) => relay_tx(args).await,
+        Command::Jobs(args) => jobs(args),
+        Command::BuildInput(args) => build_input(args).await,
+        Command::InputSize(args) => input_size(args),
+        Command::Snapshot(args) => snapshot(args).await,
+        Command::PrepareTx(args) => prepare_tx(args).await,
+        Command::SignTx(args) => sign_tx(args).await,
+        Command::Broadcast(args) => broadcast_tx(args).await,
+        Command::Reconcile(args) => reconcile(args).await,
+        Command::Messages(args) => messages(args).await,
+        Command::MerkleBranches(args) => branches(args).await,
+        Command::Export(args) => export(args),
+        Command::VerifyBundle(args) => verify_bundle(args),
+        Command::TestVectors(args) => test_vectors(args),
+        Command::BenchCycles(args) => bench_cycles(args),
+        Command::Tui(args) => {
+            tui::run(args.api_url, Duration::from_secs(args.refresh_seconds)).await
+        }
+        Command::Version(args) => version(args),
+    }
+}
+
+/// Ensures the embedded default guest has the `expected` image ID, so a rebuilt guest that the
+/// destination contracts would reject is not deployed by accident.
+fn check_pinned_image_id(expected: Digest) -> Result<()> {
+    let guest = &zkvm::NTT_MESSAGE_INCLUSION;
+    let embedded = Digest::from(guest.image_id);
+    ensure!(
+        embedded == expected,
+        "Embedded guest {} has image ID {embedded}, but {expected} is expected. Build the guest \
+         from the revision the contracts were configured with, or update the expected image ID \
+         after updating the contracts",
+        guest.name
+    );
+    log::info!("Embedded guest {} has the expected image ID", guest.name);
+    Ok(())
+}
+
+fn parse_method_weight(s: &str) -> Result<(String, u32), String> {
+    let (method, weight) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected <method>=<weight>, got {s}"))?;
+    let weight = weight
+        .parse()
+        .map_err(|e| format!("invalid weight {weight}: {e}"))?;
+    Ok((method.to_string(), weight))
+}
+
+fn guest_program(name: &str) -> Result<&'static GuestProgram> {
+    GuestProgram::by_name(name).with_context(|| format!("Unknown guest program: {name}"))
+}
+
+impl SourceArgs {
+    /// Creates the pools of source chain RPC and beacon API endpoints, recording their requests in
+    /// `metrics` if given.
+    fn pools(
+        &self,
+        metrics: Option<&Arc<Metrics>>,
+    ) -> Result<(Arc<EndpointPool>, Arc<EndpointPool>)> {
+        let mut budget = RpcBudget {
+            requests_per_second: self.rpc_requests_per_second,
+            max_concurrent: self.rpc_max_concurrent,
+            ..Default::default()
+        };
+        budget
+            .weights
+            .extend(self.rpc_method_weights.iter().cloned());
+        let (rpc_urls, beacon_urls) = match (&self.vcr_record, &self.vcr_replay) {
+            (Some(path), _) => {
+                let cassette = Cassette::record(path)?;
+                (
+                    self.eth_rpc_url
+                        .iter()
+                        .map(|url| cassette.serve("rpc", Some(url.clone())))
+                        .collect::<Result<_>>()?,
+                    self.beacon_api_url
+                        .iter()
+                        .map(|url| cassette.serve("beacon", Some(url.clone())))
+                        .collect::<Result<_>>()?,
+                )
+            }
+            (None, Some(path)) => {
+                let cassette = Cassette::replay(path)?;
+                (
+                    vec![cassette.serve("rpc", None)?],
+                    vec![cassette.serve("beacon", None)?],
+                )
+            }
+            (None, None) => (self.eth_rpc_url.clone(), self.beacon_api_url.clone()),
+        };
+        let mut rpc = EndpointPool::new(EndpointKind::ExecutionRpc, rpc_urls)?.with_budget(budget);
+        let mut beacon = EndpointPool::new(EndpointKind::BeaconApi, beacon_urls)?;
+        let auth = self
+            .endpoint_auth
+            .as_ref()
+            .map(AuthConfig::load)
+            .transpose()?;
+        let transport = TransportOptions {
+            connect_timeout: Duration::from_secs(self.connect_timeout_seconds),
+            read_timeout: Duration::from_secs(self.read_timeout_seconds),
+            tcp_keepalive: (self.tcp_keepalive_seconds > 0)
+                .then(|| Duration::from_secs(self.tcp_keepalive_seconds)),
+            pool_idle_timeout: Duration::from_secs(self.pool_idle_timeout_seconds),
+            pool_max_idle_per_host: self.pool_max_idle_per_host,
+        };
+        rpc = rpc.with_transport(&transport, auth.as_ref())?;
+        beacon = beacon.with_transport(&transport, auth.as_ref())?;
+        if let Some(metrics) = metrics {
+            rpc = rpc.with_metrics(metrics.clone());
+            beacon = beacon.with_metrics(metrics.clone());
+        }
+        Ok((Arc::new(rpc), Arc::new(beacon)))
+    }
+
+    fn input_options(&self) -> Result<InputOptions> {
+        Ok(InputOptions {
+            cache: self
+                .input_cache_dir
+                .as_ref()
+                .map(InputCache::open)
+                .transpose()?,
+            ..Default::default()
+        })
+    }
+}
+
+/// Sets up the relayer shared by all messages and starts serving the API, if enabled. With an
+/// `admin_token`, the admin API is served too, returning the link the daemon is controlled
+/// through. The daemon `config` is shown on the dashboard.
+fn relayer(
+    source: &SourceArgs,
+    service: &ServiceArgs,
+    wormhole: &WormholeArgs,
+    compressor: Option<Compressor>,
+    admin_token: Option<String>,
+    config: Option<&DaemonConfig>,
+) -> Result<(Arc<Relayer>, Option<AdminLink>)> {
+    let offer = config
+        .map(|config| config.market.clone())
+        .unwrap_or_default();
+    let prover = match (&service.proof_queue, &service.boundless_rpc_url) {
+        (Some(location), _) => Prover::Workers(
+            queue::open(location)?,
+            Duration::from_secs(service.proof_queue_timeout_seconds),
+        ),
+        (None, Some(rpc_url)) => {
+            let private_key = service
+                .boundless_private_key
+                .clone()
+                .context("--boundless-private-key is required with --boundless-rpc-url")?;
+            for backend in &service.market_fallback {
+                backend.check()?;
+            }
+            Prover::Market(
+                MarketProver::new(rpc_url.clone(), private_key)
+                    .with_offer(offer.clone())
+                    .with_fallback(service.market_fallback.clone()),
+            )
+        }
+        // Fail before doing any work if no Groth16 seal could be produced in the end
+        (None, None) => Prover::Backend(SnarkBackend::detect()?),
+    };
+
+    let metrics = Arc::new(Metrics::default());
+    let (rpc, beacon) = source.pools(Some(&metrics))?;
+    let health_check_interval = Duration::from_secs(source.health_check_seconds);
+    rpc.spawn_health_checks(health_check_interval);
+    beacon.spawn_health_checks(health_check_interval);
 
-    // Create an alloy provider for that private key and URL.
-    let wallet = EthereumWallet::from(args.dest_wallet_private_key);
-    let provider = ProviderBuilder::new()
-        .wallet(wallet)
-        .connect_http(args.eth_rpc_url.clone());
+    let store = service
+        .store_dir
+        .as_ref()
+        .map(FileJobStore::open)
+        .transpose()?
+        .map(|store| Arc::new(store) as Arc<dyn JobStore>);
+    let budget = ProvingBudget::new(
+        BudgetLimits {
+            daily_market_wei: service
+                .max_daily_market_spend_eth
+                .map(|eth| (eth * 1e18) as u128),
+            message_market_wei: service
+                .max_message_market_spend_eth
+                .map(|eth| (eth * 1e18) as u128),
+            daily_usd: service.max_daily_bonsai_spend_usd,
+            message_usd: service.max_message_bonsai_spend_usd,
+        },
+        store.clone(),
+    )?;
+    let breaker = Arc::new(CircuitBreaker::new(
+        BreakerLimits {
+            window: Duration::from_secs(service.breaker_window_seconds),
+            max_reverts: service.breaker_max_reverts,
+            max_spend_wei: service
+                .breaker_max_spend_eth
+                .map(|eth| (eth * 1e18) as u128),
+        },
+        metrics.clone(),
+        Alerter::new(service.alert_webhook_url.clone()),
+    ));
+    let (admin, admin_link) = admin_token
+        .map(|token| Admin::new(token, breaker.clone()))
+        .unzip();
 
-    let prove_info = build_proof(
-        args.tx_hash,
+    let relayer = Arc::new(Relayer {
+        rpc,
+        beacon,
+        input: source.input_options()?,
+        prover,
+        compressor,
+        store,
+        max_attempts: service.max_attempts,
+        sla: SlaMonitor::new(
+            Duration::from_secs(service.sla_seconds),
+            metrics.clone(),
+            Alerter::new(service.alert_webhook_url.clone()),
+        ),
+        stale: Arc::new(StaleMonitor::new(
+            Duration::from_secs(service.stale_after_seconds),
+            metrics.clone(),
+            Alerter::new(service.alert_webhook_url.clone()),
+        )),
+        breaker,
+        metrics,
+        pprof_out: service.pprof_out.clone(),
+        guardian: wormhole.guardian_api_url.clone().map(|api_url| {
+            GuardianClient::new(
+                api_url,
+                wormhole.hybrid_mode,
+                Duration::from_secs(wormhole.vaa_poll_seconds),
+                Duration::from_secs(wormhole.vaa_timeout_seconds),
+            )
+        }),
+        audit_dir: service.audit_dir.clone(),
+        pricing: config
+            .map(|config| config.pricing.clone())
+            .unwrap_or_default(),
+        offer,
+        budget,
+    });
+
+    if let Some(addr) = service.api_addr {
+        let api = Arc::new(ApiState {
+            metrics: relayer.metrics.clone(),
+            store: relayer.store.clone(),
+            admin,
+            config: config.cloned(),
+            relayer: Some(relayer.clone()),
+        });
+        tokio::spawn(async move {
+            if let Err(e) = api::serve(addr, api).await {
+                log::error!("API server failed: {e:#}");
+            }
+        });
+    }
+    Ok((relayer, admin_link))
+}
+
+async fn send(args: SendArgs) -> Result<()> {
+    let (relayer, _) = relayer(
+        &args.source,
+        &args.service,
+        &args.wormhole,
+        None,
+        None,
+        None,
+    )?;
+    let signer = args.dest_wallet_private_key;
+    let destination =
+        Destination::new(args.dst_transceiver_addr, args.dest_rpc_url, signer.clone());
+    let guest = select_guest(
+        &relayer,
+        &destination,
+        args.src_transceiver_addr,
+        args.guest.as_deref(),
+    )
+    .await?;
+
+    let id = MessageId {
+        tx_hash: args.tx_hash,
+        index: args.index,
+    };
+    if let Some(raw_tx_out) = &args.raw_tx_out {
+        let proof = relayer
+            .prove(id, args.src_transceiver_addr, guest, args.commitment_block)
+            .await?;
+        let tx = destination.receive_message_tx(&proof, guest).await?;
+        let tx = destination.fill(tx, signer.address()).await?;
+        let raw_tx = sign_transaction(tx, signer).await?;
+        if raw_tx_out.as_os_str() == "-" {
+            println!("{raw_tx}");
+        } else {
+            fs::write(raw_tx_out, raw_tx.to_string())
+                .with_context(|| format!("failed to write {}", raw_tx_out.display()))?;
+            log::info!("Wrote signed transaction to {}", raw_tx_out.display());
+        }
+        return Ok(());
+    }
+
+    let vaa = args
+        .src_wormhole_transceiver_addr
+        .zip(args.dst_wormhole_transceiver_addr)
+        .map(|(emitter, wormhole_transceiver)| VaaRoute {
+            emitter,
+            wormhole_transceiver,
+        });
+    relayer
+        .relay(
+            id,
+            args.src_transceiver_addr,
+            &destination,
+            guest,
+            args.commitment_block,
+            vaa,
+        )
+        .await
+}
+
+/// The guest named `name`, or the one `destination` verifies proofs of the messages of the source
+/// transceiver `source` with.
+async fn select_guest(
+    relayer: &Relayer,
+    destination: &Destination,
+    source: Address,
+    name: Option<&str>,
+) -> Result<&'static GuestProgram> {
+    if let Some(name) = name {
+        return guest_program(name);
+    }
+    let source_chain = relayer
+        .rpc
+        .run(|endpoint| async move { source_chain_id(&endpoint.provider(), source).await })
+        .await?;
+    destination.guest(source_chain).await
+}
+
+async fn relay_tx(args: RelayTxArgs) -> Result<()> {
+    let (relayer, _) = relayer(
+        &args.source,
+        &args.service,
+        &args.wormhole,
+        None,
+        None,
+        None,
+    )?;
+    let destination = Destination::new(
+        args.dst_transceiver_addr,
+        args.dest_rpc_url,
+        args.dest_wallet_private_key,
+    );
+    let guest = select_guest(
+        &relayer,
+        &destination,
         args.src_transceiver_addr,
-        args.eth_rpc_url,
-        args.beacon_api_url,
+        args.guest.as_deref(),
+    )
+    .await?;
+    let (tx_hash, source) = (args.tx_hash, args.src_transceiver_addr);
+    let poll_interval = Duration::from_secs(args.poll_seconds);
+
+    // Wait for the transaction to be included, then for its block to be final
+    let execution_block = loop {
+        let block_number = relayer
+            .rpc
+            .run(|endpoint| async move {
+                let receipt = endpoint.provider().get_transaction_receipt(tx_hash).await?;
+                Ok(receipt.and_then(|receipt| receipt.block_number))
+            })
+            .await?;
+        match block_number {
+            Some(block_number) => break block_number,
+            None => log::info!("Waiting for transaction {tx_hash} to be included"),
+        }
+        tokio::time::sleep(poll_interval).await;
+    };
+    let finalized = loop {
+        let finalized = finalized_block(&relayer.rpc, args.finality).await?;
+        if finalized >= execution_block {
+            break finalized;
+        }
+        log::info!(
+            "Waiting for block {execution_block} to be final, {} blocks to go",
+            execution_block - finalized
+        );
+        tokio::time::sleep(poll_interval).await;
+    };
+    let commitment_block = match args.commitment {
+        CommitmentStrategy::Finalized => finalized,
+        CommitmentStrategy::MessageBlock => execution_block,
+    };
+
+    let messages = relayer
+        .rpc
+        .run(|endpoint| async move {
+            transaction_messages(&endpoint.provider(), tx_hash, source, guest.transceiver).await
+        })
+        .await?;
+    ensure!(
+        !messages.is_empty(),
+        "Transaction {tx_hash} sent no messages through {source}"
+    );
+    log::info!(
+        "Relaying {} messages of {tx_hash}, committing to block {commitment_block}",
+        messages.len()
+    );
+    for message in messages {
+        relayer
+            .relay(
+                message.id,
+                source,
+                &destination,
+                guest,
+                commitment_block,
+                None,
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+async fn watch(args: WatchArgs) -> Result<()> {
+    let config = DaemonConfig::load(&args.config)?;
+    if args.self_test {
+        selftest::run_all(&config).await?;
+    }
+    let compressor = Compressor::spawn(
+        args.compress_batch_size,
+        Duration::from_secs(args.compress_batch_wait_seconds),
+    );
+    let (relayer, admin_link) = relayer(
+        &args.source,
+        &args.service,
+        &args.wormhole,
+        Some(compressor),
+        args.admin_token,
+        Some(&config),
+    )?;
+    relayer
+        .stale
+        .clone()
+        .spawn_checks(Duration::from_secs(config.poll_interval_secs));
+
+    let mut daemon = Daemon::new(
+        config,
+        relayer,
+        args.dest_wallet_private_key.clone(),
+        args.max_concurrent_jobs,
+    )?;
+    if let Some(link) = admin_link {
+        daemon = daemon.with_admin(link);
+    }
+    if let Some(url) = args.heartbeat_url {
+        daemon = daemon.with_heartbeat(
+            Heartbeater::new(url, args.dest_wallet_private_key),
+            Duration::from_secs(args.heartbeat_seconds),
+        );
+    }
+    daemon.run().await
+}
+
+async fn check_image_id(args: CheckImageIdArgs) -> Result<()> {
+    let guest = guest_program(&args.guest)?;
+    let guest_image_id = Digest::from(guest.image_id);
+    let provider = ProviderBuilder::new().connect_http(args.dest_rpc_url);
+
+    log::info!("Embedded image ID of {}: {guest_image_id}", guest.name);
+    let mut mismatches = 0;
+    for addr in args.dst_transceiver_addr {
+        let contract_image_id = source_image_id(addr, args.source_chain, &provider)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to fetch image ID of {addr} for chain {}",
+                    args.source_chain
+                )
+            })?;
+        if contract_image_id == guest_image_id {
+            log::info!("{addr}: {contract_image_id} matches");
+        } else {
+            log::error!("{addr}: {contract_image_id} does not match");
+            mismatches += 1;
+        }
+    }
+    ensure!(
+        mismatches == 0,
+        "{mismatches} destination contract(s) do not match the embedded image ID"
+    );
+
+    Ok(())
+}
+
+async fn check_config(args: CheckConfigArgs) -> Result<()> {
+    let config = DaemonConfig::load(&args.config)?;
+    let (rpc, beacon) = args.source.pools(None)?;
+    let checkup = Checkup::run(
+        &config,
+        &rpc,
+        &beacon,
+        &args.dest_wallet_private_key,
+        (args.min_balance_eth * 1e18) as u128,
+    )
+    .await;
+    println!("{}", serde_json::to_string_pretty(&checkup)?);
+    let failures = checkup.failures();
+    ensure!(
+        failures == 0,
+        "{failures} of {} checks failed",
+        checkup.checks.len()
+    );
+    Ok(())
+}
+
+async fn compare_backends(args: CompareBackendsArgs) -> Result<()> {
+    let guest = guest_program(&args.guest)?;
+    let (rpc, beacon) = args.source.pools(None)?;
+    let input = fetch_input(
+        MessageId {
+            tx_hash: args.tx_hash,
+            index: args.index,
+        },
+        args.src_transceiver_addr,
+        &rpc,
+        &beacon,
         args.commitment_block,
+        guest,
+        &args.source.input_options()?,
     )
     .await?;
+    let (pricing, offer) = match &args.config {
+        Some(path) => {
+            let config = DaemonConfig::load(path)?;
+            (config.pricing, config.market)
+        }
+        None => Default::default(),
+    };
 
-    let receipt = prove_info.receipt;
-    let journal = &receipt.journal.bytes;
+    let quote = quote(input, guest, &pricing, &offer).await?;
+    let throughput = Throughput {
+        cpu_hz: args.cpu_hz,
+        gpu_hz: args.gpu_hz,
+        bonsai_hz: args.bonsai_hz,
+        market_hz: args.market_hz,
+    };
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "cycles": quote.cycles,
+            "backends": quote.compare(&pricing, &offer, &throughput),
+        }))?
+    );
+    Ok(())
+}
+
+async fn check_commitment(args: CheckCommitmentArgs) -> Result<()> {
+    let journal = read_journal(&args.journal)?;
+    let commitment = &journal.commitment;
+    let (claim, version) = commitment.decode_id();
+    let provider = ProviderBuilder::new().connect_http(args.dest_rpc_url.clone());
+    let now = provider
+        .get_block_by_number(BlockNumberOrTag::Latest)
+        .await?
+        .context("no latest block")?
+        .header
+        .timestamp;
 
-    // Decode and log the commitment
-    let journal = Journal::abi_decode(journal).context("invalid journal")?;
-    log::debug!("Steel commitment: {:?}", journal.commitment);
+    let timestamp = eip4788::commitment_timestamp(commitment);
+    let result = match (timestamp, args.dst_transceiver_addr) {
+        (Some(timestamp), _) => check_beacon_root(&provider, timestamp, commitment.digest).await,
+        (None, Some(transceiver)) => {
+            Destination::read_only(transceiver, args.dest_rpc_url)
+                .check_commitment(&journal)
+                .await
+        }
+        (None, None) => Err(anyhow!(
+            "version {version} commitments are checked by the validator of a destination, which \
+             requires --dst-transceiver-addr"
+        )),
+    };
+    // Roots of validators do not expire, unlike those of the beacon roots contract
+    let expires_at = timestamp.map(eip4788::expires_at);
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "commitment": {
+                "version": version,
+                "claim": claim,
+                "digest": commitment.digest,
+                "configID": commitment.configID,
+            },
+            "verifiable": result.is_ok(),
+            "reason": result.as_ref().err().map(|e| format!("{e:#}")),
+            "expires_at": expires_at,
+            "expires_in_secs": expires_at.map(|at| at.saturating_sub(now)),
+        }))?
+    );
+    result
+}
 
-    // ABI encode the seal.
-    let seal = encode_seal(&receipt).context("invalid receipt")?;
+/// Reads an ABI encoded journal from `journal`, which is either a file holding it in binary or
+/// hex, or hex.
+fn read_journal(journal: &str) -> Result<Journal> {
+    Journal::decode_any(&read_journal_bytes(journal)?)
+        .map_err(anyhow::Error::msg)
+        .context("invalid journal")
+}
 
-    // Create an alloy instance of the BoundlessTransceiver contract.
-    let contract = IBoundlessTransceiver::new(args.dst_transceiver_addr, &provider);
+fn read_journal_bytes(journal: &str) -> Result<Vec<u8>> {
+    Ok(match fs::read(journal) {
+        // Files may hold the journal in hex too
+        Ok(bytes) => std::str::from_utf8(&bytes)
+            .ok()
+            .and_then(|hex| hex.trim().parse::<Bytes>().ok())
+            .map_or(bytes, |hex| hex.to_vec()),
+        Err(_) => journal
+            .parse::<Bytes>()
+            .context("journal is neither a file nor hex")?
+            .to_vec(),
+    })
+}
 
-    // Call IBoundlessTransceiver::imageID() to check that the contract has been deployed correctly
-    // and ensure valid proofs will verify
-    let contract_image_id = Digest::from(contract.imageID().call().await?.0);
+/// Checks that the EIP-4788 beacon roots contract of the chain of `provider` holds `digest` for
+/// `timestamp`.
+async fn check_beacon_root(provider: &impl Provider, timestamp: u64, digest: B256) -> Result<()> {
+    let tx = TransactionRequest::default()
+        .to(eip4788::BEACON_ROOTS_ADDRESS)
+        .input(Bytes::from(U256::from(timestamp).to_be_bytes::<32>()).into());
+    let root = provider
+        .call(tx)
+        .await
+        .with_context(|| format!("beacon roots contract has no root for timestamp {timestamp}"))?;
     ensure!(
-        contract_image_id == NTT_MESSAGE_INCLUSION_ID.into(),
-        "Contract image ID does not match image ID being used to build proofs: {contract_image_id}, expected: {NTT_MESSAGE_INCLUSION_ID:?}",
+        root.as_ref() == digest.as_slice(),
+        "beacon roots contract holds {root} for timestamp {timestamp} instead of {digest}"
     );
+    Ok(())
+}
+
+async fn build_input(args: BuildInputArgs) -> Result<()> {
+    let guest = guest_program(&args.guest)?;
+    let (rpc, beacon) = args.source.pools(None)?;
+    let id = MessageId {
+        tx_hash: args.tx_hash,
+        index: args.index,
+    };
+    let input = fetch_input(
+        id,
+        args.src_transceiver_addr,
+        &rpc,
+        &beacon,
+        args.commitment_block,
+        guest,
+        &args.source.input_options()?,
+    )
+    .await?;
 
-    // Call the receiveMessage function of the contract and wait for confirmation.
+    let artifact = InputArtifact {
+        id,
+        source: args.src_transceiver_addr,
+        commitment_block: args.commitment_block,
+        guest: guest.name.to_string(),
+        image_id: Digest::from(guest.image_id),
+        input,
+    };
+    artifact.write(&args.out)?;
     log::info!(
-        "Sending Tx calling {} Function of {:#}...",
-        IBoundlessTransceiver::receiveMessageCall::SIGNATURE,
-        contract.address()
+        "Wrote input of {} bytes for guest {} to {}",
+        artifact.input.len(),
+        guest.name,
+        args.out.display()
     );
-    let call_builder = contract.receiveMessage(receipt.journal.bytes.into(), seal.into());
+    Ok(())
+}
 
-    log::debug!("Send {} {}", contract.address(), call_builder.calldata());
-    let pending_tx = call_builder.send().await?;
-    let tx_hash = *pending_tx.tx_hash();
-    let receipt = pending_tx
-        .get_receipt()
-        .await
-        .with_context(|| format!("transaction did not confirm: {tx_hash}"))?;
+fn input_size(args: InputSizeArgs) -> Result<()> {
+    let input = match (args.input, args.snapshot) {
+        (Some(path), _) => InputArtifact::read(&path)?.input,
+        (None, Some(name)) => Snapshot::load(&args.snapshot_dir, &name)?.1,
+        (None, None) => unreachable!("clap requires an input or a snapshot"),
+    };
+    let report = SizeReport::of_input(&input, args.depth)?;
+    println!("{}", serde_json::to_string_pretty(&report)?);
+    Ok(())
+}
+
+async fn snapshot(args: SnapshotArgs) -> Result<()> {
+    let guest = guest_program(&args.guest)?;
+    let (rpc, beacon) = args.source.pools(None)?;
+    let input = fetch_input(
+        MessageId {
+            tx_hash: args.tx_hash,
+            index: 0,
+        },
+        args.src_transceiver_addr,
+        &rpc,
+        &beacon,
+        args.commitment_block,
+        guest,
+        &args.source.input_options()?,
+    )
+    .await?;
 
-    ensure!(receipt.status(), "transaction failed: {}", tx_hash);
+    let name = args.name.clone();
+    let snapshot = tokio::task::spawn_blocking(move || {
+        Snapshot::capture(
+            &args.dir,
+            &args.name,
+            args.tx_hash,
+            args.src_transceiver_addr,
+            args.commitment_block,
+            guest,
+            &input,
+        )
+    })
+    .await??;
+    log::info!(
+        "Wrote snapshot {name} with a journal of {} bytes",
+        snapshot.journal.len()
+    );
+    Ok(())
+}
 
+async fn prepare_tx(args: PrepareTxArgs) -> Result<()> {
+    let proof = ProofArtifact::read(&args.proof)?;
+    let guest = guest_program(&proof.guest)?;
+    let destination = Destination::read_only(args.dst_transceiver_addr, args.dest_rpc_url);
+
+    let tx = destination
+        .receive_message_tx(&proof.receipt.into(), guest)
+        .await?;
+    let tx = destination.fill(tx, args.from).await?;
+    fs::write(&args.out, serde_json::to_string_pretty(&tx)?)
+        .with_context(|| format!("failed to write {}", args.out.display()))?;
+    log::info!(
+        "Wrote transaction delivering message {} to {}",
+        proof.id,
+        args.out.display()
+    );
+    Ok(())
+}
+
+async fn sign_tx(args: SignTxArgs) -> Result<()> {
+    let tx = fs::read_to_string(&args.tx)
+        .with_context(|| format!("failed to read {}", args.tx.display()))?;
+    let tx: TransactionRequest = serde_json::from_str(&tx).context("invalid transaction")?;
+
+    let raw_tx = sign_transaction(tx, args.dest_wallet_private_key).await?;
+    fs::write(&args.out, raw_tx.to_string())
+        .with_context(|| format!("failed to write {}", args.out.display()))?;
+    log::info!("Wrote signed transaction to {}", args.out.display());
+    Ok(())
+}
+
+async fn broadcast_tx(args: BroadcastArgs) -> Result<()> {
+    let raw_tx = fs::read_to_string(&args.raw_tx)
+        .with_context(|| format!("failed to read {}", args.raw_tx.display()))?;
+    let raw_tx: Bytes = raw_tx.trim().parse().context("invalid raw transaction")?;
+
+    let provider = ProviderBuilder::new().connect_http(args.dest_rpc_url);
+    let tx_hash = broadcast(provider, &raw_tx).await?;
+    log::info!("Transaction {tx_hash} confirmed");
+    Ok(())
+}
+
+async fn messages(args: MessagesArgs) -> Result<()> {
+    let messages = sent_messages(args).await?;
+    println!("{}", serde_json::to_string_pretty(&messages)?);
+    Ok(())
+}
+
+async fn branches(args: MerkleBranchesArgs) -> Result<()> {
+    let journal = read_journal_bytes(&args.journal)?;
+    let emitter_contract = to_wormhole_address(args.messages.src_transceiver_addr);
+    let claims = sent_messages(args.messages)
+        .await?
+        .into_iter()
+        .map(|message| {
+            let claim = MessageClaim {
+                encoded_message: message.encoded_message,
+                contract_addr: emitter_contract,
+            };
+            (claim, message.recipient_chain)
+        })
+        .collect();
+    let branches = merkle_branches(&journal, claims)?;
+    println!("{}", serde_json::to_string_pretty(&branches)?);
+    Ok(())
+}
+
+async fn sent_messages(args: MessagesArgs) -> Result<Vec<SentMessage>> {
+    let guest = guest_program(&args.guest)?;
+    let rpc = EndpointPool::new(EndpointKind::ExecutionRpc, args.eth_rpc_url)?;
+    let (tx_hash, source) = (args.tx_hash, args.src_transceiver_addr);
+    rpc.run(|endpoint| async move {
+        transaction_messages(&endpoint.provider(), tx_hash, source, guest.transceiver).await
+    })
+    .await
+}
+
+async fn reconcile(args: ReconcileArgs) -> Result<()> {
+    let config = DaemonConfig::load(&args.config)?;
+    let rpc = EndpointPool::new(EndpointKind::ExecutionRpc, args.eth_rpc_url)?;
+    let to_block = match args.to_block {
+        Some(block) => block,
+        None => finalized_block(&rpc, config.finality).await?,
+    };
+
+    let undelivered = find_undelivered(&rpc, &config, args.from_block, to_block).await?;
+    log::info!(
+        "{} undelivered messages in blocks {}..={to_block}",
+        undelivered.len(),
+        args.from_block
+    );
+    println!("{}", serde_json::to_string_pretty(&undelivered)?);
+    Ok(())
+}
+
+fn jobs(args: JobsArgs) -> Result<()> {
+    let store = FileJobStore::open(args.store_dir)?;
+    let jobs = match args.id {
+        Some(id) => vec![
+            store
+                .load(&id)?
+                .with_context(|| format!("no message {id}"))?,
+        ],
+        None => store.list()?,
+    };
+    println!("{}", serde_json::to_string_pretty(&jobs)?);
+    Ok(())
+}
+
+fn export(args: ExportArgs) -> Result<()> {
+    let store = FileJobStore::open(args.store_dir)?;
+    let mut records: Vec<MessageRecord> = store.list()?.iter().map(MessageRecord::from).collect();
+    records.sort_by_key(|record| record.finalized_at);
+
+    let file = File::create(&args.out)
+        .with_context(|| format!("failed to create {}", args.out.display()))?;
+    match args.format {
+        ExportFormat::Csv => write_csv(&records, BufWriter::new(file))?,
+        ExportFormat::Parquet => write_parquet(&records, file)?,
+    }
+    log::info!(
+        "Exported {} messages to {}",
+        records.len(),
+        args.out.display()
+    );
+    Ok(())
+}
+
+fn test_vectors(args: TestVectorsArgs) -> Result<()> {
+    let guest = guest_program(&args.guest)?;
+    let defaults = SyntheticMessage::default();
+    let message = SyntheticMessage {
+        source_chain: args.source_chain,
+        recipient_chain: args.recipient_chain,
+        recipient_manager: to_wormhole_address(args.recipient_manager),
+        recipient: args.recipient.unwrap_or(defaults.recipient),
+        slot: args.slot,
+        ..defaults
+    };
+    let vector = TestVector::new(&message, Digest::from(guest.image_id), args.selector)?;
+    let output = match args.format {
+        VectorFormat::Solidity => vector.to_solidity(&args.library),
+        VectorFormat::Json => serde_json::to_string_pretty(&vector)? + "\n",
+    };
+    match &args.out {
+        Some(path) => {
+            fs::write(path, output)
+                .with_context(|| format!("failed to write {}", path.display()))?;
+            log::info!("Wrote test vectors to {}", path.display());
+        }
+        None => print!("{output}"),
+    }
+    Ok(())
+}
+
+fn verify_bundle(args: VerifyBundleArgs) -> Result<()> {
+    let bundle = AuditBundle::read(&args.bundle)?;
+    let journal = bundle.verify()?;
+    // A block number for block commitments, a beacon block timestamp for beacon commitments
+    let (claim, version) = journal.commitment.decode_id();
+    log::info!(
+        "Message {} was proven with guest {} ({}) and delivered to {} in transaction {}",
+        bundle.id,
+        bundle.guest,
+        bundle.image_id,
+        bundle.destination,
+        bundle.destination_receipt.transaction_hash
+    );
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&serde_json::json!({
+            "id": bundle.id,
+            "commitment": {
+                "version": version,
+                "claim": claim,
+                "digest": journal.commitment.digest,
+                "configID": journal.commitment.configID,
+                // When the beacon roots contract may drop the root of a beacon commitment
+                "expires_at": eip4788::commitment_timestamp(&journal.commitment)
+                    .map(eip4788::expires_at),
+            },
+        }))?
+    );
+    Ok(())
+}
+
+fn version(args: VersionArgs) -> Result<()> {
+    let info = VersionInfo::current();
+    match (args.json, args.verbose) {
+        (true, true) => println!("{}", serde_json::to_string_pretty(&info)?),
+        (true, false) => println!(
+            "{}",
+            serde_json::json!({ "version": info.version, "git_commit": info.git_commit })
+        ),
+        (false, true) => print!("{info}"),
+        (false, false) => println!("{}", info.summary()),
+    }
+    Ok(())
+}
+
+fn bench_cycles(args: BenchCyclesArgs) -> Result<()> {
+    let mut inputs = Vec::new();
+    for name in snapshot::names(&args.snapshot_dir)? {
+        let (snapshot, input) = Snapshot::load(&args.snapshot_dir, &name)?;
+        inputs.push(BenchInput {
+            guest: snapshot.guest()?,
+            name,
+            input,
+        });
+    }
+    for path in &args.input {
+        let artifact = InputArtifact::read(path)?;
+        inputs.push(BenchInput {
+            name: path.display().to_string(),
+            guest: guest_program(&artifact.guest)?,
+            input: artifact.input,
+        });
+    }
+    ensure!(!inputs.is_empty(), "No inputs to benchmark");
+
+    let mut comparisons = Vec::new();
+    for input in &inputs {
+        match bench::compare(input, &args.artifacts_dir)? {
+            Some(comparison) => {
+                log::info!(
+                    "{}: {} cycles, {:+} ({:+.2}%)",
+                    comparison.input,
+                    comparison.current_cycles,
+                    comparison.delta,
+                    comparison.delta_percent
+                );
+                comparisons.push(comparison);
+            }
+            None => log::warn!(
+                "No released build of guest {} to compare {} against",
+                input.guest.name,
+                input.name
+            ),
+        }
+    }
+    println!("{}", serde_json::to_string_pretty(&comparisons)?);
+
+    if let Some(max) = args.max_regression_percent {
+        let regressions: Vec<&str> = comparisons
+            .iter()
+            .filter(|comparison| comparison.delta_percent > max)
+            .map(|comparison| comparison.input.as_str())
+            .collect();
+        ensure!(
+            regressions.is_empty(),
+            "Cycles regressed by more than {max}% on {}",
+            regressions.join(", ")
+        );
+    }
     Ok(())
 }
@@ -0,0 +1,236 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Circuit breaker halting submissions when the relay behaves anomalously, protecting the funds of
+//! the delivering wallets during incidents. Once tripped it stays tripped until an operator resumes
+//! the relay.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use alloy_primitives::Address;
+use anyhow::{Result, bail};
+use risc0_zkvm::Digest;
+use tracing::log;
+
+use crate::alert::{Alert, Alerter};
+use crate::metrics::Metrics;
+
+/// Thresholds over a sliding window past which the breaker trips.
+#[derive(Clone, Debug)]
+pub struct BreakerLimits {
+    pub window: Duration,
+    /// Maximum number of reverted submissions in the window.
+    pub max_reverts: usize,
+    /// Maximum gas spent on submissions in the window, in wei, if limited.
+    pub max_spend_wei: Option<u128>,
+}
+
+/// An anomaly tripping the breaker.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Anomaly {
+    Reverts {
+        count: usize,
+        window: Duration,
+    },
+    ImageIdMismatch {
+        contract: Address,
+        contract_image_id: Digest,
+        guest_image_id: Digest,
+    },
+    GasSpend {
+        wei: u128,
+        window: Duration,
+    },
+    VerifierStopped {
+        verifier: Address,
+    },
+}
+
+impl fmt::Display for Anomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Reverts { count, window } => {
+                write!(f, "{count} submissions reverted within {window:?}")
+            }
+            Self::ImageIdMismatch {
+                contract,
+                contract_image_id,
+                guest_image_id,
+            } => write!(
+                f,
+                "Contract image ID of {contract} does not match image ID being used to build proofs: {contract_image_id}, expected: {guest_image_id}"
+            ),
+            Self::GasSpend { wei, window } => {
+                write!(f, "{wei} wei spent on submissions within {window:?}")
+            }
+            Self::VerifierStopped { verifier } => write!(f, "Verifier {verifier} is paused"),
+        }
+    }
+}
+
+impl std::error::Error for Anomaly {}
+
+#[derive(Default)]
+struct State {
+    tripped: Option<Anomaly>,
+    reverts: VecDeque<SystemTime>,
+    spend: VecDeque<(SystemTime, u128)>,
+}
+
+pub struct CircuitBreaker {
+    limits: BreakerLimits,
+    state: Mutex<State>,
+    metrics: Arc<Metrics>,
+    alerter: Alerter,
+}
+
+impl CircuitBreaker {
+    pub fn new(limits: BreakerLimits, metrics: Arc<Metrics>, alerter: Alerter) -> Self {
+        Self {
+            limits,
+            state: Mutex::default(),
+            metrics,
+            alerter,
+        }
+    }
+
+    /// The anomaly the breaker was tripped by, if it is tripped.
+    pub fn tripped(&self) -> Option<Anomaly> {
+        self.state.lock().unwrap().tripped.clone()
+    }
+
+    /// Fails if the breaker is tripped, so nothing is submitted until an operator resumes.
+    pub fn check(&self) -> Result<()> {
+        if let Some(anomaly) = self.tripped() {
+            bail!("Circuit breaker tripped, awaiting operator resume: {anomaly}");
+        }
+        Ok(())
+    }
+
+    /// Trips the breaker, alerting unless it already was tripped.
+    pub async fn trip(&self, anomaly: Anomaly) {
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.tripped.is_some() {
+                return;
+            }
+            state.tripped = Some(anomaly.clone());
+        }
+        self.metrics.set_breaker_tripped(true);
+        self.alerter
+            .fire(Alert {
+                kind: "circuit_breaker",
+                message: format!("Submissions halted until an operator resumes: {anomaly}"),
+            })
+            .await;
+    }
+
+    /// Closes the breaker again, forgetting the submissions that led up to tripping it.
+    pub fn reset(&self) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(anomaly) = state.tripped.take() {
+            log::info!("Circuit breaker reset after: {anomaly}");
+        }
+        state.reverts.clear();
+        state.spend.clear();
+        self.metrics.set_breaker_tripped(false);
+    }
+
+    /// Records a submission confirmed at `now` that spent `spent_wei` on gas, tripping the breaker
+    /// if the reverts or spend in the window exceed their limits.
+    pub async fn record_submission(&self, now: SystemTime, reverted: bool, spent_wei: u128) {
+        let anomaly = {
+            let mut state = self.state.lock().unwrap();
+            let window = self.limits.window;
+            let expired = |at: &SystemTime| now.duration_since(*at).unwrap_or_default() > window;
+
+            if reverted {
+                state.reverts.push_back(now);
+            }
+            while state.reverts.front().is_some_and(expired) {
+                state.reverts.pop_front();
+            }
+            state.spend.push_back((now, spent_wei));
+            while state.spend.front().is_some_and(|(at, _)| expired(at)) {
+                state.spend.pop_front();
+            }
+
+            let spent: u128 = state.spend.iter().map(|(_, wei)| wei).sum();
+            if state.reverts.len() > self.limits.max_reverts {
+                Some(Anomaly::Reverts {
+                    count: state.reverts.len(),
+                    window,
+                })
+            } else if self.limits.max_spend_wei.is_some_and(|max| spent > max) {
+                Some(Anomaly::GasSpend { wei: spent, window })
+            } else {
+                None
+            }
+        };
+        if let Some(anomaly) = anomaly {
+            self.trip(anomaly).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn trips_on_reverts_until_reset() {
+        let metrics = Arc::new(Metrics::default());
+        let limits = BreakerLimits {
+            window: Duration::from_secs(60),
+            max_reverts: 1,
+            max_spend_wei: Some(1000),
+        };
+        let breaker = CircuitBreaker::new(limits, metrics.clone(), Alerter::default());
+        let start = SystemTime::UNIX_EPOCH;
+
+        breaker.record_submission(start, true, 10).await;
+        // The first revert has left the window
+        breaker
+            .record_submission(start + Duration::from_secs(90), true, 10)
+            .await;
+        assert!(breaker.check().is_ok());
+
+        breaker
+            .record_submission(start + Duration::from_secs(100), true, 10)
+            .await;
+        assert!(matches!(
+            breaker.tripped(),
+            Some(Anomaly::Reverts { count: 2, .. })
+        ));
+        assert!(breaker.check().is_err());
+        assert!(
+            metrics
+                .render_prometheus()
+                .contains("relay_circuit_breaker_tripped 1\n")
+        );
+
+        breaker.reset();
+        assert!(breaker.check().is_ok());
+        breaker
+            .record_submission(start + Duration::from_secs(110), false, 2000)
+            .await;
+        assert!(matches!(
+            breaker.tripped(),
+            Some(Anomaly::GasSpend { wei: 2000, .. })
+        ));
+    }
+}
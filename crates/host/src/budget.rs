@@ -0,0 +1,381 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Budgets capping what is spent on proving, the counterpart of the gas spend cap of the circuit
+//! breaker. Proofs are paid for up front when proving starts, at the price quoted from a dry run of
+//! the guest: the maximum price of the offer on the market, or the rate of Bonsai. A message
+//! quoted above the per-message budget fails, while one that would exceed the daily budget waits
+//! for the next day, which starts at midnight UTC. What each message reserved is kept for the day,
+//! and saved to the job store if any, so neither retries nor restarts pay for a proof twice.
+
+use std::ops::AddAssign;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Result, ensure};
+use serde::{Deserialize, Serialize};
+use tracing::log;
+
+use crate::job::MessageId;
+use crate::quote::CostQuote;
+use crate::store::JobStore;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// An amount spent on proving.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Spend {
+    /// Paid for proofs from the market, in wei.
+    pub market_wei: u128,
+    /// Paid for proofs from Bonsai, in USD.
+    pub usd: f64,
+}
+
+impl Spend {
+    /// The spend of a proof from the market, as quoted by `quote`.
+    pub fn market(quote: &CostQuote) -> Self {
+        Self {
+            market_wei: quote.market_max_wei.saturating_to(),
+            usd: 0.0,
+        }
+    }
+
+    /// The spend of a proof from Bonsai, as quoted by `quote`.
+    pub fn bonsai(quote: &CostQuote) -> Self {
+        Self {
+            market_wei: 0,
+            usd: quote.bonsai_usd,
+        }
+    }
+}
+
+impl Spend {
+    /// What `self` adds to the spend already reserved in `reserved`.
+    fn beyond(self, reserved: Self) -> Self {
+        Self {
+            market_wei: self.market_wei.saturating_sub(reserved.market_wei),
+            usd: (self.usd - reserved.usd).max(0.0),
+        }
+    }
+
+    /// The part of `self` that was reserved in `reserved`.
+    fn within(self, reserved: Self) -> Self {
+        Self {
+            market_wei: self.market_wei.min(reserved.market_wei),
+            usd: self.usd.min(reserved.usd),
+        }
+    }
+
+    fn saturating_sub(self, other: Self) -> Self {
+        Self {
+            market_wei: self.market_wei.saturating_sub(other.market_wei),
+            usd: (self.usd - other.usd).max(0.0),
+        }
+    }
+}
+
+impl AddAssign for Spend {
+    fn add_assign(&mut self, other: Self) {
+        self.market_wei = self.market_wei.saturating_add(other.market_wei);
+        self.usd += other.usd;
+    }
+}
+
+/// Caps on the spend on proving. Unset caps are not enforced.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BudgetLimits {
+    /// Maximum spend on market proofs per day, in wei.
+    pub daily_market_wei: Option<u128>,
+    /// Maximum spend on the market proof of a single message, in wei.
+    pub message_market_wei: Option<u128>,
+    /// Maximum spend on Bonsai proofs per day, in USD.
+    pub daily_usd: Option<f64>,
+    /// Maximum spend on the Bonsai proof of a single message, in USD.
+    pub message_usd: Option<f64>,
+}
+
+impl BudgetLimits {
+    /// Whether any cap is set.
+    pub fn is_limited(&self) -> bool {
+        self.daily_market_wei.is_some()
+            || self.message_market_wei.is_some()
+            || self.daily_usd.is_some()
+            || self.message_usd.is_some()
+    }
+
+    fn within_message(&self, cost: Spend) -> bool {
+        self.message_market_wei
+            .is_none_or(|max| cost.market_wei <= max)
+            && self.message_usd.is_none_or(|max| cost.usd <= max)
+    }
+
+    fn within_daily(&self, spent: Spend) -> bool {
+        self.daily_market_wei
+            .is_none_or(|max| spent.market_wei <= max)
+            && self.daily_usd.is_none_or(|max| spent.usd <= max)
+    }
+}
+
+/// The spend of a day, as saved to the job store.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetState {
+    /// Days since the Unix epoch.
+    pub day: u64,
+    pub spent: Spend,
+    /// What proving each message reserved during the day.
+    pub reservations: Vec<(MessageId, Spend)>,
+}
+
+impl BudgetState {
+    fn reservation(&mut self, id: MessageId) -> &mut Spend {
+        let index = match self
+            .reservations
+            .iter()
+            .position(|(reserved, _)| *reserved == id)
+        {
+            Some(index) => index,
+            None => {
+                self.reservations.push((id, Spend::default()));
+                self.reservations.len() - 1
+            }
+        };
+        &mut self.reservations[index].1
+    }
+}
+
+pub struct ProvingBudget {
+    limits: BudgetLimits,
+    state: Mutex<BudgetState>,
+    store: Option<Arc<dyn JobStore>>,
+}
+
+impl ProvingBudget {
+    /// Creates a budget going on from the spend saved in `store`, if any.
+    pub fn new(limits: BudgetLimits, store: Option<Arc<dyn JobStore>>) -> Result<Self> {
+        let state = match &store {
+            Some(store) => store.load_budget()?.unwrap_or_default(),
+            None => BudgetState::default(),
+        };
+        Ok(Self {
+            limits,
+            state: Mutex::new(state),
+            store,
+        })
+    }
+
+    pub fn limits(&self) -> &BudgetLimits {
+        &self.limits
+    }
+
+    /// The spend of the current day.
+    pub fn spent_today(&self) -> Spend {
+        let state = self.state.lock().unwrap();
+        if state.day == unix_now() / DAY_SECS {
+            state.spent
+        } else {
+            Spend::default()
+        }
+    }
+
+    /// Takes `cost` out of the budget before proving the message `id`, waiting for the next day
+    /// while it would exceed the daily budget. Fails if it exceeds the per-message budget, or the
+    /// daily budget on its own. Only what `id` has not reserved yet that day is taken, so retrying
+    /// a message is not paid for twice.
+    pub async fn reserve(&self, id: MessageId, cost: Spend) -> Result<()> {
+        ensure!(
+            self.limits.within_message(cost),
+            "Proving message {id} would cost {cost:?}, more than the per-message budget"
+        );
+        ensure!(
+            self.limits.within_daily(cost),
+            "Proving message {id} would cost {cost:?}, more than the daily budget"
+        );
+        loop {
+            let wait = match self.try_reserve(unix_now(), id, cost) {
+                Ok(()) => return self.save(),
+                Err(wait) => wait,
+            };
+            log::warn!(
+                "Daily proving budget spent, message {id} waits {wait:?} for the next day: {:?}",
+                self.spent_today()
+            );
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Gives back what the message `id` reserved of `cost`, for a proof that was not paid for
+    /// after all, such as a market request that expired unfulfilled.
+    pub fn refund(&self, id: MessageId, cost: Spend) -> Result<()> {
+        self.try_refund(unix_now(), id, cost);
+        self.save()
+    }
+
+    /// Takes `cost` out of the budget of the day of `now` for the message `id`, or returns how
+    /// long until the next day.
+    fn try_reserve(&self, now: u64, id: MessageId, cost: Spend) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap();
+        let day = now / DAY_SECS;
+        if state.day != day {
+            *state = BudgetState {
+                day,
+                ..Default::default()
+            };
+        }
+        let extra = cost.beyond(*state.reservation(id));
+        let mut spent = state.spent;
+        spent += extra;
+        if !self.limits.within_daily(spent) {
+            return Err(Duration::from_secs((day + 1) * DAY_SECS - now));
+        }
+        state.spent = spent;
+        *state.reservation(id) += extra;
+        Ok(())
+    }
+
+    /// Gives back what `id` reserved of `cost` on the day of `now`. Reservations of past days
+    /// are left alone, their budget is gone either way.
+    fn try_refund(&self, now: u64, id: MessageId, cost: Spend) {
+        let mut state = self.state.lock().unwrap();
+        if state.day != now / DAY_SECS {
+            return;
+        }
+        let reservation = state.reservation(id);
+        let refund = cost.within(*reservation);
+        *reservation = reservation.saturating_sub(refund);
+        state.spent = state.spent.saturating_sub(refund);
+    }
+
+    /// Saves the spend of the day to the store, if any.
+    fn save(&self) -> Result<()> {
+        match &self.store {
+            Some(store) => store.save_budget(&self.state.lock().unwrap().clone()),
+            None => Ok(()),
+        }
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::FileJobStore;
+
+    fn id(index: usize) -> MessageId {
+        MessageId {
+            tx_hash: Default::default(),
+            index,
+        }
+    }
+
+    #[test]
+    fn defers_to_next_day() {
+        let budget = ProvingBudget::new(
+            BudgetLimits {
+                daily_market_wei: Some(100),
+                daily_usd: Some(1.0),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+        let market = Spend {
+            market_wei: 60,
+            usd: 0.0,
+        };
+        let start = 10 * DAY_SECS;
+
+        assert_eq!(budget.try_reserve(start, id(0), market), Ok(()));
+        assert_eq!(
+            budget.try_reserve(start + 100, id(1), market),
+            Err(Duration::from_secs(DAY_SECS - 100))
+        );
+        // Bonsai has a budget of its own
+        let bonsai = Spend {
+            market_wei: 0,
+            usd: 0.5,
+        };
+        assert_eq!(budget.try_reserve(start + 200, id(1), bonsai), Ok(()));
+        assert_eq!(budget.try_reserve(start + DAY_SECS, id(1), market), Ok(()));
+    }
+
+    #[test]
+    fn reserves_once_per_message() {
+        let budget = ProvingBudget::new(
+            BudgetLimits {
+                daily_market_wei: Some(100),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+        let market = Spend {
+            market_wei: 60,
+            usd: 0.0,
+        };
+        let start = 10 * DAY_SECS;
+
+        assert_eq!(budget.try_reserve(start, id(0), market), Ok(()));
+        // A retry of the same message is already paid for
+        assert_eq!(budget.try_reserve(start + 100, id(0), market), Ok(()));
+        assert_eq!(budget.state.lock().unwrap().spent, market);
+        // Refunding an expired request frees the budget for another message
+        budget.try_refund(start + 200, id(0), market);
+        assert_eq!(budget.try_reserve(start + 300, id(1), market), Ok(()));
+        assert_eq!(budget.state.lock().unwrap().spent, market);
+    }
+
+    #[test]
+    fn resumes_from_store() {
+        let dir = std::env::temp_dir().join(format!("budget-test-{}", std::process::id()));
+        let store: Arc<dyn JobStore> = Arc::new(FileJobStore::open(&dir).unwrap());
+        let limits = BudgetLimits {
+            daily_market_wei: Some(100),
+            ..Default::default()
+        };
+        let market = Spend {
+            market_wei: 60,
+            usd: 0.0,
+        };
+        let budget = ProvingBudget::new(limits, Some(store.clone())).unwrap();
+        let now = unix_now();
+        assert_eq!(budget.try_reserve(now, id(0), market), Ok(()));
+        budget.save().unwrap();
+
+        let budget = ProvingBudget::new(limits, Some(store)).unwrap();
+        assert_eq!(budget.spent_today(), market);
+        assert!(budget.try_reserve(now, id(1), market).is_err());
+    }
+
+    #[test]
+    fn caps_single_messages() {
+        let limits = BudgetLimits {
+            message_usd: Some(0.1),
+            ..Default::default()
+        };
+        assert!(limits.within_message(Spend {
+            market_wei: u128::MAX,
+            usd: 0.1,
+        }));
+        assert!(!limits.within_message(Spend {
+            market_wei: 0,
+            usd: 0.2,
+        }));
+    }
+}
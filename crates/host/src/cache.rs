@@ -0,0 +1,89 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! On-disk cache of preflighted Steel inputs.
+//!
+//! Preflight walks the RPC for every account, storage slot and receipt the guest touches, which
+//! is by far the slowest part of building an input. Caching its result lets a message be
+//! re-proven after a prover crash or an image upgrade without repeating it.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use alloy_primitives::Address;
+use anyhow::{Context, Result};
+use risc0_steel::ethereum::EthEvmInput;
+
+/// Identifies a preflighted input: the events of `contract` in `execution_block`, committed to
+/// through `commitment_block` on `chain_id`, along with the state of the sending NTT manager if
+/// `manager_state` is set and the holdings of its token if `token_backing` is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct InputKey {
+    pub chain_id: u64,
+    pub contract: Address,
+    pub execution_block: u64,
+    pub commitment_block: u64,
+    pub manager_state: bool,
+    pub token_backing: bool,
+}
+
+/// Stores each input as a bincode file in a directory.
+pub struct InputCache {
+    dir: PathBuf,
+}
+
+impl InputCache {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create input cache {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, key: &InputKey) -> PathBuf {
+        // Inputs without the manager state keep the names of entries cached before it existed
+        let state = if key.manager_state { "-state" } else { "" };
+        let backing = if key.token_backing { "-backing" } else { "" };
+        self.dir.join(format!(
+            "{}-{}-{}-{}{state}{backing}.bin",
+            key.chain_id, key.contract, key.execution_block, key.commitment_block
+        ))
+    }
+
+    pub fn load(&self, key: &InputKey) -> Result<Option<EthEvmInput>> {
+        let path = self.path(key);
+        match fs::read(&path) {
+            Ok(data) => match bincode::deserialize(&data) {
+                Ok(input) => Ok(Some(input)),
+                // A stale or corrupt entry is just a miss, it is overwritten once preflight reruns
+                Err(e) => {
+                    tracing::warn!("Ignoring invalid cached input {}: {e}", path.display());
+                    Ok(None)
+                }
+            },
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn store(&self, key: &InputKey, input: &EthEvmInput) -> Result<()> {
+        // Write to a temporary file first so a crash never leaves a truncated input behind
+        let path = self.path(key);
+        let tmp = path.with_extension("bin.tmp");
+        fs::write(&tmp, bincode::serialize(input)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+}
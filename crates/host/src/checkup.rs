@@ -0,0 +1,379 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of a relay setup before it goes live: the source chain endpoints, the destination
+//! chains of a daemon config, the contracts at its addresses, the image IDs they authorize for the
+//! source chain and the wallets paying for delivery.
+
+use std::collections::BTreeSet;
+
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{Address, Bytes};
+use anyhow::{Context, Result, anyhow, ensure};
+use risc0_zkvm::Digest;
+use serde::Serialize;
+use tracing::log;
+use zkvm::GuestProgram;
+
+use crate::config::DaemonConfig;
+use crate::destination::source_image_id;
+use crate::rpc::EndpointPool;
+use crate::source_chain_id;
+
+/// The outcome of a single check.
+#[derive(Clone, Debug, Serialize)]
+pub struct Check {
+    pub name: String,
+    pub passed: bool,
+    /// What was found, or why the check failed.
+    pub detail: String,
+}
+
+/// The checks of a setup, in the order they ran. Checking goes on after a failure wherever
+/// possible, so a single run reports everything that needs fixing.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Checkup {
+    pub checks: Vec<Check>,
+}
+
+/// A contract messages are delivered through, and the guest the config proves them with.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct DestinationContract {
+    chain: String,
+    address: Address,
+    /// Name of the guest, or `None` if any embedded guest may be selected by image ID.
+    guest: Option<String>,
+    wormhole_transceiver: Option<Address>,
+}
+
+impl Checkup {
+    /// Checks the setup of a daemon running `config` with the source chain endpoints `rpc` and
+    /// `beacon`, delivering with `signer` for the transceivers without a key of their own. Wallets
+    /// holding less than `min_balance_wei`, or nothing, on a destination chain fail the check.
+    pub async fn run(
+        config: &DaemonConfig,
+        rpc: &EndpointPool,
+        beacon: &EndpointPool,
+        signer: &PrivateKeySigner,
+        min_balance_wei: u128,
+    ) -> Self {
+        let mut checkup = Self::default();
+        let source_chains = checkup.check_source(config, rpc, beacon).await;
+        let signers = checkup.check_signers(config, signer);
+        checkup
+            .check_destinations(config, &source_chains, &signers, min_balance_wei)
+            .await;
+        checkup
+    }
+
+    /// Number of checks that failed.
+    pub fn failures(&self) -> usize {
+        self.checks.iter().filter(|check| !check.passed).count()
+    }
+
+    /// Records the outcome of the check `name`, returning whether it passed.
+    fn record(&mut self, name: impl Into<String>, result: Result<String>) -> bool {
+        let name = name.into();
+        let (passed, detail) = match result {
+            Ok(detail) => {
+                log::info!("{name}: {detail}");
+                (true, detail)
+            }
+            Err(e) => {
+                let detail = format!("{e:#}");
+                log::error!("{name}: {detail}");
+                (false, detail)
+            }
+        };
+        self.checks.push(Check {
+            name,
+            passed,
+            detail,
+        });
+        passed
+    }
+
+    /// Checks the source chain endpoints and contracts, returning the Wormhole chain IDs the source
+    /// transceivers send messages from.
+    async fn check_source(
+        &mut self,
+        config: &DaemonConfig,
+        rpc: &EndpointPool,
+        beacon: &EndpointPool,
+    ) -> BTreeSet<u16> {
+        for (host, result) in rpc.probe().await {
+            self.record(
+                format!("source RPC {host}"),
+                result.map(|()| "reachable".to_string()),
+            );
+        }
+        for (host, result) in beacon.probe().await {
+            self.record(
+                format!("beacon API {host}"),
+                result.map(|()| "synced".to_string()),
+            );
+        }
+        let chain_id = rpc
+            .run(|endpoint| async move { Ok(endpoint.provider().get_chain_id().await?) })
+            .await;
+        let chain_id = match chain_id {
+            Ok(chain_id) => chain_id,
+            Err(e) => {
+                self.record("source chain ID", Err(e));
+                return BTreeSet::new();
+            }
+        };
+        self.record("source chain ID", Ok(chain_id.to_string()));
+
+        let mut source_chains = BTreeSet::new();
+        for transceiver in &config.transceivers {
+            let mut contracts = vec![("source transceiver", transceiver.source)];
+            contracts.extend(
+                transceiver
+                    .wormhole_emitter
+                    .map(|emitter| ("source WormholeTransceiver", emitter)),
+            );
+            for (kind, address) in contracts {
+                let code =
+                    rpc.run(|endpoint| async move {
+                        Ok(endpoint.provider().get_code_at(address).await?)
+                    })
+                    .await;
+                self.record(
+                    format!("{kind} {address}"),
+                    code.and_then(|code| deployed(address, code)),
+                );
+            }
+            let source = transceiver.source;
+            let source_chain = rpc
+                .run(|endpoint| async move { source_chain_id(&endpoint.provider(), source).await })
+                .await;
+            if let Ok(chain) = source_chain {
+                source_chains.insert(chain);
+            }
+            self.record(
+                format!("Wormhole chain of {source}"),
+                source_chain.map(|chain| chain.to_string()),
+            );
+            if let Some(name) = &transceiver.guest {
+                let result = guest_program(name).and_then(|guest| {
+                    ensure!(
+                        guest
+                            .chain_id
+                            .is_none_or(|guest_chain| guest_chain == chain_id),
+                        "guest {name} is built for chain {}, but the source chain is {chain_id}",
+                        guest.chain_id.unwrap_or_default()
+                    );
+                    Ok(format!("{name} proves messages of chain {chain_id}"))
+                });
+                self.record(format!("guest of {}", transceiver.source), result);
+            }
+        }
+        source_chains
+    }
+
+    /// Checks that the private keys of the transceivers are set and valid, returning the
+    /// addresses of the wallets paying for delivery.
+    fn check_signers(&mut self, config: &DaemonConfig, signer: &PrivateKeySigner) -> Vec<Address> {
+        let mut addresses = BTreeSet::new();
+        if config
+            .transceivers
+            .iter()
+            .any(|transceiver| transceiver.private_key_env.is_none())
+        {
+            self.record("signer", Ok(signer.address().to_string()));
+            addresses.insert(signer.address());
+        }
+        for transceiver in &config.transceivers {
+            let Some(var) = &transceiver.private_key_env else {
+                continue;
+            };
+            let signer = std::env::var(var)
+                .with_context(|| format!("{var} is not set"))
+                .and_then(|key| {
+                    key.parse::<PrivateKeySigner>()
+                        .with_context(|| format!("{var} is not a valid private key"))
+                });
+            let result = signer.map(|signer| {
+                addresses.insert(signer.address());
+                signer.address().to_string()
+            });
+            self.record(format!("signer of {}", transceiver.source), result);
+        }
+        addresses.into_iter().collect()
+    }
+
+    /// Checks the chain ID of every destination chain, the contracts messages are delivered
+    /// through, the image IDs they authorize for the Wormhole chains `source_chains` and the
+    /// balance of the `signers` on the chains with destinations.
+    async fn check_destinations(
+        &mut self,
+        config: &DaemonConfig,
+        source_chains: &BTreeSet<u16>,
+        signers: &[Address],
+        min_balance_wei: u128,
+    ) {
+        let contracts = destination_contracts(config);
+        let mut chains: Vec<_> = config.chains.iter().collect();
+        chains.sort_by_key(|(name, _)| *name);
+        for (name, chain) in chains {
+            let provider = ProviderBuilder::new().connect_http(chain.rpc_url.clone());
+            let result = provider
+                .get_chain_id()
+                .await
+                .map_err(anyhow::Error::from)
+                .and_then(|chain_id| match chain.chain_id {
+                    Some(expected) if expected != chain_id => Err(anyhow!(
+                        "RPC serves chain {chain_id}, but the config sets chain ID {expected}"
+                    )),
+                    _ => Ok(chain_id.to_string()),
+                });
+            if !self.record(format!("chain {name}"), result) {
+                continue;
+            }
+
+            let on_chain: Vec<_> = contracts
+                .iter()
+                .filter(|contract| contract.chain == *name)
+                .collect();
+            for contract in &on_chain {
+                let address = contract.address;
+                let code = provider.get_code_at(address).await;
+                let check = format!("destination {address} on {name}");
+                if !self.record(
+                    check.clone(),
+                    code.map_err(anyhow::Error::from)
+                        .and_then(|code| deployed(address, code)),
+                ) {
+                    continue;
+                }
+                for &source_chain in source_chains {
+                    let result = match source_image_id(address, source_chain, &provider).await {
+                        Ok(image_id) => matching_guest(image_id, contract.guest.as_deref()),
+                        Err(e) => Err(e),
+                    };
+                    self.record(
+                        format!("image ID of {address} on {name} for chain {source_chain}"),
+                        result,
+                    );
+                }
+                if let Some(wormhole_transceiver) = contract.wormhole_transceiver {
+                    let code = provider.get_code_at(wormhole_transceiver).await;
+                    self.record(
+                        format!("WormholeTransceiver {wormhole_transceiver} on {name}"),
+                        code.map_err(anyhow::Error::from)
+                            .and_then(|code| deployed(wormhole_transceiver, code)),
+                    );
+                }
+            }
+            if on_chain.is_empty() {
+                continue;
+            }
+            for &address in signers {
+                let result = provider
+                    .get_balance(address)
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|balance| {
+                        let balance: u128 = balance.saturating_to();
+                        let eth = balance as f64 / 1e18;
+                        ensure!(
+                            balance > 0 && balance >= min_balance_wei,
+                            "holds {eth:.6} ETH, less than the required {:.6} ETH",
+                            min_balance_wei as f64 / 1e18
+                        );
+                        Ok(format!("{eth:.6} ETH"))
+                    });
+                self.record(format!("balance of {address} on {name}"), result);
+            }
+        }
+    }
+}
+
+/// The contracts messages may be delivered through: the default destinations of the
+/// transceivers, the destinations of the routes and the transceivers of the chains.
+fn destination_contracts(config: &DaemonConfig) -> BTreeSet<DestinationContract> {
+    let defaults = config.transceivers.iter().filter_map(|transceiver| {
+        let destination = transceiver.destination.as_ref()?;
+        Some(DestinationContract {
+            chain: destination.chain.clone(),
+            address: destination.destination,
+            guest: transceiver.guest.clone(),
+            wormhole_transceiver: destination.wormhole_transceiver,
+        })
+    });
+    let routes = config.routes.iter().map(|route| DestinationContract {
+        chain: route.destination.chain.clone(),
+        address: route.destination.destination,
+        guest: None,
+        wormhole_transceiver: route.destination.wormhole_transceiver,
+    });
+    let registered = config.chains.iter().filter_map(|(name, chain)| {
+        Some(DestinationContract {
+            chain: name.clone(),
+            address: chain.transceiver?,
+            guest: None,
+            wormhole_transceiver: None,
+        })
+    });
+    defaults.chain(routes).chain(registered).collect()
+}
+
+/// Checks that `code` was deployed at `address`.
+fn deployed(address: Address, code: Bytes) -> Result<String> {
+    ensure!(!code.is_empty(), "no contract is deployed at {address}");
+    Ok(format!("{} bytes of code", code.len()))
+}
+
+/// Checks that `image_id` is the image ID of the guest named `guest`, or of any embedded guest
+/// if `None`.
+fn matching_guest(image_id: Digest, guest: Option<&str>) -> Result<String> {
+    let guest = match guest {
+        Some(name) => {
+            let guest = guest_program(name)?;
+            ensure!(
+                Digest::from(guest.image_id) == image_id,
+                "{image_id} does not match the image ID {} of guest {name}",
+                Digest::from(guest.image_id)
+            );
+            guest
+        }
+        None => GuestProgram::all()
+            .into_iter()
+            .find(|guest| Digest::from(guest.image_id) == image_id)
+            .with_context(|| format!("no embedded or loaded guest matches {image_id}"))?,
+    };
+    Ok(format!("{image_id} matches guest {}", guest.name))
+}
+
+fn guest_program(name: &str) -> Result<&'static GuestProgram> {
+    GuestProgram::by_name(name).with_context(|| format!("unknown guest program {name}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_image_id_of_guest() {
+        let guest = &zkvm::GUEST_PROGRAMS[0];
+        let image_id = Digest::from(guest.image_id);
+        assert!(matching_guest(image_id, None).is_ok());
+        assert!(matching_guest(image_id, Some(guest.name)).is_ok());
+        assert!(matching_guest(Digest::ZERO, None).is_err());
+        assert!(matching_guest(Digest::ZERO, Some(guest.name)).is_err());
+        assert!(matching_guest(image_id, Some("unknown")).is_err());
+    }
+}
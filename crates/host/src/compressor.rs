@@ -0,0 +1,83 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deferred Groth16 compression of succinct receipts.
+//!
+//! Proving a message to a succinct receipt is fast, while compressing it into a SNARK that can be
+//! verified on-chain is slow and needs resources of its own. With a [Compressor], the relayer
+//! hands out succinct proofs and only waits for their compression once proving capacity was
+//! released, so provers move on to the next message as soon as a receipt exists. The compressor
+//! collects queued receipts into batches and compresses each batch back to back on its own task,
+//! keeping compression from competing with itself for resources.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use risc0_zkvm::Receipt;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{Instant, timeout_at};
+use tracing::log;
+
+use crate::compress;
+
+struct Request {
+    receipt: Receipt,
+    respond: oneshot::Sender<Result<(Receipt, Duration)>>,
+}
+
+/// Handle to a compression task. Clones share the same task.
+#[derive(Clone)]
+pub struct Compressor {
+    requests: mpsc::Sender<Request>,
+}
+
+impl Compressor {
+    /// Spawns a compression task which collects up to `batch_size` receipts, waiting at most
+    /// `max_wait` after the first for more to arrive, before compressing them back to back.
+    pub fn spawn(batch_size: usize, max_wait: Duration) -> Self {
+        let (requests, mut rx) = mpsc::channel::<Request>(batch_size.max(1) * 4);
+        tokio::spawn(async move {
+            while let Some(first) = rx.recv().await {
+                let deadline = Instant::now() + max_wait;
+                let mut batch = vec![first];
+                while batch.len() < batch_size {
+                    match timeout_at(deadline, rx.recv()).await {
+                        Ok(Some(request)) => batch.push(request),
+                        Ok(None) | Err(_) => break,
+                    }
+                }
+
+                log::info!("Compressing batch of {} receipts", batch.len());
+                for request in batch {
+                    let result = compress(request.receipt).await;
+                    // The requester may have given up waiting, which is fine
+                    let _ = request.respond.send(result);
+                }
+            }
+        });
+        Self { requests }
+    }
+
+    /// Queues `receipt` for compression and waits for the resulting Groth16 receipt along with
+    /// the time compressing it took.
+    pub async fn compress(&self, receipt: Receipt) -> Result<(Receipt, Duration)> {
+        let (respond, response) = oneshot::channel();
+        self.requests
+            .send(Request { receipt, respond })
+            .await
+            .ok()
+            .context("compressor stopped")?;
+        response.await.context("compressor stopped")?
+    }
+}
@@ -0,0 +1,590 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Configuration of the relay daemon, read from a TOML file.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use alloy_primitives::{Address, U256};
+use anyhow::{Context, Result, ensure};
+use reqwest::Url;
+use serde::Deserialize;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DaemonConfig {
+    /// First source chain block to scan for messages. Defaults to the latest final block. Unused
+    /// once the watcher has saved a checkpoint in the job store.
+    pub start_block: Option<u64>,
+    /// When a source chain block is final enough for its messages to be proven.
+    #[serde(default)]
+    pub finality: Finality,
+    /// Interval in seconds between scans of the source chain for newly finalized messages.
+    #[serde(default = "default_poll_interval_secs")]
+    pub poll_interval_secs: u64,
+    /// Interval in seconds between reconciliations of recent messages against their deliveries,
+    /// relaying any that were missed. Disabled if unset.
+    pub heal_interval_secs: Option<u64>,
+    /// Number of finalized source chain blocks each reconciliation looks back over.
+    #[serde(default = "default_heal_lookback_blocks")]
+    pub heal_lookback_blocks: u64,
+    /// Destination chains by name.
+    #[serde(default)]
+    pub chains: HashMap<String, ChainProfile>,
+    /// The source transceivers to watch.
+    pub transceivers: Vec<TransceiverConfig>,
+    /// Destinations of messages by the NTT manager that sent them and the chain they are for,
+    /// taking precedence over the default destination of the transceiver that emitted them.
+    #[serde(default)]
+    pub routes: Vec<RouteConfig>,
+    /// Ranking of the messages waiting for proving capacity.
+    #[serde(default)]
+    pub priority: PriorityConfig,
+    /// Maximum number of messages waiting in, and being worked on by, each stage of the relay
+    /// pipeline. Once full, scanning for new messages waits.
+    #[serde(default = "default_pipeline_capacity")]
+    pub pipeline_capacity: usize,
+    /// Offers made for proofs requested from the Boundless market.
+    #[serde(default)]
+    pub market: OfferConfig,
+    /// Prices of the other proving backends, used to quote what proving a message costs.
+    #[serde(default)]
+    pub pricing: Pricing,
+    /// Scope of the messages relayed, and blocks caught up on at startup.
+    #[serde(default)]
+    pub watch: WatchFilter,
+    /// Relay fees messages must be paid before they are relayed. Messages are relayed regardless
+    /// of payment if unset.
+    pub fees: Option<FeeConfig>,
+}
+
+/// A destination chain.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ChainProfile {
+    /// RPC endpoint URL.
+    pub rpc_url: Url,
+    /// Wormhole chain ID of the chain. If set, only messages sent to this chain are delivered to
+    /// its destinations, and the chain is added to the chain registry.
+    pub wormhole_chain_id: Option<u16>,
+    /// EVM chain ID of the chain, selecting the chain spec proofs of its messages are built with.
+    pub chain_id: Option<u64>,
+    /// Address of the BoundlessTransceiver on the chain. Messages sent to the chain without a
+    /// matching route or default destination are delivered to it.
+    pub transceiver: Option<Address>,
+    /// How the commitment block of messages sent from the chain is chosen.
+    #[serde(default)]
+    pub commitment: CommitmentStrategy,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CommitmentStrategy {
+    /// Commit to the latest finalized block when the message is picked up, so messages found in
+    /// the same scan share a commitment.
+    #[default]
+    Finalized,
+    /// Commit to the block containing the message.
+    MessageBlock,
+}
+
+/// When a source chain block is considered final. Messages in blocks that are not final are left
+/// for a later scan, and final blocks are what messages commit to by default.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Finality {
+    /// Blocks up to the one tagged `finalized`, which cannot be reverted without slashing.
+    #[default]
+    Finalized,
+    /// Blocks up to the one tagged `safe`, which are unlikely to be reorged but may be.
+    Safe,
+    /// Blocks with at least this many blocks built on top of them.
+    Confirmations(u64),
+}
+
+impl FromStr for Finality {
+    type Err = String;
+
+    /// Parses `finalized`, `safe` or a number of confirmations.
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "finalized" => Ok(Self::Finalized),
+            "safe" => Ok(Self::Safe),
+            other => other.parse().map(Self::Confirmations).map_err(|_| {
+                format!(
+                    "unknown finality {other}, expected finalized, safe or a number of \
+                     confirmations"
+                )
+            }),
+        }
+    }
+}
+
+impl FromStr for CommitmentStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "finalized" => Ok(Self::Finalized),
+            "message_block" => Ok(Self::MessageBlock),
+            other => Err(format!(
+                "unknown commitment strategy {other}, expected finalized or message_block"
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct TransceiverConfig {
+    /// Address of the BoundlessTransceiver contract on the source chain.
+    pub source: Address,
+    /// Name of the pair in logs and metrics labels. Defaults to the source address.
+    pub name: Option<String>,
+    /// Environment variable holding the private key paying for delivering this transceiver's
+    /// messages. Defaults to the key of the daemon.
+    pub private_key_env: Option<String>,
+    /// Maximum number of this transceiver's messages relayed at once. Transceivers with a limit
+    /// get a queue of their own, the others share the queue of the daemon.
+    pub max_concurrent_jobs: Option<usize>,
+    /// Name of the embedded guest program to prove with. Defaults to the guest matching the image
+    /// ID of the destination of each message.
+    pub guest: Option<String>,
+    /// Address of the WormholeTransceiver contract on the source chain. If set, the VAA of
+    /// messages is delivered alongside their proof to destinations with a WormholeTransceiver.
+    pub wormhole_emitter: Option<Address>,
+    /// Where messages without a matching route are delivered, if anywhere.
+    #[serde(flatten)]
+    pub destination: Option<DestinationConfig>,
+}
+
+/// A BoundlessTransceiver contract on a destination chain.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct DestinationConfig {
+    /// Address of the BoundlessTransceiver contract on the destination chain.
+    pub destination: Address,
+    /// Name of the destination chain in `chains`.
+    pub chain: String,
+    /// Address of the WormholeTransceiver contract on the destination chain, if any.
+    pub wormhole_transceiver: Option<Address>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct RouteConfig {
+    /// The source NTT manager sending the messages.
+    pub manager: Address,
+    /// Wormhole chain ID the messages are sent to.
+    pub recipient_chain: u16,
+    #[serde(flatten)]
+    pub destination: DestinationConfig,
+}
+
+/// Ranks token transfers by their value, so the most valuable are relayed first when more messages
+/// are waiting than can be relayed at once.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PriorityConfig {
+    /// Source tokens whose transfers are ranked by value. Transfers of other tokens and messages
+    /// that are not transfers get the lowest priority.
+    #[serde(default)]
+    pub tokens: Vec<TokenValue>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenValue {
+    /// Address of the token on the source chain.
+    pub token: Address,
+    /// Value of a whole token, in any unit shared by all tokens.
+    pub value: f64,
+}
+
+impl PriorityConfig {
+    /// Priority of a transfer of `amount` whole `token`s.
+    pub fn priority(&self, token: Address, amount: f64) -> u64 {
+        self.tokens
+            .iter()
+            .find(|entry| entry.token == token)
+            // Saturates at the bounds of u64
+            .map_or(0, |entry| (amount * entry.value).round() as u64)
+    }
+}
+
+/// Parameters of the offers made for proofs requested from the Boundless market. Unset prices and
+/// timeouts are derived from the number of cycles of the guest execution being proven.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OfferConfig {
+    /// Price in wei per million cycles the offer starts at.
+    pub min_price_per_mcycle_wei: Option<u64>,
+    /// Price in wei per million cycles the offer rises to by the end of the ramp-up period.
+    pub max_price_per_mcycle_wei: Option<u64>,
+    /// Seconds over which the price rises from the minimum to the maximum.
+    pub ramp_up_period_secs: Option<u32>,
+    /// Seconds the prover locking the request has to fulfill it before its stake is slashed.
+    pub lock_timeout_secs: Option<u32>,
+    /// Seconds after which the request expires unfulfilled.
+    pub timeout_secs: Option<u32>,
+    /// Stake the prover locking the request puts up, in the smallest unit of the stake token.
+    /// Defaults to the default of the market.
+    pub lock_stake: Option<U256>,
+}
+
+/// An offer for a proof, as derived by [OfferConfig::offer].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Offer {
+    pub min_price: U256,
+    pub max_price: U256,
+    pub ramp_up_period: u32,
+    pub lock_timeout: u32,
+    pub timeout: u32,
+    pub lock_stake: Option<U256>,
+}
+
+/// Default maximum price, in wei per million cycles.
+const DEFAULT_MAX_PRICE_PER_MCYCLE_WEI: u64 = 100_000_000_000;
+
+/// Cycles per second a prover is assumed to prove at when deriving timeouts.
+const ASSUMED_PROVING_HZ: u64 = 1_000_000;
+
+impl OfferConfig {
+    /// The offer for proving an execution of `cycles` cycles.
+    pub fn offer(&self, cycles: u64) -> Offer {
+        // Priced by the started million cycles, so tiny executions are not free
+        let mcycles = U256::from(cycles.div_ceil(1_000_000).max(1));
+        let price = |per_mcycle: u64| mcycles * U256::from(per_mcycle);
+
+        // Leaves a prover the time to prove the execution a few times over on top of a fixed
+        // allowance for fetching the program and input
+        let proving_secs = u32::try_from(cycles / ASSUMED_PROVING_HZ).unwrap_or(u32::MAX);
+        let lock_timeout = self.lock_timeout_secs.unwrap_or_else(|| {
+            let lock_timeout = proving_secs.saturating_mul(3).saturating_add(300);
+            self.timeout_secs
+                .map_or(lock_timeout, |timeout| lock_timeout.min(timeout))
+        });
+        let timeout = self
+            .timeout_secs
+            .unwrap_or_else(|| lock_timeout.saturating_mul(2));
+        Offer {
+            min_price: price(self.min_price_per_mcycle_wei.unwrap_or(0)),
+            max_price: price(
+                self.max_price_per_mcycle_wei
+                    .unwrap_or(DEFAULT_MAX_PRICE_PER_MCYCLE_WEI),
+            ),
+            ramp_up_period: self
+                .ramp_up_period_secs
+                .unwrap_or_else(|| (lock_timeout / 5).min(120)),
+            lock_timeout,
+            timeout,
+            lock_stake: self.lock_stake,
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let (min, max) = (self.min_price_per_mcycle_wei, self.max_price_per_mcycle_wei);
+        ensure!(
+            min.zip(max).is_none_or(|(min, max)| min <= max),
+            "market min_price_per_mcycle_wei exceeds max_price_per_mcycle_wei"
+        );
+        let (lock_timeout, timeout) = (self.lock_timeout_secs, self.timeout_secs);
+        ensure!(
+            lock_timeout
+                .zip(timeout)
+                .is_none_or(|(lock_timeout, timeout)| lock_timeout <= timeout),
+            "market lock_timeout_secs exceeds timeout_secs"
+        );
+        Ok(())
+    }
+}
+
+/// Prices of proving on Bonsai and on this host, in USD per million cycles. The defaults are rough
+/// estimates, to be replaced with the rates of the actual deployment.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct Pricing {
+    /// Rate of Bonsai.
+    pub bonsai_usd_per_mcycle: f64,
+    /// Cost of proving on this host, e.g. the hourly cost of its GPUs divided by the million
+    /// cycles they prove in an hour.
+    pub local_usd_per_mcycle: f64,
+    /// Cost of proving on a host without GPUs, for comparing backends.
+    pub cpu_usd_per_mcycle: f64,
+}
+
+impl Default for Pricing {
+    fn default() -> Self {
+        Self {
+            bonsai_usd_per_mcycle: 0.02,
+            local_usd_per_mcycle: 0.005,
+            cpu_usd_per_mcycle: 0.05,
+        }
+    }
+}
+
+/// Narrows down the messages the daemon relays, whether found while watching, catching up or
+/// healing. Messages retried or replayed through the admin API are relayed regardless.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields, default)]
+pub struct WatchFilter {
+    /// Source transceivers whose messages are relayed. Defaults to all configured transceivers.
+    pub sources: Vec<Address>,
+    /// Wormhole chain IDs of the chains whose messages are relayed. Defaults to all chains.
+    pub recipient_chains: Vec<u16>,
+    /// Blocks scanned once at startup, before watching for newly final blocks, e.g. to relay the
+    /// messages emitted while the daemon was down.
+    pub catch_up: Option<BlockRange>,
+}
+
+/// Relaying only the messages whose relay was paid for, through `RelayFeePaid` events on the
+/// source chain (see `IRelayFee.sol`). Fees are in the native token of the source chain, and the
+/// costs of relaying are converted into it at configured rates.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeeConfig {
+    /// Source chain contracts whose `RelayFeePaid` events count as payments.
+    pub payment_contracts: Vec<Address>,
+    /// Wei of the source chain token one wei of the native token of each destination chain is
+    /// worth, by chain name, converting the gas cost of deliveries.
+    pub rates: HashMap<String, f64>,
+    /// Wei of the source chain token one wei paid on the Boundless market is worth, converting
+    /// the quoted cost of proving a message.
+    #[serde(default = "default_market_rate")]
+    pub market_rate: f64,
+    /// Gas a delivery is estimated to use, priced at the gas price of the destination chain.
+    #[serde(default = "default_delivery_gas")]
+    pub delivery_gas: u64,
+    /// Number of blocks after which a message that is still not paid for is dropped, or `None`
+    /// to keep it parked until paid or retried.
+    #[serde(default)]
+    pub expire_after_blocks: Option<u64>,
+}
+
+/// A range of source chain blocks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlockRange {
+    pub from_block: u64,
+    /// Last block of the range, inclusive. Defaults to the latest final block.
+    pub to_block: Option<u64>,
+}
+
+impl WatchFilter {
+    /// Whether messages emitted by `source` for `recipient_chain` are relayed.
+    pub fn matches(&self, source: Address, recipient_chain: u16) -> bool {
+        (self.sources.is_empty() || self.sources.contains(&source))
+            && (self.recipient_chains.is_empty()
+                || self.recipient_chains.contains(&recipient_chain))
+    }
+
+    fn validate(&self, transceivers: &[TransceiverConfig]) -> Result<()> {
+        for source in &self.sources {
+            ensure!(
+                transceivers
+                    .iter()
+                    .any(|transceiver| transceiver.source == *source),
+                "watched source {source} is not a configured transceiver"
+            );
+        }
+        if let Some(range) = self.catch_up {
+            ensure!(
+                range
+                    .to_block
+                    .is_none_or(|to_block| range.from_block <= to_block),
+                "catch_up from_block exceeds to_block"
+            );
+        }
+        Ok(())
+    }
+}
+
+fn default_poll_interval_secs() -> u64 {
+    12
+}
+
+fn default_pipeline_capacity() -> usize {
+    16
+}
+
+fn default_delivery_gas() -> u64 {
+    400_000
+}
+
+fn default_market_rate() -> f64 {
+    1.0
+}
+
+fn default_heal_lookback_blocks() -> u64 {
+    // About a day of Ethereum blocks
+    7200
+}
+
+impl DaemonConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read config {}", path.display()))?;
+        let config: Self = toml::from_str(&contents)
+            .with_context(|| format!("invalid config {}", path.display()))?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        ensure!(
+            !self.transceivers.is_empty(),
+            "at least one transceiver must be configured"
+        );
+        ensure!(
+            self.pipeline_capacity > 0,
+            "pipeline_capacity must be positive"
+        );
+        self.market.validate()?;
+        self.watch.validate(&self.transceivers)?;
+        if let Some(fees) = &self.fees {
+            ensure!(
+                !fees.payment_contracts.is_empty(),
+                "fees must name at least one payment contract"
+            );
+            for name in self.chains.keys() {
+                ensure!(
+                    fees.rates.contains_key(name),
+                    "fees must set the rate of chain {name}"
+                );
+            }
+        }
+        for (i, transceiver) in self.transceivers.iter().enumerate() {
+            ensure!(
+                !self.transceivers[..i]
+                    .iter()
+                    .any(|other| other.source == transceiver.source),
+                "source transceiver {} is configured more than once",
+                transceiver.source
+            );
+        }
+        let destinations = self
+            .transceivers
+            .iter()
+            .filter_map(|transceiver| transceiver.destination.as_ref())
+            .chain(self.routes.iter().map(|route| &route.destination));
+        for destination in destinations {
+            ensure!(
+                self.chains.contains_key(&destination.chain),
+                "destination {} is on unknown chain {}",
+                destination.destination,
+                destination.chain
+            );
+        }
+        for (name, chain) in &self.chains {
+            ensure!(
+                chain.transceiver.is_none() || chain.wormhole_chain_id.is_some(),
+                "chain {name} has a transceiver but no Wormhole chain ID"
+            );
+            let duplicate = self.chains.iter().any(|(other_name, other)| {
+                other_name != name
+                    && other.wormhole_chain_id.is_some()
+                    && other.wormhole_chain_id == chain.wormhole_chain_id
+            });
+            ensure!(
+                !duplicate,
+                "Wormhole chain ID {} is configured for several chains",
+                chain.wormhole_chain_id.unwrap_or_default()
+            );
+        }
+        for route in &self.routes {
+            let chain_id = self.chains[&route.destination.chain].wormhole_chain_id;
+            ensure!(
+                chain_id.is_none_or(|chain_id| chain_id == route.recipient_chain),
+                "route for recipient chain {} leads to chain {} with Wormhole chain ID {}",
+                route.recipient_chain,
+                route.destination.chain,
+                chain_id.unwrap_or_default()
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Source {
+        finality: Finality,
+    }
+
+    #[test]
+    fn parses_finality() {
+        let parse = |toml: &str| toml::from_str::<Source>(toml).unwrap().finality;
+        assert_eq!(parse(r#"finality = "finalized""#), Finality::Finalized);
+        assert_eq!(parse(r#"finality = "safe""#), Finality::Safe);
+        assert_eq!(
+            parse("finality = { confirmations = 64 }"),
+            Finality::Confirmations(64)
+        );
+        assert_eq!("safe".parse(), Ok(Finality::Safe));
+        assert_eq!("64".parse(), Ok(Finality::Confirmations(64)));
+        assert!("latest".parse::<Finality>().is_err());
+    }
+
+    #[test]
+    fn filters_watched_messages() {
+        let source = Address::repeat_byte(1);
+        let filter: WatchFilter = toml::from_str(&format!(
+            r#"
+            sources = ["{source}"]
+            recipient_chains = [23, 30]
+            catch_up = {{ from_block = 100 }}
+            "#
+        ))
+        .unwrap();
+        assert!(filter.matches(source, 30));
+        assert!(!filter.matches(source, 2));
+        assert!(!filter.matches(Address::repeat_byte(2), 30));
+        assert_eq!(
+            filter.catch_up,
+            Some(BlockRange {
+                from_block: 100,
+                to_block: None
+            })
+        );
+        assert!(WatchFilter::default().matches(Address::repeat_byte(2), 2));
+    }
+
+    #[test]
+    fn derives_offer_from_cycles() {
+        let offer = OfferConfig::default().offer(60_000_000);
+        assert_eq!(offer.min_price, U256::ZERO);
+        assert_eq!(
+            offer.max_price,
+            U256::from(60 * DEFAULT_MAX_PRICE_PER_MCYCLE_WEI)
+        );
+        assert_eq!(offer.lock_timeout, 480);
+        assert_eq!(offer.timeout, 960);
+        assert_eq!(offer.ramp_up_period, 96);
+
+        let config = OfferConfig {
+            max_price_per_mcycle_wei: Some(5),
+            lock_timeout_secs: Some(600),
+            ..Default::default()
+        };
+        let offer = config.offer(1);
+        assert_eq!(offer.max_price, U256::from(5));
+        assert_eq!(offer.lock_timeout, 600);
+        assert_eq!(offer.timeout, 1200);
+        assert_eq!(offer.ramp_up_period, 120);
+    }
+}
@@ -0,0 +1,695 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Long running relayer delivering the messages of several source transceivers as they finalize.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::providers::Provider;
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::Address;
+use anyhow::{Context, Result, ensure};
+use common::from_wormhole_address;
+use common::ntt::TransceiverMessage;
+use tokio::sync::mpsc;
+use tracing::log;
+use zkvm::GuestProgram;
+
+use crate::admin::{AdminCommand, AdminLink, DaemonControl};
+use crate::config::{BlockRange, CommitmentStrategy, DaemonConfig};
+use crate::deliveries::{Deliveries, DeliveryListener};
+use crate::destination::Destination;
+use crate::fees::{self, FeeCheck, UnpaidMessage};
+use crate::heartbeat::Heartbeater;
+use crate::job::MessageId;
+use crate::pipeline::{Intake, Pipeline, Route};
+use crate::reconcile::find_undelivered;
+use crate::relayer::Relayer;
+use crate::routing::RoutingTable;
+use crate::scheduler::Scheduler;
+use crate::vaa::VaaRoute;
+use crate::watcher::{ObservedMessage, Watcher, finalized_block, scan};
+
+/// Number of blocks scanned at once when catching up on past blocks.
+const CATCH_UP_BATCH_BLOCKS: u64 = 2_000;
+
+/// A source transceiver along with the keys and limits its messages are relayed with.
+struct Tenant {
+    /// Name of the pair in logs and metrics.
+    name: String,
+    /// Guest proving the messages, or `None` to use the guest matching the image ID of each
+    /// message's destination.
+    guest: Option<&'static GuestProgram>,
+    /// WormholeTransceiver alongside the source transceiver, if any.
+    emitter: Option<Address>,
+    /// Pays for delivering the messages.
+    signer: PrivateKeySigner,
+    /// Queue of the messages, shared with the tenants without a limit of their own.
+    jobs: Arc<Scheduler>,
+}
+
+pub struct Daemon {
+    config: DaemonConfig,
+    relayer: Arc<Relayer>,
+    routing: RoutingTable,
+    /// Destination contracts by chain name, address and the address of the wallet paying for
+    /// delivery.
+    destinations: HashMap<(String, Address, Address), Destination>,
+    /// Tenants by source transceiver.
+    tenants: HashMap<Address, Tenant>,
+    /// Messages delivered to the destinations, by this or any other relayer.
+    deliveries: Deliveries,
+    /// Pause state and messages in flight, shared with the admin API.
+    control: Arc<DaemonControl>,
+    /// Changes requested through the admin API, if enabled.
+    commands: Option<mpsc::UnboundedReceiver<AdminCommand>>,
+    /// Destination contracts whose deliveries are listened for, by chain name and address.
+    listening: HashSet<(String, Address)>,
+    /// The stages relaying the messages, once running.
+    pipeline: Option<Pipeline>,
+    /// Pushes heartbeats to the operator at the given interval, once running, if enabled.
+    heartbeat: Option<(Heartbeater, Duration)>,
+}
+
+impl Daemon {
+    /// Creates a daemon delivering messages with `signer`, relaying at most `max_concurrent_jobs`
+    /// messages at once. Source transceivers with their own key or limit in the config use those
+    /// instead.
+    pub fn new(
+        config: DaemonConfig,
+        relayer: Arc<Relayer>,
+        signer: PrivateKeySigner,
+        max_concurrent_jobs: usize,
+    ) -> Result<Self> {
+        let shared_jobs = Scheduler::new(max_concurrent_jobs);
+        let mut tenants = HashMap::new();
+        for transceiver in &config.transceivers {
+            let guest = transceiver
+                .guest
+                .as_deref()
+                .map(|name| {
+                    GuestProgram::by_name(name)
+                        .with_context(|| format!("Unknown guest program: {name}"))
+                })
+                .transpose()?;
+            let signer = match &transceiver.private_key_env {
+                Some(var) => std::env::var(var)
+                    .with_context(|| format!("{var} is not set"))?
+                    .parse::<PrivateKeySigner>()
+                    .with_context(|| format!("{var} is not a valid private key"))?,
+                None => signer.clone(),
+            };
+            let tenant = Tenant {
+                name: transceiver
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| transceiver.source.to_string()),
+                guest,
+                emitter: transceiver.wormhole_emitter,
+                signer,
+                jobs: transceiver
+                    .max_concurrent_jobs
+                    .map_or_else(|| shared_jobs.clone(), Scheduler::new),
+            };
+            tenants.insert(transceiver.source, tenant);
+        }
+
+        let routing = RoutingTable::new(&config);
+        let registered: Vec<_> = routing
+            .registry()
+            .iter()
+            .filter_map(|(_, chain)| chain.destination())
+            .collect();
+        let mut destinations = HashMap::new();
+        let configured: Vec<_> = config
+            .transceivers
+            .iter()
+            .filter_map(|transceiver| transceiver.destination.as_ref())
+            .chain(config.routes.iter().map(|route| &route.destination))
+            .chain(&registered)
+            .collect();
+        // Messages may be routed to any destination, so each is set up with every tenant's wallet
+        for tenant in tenants.values() {
+            for destination in &configured {
+                let chain = &config.chains[&destination.chain];
+                destinations
+                    .entry((
+                        destination.chain.clone(),
+                        destination.destination,
+                        tenant.signer.address(),
+                    ))
+                    .or_insert_with(|| {
+                        Destination::new(
+                            destination.destination,
+                            chain.rpc_url.clone(),
+                            tenant.signer.clone(),
+                        )
+                    });
+            }
+        }
+
+        Ok(Self {
+            routing,
+            config,
+            relayer,
+            destinations,
+            tenants,
+            deliveries: Deliveries::default(),
+            control: Arc::default(),
+            commands: None,
+            listening: HashSet::new(),
+            pipeline: None,
+            heartbeat: None,
+        })
+    }
+
+    /// Lets the daemon be controlled through the admin API.
+    pub fn with_admin(mut self, link: AdminLink) -> Self {
+        self.control = link.control;
+        self.commands = Some(link.commands);
+        self
+    }
+
+    /// Pushes a heartbeat with `heartbeater` every `interval` while running.
+    pub fn with_heartbeat(mut self, heartbeater: Heartbeater, interval: Duration) -> Self {
+        self.heartbeat = Some((heartbeater, interval));
+        self
+    }
+
+    /// Watches the source transceivers and relays their messages until an unrecoverable error.
+    pub async fn run(mut self) -> Result<()> {
+        let sources = self.tenants.keys().copied().collect();
+        let mut watcher = Watcher::new(
+            self.relayer.rpc.clone(),
+            sources,
+            self.config.finality,
+            self.config.start_block,
+        )
+        .await?
+        .with_filter(self.config.watch.clone());
+        if let Some(store) = &self.relayer.store {
+            watcher = watcher.with_checkpoints(store.clone())?;
+            let unpaid = store.load_unpaid()?;
+            if !unpaid.is_empty() {
+                log::info!("Resuming {} parked messages", unpaid.len());
+            }
+            for message in unpaid {
+                self.control.park(message);
+            }
+        }
+        log::info!(
+            "Watching {} source transceivers from block {}",
+            self.tenants.len(),
+            watcher.next_block()
+        );
+
+        self.pipeline = Some(Pipeline::spawn(
+            self.relayer.clone(),
+            self.deliveries.clone(),
+            self.control.clone(),
+            self.config.pipeline_capacity,
+            self.config.finality,
+            Duration::from_secs(self.config.poll_interval_secs),
+        ));
+
+        let destinations: Vec<_> = self
+            .destinations
+            .iter()
+            .map(|((chain, _, _), destination)| (chain.clone(), destination.clone()))
+            .collect();
+        for (chain, destination) in destinations {
+            self.listen(chain, destination).await;
+        }
+        for destination in self.destinations.values() {
+            self.relayer.record_wallet_balance(destination).await;
+        }
+        if let Some((heartbeater, interval)) = self.heartbeat.take() {
+            heartbeater.spawn(self.control.clone(), interval);
+        }
+        if let Some(range) = self.config.watch.catch_up {
+            self.catch_up(&watcher, range)
+                .await
+                .context("failed to catch up on past blocks")?;
+        }
+
+        let mut ticker = tokio::time::interval(Duration::from_secs(self.config.poll_interval_secs));
+        let mut heal_ticker = self
+            .config
+            .heal_interval_secs
+            .map(|secs| tokio::time::interval(Duration::from_secs(secs)));
+        let mut commands = self.commands.take();
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => self.poll(&mut watcher).await,
+                _ = async { heal_ticker.as_mut().unwrap().tick().await }, if heal_ticker.is_some() => {
+                    self.heal().await
+                }
+                Some(command) = async { commands.as_mut().unwrap().recv().await }, if commands.is_some() => {
+                    if let Err(e) = self.apply(command).await {
+                        log::error!("Failed to apply admin request: {e:#}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Starts recording the deliveries to `destination` on `chain`, unless already listening.
+    /// Deliveries do not depend on the wallet, so one listener per contract suffices.
+    async fn listen(&mut self, chain: String, destination: Destination) {
+        if !self.listening.insert((chain, destination.address())) {
+            return;
+        }
+        match DeliveryListener::new(destination.clone(), self.deliveries.clone()).await {
+            Ok(listener) => {
+                tokio::spawn(listener.run(Duration::from_secs(self.config.poll_interval_secs)));
+            }
+            Err(e) => log::warn!(
+                "Not listening for deliveries to {}: {e:#}",
+                destination.address()
+            ),
+        }
+    }
+
+    /// Applies a change requested through the admin API.
+    async fn apply(&mut self, command: AdminCommand) -> Result<()> {
+        match command {
+            AdminCommand::Retry(id) => self.retry(id).await,
+            AdminCommand::Replay(id) => {
+                self.relayer.release(&id)?;
+                self.retry(id).await
+            }
+            AdminCommand::AddRoute(route) => {
+                let chain = self
+                    .config
+                    .chains
+                    .get(&route.destination.chain)
+                    .with_context(|| format!("unknown chain {}", route.destination.chain))?
+                    .clone();
+                if let Some(id) = chain.wormhole_chain_id {
+                    ensure!(
+                        id == route.recipient_chain,
+                        "chain {} has Wormhole chain ID {id}, not {}",
+                        route.destination.chain,
+                        route.recipient_chain
+                    );
+                }
+                let signers: Vec<_> = self
+                    .tenants
+                    .values()
+                    .map(|tenant| tenant.signer.clone())
+                    .collect();
+                for signer in signers {
+                    let key = (
+                        route.destination.chain.clone(),
+                        route.destination.destination,
+                        signer.address(),
+                    );
+                    let destination = self
+                        .destinations
+                        .entry(key)
+                        .or_insert_with(|| {
+                            Destination::new(
+                                route.destination.destination,
+                                chain.rpc_url.clone(),
+                                signer,
+                            )
+                        })
+                        .clone();
+                    self.listen(route.destination.chain.clone(), destination)
+                        .await;
+                }
+                self.routing.add_route(&route);
+                // Keep the config in sync, as reconciliation routes messages by it
+                self.config.routes.retain(|existing| {
+                    (existing.manager, existing.recipient_chain)
+                        != (route.manager, route.recipient_chain)
+                });
+                log::info!(
+                    "Added route of messages from {} to chain {}",
+                    route.manager,
+                    route.recipient_chain
+                );
+                self.config.routes.push(route);
+                Ok(())
+            }
+            AdminCommand::RemoveRoute {
+                manager,
+                recipient_chain,
+            } => {
+                self.routing
+                    .remove_route(manager, recipient_chain)
+                    .with_context(|| {
+                        format!("no route of messages from {manager} to chain {recipient_chain}")
+                    })?;
+                self.config.routes.retain(|route| {
+                    (route.manager, route.recipient_chain) != (manager, recipient_chain)
+                });
+                log::info!("Removed route of messages from {manager} to chain {recipient_chain}");
+                Ok(())
+            }
+        }
+    }
+
+    /// Relays the message `id` of a watched source transceiver again.
+    async fn retry(&self, id: MessageId) -> Result<()> {
+        let rpc = &self.relayer.rpc;
+        let block = rpc
+            .run(|endpoint| async move {
+                let receipt = endpoint
+                    .provider()
+                    .get_transaction_receipt(id.tx_hash)
+                    .await?
+                    .with_context(|| format!("transaction {} not found", id.tx_hash))?;
+                receipt.block_number.context("transaction is pending")
+            })
+            .await?;
+        let finalized = finalized_block(rpc, self.config.finality).await?;
+        ensure!(
+            block <= finalized,
+            "block {block} of message {id} is not final yet"
+        );
+
+        let sources: Vec<Address> = self.tenants.keys().copied().collect();
+        let message = scan(rpc, &sources, block, block)
+            .await?
+            .into_iter()
+            .find(|message| message.id == id)
+            .with_context(|| format!("no message {id} from a watched source transceiver"))?;
+        log::info!("Retrying message {id}");
+        // Retrying relays the message whether or not its relay was paid for
+        self.admit(message, finalized, false).await;
+        Ok(())
+    }
+
+    /// Whether new messages are held back, either by an operator or by the circuit breaker.
+    fn is_halted(&self) -> bool {
+        self.control.is_paused() || self.relayer.breaker.tripped().is_some()
+    }
+
+    /// Relays the messages in blocks finalized since the last poll. While halted, the blocks are
+    /// left for the first poll after resuming.
+    async fn poll(&self, watcher: &mut Watcher) {
+        if self.is_halted() {
+            return;
+        }
+        let (messages, commitment_block) = match watcher.poll().await {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("Failed to scan for messages: {e:#}");
+                return;
+            }
+        };
+        for message in messages {
+            self.admit(message, commitment_block, true).await;
+        }
+        // Parked messages are relayed once the fees paid since cover their relay
+        let expire_after = self
+            .config
+            .fees
+            .as_ref()
+            .and_then(|fees| fees.expire_after_blocks);
+        for unpaid in self.control.unpaid() {
+            if let Some(blocks) = expire_after
+                .filter(|blocks| unpaid.message.block_number + blocks < commitment_block)
+            {
+                log::warn!(
+                    "Dropping message {}: not paid for within {blocks} blocks",
+                    unpaid.id()
+                );
+                self.control.unpark(&unpaid.id());
+                continue;
+            }
+            self.admit(unpaid.message, commitment_block, true).await;
+        }
+        self.control.set_next_block(watcher.next_block());
+        // The checkpoint must not move past parked messages before they are saved
+        if let Err(e) = self.save_unpaid() {
+            log::warn!("Failed to save parked messages: {e:#}");
+            return;
+        }
+        if let Err(e) = watcher.save() {
+            log::warn!("Failed to save watcher checkpoint: {e:#}");
+        }
+    }
+
+    /// Relays the messages in `range`, up to the latest final block. The range is scanned a batch
+    /// of blocks at a time, letting the pipeline hold back scanning as it does while watching.
+    async fn catch_up(&self, watcher: &Watcher, range: BlockRange) -> Result<()> {
+        let finalized = finalized_block(&self.relayer.rpc, self.config.finality).await?;
+        let to_block = range
+            .to_block
+            .map_or(finalized, |block| block.min(finalized));
+        log::info!("Catching up on blocks {} to {to_block}", range.from_block);
+        let mut from_block = range.from_block;
+        while from_block <= to_block {
+            let batch_end = to_block.min(from_block + CATCH_UP_BATCH_BLOCKS - 1);
+            // Messages also found by the watcher are only relayed once
+            for message in watcher.scan_range(from_block, batch_end).await? {
+                self.admit(message, finalized, true).await;
+            }
+            from_block = batch_end + 1;
+        }
+        Ok(())
+    }
+
+    /// Reconciles the recently finalized messages against their deliveries and relays those that
+    /// were missed, e.g. because they failed or were emitted while the daemon was down.
+    async fn heal(&self) {
+        if self.is_halted() {
+            return;
+        }
+        let rpc = &self.relayer.rpc;
+        let result = async {
+            let finalized = finalized_block(rpc, self.config.finality).await?;
+            let from_block = finalized.saturating_sub(self.config.heal_lookback_blocks);
+            let undelivered = find_undelivered(rpc, &self.config, from_block, finalized).await?;
+            anyhow::Ok((undelivered, finalized))
+        }
+        .await;
+        let (undelivered, commitment_block) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                log::warn!("Failed to reconcile deliveries: {e:#}");
+                return;
+            }
+        };
+        for message in undelivered {
+            if !self
+                .config
+                .watch
+                .matches(message.source, message.recipient_chain)
+            {
+                continue;
+            }
+            // Dead-lettered messages wait for an operator to replay them
+            if self.control.is_in_flight(&message.id)
+                || self
+                    .relayer
+                    .is_dead_lettered(&message.id)
+                    .unwrap_or_default()
+            {
+                continue;
+            }
+            log::info!(
+                "Healing message {}, undelivered for {}s",
+                message.id,
+                message.age_secs
+            );
+            self.admit(
+                ObservedMessage {
+                    id: message.id,
+                    source: message.source,
+                    block_number: message.block_number,
+                    recipient_chain: message.recipient_chain,
+                    encoded_message: message.encoded_message,
+                },
+                commitment_block,
+                true,
+            )
+            .await;
+        }
+    }
+
+    /// Looks up how a message is delivered.
+    fn route(&self, message: &ObservedMessage) -> Result<Option<Route>> {
+        let tenant = &self.tenants[&message.source];
+        let transceiver_message =
+            TransceiverMessage::parse(&message.encoded_message).map_err(anyhow::Error::msg)?;
+        let manager = from_wormhole_address(transceiver_message.source_ntt_manager);
+        let Some(destination) =
+            self.routing
+                .resolve(message.source, manager, message.recipient_chain)
+        else {
+            return Ok(None);
+        };
+        let vaa = tenant.emitter.zip(destination.wormhole_transceiver).map(
+            |(emitter, wormhole_transceiver)| VaaRoute {
+                emitter,
+                wormhole_transceiver,
+            },
+        );
+        let chain = destination.chain.clone();
+        let destination = self.destinations[&(
+            destination.chain.clone(),
+            destination.destination,
+            tenant.signer.address(),
+        )]
+            .clone();
+        // Only BoundlessTransceiver messages carry their source chain
+        let source_chain_id = transceiver_message.source_chain().ok();
+        let digest = source_chain_id.map(|chain| transceiver_message.digest(chain));
+        let source_chain = source_chain_id
+            .and_then(|chain| self.routing.registry().resolve(chain))
+            .cloned();
+        let priority = transceiver_message.transfer().map_or(0, |transfer| {
+            self.config.priority.priority(
+                from_wormhole_address(transfer.source_token),
+                transfer.tokens(),
+            )
+        });
+        Ok(Some(Route {
+            guest: tenant.guest,
+            destination,
+            chain,
+            vaa,
+            digest,
+            priority,
+            source_chain,
+            source_chain_id,
+        }))
+    }
+
+    /// Hands a finalized message to the pipeline, unless it has no route, is already delivered or
+    /// is already being relayed. With `charge` and fees configured, a message whose relay was not
+    /// paid for is parked instead. Waits while the pipeline is full.
+    async fn admit(&self, message: ObservedMessage, commitment_block: u64, charge: bool) {
+        let route = match self.route(&message) {
+            Ok(Some(route)) => route,
+            Ok(None) => {
+                // Not an error: messages for chains this relayer does not serve are expected
+                log::debug!(
+                    "Skipping message {}: no route to chain {}",
+                    message.id,
+                    message.recipient_chain
+                );
+                return;
+            }
+            Err(e) => {
+                log::warn!("Skipping message {}: {e:#}", message.id);
+                return;
+            }
+        };
+        let transceiver = route.destination.address();
+        if let Some(tx_hash) = route
+            .digest
+            .and_then(|digest| self.deliveries.get(transceiver, digest))
+        {
+            log::info!(
+                "Skipping message {}: already delivered in transaction {tx_hash}",
+                message.id
+            );
+            self.control.unpark(&message.id);
+            return;
+        }
+        if self.control.is_in_flight(&message.id) {
+            log::debug!("Message {} is already being relayed", message.id);
+            return;
+        }
+        if let Some(fees) = self.config.fees.as_ref().filter(|_| charge) {
+            let previous = self
+                .control
+                .parked(&message.id)
+                .and_then(|unpaid| unpaid.fee);
+            let check = fees::check(
+                fees,
+                &self.relayer,
+                &message,
+                &route,
+                previous,
+                commitment_block,
+            )
+            .await;
+            match check {
+                Ok(fee) if fee.covered() => {}
+                Ok(fee) => {
+                    log::info!(
+                        "Parking message {}: {} wei paid of a {} wei relay cost",
+                        message.id,
+                        fee.paid_wei,
+                        fee.cost_wei
+                    );
+                    self.park(message, Some(fee));
+                    return;
+                }
+                Err(e) => {
+                    log::warn!(
+                        "Parking message {}: failed to check its fee: {e:#}",
+                        message.id
+                    );
+                    self.park(message, previous);
+                    return;
+                }
+            }
+        }
+        self.control.unpark(&message.id);
+        if !self.control.start(message.id) {
+            log::debug!("Message {} is already being relayed", message.id);
+            return;
+        }
+        let tenant = &self.tenants[&message.source];
+        log::info!(
+            "Observed message {} from {} ({}) in block {}",
+            message.id,
+            message.source,
+            tenant.name,
+            message.block_number
+        );
+
+        let commitment_block = match route.source_chain.as_ref().map(|chain| chain.commitment) {
+            Some(CommitmentStrategy::MessageBlock) => message.block_number,
+            _ => commitment_block,
+        };
+        let intake = Intake {
+            id: message.id,
+            source: message.source,
+            tenant: tenant.name.clone(),
+            jobs: tenant.jobs.clone(),
+            route,
+            commitment_block,
+        };
+        self.pipeline().send(intake).await;
+    }
+
+    /// Holds back a message until its relay is paid for.
+    fn park(&self, message: ObservedMessage, fee: Option<FeeCheck>) {
+        self.control.park(UnpaidMessage { message, fee });
+    }
+
+    /// Persists the parked messages, so they are checked again after a restart although the
+    /// watcher moved past their blocks.
+    fn save_unpaid(&self) -> Result<()> {
+        match &self.relayer.store {
+            Some(store) => store.save_unpaid(&self.control.unpaid()),
+            None => Ok(()),
+        }
+    }
+
+    fn pipeline(&self) -> &Pipeline {
+        self.pipeline
+            .as_ref()
+            .expect("the pipeline runs while the daemon does")
+    }
+}
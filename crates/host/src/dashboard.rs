@@ -0,0 +1,252 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Read-only HTML dashboard of the relay, served at the root of the API for those who would rather
+//! not use the command line to check on it.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use anyhow::Result;
+use reqwest::Url;
+
+use crate::api::ApiState;
+use crate::config::DaemonConfig;
+use crate::job::MessageState;
+
+/// Number of recent failures shown.
+const RECENT_FAILURES: usize = 20;
+
+/// Seconds after which the browser reloads the dashboard.
+const REFRESH_SECONDS: u32 = 10;
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;color:#222}\
+table{border-collapse:collapse;margin-bottom:1.5em}\
+td,th{border:1px solid #ccc;padding:.3em .6em;text-align:left}\
+th{background:#f4f4f4}.bad{color:#b00}.good{color:#070}code{font-size:.9em}";
+
+/// Renders the dashboard from the current state of the relay.
+pub fn render(state: &ApiState) -> Result<String> {
+    let mut html = String::new();
+    writeln!(
+        html,
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\">\
+         <meta http-equiv=\"refresh\" content=\"{REFRESH_SECONDS}\">\
+         <title>Relay status</title><style>{STYLE}</style></head><body><h1>Relay status</h1>"
+    )?;
+
+    let metrics = &state.metrics;
+    let (class, health) = if metrics.breaker_tripped() {
+        ("bad", "Submissions halted by the circuit breaker")
+    } else {
+        ("good", "Running")
+    };
+    let latency = metrics.latency_percentiles();
+    writeln!(
+        html,
+        "<p class=\"{class}\"><strong>{health}</strong></p>\
+         <p>Delivery latency over the last {} messages: p50 {:?}, p90 {:?}, p99 {:?}</p>",
+        latency.samples, latency.p50, latency.p90, latency.p99
+    )?;
+
+    writeln!(html, "<h2>Messages</h2>")?;
+    match &state.store {
+        Some(store) => {
+            let jobs = store.list()?;
+            let mut states: BTreeMap<String, usize> = BTreeMap::new();
+            for job in &jobs {
+                *states.entry(job.state.to_string()).or_default() += 1;
+            }
+            let dead_letters = jobs.iter().filter(|job| job.dead_lettered).count();
+            let in_flight = jobs.iter().filter(|job| !job.state.is_terminal()).count();
+            writeln!(
+                html,
+                "<p>{in_flight} in flight, {dead_letters} in the dead-letter queue</p>\
+                 <table><tr><th>State</th><th>Messages</th></tr>"
+            )?;
+            for (state, count) in states {
+                let class = if state == MessageState::Failed.to_string() {
+                    " class=\"bad\""
+                } else {
+                    ""
+                };
+                writeln!(html, "<tr{class}><td>{state}</td><td>{count}</td></tr>")?;
+            }
+            writeln!(html, "</table>")?;
+        }
+        None => writeln!(html, "<p>Messages are not persisted by this relay.</p>")?,
+    }
+
+    writeln!(
+        html,
+        "<h2>Recent failures</h2><table><tr><th>Time (UTC)</th><th>Message</th><th>Error</th></tr>"
+    )?;
+    for error in metrics.recent_errors().iter().rev().take(RECENT_FAILURES) {
+        writeln!(
+            html,
+            "<tr><td>{}</td><td><code>{}</code></td><td class=\"bad\">{}</td></tr>",
+            utc(error.at),
+            error.id,
+            escape(&error.error)
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    if let Some(config) = &state.config {
+        render_config(&mut html, config)?;
+    }
+
+    writeln!(html, "</body></html>")?;
+    Ok(html)
+}
+
+/// Renders the configuration the relay was started with. RPC URLs are reduced to their host, as
+/// their path and query often hold API keys.
+fn render_config(html: &mut String, config: &DaemonConfig) -> Result<()> {
+    writeln!(
+        html,
+        "<h2>Configuration</h2><p>Polling every {}s, healing {}, pipeline capacity {}</p>",
+        config.poll_interval_secs,
+        match config.heal_interval_secs {
+            Some(secs) => format!("every {secs}s over {} blocks", config.heal_lookback_blocks),
+            None => "disabled".to_string(),
+        },
+        config.pipeline_capacity
+    )?;
+
+    writeln!(
+        html,
+        "<table><tr><th>Transceiver</th><th>Source</th><th>Destination</th><th>Chain</th>\
+         <th>Guest</th></tr>"
+    )?;
+    for transceiver in &config.transceivers {
+        let (destination, chain) = match &transceiver.destination {
+            Some(destination) => (
+                destination.destination.to_string(),
+                destination.chain.clone(),
+            ),
+            None => ("-".to_string(), "-".to_string()),
+        };
+        writeln!(
+            html,
+            "<tr><td>{}</td><td><code>{}</code></td><td><code>{destination}</code></td>\
+             <td>{}</td><td>{}</td></tr>",
+            escape(transceiver.name.as_deref().unwrap_or("-")),
+            transceiver.source,
+            escape(&chain),
+            escape(transceiver.guest.as_deref().unwrap_or("by image ID")),
+        )?;
+    }
+    writeln!(html, "</table>")?;
+
+    if !config.routes.is_empty() {
+        writeln!(
+            html,
+            "<table><tr><th>Manager</th><th>Recipient chain</th><th>Destination</th>\
+             <th>Chain</th></tr>"
+        )?;
+        for route in &config.routes {
+            writeln!(
+                html,
+                "<tr><td><code>{}</code></td><td>{}</td><td><code>{}</code></td><td>{}</td></tr>",
+                route.manager,
+                route.recipient_chain,
+                route.destination.destination,
+                escape(&route.destination.chain)
+            )?;
+        }
+        writeln!(html, "</table>")?;
+    }
+
+    writeln!(
+        html,
+        "<table><tr><th>Chain</th><th>Chain ID</th><th>Wormhole chain ID</th><th>RPC host</th>\
+         </tr>"
+    )?;
+    let chains: BTreeMap<_, _> = config.chains.iter().collect();
+    for (name, chain) in chains {
+        let optional = |id: Option<String>| id.unwrap_or_else(|| "-".to_string());
+        writeln!(
+            html,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            escape(name),
+            optional(chain.chain_id.map(|id| id.to_string())),
+            optional(chain.wormhole_chain_id.map(|id| id.to_string())),
+            escape(&host(&chain.rpc_url))
+        )?;
+    }
+    writeln!(html, "</table>")?;
+    Ok(())
+}
+
+fn host(url: &Url) -> String {
+    url.host_str().unwrap_or("-").to_string()
+}
+
+/// Formats a Unix timestamp in seconds as a UTC date and time.
+fn utc(timestamp: u64) -> String {
+    let days = timestamp / 86400;
+    let secs = timestamp % 86400;
+    // Civil date from days since the epoch, after Howard Hinnant's algorithm
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_utc_timestamps() {
+        assert_eq!(utc(0), "1970-01-01 00:00:00");
+        assert_eq!(utc(951_782_400), "2000-02-29 00:00:00");
+        assert_eq!(utc(1_700_000_000), "2023-11-14 22:13:20");
+    }
+
+    #[test]
+    fn escapes_html() {
+        assert_eq!(
+            escape("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;"
+        );
+    }
+}
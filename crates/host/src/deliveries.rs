@@ -0,0 +1,153 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deliveries of messages to destination transceivers, whoever made them.
+//!
+//! Several relayers may serve the same transceivers. Listening for the attestations the
+//! destination NTT manager records lets the daemon stop relaying messages another relayer has
+//! already delivered instead of submitting duplicates.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy_primitives::{Address, B256, TxHash};
+use alloy_sol_types::SolEvent;
+use anyhow::Result;
+use common::IBoundlessTransceiver;
+use common::ntt::INttManager::MessageAttestedTo;
+use tokio::sync::Notify;
+use tracing::log;
+
+use crate::destination::Destination;
+
+/// The messages delivered to destination transceivers, by transceiver and NTT message digest.
+#[derive(Clone, Default)]
+pub struct Deliveries {
+    delivered: Arc<Mutex<HashMap<(Address, B256), TxHash>>>,
+    notify: Arc<Notify>,
+}
+
+impl Deliveries {
+    /// Records that the message with `digest` was delivered to `transceiver` in `tx_hash`.
+    pub fn record(&self, transceiver: Address, digest: B256, tx_hash: TxHash) {
+        self.delivered
+            .lock()
+            .unwrap()
+            .insert((transceiver, digest), tx_hash);
+        self.notify.notify_waiters();
+    }
+
+    /// The transaction that delivered the message with `digest` to `transceiver`, if seen.
+    pub fn get(&self, transceiver: Address, digest: B256) -> Option<TxHash> {
+        self.delivered
+            .lock()
+            .unwrap()
+            .get(&(transceiver, digest))
+            .copied()
+    }
+
+    /// Waits until the message with `digest` is delivered to `transceiver`.
+    pub async fn wait(&self, transceiver: Address, digest: B256) -> TxHash {
+        loop {
+            // Register for notifications before checking, so a delivery recorded in between is
+            // not missed
+            let notified = self.notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            if let Some(tx_hash) = self.get(transceiver, digest) {
+                return tx_hash;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Scans a destination for the messages its NTT manager received through the transceiver.
+pub struct DeliveryListener {
+    destination: Destination,
+    manager: Address,
+    next_block: u64,
+    deliveries: Deliveries,
+}
+
+impl DeliveryListener {
+    /// Creates a listener recording deliveries to `destination` from its latest block on.
+    pub async fn new(destination: Destination, deliveries: Deliveries) -> Result<Self> {
+        let contract = IBoundlessTransceiver::new(destination.address(), destination.provider());
+        let manager = contract.nttManager().call().await?;
+        let next_block = destination.provider().get_block_number().await?;
+        Ok(Self {
+            destination,
+            manager,
+            next_block,
+            deliveries,
+        })
+    }
+
+    /// Records the deliveries in blocks produced since the last poll.
+    pub async fn poll(&mut self) -> Result<()> {
+        let provider = self.destination.provider();
+        let latest = provider.get_block_number().await?;
+        if latest < self.next_block {
+            return Ok(());
+        }
+
+        let filter = Filter::new()
+            .address(self.manager)
+            .event_signature(MessageAttestedTo::SIGNATURE_HASH)
+            .from_block(self.next_block)
+            .to_block(latest);
+        for log in provider.get_logs(&filter).await? {
+            let event = match MessageAttestedTo::decode_log(&log.inner) {
+                Ok(event) => event,
+                Err(e) => {
+                    log::warn!("Skipping invalid MessageAttestedTo event: {e}");
+                    continue;
+                }
+            };
+            // The manager records attestations of all its transceivers
+            if event.transceiver != self.destination.address() {
+                continue;
+            }
+            let tx_hash = log.transaction_hash.unwrap_or_default();
+            log::debug!(
+                "Message {} delivered to {} in transaction {tx_hash}",
+                event.digest,
+                event.transceiver
+            );
+            self.deliveries
+                .record(event.transceiver, event.digest, tx_hash);
+        }
+
+        self.next_block = latest + 1;
+        Ok(())
+    }
+
+    /// Polls for deliveries every `interval`, forever.
+    pub async fn run(mut self, interval: Duration) {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.poll().await {
+                log::warn!(
+                    "Failed to scan {} for deliveries: {e:#}",
+                    self.destination.address()
+                );
+            }
+        }
+    }
+}
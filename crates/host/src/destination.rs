@@ -0,0 +1,436 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delivery of proofs to the BoundlessTransceiver contract on a destination chain.
+
+use alloy::eips::eip2718::Encodable2718;
+use alloy::network::{Ethereum, EthereumWallet, TransactionBuilder};
+use alloy::providers::{DynProvider, PendingTransactionBuilder, Provider, ProviderBuilder};
+use alloy::rpc::types::{TransactionReceipt, TransactionRequest};
+use alloy::signers::local::PrivateKeySigner;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use alloy_primitives::{Address, B256, Bytes, TxHash};
+use alloy_sol_types::SolCall;
+use anyhow::{Context, Result, ensure};
+use common::ntt::{INttManager, TransceiverMessage};
+use common::{IBoundlessTransceiver, ICommitmentValidator, Journal, TWO_OF_TWO_FLAG};
+use reqwest::Url;
+use risc0_zkvm::Digest;
+use risc0_zkvm::sha::{Impl, Sha256};
+use tokio::sync::OnceCell;
+use tracing::log;
+use zkvm::GuestProgram;
+
+use crate::breaker::{Anomaly, CircuitBreaker};
+use crate::job::MessageState;
+use crate::metrics::WalletBalance;
+use crate::proof::DeliveryProof;
+use crate::store::TrackedJob;
+use crate::verifier::{check_verifier, verify_seal};
+
+/// Fetches the image ID the destination contract verifies proofs of messages from the Wormhole
+/// chain `source_chain` against. Fails if the contract does not accept messages from the chain.
+pub async fn source_image_id(
+    contract: Address,
+    source_chain: u16,
+    provider: impl Provider,
+) -> Result<Digest> {
+    let source = IBoundlessTransceiver::new(contract, provider)
+        .authorizedSources(source_chain)
+        .call()
+        .await?;
+    ensure!(
+        source.commitmentValidator != Address::ZERO,
+        "{contract} does not accept messages from chain {source_chain}"
+    );
+    Ok(Digest::from(source.imageID.0))
+}
+
+/// Looks up whether messages were delivered through a destination transceiver.
+pub struct DeliveryCheck {
+    manager: INttManager::INttManagerInstance<DynProvider>,
+    /// Index of the transceiver among those of the manager.
+    index: u8,
+}
+
+impl DeliveryCheck {
+    pub async fn new(transceiver: Address, provider: DynProvider) -> Result<Self> {
+        let manager = IBoundlessTransceiver::new(transceiver, &provider)
+            .nttManager()
+            .call()
+            .await?;
+        let manager = INttManager::new(manager, provider);
+        let transceivers = manager.getTransceivers().call().await?;
+        let info = manager.getTransceiverInfo().call().await?;
+        let (_, info) = transceivers
+            .into_iter()
+            .zip(info)
+            .find(|(address, _)| *address == transceiver)
+            .with_context(|| {
+                format!(
+                    "{transceiver} is not an enabled transceiver of NTT manager {}",
+                    manager.address()
+                )
+            })?;
+        Ok(Self {
+            manager,
+            index: info.index,
+        })
+    }
+
+    /// Whether the transceiver attested to the message, or the manager executed it through its
+    /// other transceivers.
+    pub async fn delivered(&self, digest: B256) -> Result<bool> {
+        Ok(self
+            .manager
+            .transceiverAttestedToMessage(digest, self.index)
+            .call()
+            .await?
+            || self.manager.isMessageExecuted(digest).call().await?)
+    }
+}
+
+/// A BoundlessTransceiver contract on a destination chain, along with a wallet to pay for
+/// delivering messages to it.
+#[derive(Clone)]
+pub struct Destination {
+    contract: Address,
+    rpc_url: Url,
+    provider: DynProvider,
+    /// Address of the wallet paying for deliveries, if any.
+    sender: Option<Address>,
+    /// The guests matching the image IDs of the contract, by the source chain they were looked up
+    /// for.
+    guests: Arc<Mutex<HashMap<u16, &'static GuestProgram>>>,
+    /// Looks up the messages delivered through the contract, once set up.
+    deliveries: Arc<OnceCell<DeliveryCheck>>,
+}
+
+impl Destination {
+    pub fn new(contract: Address, rpc_url: Url, signer: PrivateKeySigner) -> Self {
+        let sender = signer.address();
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_http(rpc_url.clone())
+            .erased();
+        Self {
+            contract,
+            rpc_url,
+            provider,
+            sender: Some(sender),
+            guests: Default::default(),
+            deliveries: Default::default(),
+        }
+    }
+
+    /// Creates a destination without a wallet, which can only prepare transactions for signing
+    /// elsewhere.
+    pub fn read_only(contract: Address, rpc_url: Url) -> Self {
+        let provider = ProviderBuilder::new()
+            .connect_http(rpc_url.clone())
+            .erased();
+        Self {
+            contract,
+            rpc_url,
+            provider,
+            sender: None,
+            guests: Default::default(),
+            deliveries: Default::default(),
+        }
+    }
+
+    pub fn address(&self) -> Address {
+        self.contract
+    }
+
+    pub fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+
+    /// Fetches the balance of the wallet paying for deliveries, if the destination has one.
+    pub async fn wallet_balance(&self) -> Result<Option<WalletBalance>> {
+        let Some(address) = self.sender else {
+            return Ok(None);
+        };
+        let chain_id = self.provider.get_chain_id().await?;
+        let balance = self.provider.get_balance(address).await?;
+        Ok(Some(WalletBalance {
+            chain_id,
+            address,
+            balance_wei: balance.saturating_to(),
+        }))
+    }
+
+    /// Returns the embedded or loaded guest whose image ID the contract verifies proofs of messages
+    /// from the Wormhole chain `source_chain` against, so proofs are produced with the right guest
+    /// when several deployments coexist.
+    pub async fn guest(&self, source_chain: u16) -> Result<&'static GuestProgram> {
+        if let Some(guest) = self.guests.lock().unwrap().get(&source_chain) {
+            return Ok(guest);
+        }
+        let image_id = source_image_id(self.contract, source_chain, &self.provider).await?;
+        let guest = GuestProgram::all()
+            .into_iter()
+            .find(|guest| Digest::from(guest.image_id) == image_id)
+            .with_context(|| {
+                format!(
+                    "No embedded or loaded guest matches image ID {image_id} of {} for chain \
+                     {source_chain}",
+                    self.contract
+                )
+            })?;
+        log::info!(
+            "Selected guest {} matching the image ID of {} for chain {source_chain}",
+            guest.name,
+            self.contract
+        );
+        self.guests.lock().unwrap().insert(source_chain, guest);
+        Ok(guest)
+    }
+
+    /// Whether the message with `digest` was delivered through the contract, or executed by its
+    /// NTT manager through other transceivers.
+    pub async fn delivered(&self, digest: B256) -> Result<bool> {
+        self.deliveries
+            .get_or_try_init(|| DeliveryCheck::new(self.contract, self.provider.clone()))
+            .await?
+            .delivered(digest)
+            .await
+    }
+
+    /// Builds the `receiveMessage` transaction delivering the message proven by `proof`, after
+    /// checking the contract will accept the proof.
+    pub async fn receive_message_tx(
+        &self,
+        proof: &DeliveryProof,
+        guest: &GuestProgram,
+    ) -> Result<TransactionRequest> {
+        let journal = proof.journal();
+        let journal_digest = *Impl::hash_bytes(journal);
+
+        // Decode and log the commitment
+        let journal = Journal::decode_any(journal)
+            .map_err(anyhow::Error::msg)
+            .context("invalid journal")?;
+        log::debug!("Steel commitment: {:?}", journal.commitment);
+
+        // ABI encode the seal.
+        let seal = proof.seal()?;
+
+        // Create an alloy instance of the BoundlessTransceiver contract.
+        let contract = IBoundlessTransceiver::new(self.contract, &self.provider);
+        let guest_image_id = Digest::from(guest.image_id);
+
+        // Check the verifier the contract routes proofs to is live, rather than wasting gas on a
+        // transaction that is bound to revert
+        let verifier = contract.verifier().call().await?;
+        check_verifier(&self.provider, verifier, &seal).await?;
+        // Verify the seal as the contract will, telling a bad proof apart from a contract that
+        // rejects a good one
+        verify_seal(
+            &self.provider,
+            verifier,
+            &seal,
+            guest_image_id,
+            journal_digest,
+        )
+        .await?;
+        self.check_commitment(&journal).await?;
+        self.check_source_image_id(&journal, guest_image_id).await?;
+
+        let call_builder =
+            contract.receiveMessage(Bytes::copy_from_slice(proof.journal()), seal.into());
+        log::debug!(
+            "Calldata {} {}",
+            contract.address(),
+            call_builder.calldata()
+        );
+        Ok(call_builder.into_transaction_request())
+    }
+
+    /// Checks the contract verifies proofs of the source chain of the message of `journal` with
+    /// the image ID of the guest. A proof that verifies is otherwise still rejected because of the
+    /// configuration of the contract.
+    async fn check_source_image_id(&self, journal: &Journal, guest_image_id: Digest) -> Result<()> {
+        let source_chain = TransceiverMessage::parse(&journal.encodedMessage)
+            .and_then(|message| message.source_chain())
+            .map_err(anyhow::Error::msg)?;
+        let contract_image_id =
+            source_image_id(self.contract, source_chain, &self.provider).await?;
+        if contract_image_id != guest_image_id {
+            return Err(Anomaly::ImageIdMismatch {
+                contract: self.contract,
+                contract_image_id,
+                guest_image_id,
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Checks the commitment of `journal` against the validator the contract checks it with for
+    /// the source chain of the message, catching commitments of the wrong chain, or to roots the
+    /// validator does not have (yet), before paying for a transaction that reverts.
+    pub async fn check_commitment(&self, journal: &Journal) -> Result<()> {
+        let source_chain = TransceiverMessage::parse(&journal.encodedMessage)
+            .and_then(|message| message.source_chain())
+            .map_err(anyhow::Error::msg)?;
+        let contract = IBoundlessTransceiver::new(self.contract, &self.provider);
+        let source = contract.authorizedSources(source_chain).call().await?;
+        ensure!(
+            source.commitmentValidator != Address::ZERO,
+            "{} does not accept messages from chain {source_chain}",
+            self.contract
+        );
+
+        let validator = ICommitmentValidator::new(source.commitmentValidator, &self.provider);
+        let valid = validator
+            .validateCommitment(journal.commitment.clone(), TWO_OF_TWO_FLAG)
+            .call()
+            .await
+            .with_context(|| {
+                format!(
+                    "validator {} of chain {source_chain} rejected the commitment",
+                    source.commitmentValidator
+                )
+            })?;
+        ensure!(
+            valid,
+            "commitment {:?} is not valid for validator {} of chain {source_chain}",
+            journal.commitment,
+            source.commitmentValidator
+        );
+        Ok(())
+    }
+
+    /// Populates the nonce, gas and chain of a transaction sent from `from`, so it can be signed
+    /// without access to the chain.
+    pub async fn fill(&self, tx: TransactionRequest, from: Address) -> Result<TransactionRequest> {
+        let provider = ProviderBuilder::new().connect_http(self.rpc_url.clone());
+        let tx = provider.fill(tx.with_from(from)).await?;
+        tx.as_builder()
+            .cloned()
+            .context("filled transaction is unexpectedly signed")
+    }
+
+    /// Sends the transaction delivering the message proven by `proof` to the contract,
+    /// advancing `job` through `Cleared` to `Submitted`. Nothing is sent while `breaker` is
+    /// tripped.
+    ///
+    /// Returns the pending transaction, or `None` if the contract already had the message.
+    pub async fn send(
+        &self,
+        proof: &DeliveryProof,
+        guest: &GuestProgram,
+        job: &mut TrackedJob,
+        breaker: &CircuitBreaker,
+    ) -> Result<Option<PendingTransactionBuilder<Ethereum>>> {
+        let tx = self.receive_message_tx(proof, guest).await?;
+        breaker.check()?;
+
+        // Submissions may only start from `Cleared`, which every attempt reaches by checking that
+        // the message is not delivered yet, so a message is never submitted twice even when its
+        // relay is retried after a crash
+        let digest = journal_digest(proof.journal())?;
+        if self.delivered(digest).await? {
+            job.advance(
+                MessageState::Confirmed,
+                Some(format!("destination already has message {digest}")),
+            )?;
+            return Ok(None);
+        }
+        job.advance(MessageState::Cleared, Some(format!("digest {digest}")))?;
+
+        // Call the receiveMessage function of the contract
+        log::info!(
+            "Sending Tx calling {} Function of {:#}...",
+            IBoundlessTransceiver::receiveMessageCall::SIGNATURE,
+            self.contract
+        );
+        let pending_tx = self.provider.send_transaction(tx).await?;
+        let tx_hash = *pending_tx.tx_hash();
+        job.job.dest_tx_hash = Some(tx_hash);
+        job.advance(
+            MessageState::Submitted,
+            Some(format!("transaction {tx_hash}")),
+        )?;
+        Ok(Some(pending_tx))
+    }
+
+    /// Waits for the transaction sent by [Destination::send] to confirm, advancing `job` to
+    /// `Confirmed` and returning its receipt. The gas spent and any revert are recorded with
+    /// `breaker`.
+    pub async fn confirm(
+        &self,
+        pending_tx: PendingTransactionBuilder<Ethereum>,
+        job: &mut TrackedJob,
+        breaker: &CircuitBreaker,
+    ) -> Result<TransactionReceipt> {
+        let tx_hash = *pending_tx.tx_hash();
+        let tx_receipt = pending_tx
+            .get_receipt()
+            .await
+            .with_context(|| format!("transaction did not confirm: {tx_hash}"))?;
+        let spent_wei = u128::from(tx_receipt.gas_used) * tx_receipt.effective_gas_price;
+        // Summed over attempts, as reverted submissions are paid for too
+        *job.job.gas_used.get_or_insert(0) += tx_receipt.gas_used;
+        *job.job.gas_cost_wei.get_or_insert(0) += spent_wei;
+        breaker
+            .record_submission(SystemTime::now(), !tx_receipt.status(), spent_wei)
+            .await;
+
+        ensure!(tx_receipt.status(), "transaction failed: {}", tx_hash);
+        job.advance(MessageState::Confirmed, None)?;
+
+        Ok(tx_receipt)
+    }
+}
+
+/// The digest the destination NTT manager identifies the message committed to by `journal` by.
+fn journal_digest(journal: &[u8]) -> Result<B256> {
+    let journal = Journal::decode_any(journal)
+        .map_err(anyhow::Error::msg)
+        .context("invalid journal")?;
+    let message = TransceiverMessage::parse(&journal.encodedMessage).map_err(anyhow::Error::msg)?;
+    let source_chain = message.source_chain().map_err(anyhow::Error::msg)?;
+    Ok(message.digest(source_chain))
+}
+
+/// Signs a transaction populated by [Destination::fill], returning it in its raw encoding.
+pub async fn sign_transaction(tx: TransactionRequest, signer: PrivateKeySigner) -> Result<Bytes> {
+    ensure!(
+        tx.from.is_none_or(|from| from == signer.address()),
+        "Transaction is to be sent from {}, but the key is for {}",
+        tx.from.unwrap_or_default(),
+        signer.address()
+    );
+    let envelope = tx.build(&EthereumWallet::from(signer)).await?;
+    Ok(envelope.encoded_2718().into())
+}
+
+/// Broadcasts a raw signed transaction and waits for it to be confirmed.
+pub async fn broadcast(provider: impl Provider, raw_tx: &[u8]) -> Result<TxHash> {
+    let pending_tx = provider.send_raw_transaction(raw_tx).await?;
+    let tx_hash = *pending_tx.tx_hash();
+    log::info!("Broadcast transaction {tx_hash}");
+    let receipt = pending_tx
+        .get_receipt()
+        .await
+        .with_context(|| format!("transaction did not confirm: {tx_hash}"))?;
+    ensure!(receipt.status(), "transaction failed: {tx_hash}");
+    Ok(tx_hash)
+}
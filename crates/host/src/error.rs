@@ -0,0 +1,227 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Failures of guest executions. Executors and provers only report that the guest panicked along
+//! with its panic message, which is translated into a [HostError] with a hint at how to resolve
+//! it where the message is one of the guests' known failures.
+
+use std::fmt;
+
+use common::MAX_ENCODED_MESSAGE_SIZE;
+use tracing::log;
+
+/// A known failure of a guest execution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HostError {
+    /// The committed block has no event of the claimed transceiver with the claimed message.
+    MessageNotInBlock,
+    /// No block of the range has an event of the claimed transceiver with the claimed message.
+    MessageNotInRange,
+    /// The blocks of a range input are not each the parent of the block before them.
+    NonContiguousRange,
+    /// The committed block has no event of the claimed emitter with the claimed data.
+    EventNotInBlock,
+    /// The message or event data is larger than guests attest to.
+    MessageTooLarge,
+    /// The event inclusion guest was given an event of another type than it was built for.
+    EventSignatureMismatch,
+    /// The token backing guest was given a message that carries no token transfer.
+    NotATransfer,
+    /// The transfer names another token than the NTT manager that sent it transfers.
+    TokenMismatch,
+    /// The transceiver that emitted the message is not enabled on its NTT manager.
+    TransceiverNotEnabled,
+    /// The message is for a manager that is not a peer of the sending NTT manager.
+    NotAPeer,
+    /// The input claims no message.
+    NoClaims,
+    /// The input claims several messages, but the guest attests to a single one.
+    TooManyClaims,
+    /// The input is for a source chain the guest has no chain spec for.
+    UnsupportedChain,
+    /// The guest could not deserialize the input.
+    MalformedInput,
+}
+
+/// Panic messages of the guests in crates/zkvm/guest and the failures they signal.
+const GUEST_PANICS: &[(&str, HostError)] = &[
+    (
+        "Event for given message not contained in logs for this block",
+        HostError::MessageNotInBlock,
+    ),
+    (
+        "Event for given message not contained in logs of the block range",
+        HostError::MessageNotInRange,
+    ),
+    (
+        "Blocks of the range are not contiguous",
+        HostError::NonContiguousRange,
+    ),
+    (
+        "Event with given data not contained in logs for this block",
+        HostError::EventNotInBlock,
+    ),
+    (
+        "Encoded message exceeds maximum size",
+        HostError::MessageTooLarge,
+    ),
+    (
+        "Event data exceeds maximum size",
+        HostError::MessageTooLarge,
+    ),
+    (
+        "Event signature does not match the event proven by this guest",
+        HostError::EventSignatureMismatch,
+    ),
+    ("Message is not a token transfer", HostError::NotATransfer),
+    (
+        "Transferred token is not the token of the NTT manager",
+        HostError::TokenMismatch,
+    ),
+    (
+        "Transceiver is not enabled on its NTT manager",
+        HostError::TransceiverNotEnabled,
+    ),
+    (
+        "Recipient manager is not a peer of the source manager",
+        HostError::NotAPeer,
+    ),
+    ("No message claims provided", HostError::NoClaims),
+    (
+        "mode supports a single message claim",
+        HostError::TooManyClaims,
+    ),
+    ("Unsupported source chain ID", HostError::UnsupportedChain),
+    (
+        "Input does not specify a source chain ID",
+        HostError::UnsupportedChain,
+    ),
+    ("Failed to deserialize input", HostError::MalformedInput),
+];
+
+impl HostError {
+    /// Recognizes the failure of a guest from the error its execution or proof returned.
+    pub fn from_guest_failure(error: &anyhow::Error) -> Option<Self> {
+        let message = format!("{error:#}");
+        GUEST_PANICS
+            .iter()
+            .find(|(panic, _)| message.contains(panic))
+            .map(|(_, failure)| *failure)
+    }
+
+    /// Replaces the error of a guest execution or proof with the [HostError] it signals, if it is
+    /// a known failure. The original error, with the location of the panic, is logged.
+    pub fn classify(error: anyhow::Error) -> anyhow::Error {
+        match Self::from_guest_failure(&error) {
+            Some(failure) => {
+                log::debug!("Guest failed: {error:#}");
+                failure.into()
+            }
+            None => error,
+        }
+    }
+
+    /// How the failure is usually resolved.
+    pub fn hint(&self) -> String {
+        match self {
+            Self::MessageNotInBlock => "check the commitment block and the transaction hash, and \
+                that the message was sent by the given source transceiver"
+                .to_string(),
+            Self::MessageNotInRange => "check that the range ends at or after the block of the \
+                transaction and covers enough blocks"
+                .to_string(),
+            Self::NonContiguousRange => {
+                "build the input again from a single chain of blocks".to_string()
+            }
+            Self::EventNotInBlock => "check the commitment block and the transaction hash, and \
+                that the event was emitted by the given contract"
+                .to_string(),
+            Self::MessageTooLarge => {
+                format!("guests only attest to messages of up to {MAX_ENCODED_MESSAGE_SIZE} bytes")
+            }
+            Self::EventSignatureMismatch => {
+                "prove the event with a guest built for its event type".to_string()
+            }
+            Self::NotATransfer => {
+                "prove messages other than token transfers with the default guest".to_string()
+            }
+            Self::TokenMismatch => "check that the message was sent by the NTT manager of the \
+                transferred token"
+                .to_string(),
+            Self::TransceiverNotEnabled => "check that the source transceiver is registered and \
+                enabled on the NTT manager the message names as its source"
+                .to_string(),
+            Self::NotAPeer => "check the peers registered on the source NTT manager for the \
+                recipient chain"
+                .to_string(),
+            Self::NoClaims => "the input must claim at least one message".to_string(),
+            Self::TooManyClaims => {
+                "prove several messages at once with the batch guest".to_string()
+            }
+            Self::UnsupportedChain => {
+                "use a guest built for the source chain with NTT_GUEST_CHAIN".to_string()
+            }
+            Self::MalformedInput => "the input was built for another guest or by another \
+                version of the relay, build it again"
+                .to_string(),
+        }
+    }
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let failure = match self {
+            Self::MessageNotInBlock => "Message not in the committed block",
+            Self::MessageNotInRange => "Message not in the block range",
+            Self::NonContiguousRange => "Blocks of the range are not contiguous",
+            Self::EventNotInBlock => "Event not in the committed block",
+            Self::MessageTooLarge => "Message too large",
+            Self::EventSignatureMismatch => "Event of another type than the guest proves",
+            Self::NotATransfer => "Message is not a token transfer",
+            Self::TokenMismatch => "Transfer of another token than its NTT manager's",
+            Self::TransceiverNotEnabled => "Transceiver not enabled on its NTT manager",
+            Self::NotAPeer => "Recipient manager not a peer of the source manager",
+            Self::NoClaims => "No message claimed",
+            Self::TooManyClaims => "Guest attests to a single message",
+            Self::UnsupportedChain => "Source chain not supported by the guest",
+            Self::MalformedInput => "Guest could not deserialize the input",
+        };
+        write!(f, "{failure}: {}", self.hint())
+    }
+}
+
+impl std::error::Error for HostError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::anyhow;
+
+    #[test]
+    fn recognizes_guest_panics() {
+        let error = anyhow!(
+            "Guest panicked: panicked at src/lib.rs:143:9:\n\
+             Event for given message not contained in logs for this block"
+        )
+        .context("failed to create proof");
+        let error = HostError::classify(error);
+        assert_eq!(
+            error.downcast_ref::<HostError>(),
+            Some(&HostError::MessageNotInBlock)
+        );
+
+        let error = HostError::classify(anyhow!("connection refused"));
+        assert!(error.downcast_ref::<HostError>().is_none());
+    }
+}
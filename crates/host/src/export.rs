@@ -0,0 +1,229 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Export of the persisted messages as one record per message, in CSV or Parquet, for offline
+//! analysis and financial reporting.
+
+use std::io;
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::{
+    ArrayRef, BooleanArray, Decimal128Array, Float64Array, RecordBatch, StringArray, UInt64Array,
+};
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use serde::Serialize;
+
+use crate::job::{Job, MessageState};
+
+/// What is known about relaying a message. Stage timestamps are Unix timestamps in seconds of
+/// entering the stage in the last attempt.
+#[derive(Clone, Debug, Serialize)]
+pub struct MessageRecord {
+    pub message_id: String,
+    pub source: String,
+    /// Current state of the message, final unless it is still being relayed.
+    pub outcome: String,
+    pub dead_lettered: bool,
+    pub failed_attempts: u64,
+    pub commitment_block: Option<u64>,
+    pub finalized_at: Option<u64>,
+    pub proving_at: Option<u64>,
+    pub proved_at: Option<u64>,
+    pub cleared_at: Option<u64>,
+    pub submitted_at: Option<u64>,
+    /// When the message reached its terminal state.
+    pub completed_at: Option<u64>,
+    pub prover_backend: Option<String>,
+    pub total_cycles: Option<u64>,
+    pub user_cycles: Option<u64>,
+    pub segments: Option<u64>,
+    pub proving_seconds: Option<f64>,
+    pub compression_seconds: Option<f64>,
+    pub dest_tx_hash: Option<String>,
+    pub vaa_tx_hash: Option<String>,
+    /// Gas used by the delivering transactions of all attempts.
+    pub gas_used: Option<u64>,
+    /// Amount paid for the delivering transactions of all attempts, in wei.
+    pub gas_cost_wei: Option<u128>,
+}
+
+impl From<&Job> for MessageRecord {
+    fn from(job: &Job) -> Self {
+        let proof = job.proof.as_ref();
+        Self {
+            message_id: job.id.to_string(),
+            source: job.contract_addr.to_string(),
+            outcome: job.state.to_string(),
+            dead_lettered: job.dead_lettered,
+            failed_attempts: job.failures.len() as u64,
+            commitment_block: job.commitment_block,
+            finalized_at: job.entered_at(MessageState::Finalized),
+            proving_at: job.entered_at(MessageState::Proving),
+            proved_at: job.entered_at(MessageState::Proved),
+            cleared_at: job.entered_at(MessageState::Cleared),
+            submitted_at: job.entered_at(MessageState::Submitted),
+            completed_at: job
+                .state
+                .is_terminal()
+                .then(|| job.entered_at(job.state))
+                .flatten(),
+            prover_backend: proof.map(|proof| proof.backend.clone()),
+            total_cycles: proof.map(|proof| proof.total_cycles),
+            user_cycles: proof.map(|proof| proof.user_cycles),
+            segments: proof.map(|proof| proof.segments as u64),
+            proving_seconds: proof.map(|proof| proof.proving_time.as_secs_f64()),
+            compression_seconds: proof
+                .and_then(|proof| proof.compression_time)
+                .map(|time| time.as_secs_f64()),
+            dest_tx_hash: job.dest_tx_hash.map(|hash| hash.to_string()),
+            vaa_tx_hash: job.vaa_tx_hash.map(|hash| hash.to_string()),
+            gas_used: job.gas_used,
+            gas_cost_wei: job.gas_cost_wei,
+        }
+    }
+}
+
+/// Writes `records` as CSV with a header row.
+pub fn write_csv(records: &[MessageRecord], writer: impl io::Write) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(writer);
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Writes `records` as a Parquet file with the same columns as [write_csv]. Costs in wei are
+/// stored as decimals, as they may not fit in 64 bits.
+pub fn write_parquet(records: &[MessageRecord], writer: impl io::Write + Send) -> Result<()> {
+    let strings = |column: fn(&MessageRecord) -> Option<&str>| -> ArrayRef {
+        Arc::new(records.iter().map(column).collect::<StringArray>())
+    };
+    let integers = |column: fn(&MessageRecord) -> Option<u64>| -> ArrayRef {
+        Arc::new(records.iter().map(column).collect::<UInt64Array>())
+    };
+    let floats = |column: fn(&MessageRecord) -> Option<f64>| -> ArrayRef {
+        Arc::new(records.iter().map(column).collect::<Float64Array>())
+    };
+    let gas_cost_wei = records
+        .iter()
+        .map(|record| record.gas_cost_wei.map(|wei| wei as i128))
+        .collect::<Decimal128Array>()
+        .with_precision_and_scale(38, 0)?;
+
+    let batch = RecordBatch::try_from_iter([
+        ("message_id", strings(|r| Some(r.message_id.as_str()))),
+        ("source", strings(|r| Some(r.source.as_str()))),
+        ("outcome", strings(|r| Some(r.outcome.as_str()))),
+        (
+            "dead_lettered",
+            Arc::new(
+                records
+                    .iter()
+                    .map(|r| Some(r.dead_lettered))
+                    .collect::<BooleanArray>(),
+            ) as ArrayRef,
+        ),
+        ("failed_attempts", integers(|r| Some(r.failed_attempts))),
+        ("commitment_block", integers(|r| r.commitment_block)),
+        ("finalized_at", integers(|r| r.finalized_at)),
+        ("proving_at", integers(|r| r.proving_at)),
+        ("proved_at", integers(|r| r.proved_at)),
+        ("cleared_at", integers(|r| r.cleared_at)),
+        ("submitted_at", integers(|r| r.submitted_at)),
+        ("completed_at", integers(|r| r.completed_at)),
+        ("prover_backend", strings(|r| r.prover_backend.as_deref())),
+        ("total_cycles", integers(|r| r.total_cycles)),
+        ("user_cycles", integers(|r| r.user_cycles)),
+        ("segments", integers(|r| r.segments)),
+        ("proving_seconds", floats(|r| r.proving_seconds)),
+        ("compression_seconds", floats(|r| r.compression_seconds)),
+        ("dest_tx_hash", strings(|r| r.dest_tx_hash.as_deref())),
+        ("vaa_tx_hash", strings(|r| r.vaa_tx_hash.as_deref())),
+        ("gas_used", integers(|r| r.gas_used)),
+        ("gas_cost_wei", Arc::new(gas_cost_wei) as ArrayRef),
+    ])?;
+
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(writer, batch.schema(), Some(properties))?;
+    writer.write(&batch)?;
+    writer.close()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job::MessageId;
+    use alloy_primitives::{Address, TxHash};
+
+    fn record() -> MessageRecord {
+        let mut job = Job::new(
+            MessageId {
+                tx_hash: TxHash::ZERO,
+                index: 0,
+            },
+            Address::ZERO,
+        );
+        for state in [
+            MessageState::Finalized,
+            MessageState::Proving,
+            MessageState::Proved,
+            MessageState::Cleared,
+            MessageState::Submitted,
+            MessageState::Confirmed,
+        ] {
+            job.transition(state, None).unwrap();
+        }
+        job.dest_tx_hash = Some(TxHash::ZERO);
+        job.gas_used = Some(21000);
+        job.gas_cost_wei = Some(21000 * 1_000_000_000);
+        MessageRecord::from(&job)
+    }
+
+    #[test]
+    fn exports_csv() {
+        let record = record();
+        assert_eq!(record.outcome, "Confirmed");
+        assert!(record.completed_at.is_some());
+
+        let mut csv = Vec::new();
+        write_csv(&[record], &mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        let mut lines = csv.lines();
+        assert!(
+            lines
+                .next()
+                .unwrap()
+                .starts_with("message_id,source,outcome,")
+        );
+        let row = lines.next().unwrap();
+        assert!(row.contains(",Confirmed,false,0,"));
+        assert!(row.ends_with(",21000,21000000000000"));
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn exports_parquet() {
+        let mut parquet = Vec::new();
+        write_parquet(&[record()], &mut parquet).unwrap();
+        assert!(parquet.starts_with(b"PAR1"));
+        assert!(parquet.ends_with(b"PAR1"));
+    }
+}
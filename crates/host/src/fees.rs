@@ -0,0 +1,202 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fee-aware relaying. Senders pay for the relay of their messages with `RelayFeePaid` events on
+//! the source chain, and only messages whose payments cover the estimated cost of proving and
+//! delivering them are relayed. The others are parked until topped up or retried by an operator.
+//! Parked messages are checked again on every poll, only looking for payments in the blocks
+//! finalized since their last check. Proving is priced at the market offer quoted for the message
+//! and delivery at the gas price of its destination chain, both converted into the source chain
+//! token.
+
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy::sol;
+use alloy_primitives::{Address, B256, U256};
+use alloy_sol_types::SolEvent;
+use anyhow::{Context, Result};
+use common::from_wormhole_address;
+use common::ntt::{NttManagerMessage, TransceiverMessage};
+use serde::{Deserialize, Serialize};
+
+use crate::config::FeeConfig;
+use crate::job::MessageId;
+use crate::pipeline::Route;
+use crate::relayer::Relayer;
+use crate::rpc::EndpointPool;
+use crate::watcher::{ObservedMessage, get_logs};
+
+sol! {
+    /// Emitted when the relay of the message `messageId` of `nttManager` is paid for.
+    event RelayFeePaid(address indexed nttManager, bytes32 indexed messageId, uint256 amount);
+}
+
+/// The fee paid for relaying a message, against the estimated cost of relaying it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeCheck {
+    pub paid_wei: U256,
+    pub cost_wei: U256,
+    /// The part of `cost_wei` quoted for proving the message, which later checks reuse.
+    pub proving_wei: U256,
+    /// The last block whose payments are included in `paid_wei`.
+    pub checked_to: u64,
+}
+
+impl FeeCheck {
+    pub fn covered(&self) -> bool {
+        self.paid_wei >= self.cost_wei
+    }
+}
+
+/// A message parked until its relay is paid for.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct UnpaidMessage {
+    pub message: ObservedMessage,
+    /// The outcome of the last successful fee check, if any.
+    pub fee: Option<FeeCheck>,
+}
+
+impl UnpaidMessage {
+    pub fn id(&self) -> MessageId {
+        self.message.id
+    }
+}
+
+/// Checks the fees paid up to `to_block` for relaying `message` along `route`, quoting its proof
+/// from a dry run of its guest on an input committing to `to_block`. Given the `previous` check of
+/// the message, only the blocks after it are searched for payments and its quote is reused.
+pub async fn check(
+    config: &FeeConfig,
+    relayer: &Relayer,
+    message: &ObservedMessage,
+    route: &Route,
+    previous: Option<FeeCheck>,
+    to_block: u64,
+) -> Result<FeeCheck> {
+    let rate = *config
+        .rates
+        .get(&route.chain)
+        .with_context(|| format!("no fee rate is set for chain {}", route.chain))?;
+    let transceiver_message =
+        TransceiverMessage::parse(&message.encoded_message).map_err(anyhow::Error::msg)?;
+    let manager_message = NttManagerMessage::parse(&transceiver_message.ntt_manager_payload)
+        .map_err(anyhow::Error::msg)?;
+    let manager = from_wormhole_address(transceiver_message.source_ntt_manager);
+    // Fees are paid along with or after sending the message
+    let (paid_before, from_block) = match previous {
+        Some(previous) => (previous.paid_wei, previous.checked_to + 1),
+        None => (U256::ZERO, message.block_number),
+    };
+    let checked_to = to_block.max(from_block.saturating_sub(1));
+    let paid_wei = match from_block <= to_block {
+        true => {
+            paid(
+                config,
+                &relayer.rpc,
+                manager,
+                manager_message.id,
+                from_block,
+                to_block,
+            )
+            .await?
+        }
+        false => U256::ZERO,
+    };
+    let proving_wei = match previous {
+        Some(previous) => previous.proving_wei,
+        None => {
+            let guest = route.select_guest(message.id).await?;
+            let quote = relayer
+                .quote(message.id, message.source, guest, to_block)
+                .await
+                .context("failed to quote the proof")?;
+            convert(quote.market_max_wei, config.market_rate)
+        }
+    };
+    let gas_price = route
+        .destination
+        .provider()
+        .get_gas_price()
+        .await
+        .context("failed to get the destination gas price")?;
+    Ok(FeeCheck {
+        paid_wei: paid_before.saturating_add(paid_wei),
+        cost_wei: relay_cost(config, proving_wei, gas_price, rate),
+        proving_wei,
+        checked_to,
+    })
+}
+
+/// Total fees paid in blocks `from_block` to `to_block` for the message `message_id` of
+/// `manager`.
+async fn paid(
+    config: &FeeConfig,
+    rpc: &EndpointPool,
+    manager: Address,
+    message_id: B256,
+    from_block: u64,
+    to_block: u64,
+) -> Result<U256> {
+    let filter = Filter::new()
+        .address(config.payment_contracts.clone())
+        .event_signature(RelayFeePaid::SIGNATURE_HASH)
+        .topic1(manager.into_word())
+        .topic2(message_id);
+    let mut total = U256::ZERO;
+    for log in get_logs(rpc, &filter, from_block, to_block).await? {
+        let event = RelayFeePaid::decode_log(&log.inner)?;
+        total = total.saturating_add(event.amount);
+    }
+    Ok(total)
+}
+
+/// Estimated cost in wei of the source chain token of relaying a message whose proof costs
+/// `proving_wei`, at a destination gas price of `gas_price` wei worth `rate` source chain wei.
+fn relay_cost(config: &FeeConfig, proving_wei: U256, gas_price: u128, rate: f64) -> U256 {
+    let delivery_wei = U256::from(config.delivery_gas) * U256::from(gas_price);
+    proving_wei.saturating_add(convert(delivery_wei, rate))
+}
+
+/// Converts `wei` of a token into wei of the source chain token, of which it is worth `rate`.
+fn convert(wei: U256, rate: f64) -> U256 {
+    U256::from((wei.saturating_to::<u128>() as f64 * rate) as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_proving_and_delivery() {
+        let config = FeeConfig {
+            payment_contracts: vec![Address::ZERO],
+            rates: Default::default(),
+            market_rate: 1.0,
+            delivery_gas: 100_000,
+            expire_after_blocks: None,
+        };
+        let proving_wei = U256::from(1_000_000);
+        // Delivery gas is paid in a token worth half as much as the source chain token
+        let cost_wei = relay_cost(&config, proving_wei, 40, 0.5);
+        assert_eq!(cost_wei, U256::from(3_000_000));
+        let check = |paid_wei: u64| FeeCheck {
+            paid_wei: U256::from(paid_wei),
+            cost_wei,
+            proving_wei,
+            checked_to: 0,
+        };
+        assert!(check(3_000_000).covered());
+        assert!(!check(2_999_999).covered());
+    }
+}
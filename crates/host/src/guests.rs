@@ -0,0 +1,250 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Guest programs loaded at runtime from a versioned artifact registry, so one relayer can prove
+//! messages for several versions of the transceiver contracts, each verifying proofs of a
+//! different guest build, without being rebuilt.
+//!
+//! A registry is a local directory or an HTTP(S) base URL holding a `manifest.json` that lists
+//! its guests, with their ELFs at the paths the manifest gives relative to it. The image ID of
+//! every ELF is computed when it is loaded, and must match the one the manifest requires of it, so
+//! an ELF altered in storage or in transit is refused rather than proven with.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::{Context, Result, anyhow, ensure};
+use reqwest::{Client, Url};
+use risc0_zkvm::{Digest, compute_image_id};
+use serde::Deserialize;
+use tracing::log;
+use zkvm::{GuestMode, GuestProgram, Transceiver};
+
+/// Path of the manifest within a registry.
+pub const MANIFEST: &str = "manifest.json";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub guests: Vec<GuestArtifact>,
+}
+
+/// A guest program of a registry.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuestArtifact {
+    /// Name the guest is looked up by, such as in the `guest` of a configured transceiver. Must
+    /// differ from the names of the embedded guests, e.g. `ntt_message_inclusion_v1`.
+    pub name: String,
+    /// Path of the ELF, relative to the manifest.
+    pub elf: String,
+    /// Image ID the ELF must have, as hex.
+    pub image_id: String,
+    /// Chain ID of the source chain the guest was built for. Unset if the guest takes the chain
+    /// from its input.
+    pub chain_id: Option<u64>,
+    /// The kind of source transceiver whose messages the guest proves.
+    #[serde(default)]
+    pub transceiver: TransceiverKind,
+    /// Name of the [GuestMode] of the guest, `message` if unset.
+    pub mode: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransceiverKind {
+    #[default]
+    Boundless,
+    Wormhole,
+}
+
+impl From<TransceiverKind> for Transceiver {
+    fn from(kind: TransceiverKind) -> Self {
+        match kind {
+            TransceiverKind::Boundless => Transceiver::Boundless,
+            TransceiverKind::Wormhole => Transceiver::Wormhole,
+        }
+    }
+}
+
+impl GuestArtifact {
+    /// Checks `elf` is the ELF of this guest and registers it.
+    fn register(&self, elf: Vec<u8>) -> Result<&'static GuestProgram> {
+        let image_id = compute_image_id(&elf).context("invalid ELF")?;
+        let expected = Digest::from_str(self.image_id.trim_start_matches("0x"))
+            .map_err(|e| anyhow!("invalid image ID {}: {e}", self.image_id))?;
+        ensure!(
+            image_id == expected,
+            "ELF has image ID {image_id}, but the manifest expects {expected}"
+        );
+        let mode = match &self.mode {
+            Some(mode) => mode.parse().map_err(anyhow::Error::msg)?,
+            None => GuestMode::Message,
+        };
+        let mut words = [0; 8];
+        words.copy_from_slice(image_id.as_words());
+        GuestProgram::register(
+            self.name.clone(),
+            elf,
+            words,
+            self.chain_id,
+            self.transceiver.into(),
+            mode,
+        )
+        .map_err(anyhow::Error::msg)
+    }
+}
+
+/// Where guest artifacts are loaded from.
+#[derive(Clone, Debug)]
+pub enum Registry {
+    Dir(PathBuf),
+    /// Base URL the paths of the manifest and the ELFs are resolved against.
+    Remote(Url),
+}
+
+impl FromStr for Registry {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if !(s.starts_with("http://") || s.starts_with("https://")) {
+            return Ok(Self::Dir(PathBuf::from(s)));
+        }
+        // Without a trailing slash, the last segment would be replaced rather than extended
+        let url = if s.ends_with('/') {
+            s.to_string()
+        } else {
+            format!("{s}/")
+        };
+        Ok(Self::Remote(Url::parse(&url)?))
+    }
+}
+
+impl fmt::Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Dir(dir) => write!(f, "{}", dir.display()),
+            Self::Remote(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+impl Registry {
+    /// Loads the guests of the registry, making them available alongside the embedded guests for
+    /// the rest of the process.
+    pub async fn load(&self) -> Result<Vec<&'static GuestProgram>> {
+        let client = Client::new();
+        let manifest: Manifest = serde_json::from_slice(&self.fetch(&client, MANIFEST).await?)
+            .with_context(|| format!("invalid manifest of guest registry {self}"))?;
+        let mut guests = Vec::new();
+        for artifact in &manifest.guests {
+            let elf = self.fetch(&client, &artifact.elf).await?;
+            let guest = artifact
+                .register(elf)
+                .with_context(|| format!("failed to load guest {}", artifact.name))?;
+            log::info!(
+                "Loaded guest {} with image ID {} from {self}",
+                guest.name,
+                Digest::from(guest.image_id)
+            );
+            guests.push(guest);
+        }
+        Ok(guests)
+    }
+
+    async fn fetch(&self, client: &Client, path: &str) -> Result<Vec<u8>> {
+        match self {
+            Self::Dir(dir) => {
+                let path = dir.join(path);
+                fs::read(&path).with_context(|| format!("failed to read {}", path.display()))
+            }
+            Self::Remote(base) => {
+                let url = base.join(path)?;
+                let response = client
+                    .get(url.clone())
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .with_context(|| format!("failed to fetch {url}"))?;
+                Ok(response.bytes().await?.to_vec())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn loads_guests_of_directory() {
+        let dir = std::env::temp_dir().join(format!("guest-registry-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let release = &zkvm::NTT_MESSAGE_INCLUSION;
+        fs::write(dir.join("release.elf"), release.elf).unwrap();
+        let manifest = serde_json::json!({
+            "guests": [{
+                "name": "ntt_message_inclusion_release",
+                "elf": "release.elf",
+                "image_id": Digest::from(release.image_id).to_string(),
+                "chain_id": release.chain_id,
+            }]
+        });
+        fs::write(dir.join(MANIFEST), manifest.to_string()).unwrap();
+
+        let registry: Registry = dir.to_str().unwrap().parse().unwrap();
+        let guests = registry.load().await.unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(guests.len(), 1);
+        assert_eq!(guests[0].image_id, release.image_id);
+        let loaded = GuestProgram::by_name("ntt_message_inclusion_release").unwrap();
+        assert_eq!(loaded.elf, release.elf);
+    }
+
+    #[test]
+    fn requires_image_ids() {
+        let manifest = r#"{"guests": [{"name": "guest", "elf": "guest.elf"}]}"#;
+        assert!(serde_json::from_str::<Manifest>(manifest).is_err());
+
+        let artifact = GuestArtifact {
+            name: "ntt_message_inclusion_tampered".to_string(),
+            elf: "tampered.elf".to_string(),
+            image_id: Digest::from(zkvm::NTT_MESSAGE_INCLUSION.image_id).to_string(),
+            chain_id: None,
+            transceiver: TransceiverKind::Boundless,
+            mode: None,
+        };
+        let other = GuestProgram::all()
+            .into_iter()
+            .find(|guest| guest.image_id != zkvm::NTT_MESSAGE_INCLUSION.image_id)
+            .unwrap();
+        assert!(artifact.register(other.elf.to_vec()).is_err());
+        assert!(GuestProgram::by_name("ntt_message_inclusion_tampered").is_none());
+    }
+
+    #[test]
+    fn parses_registry_location() {
+        let registry: Registry = "https://example.com/guests".parse().unwrap();
+        let Registry::Remote(url) = registry else {
+            panic!("expected a remote registry");
+        };
+        assert_eq!(url.join(MANIFEST).unwrap().path(), "/guests/manifest.json");
+        assert!(matches!(
+            "artifacts".parse::<Registry>().unwrap(),
+            Registry::Dir(_)
+        ));
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Heartbeats pushed periodically to an operator endpoint, so a fleet operator notices relayers
+//! that died silently, without scraping the API of every deployment.
+//!
+//! Each heartbeat is posted as JSON, signed by the wallet of the daemon with an EIP-191 personal
+//! message signature over the exact body, which is sent in the `X-Relay-Signature` header. The
+//! operator recovers the signer from the body and the signature, so heartbeats cannot be forged
+//! by anyone without the key of the relayer.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::signers::SignerSync;
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{Address, hex};
+use anyhow::Result;
+use reqwest::{Client, Url};
+use risc0_zkvm::Digest;
+use serde::Serialize;
+use tracing::log;
+
+use crate::admin::DaemonControl;
+use crate::version::{GIT_COMMIT, VERSION};
+
+/// Header holding the hex encoded signature of the body.
+pub const SIGNATURE_HEADER: &str = "X-Relay-Signature";
+
+/// The state of a relayer at one point in time.
+#[derive(Clone, Debug, Serialize)]
+pub struct Heartbeat {
+    /// Address of the wallet signing the heartbeat.
+    pub relayer: Address,
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    /// Image ID of the embedded default guest.
+    pub image_id: String,
+    /// Number of messages being relayed.
+    pub queue_depth: usize,
+    /// Last source chain block scanned for messages, if any was yet.
+    pub last_processed_block: Option<u64>,
+    /// Whether an operator paused the daemon.
+    pub paused: bool,
+    /// Unix timestamp in seconds.
+    pub timestamp: u64,
+}
+
+/// Pushes heartbeats to an operator endpoint.
+pub struct Heartbeater {
+    url: Url,
+    signer: PrivateKeySigner,
+    client: Client,
+}
+
+impl Heartbeater {
+    /// Creates a heartbeater posting to `url`, signing with `signer`.
+    pub fn new(url: Url, signer: PrivateKeySigner) -> Self {
+        Self {
+            url,
+            signer,
+            client: Client::new(),
+        }
+    }
+
+    /// The current heartbeat of the daemon with `control`.
+    pub fn heartbeat(&self, control: &DaemonControl) -> Heartbeat {
+        Heartbeat {
+            relayer: self.signer.address(),
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+            image_id: Digest::from(zkvm::NTT_MESSAGE_INCLUSION.image_id).to_string(),
+            queue_depth: control.in_flight().len(),
+            last_processed_block: control.last_scanned_block(),
+            paused: control.is_paused(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    /// Serializes `heartbeat` into the body posted, along with the hex encoded signature of it.
+    pub fn sign(&self, heartbeat: &Heartbeat) -> Result<(Vec<u8>, String)> {
+        let body = serde_json::to_vec(heartbeat)?;
+        let signature = self.signer.sign_message_sync(&body)?;
+        Ok((body, hex::encode_prefixed(signature.as_bytes())))
+    }
+
+    async fn send(&self, heartbeat: &Heartbeat) -> Result<()> {
+        let (body, signature) = self.sign(heartbeat)?;
+        self.client
+            .post(self.url.clone())
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, signature)
+            .body(body)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    /// Spawns a task pushing a heartbeat of the daemon with `control` every `interval`. Failing to
+    /// deliver a heartbeat is logged, and the next one is sent on schedule regardless.
+    pub fn spawn(self, control: Arc<DaemonControl>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                let heartbeat = self.heartbeat(&control);
+                if let Err(e) = self.send(&heartbeat).await {
+                    log::warn!("Failed to deliver heartbeat to {}: {e:#}", self.url);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy_primitives::Signature;
+
+    use super::*;
+
+    #[test]
+    fn signs_body_recoverably() {
+        let signer = PrivateKeySigner::random();
+        let heartbeater = Heartbeater::new("http://localhost/".parse().unwrap(), signer.clone());
+        let control = DaemonControl::default();
+        control.set_next_block(101);
+        let heartbeat = heartbeater.heartbeat(&control);
+        assert_eq!(heartbeat.last_processed_block, Some(100));
+        assert_eq!(heartbeat.queue_depth, 0);
+
+        let (body, signature) = heartbeater.sign(&heartbeat).unwrap();
+        let signature = Signature::try_from(hex::decode(signature).unwrap().as_slice()).unwrap();
+        let recovered = signature.recover_address_from_msg(&body).unwrap();
+        assert_eq!(recovered, signer.address());
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Lifecycle of a message being relayed, modelled as an explicit state machine.
+//!
+//! ```text
+//! Observed → Finalized → Proving → Proved → Cleared → Submitted → Confirmed
+//! ```
+//!
+//! Any non-terminal state may move to `Failed` or `Expired`. A message that is still being proven
+//! may also move straight to `Confirmed` when it was delivered through another attestation, such
+//! as its Wormhole VAA, or by another relayer.
+//!
+//! A proof can only be submitted from `Cleared`, which is entered after checking that the
+//! destination has not received the message yet. Every attempt starts over from `Observed`, so no
+//! attempt can submit a message the destination already has.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::{Address, TxHash};
+use anyhow::{Context, Result, ensure};
+use risc0_zkvm::Digest;
+use serde::{Deserialize, Serialize};
+use tracing::log;
+
+use crate::market::MarketRequest;
+use crate::metrics::ProofMetrics;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MessageState {
+    /// The message event was seen on the source chain.
+    Observed,
+    /// The block containing the message is final and a commitment block has been chosen.
+    Finalized,
+    /// A proof of the message is being produced.
+    Proving,
+    /// A proof of the message is available.
+    Proved,
+    /// The destination was checked not to have received the message yet.
+    Cleared,
+    /// The proof was sent to the destination chain.
+    Submitted,
+    /// The destination transaction was confirmed. Terminal.
+    Confirmed,
+    /// Processing failed permanently. Terminal.
+    Failed,
+    /// The message can no longer be delivered, e.g. because its commitment expired. Terminal.
+    Expired,
+}
+
+impl MessageState {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, Self::Confirmed | Self::Failed | Self::Expired)
+    }
+
+    pub fn can_transition_to(self, next: MessageState) -> bool {
+        use MessageState::*;
+
+        if self.is_terminal() {
+            return false;
+        }
+        matches!(
+            (self, next),
+            (Observed, Finalized)
+                | (Finalized, Proving)
+                | (Proving, Proved)
+                | (Proved, Cleared)
+                | (Cleared, Submitted)
+                | (Submitted, Confirmed)
+                | (Finalized | Proving | Proved | Cleared, Confirmed)
+                | (_, Failed)
+                | (_, Expired)
+        )
+    }
+}
+
+impl fmt::Display for MessageState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Identifies a message by its source transaction and its position among the transaction's
+/// `SendTransceiverMessage` events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct MessageId {
+    pub tx_hash: TxHash,
+    pub index: usize,
+}
+
+impl fmt::Display for MessageId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.tx_hash, self.index)
+    }
+}
+
+impl FromStr for MessageId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (tx_hash, index) = s
+            .split_once('-')
+            .with_context(|| format!("invalid message ID {s}, expected <tx hash>-<index>"))?;
+        Ok(Self {
+            tx_hash: tx_hash.parse()?,
+            index: index.parse()?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Transition {
+    pub from: MessageState,
+    pub to: MessageState,
+    /// Unix timestamp in seconds.
+    pub at: u64,
+    pub reason: Option<String>,
+}
+
+/// A failed attempt at relaying a message.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Failure {
+    /// The state the attempt failed in.
+    pub state: MessageState,
+    /// Unix timestamp in seconds.
+    pub at: u64,
+    pub error: String,
+}
+
+/// A message being relayed along with the history of its state transitions.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Job {
+    pub id: MessageId,
+    /// The source transceiver contract that emitted the message.
+    pub contract_addr: Address,
+    pub state: MessageState,
+    pub commitment_block: Option<u64>,
+    /// Hash of the destination transaction delivering the message, once submitted.
+    pub dest_tx_hash: Option<TxHash>,
+    /// Hash of the destination transaction delivering the Wormhole VAA of the message, if any.
+    #[serde(default)]
+    pub vaa_tx_hash: Option<TxHash>,
+    pub history: Vec<Transition>,
+    /// Failed attempts at relaying the message, kept across attempts.
+    #[serde(default)]
+    pub failures: Vec<Failure>,
+    /// Whether the message exhausted its attempts and waits in the dead-letter queue for an
+    /// operator to replay it.
+    #[serde(default)]
+    pub dead_lettered: bool,
+    /// Measurements of the proof of the message, if it was proven on this host.
+    #[serde(default)]
+    pub proof: Option<ProofMetrics>,
+    /// SHA-256 digest of the guest input the message was proven from.
+    #[serde(default)]
+    pub input_digest: Option<Digest>,
+    /// The latest request for a proof of the message on the Boundless market, if it was proven
+    /// there. Kept across attempts, so a retry waits for the request rather than making another.
+    #[serde(default)]
+    pub market_request: Option<MarketRequest>,
+    /// Gas used by the delivering transactions of all attempts, once one was included.
+    #[serde(default)]
+    pub gas_used: Option<u64>,
+    /// Amount paid for the delivering transactions of all attempts in wei.
+    #[serde(default)]
+    pub gas_cost_wei: Option<u128>,
+}
+
+impl Job {
+    pub fn new(id: MessageId, contract_addr: Address) -> Self {
+        Self {
+            id,
+            contract_addr,
+            state: MessageState::Observed,
+            commitment_block: None,
+            dest_tx_hash: None,
+            vaa_tx_hash: None,
+            history: Vec::new(),
+            failures: Vec::new(),
+            dead_lettered: false,
+            proof: None,
+            input_digest: None,
+            market_request: None,
+            gas_used: None,
+            gas_cost_wei: None,
+        }
+    }
+
+    /// Records that the current attempt failed with `error` in the current state.
+    pub fn record_failure(&mut self, error: String) {
+        self.failures.push(Failure {
+            state: self.state,
+            at: unix_now(),
+            error,
+        });
+    }
+
+    /// Moves the job to `to`, rejecting transitions the state machine does not allow.
+    pub fn transition(&mut self, to: MessageState, reason: Option<String>) -> Result<()> {
+        ensure!(
+            self.state.can_transition_to(to),
+            "Invalid transition of message {} from {} to {to}",
+            self.id,
+            self.state
+        );
+        match &reason {
+            Some(reason) => log::info!("Message {}: {} -> {to} ({reason})", self.id, self.state),
+            None => log::info!("Message {}: {} -> {to}", self.id, self.state),
+        }
+
+        self.history.push(Transition {
+            from: self.state,
+            to,
+            at: unix_now(),
+            reason,
+        });
+        self.state = to;
+        Ok(())
+    }
+
+    /// Time of the most recent transition into `state`, as a Unix timestamp in seconds.
+    pub fn entered_at(&self, state: MessageState) -> Option<u64> {
+        self.history
+            .iter()
+            .rev()
+            .find(|transition| transition.to == state)
+            .map(|transition| transition.at)
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job() -> Job {
+        Job::new(
+            MessageId {
+                tx_hash: TxHash::ZERO,
+                index: 0,
+            },
+            Address::ZERO,
+        )
+    }
+
+    #[test]
+    fn happy_path() {
+        let mut job = job();
+        for state in [
+            MessageState::Finalized,
+            MessageState::Proving,
+            MessageState::Proved,
+            MessageState::Cleared,
+            MessageState::Submitted,
+            MessageState::Confirmed,
+        ] {
+            job.transition(state, None).unwrap();
+        }
+        assert_eq!(job.state, MessageState::Confirmed);
+        assert_eq!(job.history.len(), 6);
+    }
+
+    #[test]
+    fn submits_only_after_delivery_check() {
+        let mut job = job();
+        for state in [
+            MessageState::Finalized,
+            MessageState::Proving,
+            MessageState::Proved,
+        ] {
+            job.transition(state, None).unwrap();
+        }
+        assert!(job.transition(MessageState::Submitted, None).is_err());
+        job.transition(MessageState::Confirmed, Some("delivered".into()))
+            .unwrap();
+    }
+
+    #[test]
+    fn rejects_skipped_and_terminal_transitions() {
+        let mut job = job();
+        assert!(job.transition(MessageState::Proving, None).is_err());
+
+        job.transition(MessageState::Failed, Some("boom".into()))
+            .unwrap();
+        assert!(job.transition(MessageState::Finalized, None).is_err());
+        assert!(job.transition(MessageState::Expired, None).is_err());
+    }
+
+    #[test]
+    fn confirms_through_other_attestation() {
+        let mut job = job();
+        assert!(job.transition(MessageState::Confirmed, None).is_err());
+
+        job.transition(MessageState::Finalized, None).unwrap();
+        job.transition(MessageState::Proving, None).unwrap();
+        job.transition(MessageState::Confirmed, Some("VAA".into()))
+            .unwrap();
+        assert_eq!(job.state, MessageState::Confirmed);
+    }
+}
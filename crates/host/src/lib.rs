@@ -12,132 +12,1152 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use alloy::providers::{Provider, ProviderBuilder};
+use alloy::providers::{Provider, RootProvider};
 use alloy::rpc::types::TransactionReceipt;
-use alloy_primitives::{Address, TxHash};
-use alloy_sol_types::SolEvent;
+use alloy_primitives::{Address, B256, Bytes, TxHash, U256};
+use alloy_sol_types::{SolEvent, SolValue};
 use anyhow::{Context, Result, ensure};
-use common::{GuestInput, IBoundlessTransceiver, to_wormhole_address};
-use risc0_steel::ethereum::ETH_MAINNET_CHAIN_SPEC;
+use common::ntt::{IERC20, INttManager, IWormholeTransceiver, MessageEvent, TransceiverMessage};
+use common::{
+    EventInput, GuestInput, IBoundlessTransceiver, MAX_ENCODED_MESSAGE_SIZE, MerkleBatchJournal,
+    MessageClaim, MessageLeaf, RangeInput, eip4788, from_wormhole_address, merkle,
+    to_wormhole_address,
+};
+use risc0_steel::ethereum::{
+    ETH_MAINNET_CHAIN_SPEC, ETH_SEPOLIA_CHAIN_SPEC, EthChainSpec, EthEvmFactory,
+};
+use risc0_steel::revm::primitives::hardfork::SpecId;
 use risc0_steel::{
-    Event, alloy::transports::http::reqwest::Url, ethereum::EthEvmEnv, host::BlockNumberOrTag,
+    Contract, Event,
+    alloy::transports::http::reqwest::Url,
+    ethereum::{EthEvmEnv, EthEvmInput},
+    host::{BlockNumberOrTag, EvmEnvBuilder},
 };
-use risc0_zkvm::{ExecutorEnv, ProveInfo, ProverOpts, VerifierContext, default_prover};
+use risc0_zkvm::{
+    ExecutorEnv, ProveInfo, ProverOpts, Receipt, SessionInfo, VerifierContext, default_executor,
+    default_prover,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::task;
-use zkvm::NTT_MESSAGE_INCLUSION_ELF;
+use tracing::log;
+use zkvm::{GuestProgram, Transceiver};
 
-pub async fn build_input(
+use crate::cache::{InputCache, InputKey};
+use crate::error::HostError;
+use crate::job::MessageId;
+use crate::metrics::ProofMetrics;
+use crate::rpc::EndpointPool;
+
+pub mod admin;
+pub mod alert;
+pub mod api;
+pub mod artifact;
+pub mod audit;
+pub mod auth;
+pub mod bench;
+pub mod breaker;
+pub mod budget;
+pub mod cache;
+pub mod checkup;
+pub mod compressor;
+pub mod config;
+pub mod daemon;
+pub mod dashboard;
+pub mod deliveries;
+pub mod destination;
+pub mod error;
+pub mod export;
+pub mod fees;
+pub mod guests;
+pub mod heartbeat;
+pub mod job;
+pub mod market;
+pub mod metrics;
+pub mod pipeline;
+pub mod proof;
+pub mod queue;
+pub mod quote;
+pub mod reconcile;
+pub mod registry;
+pub mod relayer;
+pub mod routing;
+pub mod rpc;
+pub mod scheduler;
+pub mod selftest;
+pub mod sizes;
+pub mod sla;
+pub mod snapshot;
+pub mod snark;
+pub mod stale;
+pub mod store;
+pub mod throttle;
+pub mod tui;
+pub mod vaa;
+pub mod vcr;
+pub mod vectors;
+pub mod verifier;
+pub mod version;
+pub mod watcher;
+
+/// Chain specs of the chains other than mainnet and sepolia, by chain ID.
+static CUSTOM_SPECS: LazyLock<Mutex<HashMap<u64, &'static EthChainSpec>>> =
+    LazyLock::new(Default::default);
+
+/// Registers `chain_id` as a chain with the single-fork spec of the guest's `custom-spec` feature,
+/// returning its spec. Mainnet and sepolia keep their specs.
+pub fn register_chain_spec(chain_id: u64) -> &'static EthChainSpec {
+    match chain_id {
+        1 => &ETH_MAINNET_CHAIN_SPEC,
+        11155111 => &ETH_SEPOLIA_CHAIN_SPEC,
+        _ => *CUSTOM_SPECS
+            .lock()
+            .unwrap()
+            .entry(chain_id)
+            .or_insert_with(|| {
+                &*Box::leak(Box::new(EthChainSpec::new_single(chain_id, SpecId::PRAGUE)))
+            }),
+    }
+}
+
+/// Returns the Steel chain spec matching the one a guest was built with for the given chain.
+/// Chains other than mainnet and sepolia need a spec registered from the chain registry of the
+/// config, or an embedded or loaded guest built for them with the `custom-spec` feature.
+pub fn chain_spec(chain_id: u64) -> Result<&'static EthChainSpec> {
+    let known = matches!(chain_id, 1 | 11155111)
+        || CUSTOM_SPECS.lock().unwrap().contains_key(&chain_id)
+        || GuestProgram::all()
+            .iter()
+            .any(|guest| guest.chain_id == Some(chain_id));
+    ensure!(
+        known,
+        "no chain spec is known for chain {chain_id}, set it as the chain_id of a chain in the \
+         config or load a guest built for it"
+    );
+    Ok(register_chain_spec(chain_id))
+}
+
+/// The Steel env builder inputs are preflighted with, once its provider is set.
+pub type EnvBuilder = EvmEnvBuilder<RootProvider, EthEvmFactory, &'static EthChainSpec, ()>;
+
+/// Options of building guest inputs.
+#[derive(Default)]
+pub struct InputOptions {
+    /// Preflighted state is reused from and stored in this cache, if set.
+    pub cache: Option<InputCache>,
+    /// Customizes the Steel env builder before preflight, if set. The block, commitment and chain
+    /// spec of the input are applied afterwards.
+    pub configure_env: Option<Arc<dyn Fn(EnvBuilder) -> EnvBuilder + Send + Sync>>,
+    /// Also preflights the state queries of the NTT manager that sent each message, as the
+    /// `ntt_message_inclusion_state` guest requires.
+    pub manager_state: bool,
+    /// Also preflights the queries of the holdings of the token each transfer is backed by, as
+    /// the `ntt_message_inclusion_backing` guest requires.
+    pub token_backing: bool,
+    /// Also preflights the queries of the registration of the NTT manager that sent each message,
+    /// as the `ntt_message_inclusion_peer` guest requires. The peer queried depends on the
+    /// recipient chain of the message rather than just its block, so these inputs are not cached.
+    pub peer_registration: bool,
+}
+
+impl InputOptions {
+    /// Creates the env builder for `provider`, customized by [Self::configure_env].
+    pub fn env_builder(&self, provider: RootProvider) -> EnvBuilder {
+        let builder = EthEvmEnv::builder().provider(provider);
+        match &self.configure_env {
+            Some(configure_env) => configure_env(builder),
+            None => builder,
+        }
+    }
+}
+
+/// A message a transaction sent through a source transceiver.
+#[derive(Clone, Debug, Serialize)]
+pub struct SentMessage {
+    pub id: MessageId,
+    /// Index of the log of the message in its block.
+    pub log_index: Option<u64>,
+    /// Wormhole chain ID the message is sent to.
+    pub recipient_chain: u16,
+    pub encoded_message: Bytes,
+}
+
+/// The messages of type `E` that `contract_addr` sent in the transaction of `receipt`, in the
+/// order of their logs, which is the order of their [MessageId] indices.
+fn sent_messages<E: MessageEvent>(
+    receipt: &TransactionReceipt,
+    contract_addr: Address,
+) -> Vec<SentMessage> {
+    receipt
+        .logs()
+        .iter()
+        .filter(|log| log.address() == contract_addr)
+        .filter_map(|log| Some((log.log_index, E::decode_log(&log.inner).ok()?.data)))
+        .enumerate()
+        .map(|(index, (log_index, event))| SentMessage {
+            id: MessageId {
+                tx_hash: receipt.transaction_hash,
+                index,
+            },
+            log_index,
+            recipient_chain: event.recipient_chain(),
+            encoded_message: event.encoded_message(),
+        })
+        .collect()
+}
+
+/// A message of a batch proven by the `ntt_message_inclusion_merkle` guest, with the branch the
+/// destination verifies it against the committed root with.
+#[derive(Clone, Debug, Serialize)]
+pub struct MessageBranch {
+    pub encoded_message: Bytes,
+    pub emitter_contract: B256,
+    pub recipient_chain: u16,
+    /// Sibling hashes on the path from the leaf of the message to the root, bottom up.
+    pub branch: Vec<B256>,
+}
+
+/// Produces the branch of every message of a batch proven by the Merkle batch guest, from the
+/// claims of its input along with the chain each message was sent to, in input order, and the
+/// journal the guest committed. Fails if the journal does not commit to exactly these messages.
+pub fn merkle_branches(
+    journal: &[u8],
+    messages: Vec<(MessageClaim, u16)>,
+) -> Result<Vec<MessageBranch>> {
+    let journal =
+        MerkleBatchJournal::abi_decode(journal).context("invalid Merkle batch journal")?;
+    let leaves: Vec<B256> = messages
+        .iter()
+        .map(|(claim, recipient_chain)| {
+            MessageLeaf::new(
+                &claim.encoded_message,
+                claim.contract_addr,
+                *recipient_chain,
+            )
+            .hash()
+        })
+        .collect();
+    ensure!(
+        journal.messageCount as usize == leaves.len()
+            && journal.messagesRoot == merkle::root(&leaves),
+        "journal does not commit to the {} messages given",
+        leaves.len()
+    );
+    Ok(messages
+        .into_iter()
+        .enumerate()
+        .map(|(index, (claim, recipient_chain))| MessageBranch {
+            encoded_message: claim.encoded_message,
+            emitter_contract: claim.contract_addr,
+            recipient_chain,
+            branch: merkle::branch(&leaves, index),
+        })
+        .collect())
+}
+
+/// Enumerates the messages the source transceiver `contract_addr` of kind `transceiver` sent in
+/// `tx_hash`, each of which is proven on its own.
+pub async fn transaction_messages(
+    provider: &RootProvider,
     tx_hash: TxHash,
     contract_addr: Address,
-    rpc_url: Url,
+    transceiver: Transceiver,
+) -> Result<Vec<SentMessage>> {
+    let receipt = provider
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .context("No transaction found with given tx_hash")?;
+    Ok(match transceiver {
+        Transceiver::Boundless => {
+            sent_messages::<IBoundlessTransceiver::SendTransceiverMessage>(&receipt, contract_addr)
+        }
+        Transceiver::Wormhole => {
+            sent_messages::<IWormholeTransceiver::SendTransceiverMessage>(&receipt, contract_addr)
+        }
+    })
+}
+
+/// Builds the framed input of `guest` proving the message `id`, querying the source chain through
+/// `provider`. Guests that take the chain from their input get the chain reported by the RPC.
+pub async fn build_input(
+    id: MessageId,
+    contract_addr: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    commitment_block: u64,
+    guest: &GuestProgram,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
+    let chain_id = guest_chain_id(guest, &provider).await?;
+    match guest.transceiver {
+        Transceiver::Boundless => {
+            build_message_input::<IBoundlessTransceiver::SendTransceiverMessage>(
+                id,
+                contract_addr,
+                provider,
+                beacon_api_url,
+                commitment_block,
+                chain_id,
+                options,
+            )
+            .await
+        }
+        Transceiver::Wormhole => {
+            build_message_input::<IWormholeTransceiver::SendTransceiverMessage>(
+                id,
+                contract_addr,
+                provider,
+                beacon_api_url,
+                commitment_block,
+                chain_id,
+                options,
+            )
+            .await
+        }
+    }
+}
+
+/// Builds the framed input of `guest` proving that `contract_addr` sent `encoded_message` in
+/// `execution_block`, such as a message obtained from an indexer. Unlike [build_input], no
+/// transaction receipt is looked up; preflight still checks that the block has the event.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_input_from_log(
+    execution_block: u64,
+    encoded_message: Bytes,
+    contract_addr: Address,
+    provider: RootProvider,
     beacon_api_url: Url,
     commitment_block: u64,
+    guest: &GuestProgram,
+    options: &InputOptions,
 ) -> Result<Vec<u8>> {
-    let provider = ProviderBuilder::new().connect_http(rpc_url.clone());
+    let chain_id = guest_chain_id(guest, &provider).await?;
+    match guest.transceiver {
+        Transceiver::Boundless => {
+            build_log_input::<IBoundlessTransceiver::SendTransceiverMessage>(
+                execution_block,
+                encoded_message,
+                contract_addr,
+                provider,
+                beacon_api_url,
+                commitment_block,
+                chain_id,
+                options,
+            )
+            .await
+        }
+        Transceiver::Wormhole => {
+            build_log_input::<IWormholeTransceiver::SendTransceiverMessage>(
+                execution_block,
+                encoded_message,
+                contract_addr,
+                provider,
+                beacon_api_url,
+                commitment_block,
+                chain_id,
+                options,
+            )
+            .await
+        }
+    }
+}
+
+/// Builds the framed input of `guest` proving the message with `index` among those
+/// `contract_addr` sent in the transaction of `receipt`, for callers that already fetched the
+/// receipt, e.g. through a provider of their own. Unlike [build_input], the receipt is not looked
+/// up again; `provider` is only used to preflight the block.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_input_from_receipt(
+    receipt: &TransactionReceipt,
+    index: usize,
+    contract_addr: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    commitment_block: u64,
+    guest: &GuestProgram,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
+    let chain_id = guest_chain_id(guest, &provider).await?;
+    match guest.transceiver {
+        Transceiver::Boundless => {
+            build_receipt_input::<IBoundlessTransceiver::SendTransceiverMessage>(
+                receipt,
+                index,
+                contract_addr,
+                provider,
+                beacon_api_url,
+                commitment_block,
+                chain_id,
+                options,
+            )
+            .await
+        }
+        Transceiver::Wormhole => {
+            build_receipt_input::<IWormholeTransceiver::SendTransceiverMessage>(
+                receipt,
+                index,
+                contract_addr,
+                provider,
+                beacon_api_url,
+                commitment_block,
+                chain_id,
+                options,
+            )
+            .await
+        }
+    }
+}
 
+/// The chain `guest` proves messages of, as reported by the RPC for guests that take the chain
+/// from their input.
+async fn guest_chain_id(guest: &GuestProgram, provider: &RootProvider) -> Result<u64> {
+    Ok(match guest.chain_id {
+        Some(chain_id) => chain_id,
+        None => provider.get_chain_id().await?,
+    })
+}
+
+async fn build_message_input<E: MessageEvent>(
+    id: MessageId,
+    contract_addr: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    commitment_block: u64,
+    chain_id: u64,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
     // Get the transaction receipt
     let receipt: TransactionReceipt = provider
-        .get_transaction_receipt(tx_hash)
+        .get_transaction_receipt(id.tx_hash)
         .await?
         .context("No transaction found with given tx_hash")?;
 
+    build_receipt_input::<E>(
+        &receipt,
+        id.index,
+        contract_addr,
+        provider,
+        beacon_api_url,
+        commitment_block,
+        chain_id,
+        options,
+    )
+    .await
+}
+
+/// Builds the input proving the message with `index` among those `contract_addr` sent in the
+/// transaction of `receipt`.
+#[allow(clippy::too_many_arguments)]
+async fn build_receipt_input<E: MessageEvent>(
+    receipt: &TransactionReceipt,
+    index: usize,
+    contract_addr: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    commitment_block: u64,
+    chain_id: u64,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
+    let id = MessageId {
+        tx_hash: receipt.transaction_hash,
+        index,
+    };
     let execution_block = receipt
         .block_number
         .context("Tx was not included in a block")?;
+
+    // Find the matching event emitted by the contract in the transaction receipt
+    let messages = sent_messages::<E>(receipt, contract_addr);
+    ensure!(
+        !messages.is_empty(),
+        "No SendTransceiverMessage event found in transaction receipt"
+    );
+    let count = messages.len();
+    let encoded_message = messages
+        .into_iter()
+        .nth(id.index)
+        .with_context(|| format!("Message {id} not found, the transaction sent {count}"))?
+        .encoded_message;
+
+    build_log_input::<E>(
+        execution_block,
+        encoded_message,
+        contract_addr,
+        provider,
+        beacon_api_url,
+        commitment_block,
+        chain_id,
+        options,
+    )
+    .await
+}
+
+/// Builds the input proving that `contract_addr` sent `encoded_message` in `execution_block`.
+#[allow(clippy::too_many_arguments)]
+async fn build_log_input<E: MessageEvent>(
+    execution_block: u64,
+    encoded_message: Bytes,
+    contract_addr: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    commitment_block: u64,
+    chain_id: u64,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
     ensure!(
         commitment_block >= execution_block,
         "commitment block must be greater than or equal to execution block"
     );
+    ensure!(
+        !encoded_message.is_empty(),
+        "No encoded message found in SendTransceiverMessage event"
+    );
+    ensure!(
+        encoded_message.len() <= MAX_ENCODED_MESSAGE_SIZE,
+        "Encoded message is {} bytes, exceeding the maximum of {MAX_ENCODED_MESSAGE_SIZE}",
+        encoded_message.len()
+    );
 
-    // Find the first matching event emitted by the contract in the transaction receipt
-    // NOTE(willem): This assumes that only a single NTT message is being sent in the transaction
-    // it is possible we might want to support handling multiple per tx in the future
-    let encoded_message = receipt
-        .logs()
-        .iter()
-        .find_map(|log| {
-            if log.address() == contract_addr {
-                IBoundlessTransceiver::SendTransceiverMessage::decode_log(&log.inner)
-                    .ok()
-                    .map(|event| event.encodedMessage.clone())
-            } else {
-                None
+    let key = InputKey {
+        chain_id,
+        contract: contract_addr,
+        execution_block,
+        commitment_block,
+        manager_state: options.manager_state,
+        token_backing: options.token_backing,
+    };
+    let cache = options
+        .cache
+        .as_ref()
+        .filter(|_| !options.peer_registration);
+    let evm_input = match cache.map(|cache| cache.load(&key)).transpose()?.flatten() {
+        Some(evm_input) => {
+            log::info!("Using cached input for block {execution_block}");
+            evm_input
+        }
+        None => {
+            let evm_input = preflight::<E>(
+                contract_addr,
+                &encoded_message,
+                &provider,
+                options,
+                beacon_api_url,
+                execution_block,
+                commitment_block,
+                chain_id,
+            )
+            .await?;
+            if let Some(cache) = cache {
+                cache.store(&key, &evm_input)?;
             }
-        })
-        .context("No SendTransceiverMessage event found in transaction receipt")?;
+            evm_input
+        }
+    };
+
+    let input = GuestInput::single(
+        evm_input,
+        encoded_message,
+        to_wormhole_address(contract_addr),
+    )
+    .with_chain_id(chain_id);
+
+    let input_bytes = input.serialize().map_err(anyhow::Error::msg)?;
+    Ok(frame(&input_bytes))
+}
+
+/// Builds the framed input of the event inclusion guest proving the first event of type `E`
+/// that `emitter` emitted in `tx_hash`. Inputs of events are not cached.
+pub async fn build_event_input<E: SolEvent>(
+    tx_hash: TxHash,
+    emitter: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    commitment_block: u64,
+    chain_id: u64,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
+    let receipt: TransactionReceipt = provider
+        .get_transaction_receipt(tx_hash)
+        .await?
+        .context("No transaction found with given tx_hash")?;
+    let execution_block = receipt
+        .block_number
+        .context("Tx was not included in a block")?;
+    ensure!(
+        commitment_block >= execution_block,
+        "commitment block must be greater than or equal to execution block"
+    );
 
+    let data = receipt
+        .logs()
+        .iter()
+        .find(|log| log.address() == emitter && log.topic0() == Some(&E::SIGNATURE_HASH))
+        .map(|log| log.inner.data.data.clone())
+        .with_context(|| format!("No {} event found in transaction receipt", E::SIGNATURE))?;
     ensure!(
-        !encoded_message.is_empty(),
-        "No encoded message found in SendTransceiverMessage event"
+        data.len() <= MAX_ENCODED_MESSAGE_SIZE,
+        "Event data is {} bytes, exceeding the maximum of {MAX_ENCODED_MESSAGE_SIZE}",
+        data.len()
     );
 
-    let builder = EthEvmEnv::builder()
-        .rpc(rpc_url)
+    let mut env = options
+        .env_builder(provider.clone())
         .block_number_or_tag(BlockNumberOrTag::Number(execution_block))
         .beacon_api(beacon_api_url)
-        .commitment_block_number_or_tag(BlockNumberOrTag::Number(commitment_block));
-
-    let mut env = builder.chain_spec(&ETH_MAINNET_CHAIN_SPEC).build().await?;
-
-    let event = Event::preflight::<IBoundlessTransceiver::SendTransceiverMessage>(&mut env);
-    let logs = event.address(contract_addr).query().await?;
+        .commitment_block_number_or_tag(BlockNumberOrTag::Number(commitment_block))
+        .chain_spec(chain_spec(chain_id)?)
+        .build()
+        .await?;
+    let logs = Event::preflight::<E>(&mut env)
+        .address(emitter)
+        .query()
+        .await?;
     ensure!(
         logs.iter()
-            .any(|log| { log.encodedMessage == encoded_message }),
-        "Log with digest {encoded_message} not found in contract {contract_addr}, block {execution_block}",
+            .any(|log| log.data.encode_data() == data.as_ref()),
+        "{} event not found in contract {emitter}, block {execution_block}",
+        E::SIGNATURE
     );
 
-    // Finally, construct the input from the environment.
     let evm_input = env.into_input().await?;
+    check_commitment(&provider, &evm_input, chain_id, commitment_block).await?;
 
-    let input = GuestInput {
+    let input = EventInput {
         commitment: evm_input,
-        contract_addr: to_wormhole_address(contract_addr),
-        encoded_message,
+        emitter_contract: to_wormhole_address(emitter),
+        event_signature: E::SIGNATURE_HASH,
+        data,
     };
+    let input_bytes = input.serialize().map_err(anyhow::Error::msg)?;
+    Ok(frame(&input_bytes))
+}
+
+/// Builds the framed input of the block range guest proving that `contract_addr` sent
+/// `encoded_message` as an `E` event in one of the `blocks` blocks up to `last_block`, committing
+/// to `last_block` at `commitment_block`. The range only extends back as far as the newest block
+/// with the message, so inputs do not grow with blocks the guest would not need to search.
+#[allow(clippy::too_many_arguments)]
+pub async fn build_range_input<E: MessageEvent>(
+    encoded_message: Bytes,
+    contract_addr: Address,
+    provider: RootProvider,
+    beacon_api_url: Url,
+    last_block: u64,
+    blocks: u64,
+    commitment_block: u64,
+    chain_id: u64,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
+    ensure!(blocks > 0, "the block range must not be empty");
+    ensure!(
+        blocks <= last_block + 1,
+        "the block range starts before the genesis block"
+    );
+    ensure!(
+        commitment_block >= last_block,
+        "commitment block must be greater than or equal to the last block of the range"
+    );
+    ensure!(
+        encoded_message.len() <= MAX_ENCODED_MESSAGE_SIZE,
+        "Encoded message is {} bytes, exceeding the maximum of {MAX_ENCODED_MESSAGE_SIZE}",
+        encoded_message.len()
+    );
+
+    // Only the newest block is committed to, the others are linked to it by their hashes
+    let mut env = options
+        .env_builder(provider.clone())
+        .block_number_or_tag(BlockNumberOrTag::Number(last_block))
+        .beacon_api(beacon_api_url)
+        .commitment_block_number_or_tag(BlockNumberOrTag::Number(commitment_block))
+        .chain_spec(chain_spec(chain_id)?)
+        .build()
+        .await?;
+    let logs = Event::preflight::<E>(&mut env)
+        .address(contract_addr)
+        .query()
+        .await?;
+    let mut found = logs
+        .iter()
+        .any(|log| log.data.encoded_message() == encoded_message);
+    let commitment = env.into_input().await?;
+    check_commitment(&provider, &commitment, chain_id, commitment_block).await?;
+
+    let mut ancestors = Vec::new();
+    for number in (last_block + 1 - blocks..last_block).rev() {
+        if found {
+            break;
+        }
+        let mut env = options
+            .env_builder(provider.clone())
+            .block_number_or_tag(BlockNumberOrTag::Number(number))
+            .chain_spec(chain_spec(chain_id)?)
+            .build()
+            .await?;
+        let logs = Event::preflight::<E>(&mut env)
+            .address(contract_addr)
+            .query()
+            .await?;
+        found = logs
+            .iter()
+            .any(|log| log.data.encoded_message() == encoded_message);
+        ancestors.push(env.into_input().await?);
+    }
+    ensure!(
+        found,
+        "Log with digest {encoded_message} not found in contract {contract_addr}, blocks {} to \
+         {last_block}",
+        last_block + 1 - blocks
+    );
 
+    let input = RangeInput {
+        commitment,
+        ancestors,
+        claim: MessageClaim {
+            encoded_message,
+            contract_addr: to_wormhole_address(contract_addr),
+        },
+    };
     let input_bytes = input.serialize().map_err(anyhow::Error::msg)?;
+    Ok(frame(&input_bytes))
+}
 
-    // Produce the env_in by applying the length prefix as read_frame expects
+/// Produces the env_in by applying the length prefix as read_frame expects.
+fn frame(input_bytes: &[u8]) -> Vec<u8> {
     let mut guest_env_in = Vec::<u8>::new();
     guest_env_in.extend_from_slice(&input_bytes.len().to_le_bytes());
-    guest_env_in.extend_from_slice(&input_bytes);
+    guest_env_in.extend_from_slice(input_bytes);
+    guest_env_in
+}
+
+/// Preflights the query for the `SendTransceiverMessage` events of `contract_addr` in
+/// `execution_block`, checking `encoded_message` is among them, along with the queries of the NTT
+/// manager state and token backing if [InputOptions] asks for them.
+#[allow(clippy::too_many_arguments)]
+async fn preflight<E: MessageEvent>(
+    contract_addr: Address,
+    encoded_message: &Bytes,
+    provider: &RootProvider,
+    options: &InputOptions,
+    beacon_api_url: Url,
+    execution_block: u64,
+    commitment_block: u64,
+    chain_id: u64,
+) -> Result<EthEvmInput> {
+    let builder = options
+        .env_builder(provider.clone())
+        .block_number_or_tag(BlockNumberOrTag::Number(execution_block))
+        .beacon_api(beacon_api_url)
+        .commitment_block_number_or_tag(BlockNumberOrTag::Number(commitment_block));
 
-    Ok(guest_env_in)
+    let mut env = builder.chain_spec(chain_spec(chain_id)?).build().await?;
+
+    let event = Event::preflight::<E>(&mut env);
+    let logs = event.address(contract_addr).query().await?;
+    let recipient_chain = logs
+        .iter()
+        .find(|log| log.data.encoded_message() == *encoded_message)
+        .map(|log| log.data.recipient_chain())
+        .with_context(|| {
+            format!(
+                "Log with digest {encoded_message} not found in contract {contract_addr}, block \
+                 {execution_block}"
+            )
+        })?;
+
+    if options.manager_state {
+        let message = TransceiverMessage::parse(encoded_message).map_err(anyhow::Error::msg)?;
+        let manager = from_wormhole_address(message.source_ntt_manager);
+        let mut contract = Contract::preflight(manager, &mut env);
+        contract
+            .call_builder(&INttManager::nextMessageSequenceCall {})
+            .call()
+            .await
+            .with_context(|| format!("failed to read the sequence of NTT manager {manager}"))?;
+        contract
+            .call_builder(&INttManager::getCurrentOutboundCapacityCall {})
+            .call()
+            .await
+            .with_context(|| {
+                format!("failed to read the outbound capacity of NTT manager {manager}")
+            })?;
+    }
+    if options.token_backing {
+        let message = TransceiverMessage::parse(encoded_message).map_err(anyhow::Error::msg)?;
+        let transfer = message.transfer().map_err(anyhow::Error::msg)?;
+        let manager = from_wormhole_address(message.source_ntt_manager);
+        let mut contract = Contract::preflight(manager, &mut env);
+        let token = contract
+            .call_builder(&INttManager::tokenCall {})
+            .call()
+            .await
+            .with_context(|| format!("failed to read the token of NTT manager {manager}"))?;
+        ensure!(
+            token == from_wormhole_address(transfer.source_token),
+            "Transfer of another token than {token} of NTT manager {manager}"
+        );
+        contract
+            .call_builder(&INttManager::getModeCall {})
+            .call()
+            .await
+            .with_context(|| format!("failed to read the mode of NTT manager {manager}"))?;
+        let mut erc20 = Contract::preflight(token, &mut env);
+        erc20
+            .call_builder(&IERC20::balanceOfCall { account: manager })
+            .call()
+            .await
+            .with_context(|| format!("failed to read the balance of NTT manager {manager}"))?;
+        erc20
+            .call_builder(&IERC20::totalSupplyCall {})
+            .call()
+            .await
+            .with_context(|| format!("failed to read the total supply of token {token}"))?;
+    }
+    if options.peer_registration {
+        let message = TransceiverMessage::parse(encoded_message).map_err(anyhow::Error::msg)?;
+        let manager = from_wormhole_address(message.source_ntt_manager);
+        let mut contract = Contract::preflight(manager, &mut env);
+        let transceivers = contract
+            .call_builder(&INttManager::getTransceiversCall {})
+            .call()
+            .await
+            .with_context(|| format!("failed to read the transceivers of NTT manager {manager}"))?;
+        ensure!(
+            transceivers.contains(&contract_addr),
+            "Transceiver {contract_addr} is not enabled on NTT manager {manager}"
+        );
+        let peer = contract
+            .call_builder(&INttManager::getPeerCall {
+                chainId: recipient_chain,
+            })
+            .call()
+            .await
+            .with_context(|| format!("failed to read the peers of NTT manager {manager}"))?;
+        ensure!(
+            peer.peerAddress == message.recipient_ntt_manager,
+            "Recipient manager {} is not the peer {} of NTT manager {manager} on chain \
+             {recipient_chain}",
+            message.recipient_ntt_manager,
+            peer.peerAddress
+        );
+    }
+
+    // Finally, construct the input from the environment.
+    let evm_input = env.into_input().await?;
+    check_commitment(provider, &evm_input, chain_id, commitment_block).await?;
+    Ok(evm_input)
+}
+
+/// Checks that the commitment `evm_input` proves its block against corresponds to
+/// `commitment_block` as reported by the execution RPC `provider`. The beacon API only supplies
+/// the proof linking the block to a beacon block root, so this catches a faulty or malicious
+/// beacon endpoint before proving a commitment the destination would never confirm.
+pub async fn check_commitment(
+    provider: &RootProvider,
+    evm_input: &EthEvmInput,
+    chain_id: u64,
+    commitment_block: u64,
+) -> Result<()> {
+    let commitment = evm_input
+        .clone()
+        .into_env(chain_spec(chain_id)?)
+        .into_commitment();
+    let (claim, version) = commitment.decode_id();
+    match version {
+        // Committed to by its block hash
+        0 => {
+            let block = provider
+                .get_block_by_number(BlockNumberOrTag::Number(commitment_block))
+                .await?
+                .with_context(|| format!("block {commitment_block} not found"))?;
+            ensure!(
+                claim == U256::from(commitment_block) && commitment.digest == block.header.hash,
+                "commitment to block {claim} with hash {} does not match block {commitment_block} \
+                 with hash {}",
+                commitment.digest,
+                block.header.hash
+            );
+        }
+        // Committed to by its beacon block root, which is the parent beacon block root of its
+        // child. Beacon commitments are keyed by the timestamp of the child, under which EIP-4788
+        // exposes the root, consensus commitments by the slot of the beacon block.
+        1 | 2 => {
+            let child = provider
+                .get_block_by_number(BlockNumberOrTag::Number(commitment_block + 1))
+                .await?
+                .with_context(|| format!("child of block {commitment_block} not found"))?;
+            ensure!(
+                child.header.parent_beacon_block_root == Some(commitment.digest),
+                "commitment to beacon block root {} does not match the parent beacon block root \
+                 {:?} of the child of block {commitment_block}",
+                commitment.digest,
+                child.header.parent_beacon_block_root
+            );
+            if version == 1 {
+                ensure!(
+                    claim == U256::from(child.header.timestamp),
+                    "commitment to timestamp {claim} does not match timestamp {} of the child of \
+                     block {commitment_block}",
+                    child.header.timestamp
+                );
+                let now = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                ensure!(
+                    !eip4788::is_expired(child.header.timestamp, now),
+                    "commitment to timestamp {claim} expired from the beacon roots contract at {}",
+                    eip4788::expires_at(child.header.timestamp)
+                );
+            }
+        }
+        version => anyhow::bail!("unsupported commitment version {version}"),
+    }
+    Ok(())
+}
+
+/// Builds the guest input for the message `id` from the first of the `rpc` and `beacon` endpoints
+/// that work.
+pub async fn fetch_input(
+    id: MessageId,
+    contract_addr: Address,
+    rpc: &EndpointPool,
+    beacon: &EndpointPool,
+    commitment_block: u64,
+    guest: &GuestProgram,
+    options: &InputOptions,
+) -> Result<Vec<u8>> {
+    rpc.run(|rpc_endpoint| async move {
+        beacon
+            .run(|beacon_endpoint| {
+                build_input(
+                    id,
+                    contract_addr,
+                    rpc_endpoint.provider(),
+                    beacon_endpoint.url,
+                    commitment_block,
+                    guest,
+                    options,
+                )
+            })
+            .await
+    })
+    .await
 }
 
+/// Fetches the Wormhole chain ID of the source chain of `transceiver`, which destinations look up
+/// the image ID of its messages by: that of its NTT manager.
+pub async fn source_chain_id(provider: &RootProvider, transceiver: Address) -> Result<u16> {
+    let manager = IBoundlessTransceiver::new(transceiver, provider)
+        .nttManager()
+        .call()
+        .await?;
+    Ok(INttManager::new(manager, provider).chainId().call().await?)
+}
+
+/// Executes the guest on `env_input` without proving it.
+pub async fn execute(env_input: Vec<u8>, guest: &'static GuestProgram) -> Result<SessionInfo> {
+    task::spawn_blocking(move || -> Result<_> {
+        let env = ExecutorEnv::builder().write_slice(&env_input).build()?;
+        default_executor()
+            .execute(env, guest.elf)
+            .map_err(HostError::classify)
+            .with_context(|| format!("guest {} rejected the input", guest.name))
+    })
+    .await?
+}
+
+/// Executes the guest on `env_input` with the profiler enabled, writing a pprof profile of where
+/// its cycles are spent to `pprof_out`.
+pub async fn profile(
+    env_input: Vec<u8>,
+    guest: &'static GuestProgram,
+    pprof_out: PathBuf,
+) -> Result<SessionInfo> {
+    task::spawn_blocking(move || -> Result<_> {
+        let env = ExecutorEnv::builder()
+            .write_slice(&env_input)
+            .enable_profiler(&pprof_out)
+            .build()?;
+        let session = default_executor()
+            .execute(env, guest.elf)
+            .map_err(HostError::classify)?;
+        log::info!(
+            "Wrote profile of {} cycles to {}",
+            session.cycles(),
+            pprof_out.display()
+        );
+        Ok(session)
+    })
+    .await?
+}
+
+/// Proves the message `id` and compresses the proof into a Groth16 SNARK that can be verified
+/// on-chain, returning the proof along with measurements of both phases. The input is built from
+/// the first of the `rpc` and `beacon` endpoints that work.
 pub async fn build_proof(
-    tx_hash: TxHash,
+    id: MessageId,
     contract_addr: Address,
-    rpc_url: Url,
-    beacon_api_url: Url,
+    rpc: &EndpointPool,
+    beacon: &EndpointPool,
     commitment_block: u64,
-) -> Result<ProveInfo> {
-    let env_input = build_input(
-        tx_hash,
+    guest: &'static GuestProgram,
+    options: &InputOptions,
+) -> Result<(ProveInfo, ProofMetrics)> {
+    let env_input = fetch_input(
+        id,
         contract_addr,
-        rpc_url,
-        beacon_api_url,
+        rpc,
+        beacon,
         commitment_block,
+        guest,
+        options,
     )
     .await?;
+    prove(env_input, guest).await
+}
+
+/// Proves `env_input` with `guest` and compresses the proof into a Groth16 SNARK.
+pub async fn prove(
+    env_input: Vec<u8>,
+    guest: &'static GuestProgram,
+) -> Result<(ProveInfo, ProofMetrics)> {
+    let (mut prove_info, mut metrics) = prove_succinct(env_input, guest).await?;
+    let (receipt, compression_time) = compress(prove_info.receipt).await?;
+    prove_info.receipt = receipt;
+    metrics.compression_time = Some(compression_time);
+    Ok((prove_info, metrics))
+}
 
+/// Proves `env_input` with `guest`, producing a succinct STARK receipt. This is the latency
+/// sensitive part of proving; the receipt still has to be [compress]ed before it can be verified
+/// on-chain.
+pub async fn prove_succinct(
+    env_input: Vec<u8>,
+    guest: &'static GuestProgram,
+) -> Result<(ProveInfo, ProofMetrics)> {
     // Create the RISC Zero proof
-    let prove_info = task::spawn_blocking(move || {
+    let proof = task::spawn_blocking(move || -> Result<_> {
         let env = ExecutorEnv::builder()
             .write_slice(&env_input)
             .build()
             .unwrap();
+        let prover = default_prover();
 
-        default_prover().prove_with_ctx(
-            env,
-            &VerifierContext::default(),
-            NTT_MESSAGE_INCLUSION_ELF,
-            &ProverOpts::groth16(),
-        )
+        let start = Instant::now();
+        let prove_info = prover
+            .prove_with_ctx(
+                env,
+                &VerifierContext::default(),
+                guest.elf,
+                &ProverOpts::succinct(),
+            )
+            .map_err(HostError::classify)?;
+        let metrics = ProofMetrics::new(prover.get_name(), &prove_info, start.elapsed());
+
+        Ok((prove_info, metrics))
     })
     .await?
     .context("failed to create proof")?;
 
-    Ok(prove_info)
+    Ok(proof)
+}
+
+/// Proves `env_input` with `guest` straight to a Groth16 receipt, for provers such as Bonsai that
+/// do not support compressing existing receipts.
+pub async fn prove_groth16(
+    env_input: Vec<u8>,
+    guest: &'static GuestProgram,
+) -> Result<(ProveInfo, ProofMetrics)> {
+    let proof = task::spawn_blocking(move || -> Result<_> {
+        let env = ExecutorEnv::builder()
+            .write_slice(&env_input)
+            .build()
+            .unwrap();
+        let prover = default_prover();
+
+        let start = Instant::now();
+        let prove_info = prover
+            .prove_with_ctx(
+                env,
+                &VerifierContext::default(),
+                guest.elf,
+                &ProverOpts::groth16(),
+            )
+            .map_err(HostError::classify)?;
+        let metrics = ProofMetrics::new(prover.get_name(), &prove_info, start.elapsed());
+
+        Ok((prove_info, metrics))
+    })
+    .await?
+    .context("failed to create proof")?;
+
+    Ok(proof)
+}
+
+/// Compresses a succinct receipt into a Groth16 SNARK that can be verified on-chain, returning it
+/// along with the time the compression took.
+pub async fn compress(receipt: Receipt) -> Result<(Receipt, Duration)> {
+    task::spawn_blocking(move || -> Result<_> {
+        let start = Instant::now();
+        let receipt = default_prover().compress(&ProverOpts::groth16(), &receipt)?;
+        Ok((receipt, start.elapsed()))
+    })
+    .await?
+    .context("failed to compress proof")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use testkit::TestChain;
+
+    #[tokio::test]
+    async fn merkle_branches_verify_against_guest_root() -> Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        let messages = [
+            (3, Bytes::from("first message")),
+            (4, Bytes::from("second message")),
+            (3, Bytes::from("third message")),
+        ];
+        emitter.emit_in_one_block(&messages).await?;
+        let emitter_contract = to_wormhole_address(emitter.address());
+        let claims: Vec<_> = messages
+            .iter()
+            .map(|(recipient_chain, msg)| {
+                let claim = MessageClaim {
+                    encoded_message: msg.clone(),
+                    contract_addr: emitter_contract,
+                };
+                (claim, *recipient_chain)
+            })
+            .collect();
+        let input = GuestInput {
+            commitment: chain.preflight_messages(emitter.address()).await?,
+            claims: claims.iter().map(|(claim, _)| claim.clone()).collect(),
+            chain_id: None,
+        };
+        let guest = GuestProgram::by_name("ntt_message_inclusion_merkle").unwrap();
+        let input_bytes = input.serialize().map_err(anyhow::Error::msg)?;
+        let journal = execute(frame(&input_bytes), guest).await?.journal.bytes;
+
+        let root = MerkleBatchJournal::abi_decode(&journal)?.messagesRoot;
+        let branches = merkle_branches(&journal, claims.clone())?;
+        assert_eq!(branches.len(), messages.len());
+        for (branch, (recipient_chain, msg)) in branches.iter().zip(&messages) {
+            let leaf = MessageLeaf::new(msg, emitter_contract, *recipient_chain).hash();
+            assert!(merkle::verify(leaf, &branch.branch, root));
+        }
+
+        // The journal does not commit to a subset of its messages, nor to other recipient chains
+        assert!(merkle_branches(&journal, claims[1..].to_vec()).is_err());
+        let mut forged = claims;
+        forged[0].1 = 5;
+        assert!(merkle_branches(&journal, forged).is_err());
+
+        Ok(())
+    }
 }
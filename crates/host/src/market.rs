@@ -0,0 +1,298 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proving through the Boundless market.
+
+use std::fmt;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy::signers::local::PrivateKeySigner;
+use alloy_primitives::{B256, U256};
+use anyhow::{Context, Result, ensure};
+use boundless_market::Client;
+use boundless_market::contracts::RequestStatus;
+use boundless_market::request_builder::OfferParams;
+use boundless_market::storage::storage_provider_from_env;
+use reqwest::Url;
+use risc0_zkvm::sha::Digestible;
+use risc0_zkvm::{Digest, ReceiptClaim};
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+use tracing::log;
+use zkvm::GuestProgram;
+
+use crate::config::OfferConfig;
+use crate::execute;
+use crate::proof::DeliveryProof;
+use crate::snark::SnarkBackend;
+use crate::store::TrackedJob;
+
+/// How often the market is polled for the status of a request.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Requests proofs from the Boundless market. Provers aggregate the proofs they fulfill into a
+/// set, so each message is delivered with a set-inclusion proof verified by the set verifier of
+/// the destination chain.
+pub struct MarketProver {
+    rpc_url: Url,
+    private_key: PrivateKeySigner,
+    offer: OfferConfig,
+    fallback: Vec<SnarkBackend>,
+    client: OnceCell<Client>,
+}
+
+impl MarketProver {
+    /// Requests proofs through the market deployed on the chain of `rpc_url`, paying for them
+    /// from the wallet of `private_key`. The guest programs and their inputs are uploaded to the
+    /// storage provider configured in the environment.
+    pub fn new(rpc_url: Url, private_key: PrivateKeySigner) -> Self {
+        Self {
+            rpc_url,
+            private_key,
+            offer: OfferConfig::default(),
+            fallback: Vec::new(),
+            client: OnceCell::new(),
+        }
+    }
+
+    /// Makes offers with the parameters of `offer`.
+    pub fn with_offer(mut self, offer: OfferConfig) -> Self {
+        self.offer = offer;
+        self
+    }
+
+    /// Proves on the `backends`, trying each in order, when the market does not serve a request
+    /// in time: no prover locked it before its lock timeout, or it expired unfulfilled.
+    pub fn with_fallback(mut self, backends: Vec<SnarkBackend>) -> Self {
+        self.fallback = backends;
+        self
+    }
+
+    /// The backends to prove on when the market does not serve a request in time.
+    pub fn fallback(&self) -> &[SnarkBackend] {
+        &self.fallback
+    }
+
+    async fn client(&self) -> Result<&Client> {
+        self.client
+            .get_or_try_init(|| async {
+                Client::builder()
+                    .with_rpc_url(self.rpc_url.clone())
+                    .with_private_key(self.private_key.clone())
+                    .with_storage_provider(Some(storage_provider_from_env()?))
+                    .build()
+                    .await
+            })
+            .await
+            .context("failed to connect to the Boundless market")
+    }
+
+    /// Proves `env_input`, whose SHA-256 digest is `input_digest`, with `guest` through the
+    /// market, waiting for a prover to fulfill the request.
+    ///
+    /// The request is recorded in `job`, if any, so later attempts at relaying the message pick
+    /// up the request, or its fulfillment, instead of paying for another proof of the same input.
+    /// A request that expires, whether or not a prover locked it, fails the attempt with a
+    /// [MarketTimeout], as does one no prover locked in time if there are backends to fall back
+    /// to. Without them, an unlocked request may still be fulfilled until it expires.
+    pub async fn prove(
+        &self,
+        guest: &'static GuestProgram,
+        env_input: Vec<u8>,
+        input_digest: Digest,
+        mut job: Option<&mut TrackedJob>,
+    ) -> Result<DeliveryProof> {
+        // The fulfillment only carries the seal, so the journal it proves is reproduced here,
+        // along with the cycle count the offer is derived from
+        let session = execute(env_input.clone(), guest).await?;
+        let cycles = session.cycles();
+        let journal = session.journal.bytes;
+        let claim_digest = ReceiptClaim::ok(guest.image_id, journal.clone()).digest();
+
+        let client = self.client().await?;
+        let previous = job
+            .as_ref()
+            .and_then(|job| job.job.market_request.clone())
+            .filter(|request| request.input_digest == input_digest);
+        let mut request = match previous {
+            Some(request) => {
+                log::info!("Resuming proof request {:#x}", request.id);
+                request
+            }
+            None => {
+                let (id, lock_expires_at, expires_at) =
+                    self.submit(client, guest, env_input, cycles).await?;
+                let request = MarketRequest {
+                    id,
+                    lock_expires_at,
+                    expires_at,
+                    input_digest,
+                    locked: false,
+                };
+                if let Some(job) = job.as_deref_mut() {
+                    job.job.market_request = Some(request.clone());
+                    job.save()?;
+                }
+                request
+            }
+        };
+
+        let id = request.id;
+        loop {
+            let status = client
+                .boundless_market
+                .get_status(id, Some(request.expires_at))
+                .await
+                .with_context(|| format!("failed to get the status of proof request {id:#x}"))?;
+            match status {
+                RequestStatus::Fulfilled => break,
+                RequestStatus::Locked if !request.locked => {
+                    log::info!("Proof request {id:#x} locked by a prover");
+                    request.locked = true;
+                    if let Some(job) = job.as_deref_mut() {
+                        job.job.market_request = Some(request.clone());
+                        job.save()?;
+                    }
+                }
+                RequestStatus::Expired => {
+                    // The next attempt makes a new request
+                    if let Some(job) = job.as_deref_mut() {
+                        job.job.market_request = None;
+                        job.save()?;
+                    }
+                    return Err(MarketTimeout::Expired {
+                        id,
+                        locked: request.locked,
+                    }
+                    .into());
+                }
+                RequestStatus::Locked | RequestStatus::Unknown => {}
+            }
+            if !self.fallback.is_empty() && !request.locked && unix_now() > request.lock_expires_at
+            {
+                return Err(MarketTimeout::NotLocked { id }.into());
+            }
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+
+        let fulfillment = client
+            .boundless_market
+            .get_request_fulfillment(id)
+            .await
+            .with_context(|| format!("failed to get the fulfillment of proof request {id:#x}"))?;
+        // The seal is only checked on-chain, but the claim it proves can be checked here
+        ensure!(
+            fulfillment.claimDigest == B256::from(<[u8; 32]>::from(claim_digest)),
+            "Proof request {id:#x} was fulfilled with a proof of another claim than image ID {} \
+             committing to the journal",
+            Digest::from(guest.image_id)
+        );
+        log::info!("Proof request {id:#x} fulfilled");
+
+        Ok(DeliveryProof::SetInclusion {
+            journal: journal.into(),
+            seal: fulfillment.seal,
+        })
+    }
+
+    /// Submits a request to prove `env_input` with `guest`, an execution of `cycles` cycles,
+    /// returning its ID, until when it can be locked and when it expires.
+    async fn submit(
+        &self,
+        client: &Client,
+        guest: &'static GuestProgram,
+        env_input: Vec<u8>,
+        cycles: u64,
+    ) -> Result<(U256, u64, u64)> {
+        let offer = self.offer.offer(cycles);
+        log::debug!("Offering {offer:?} for {cycles} cycles");
+        let mut params = OfferParams::builder();
+        params
+            .min_price(offer.min_price)
+            .max_price(offer.max_price)
+            .ramp_up_period(offer.ramp_up_period)
+            .lock_timeout(offer.lock_timeout)
+            .timeout(offer.timeout);
+        if let Some(lock_stake) = offer.lock_stake {
+            params.lock_collateral(lock_stake);
+        }
+
+        let request = client
+            .new_request()
+            .with_program(guest.elf)
+            .with_stdin(env_input)
+            .with_offer(params);
+        // Bidding starts once the request is submitted
+        let lock_expires_at = unix_now() + u64::from(offer.lock_timeout);
+        let (request_id, expires_at) = client
+            .submit_onchain(request)
+            .await
+            .context("failed to submit the proof request")?;
+        log::info!("Submitted proof request {request_id:#x} to the Boundless market");
+        Ok((request_id, lock_expires_at, expires_at))
+    }
+}
+
+/// A proof request made on the Boundless market for a message.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MarketRequest {
+    pub id: U256,
+    /// Unix timestamp in seconds after which the request can no longer be locked.
+    pub lock_expires_at: u64,
+    /// Unix timestamp in seconds after which the request can no longer be fulfilled.
+    pub expires_at: u64,
+    /// SHA-256 digest of the guest input the request is for.
+    pub input_digest: Digest,
+    /// Whether a prover locked the request, staking on fulfilling it in time.
+    pub locked: bool,
+}
+
+/// A proof request the market did not serve in time.
+#[derive(Clone, Debug)]
+pub enum MarketTimeout {
+    /// No prover locked the request before its lock timeout.
+    NotLocked { id: U256 },
+    /// The request expired unfulfilled, slashing the prover that locked it, if any.
+    Expired { id: U256, locked: bool },
+}
+
+impl fmt::Display for MarketTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotLocked { id } => {
+                write!(
+                    f,
+                    "Proof request {id:#x} was not locked by a prover in time"
+                )
+            }
+            Self::Expired { id, locked: true } => write!(
+                f,
+                "Proof request {id:#x} expired after being locked, slashing the prover"
+            ),
+            Self::Expired { id, locked: false } => write!(
+                f,
+                "Proof request {id:#x} expired without being locked by a prover"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MarketTimeout {}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
@@ -0,0 +1,518 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Metrics of the relay pipeline, rendered in the Prometheus text format for the metrics
+//! endpoint and as JSON for the status API.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use alloy_primitives::Address;
+use risc0_zkvm::ProveInfo;
+use serde::{Deserialize, Serialize};
+
+use crate::job::MessageId;
+
+/// Number of recent proofs kept for the status API.
+const RECENT_PROOFS: usize = 100;
+
+/// Number of recent relay errors kept for the status API.
+const RECENT_ERRORS: usize = 100;
+
+/// Number of recent end-to-end latencies that percentiles are computed over.
+const LATENCY_WINDOW: usize = 1000;
+
+/// Number of recent request latencies per endpoint that percentiles are computed over.
+const ENDPOINT_LATENCY_WINDOW: usize = 1000;
+
+/// Measurements of a single proof, for capacity planning.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ProofMetrics {
+    /// Name of the prover backend, e.g. `local` or `bonsai`.
+    pub backend: String,
+    pub total_cycles: u64,
+    pub user_cycles: u64,
+    pub segments: usize,
+    /// Wall-clock time spent producing the succinct receipt.
+    pub proving_time: Duration,
+    /// Wall-clock time spent compressing the succinct receipt into a SNARK, if one was requested.
+    pub compression_time: Option<Duration>,
+}
+
+impl ProofMetrics {
+    pub fn new(backend: String, prove_info: &ProveInfo, proving_time: Duration) -> Self {
+        Self {
+            backend,
+            total_cycles: prove_info.stats.total_cycles,
+            user_cycles: prove_info.stats.user_cycles,
+            segments: prove_info.stats.segments,
+            proving_time,
+            compression_time: None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct BackendTotals {
+    proofs: u64,
+    total_cycles: u64,
+    segments: u64,
+    proving_seconds: f64,
+    compression_seconds: f64,
+}
+
+/// Percentiles of the end-to-end latency from source finalization to destination confirmation.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LatencyPercentiles {
+    pub samples: usize,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+impl LatencyPercentiles {
+    fn of(latencies: impl IntoIterator<Item = Duration>) -> Self {
+        let mut latencies: Vec<Duration> = latencies.into_iter().collect();
+        if latencies.is_empty() {
+            return Self::default();
+        }
+        latencies.sort();
+        let percentile = |p: usize| latencies[(latencies.len() - 1) * p / 100];
+        Self {
+            samples: latencies.len(),
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+        }
+    }
+}
+
+#[derive(Default)]
+struct EndpointTotals {
+    requests: u64,
+    errors: u64,
+    latencies: VecDeque<Duration>,
+}
+
+/// Requests made to a source chain RPC or beacon API endpoint.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EndpointStats {
+    /// The API of the endpoint, `execution_rpc` or `beacon_api`.
+    pub kind: String,
+    /// Host of the endpoint, leaving out any API key in the rest of its URL.
+    pub endpoint: String,
+    pub requests: u64,
+    pub errors: u64,
+    /// Percentiles over the most recent requests, whether they succeeded or not.
+    pub latency: LatencyPercentiles,
+}
+
+/// Balance of a wallet paying for deliveries, as last seen.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WalletBalance {
+    pub chain_id: u64,
+    pub address: Address,
+    pub balance_wei: u128,
+}
+
+/// An attempt at relaying a message that failed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct RelayError {
+    /// Unix timestamp in seconds.
+    pub at: u64,
+    pub id: MessageId,
+    pub error: String,
+}
+
+#[derive(Default)]
+struct Inner {
+    backends: BTreeMap<String, BackendTotals>,
+    recent_proofs: VecDeque<ProofMetrics>,
+    latencies: VecDeque<Duration>,
+    sla_breaches: u64,
+    stale_messages: usize,
+    stale_alerts: u64,
+    breaker_tripped: bool,
+    breaker_trips: u64,
+    /// Delivered and failed messages by tenant.
+    tenants: BTreeMap<String, (u64, u64)>,
+    /// Wallet balances in wei by chain ID and address.
+    wallets: BTreeMap<(u64, Address), u128>,
+    recent_errors: VecDeque<RelayError>,
+    /// Requests by endpoint kind and host.
+    endpoints: BTreeMap<(String, String), EndpointTotals>,
+}
+
+/// Registry of relay metrics shared between the pipeline and the API server.
+#[derive(Default)]
+pub struct Metrics {
+    inner: Mutex<Inner>,
+}
+
+impl Metrics {
+    pub fn record_proof(&self, proof: ProofMetrics) {
+        let mut inner = self.inner.lock().unwrap();
+        let totals = inner.backends.entry(proof.backend.clone()).or_default();
+        totals.proofs += 1;
+        totals.total_cycles += proof.total_cycles;
+        totals.segments += proof.segments as u64;
+        totals.proving_seconds += proof.proving_time.as_secs_f64();
+        totals.compression_seconds += proof.compression_time.unwrap_or_default().as_secs_f64();
+
+        if inner.recent_proofs.len() == RECENT_PROOFS {
+            inner.recent_proofs.pop_front();
+        }
+        inner.recent_proofs.push_back(proof);
+    }
+
+    /// Records the end-to-end latency of a delivered message and whether it breached the SLA.
+    pub fn record_latency(&self, latency: Duration, breached_sla: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.latencies.len() == LATENCY_WINDOW {
+            inner.latencies.pop_front();
+        }
+        inner.latencies.push_back(latency);
+        if breached_sla {
+            inner.sla_breaches += 1;
+        }
+    }
+
+    /// Sets the number of messages currently undelivered past the stale threshold.
+    pub fn set_stale_messages(&self, count: usize) {
+        self.inner.lock().unwrap().stale_messages = count;
+    }
+
+    pub fn record_stale_alert(&self) {
+        self.inner.lock().unwrap().stale_alerts += 1;
+    }
+
+    /// Sets whether the circuit breaker is tripped, counting each time it trips.
+    pub fn set_breaker_tripped(&self, tripped: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if tripped && !inner.breaker_tripped {
+            inner.breaker_trips += 1;
+        }
+        inner.breaker_tripped = tripped;
+    }
+
+    /// Records the outcome of relaying a message of `tenant`.
+    pub fn record_tenant_outcome(&self, tenant: &str, delivered: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let (delivered_count, failed_count) = inner.tenants.entry(tenant.to_string()).or_default();
+        if delivered {
+            *delivered_count += 1;
+        } else {
+            *failed_count += 1;
+        }
+    }
+
+    pub fn set_wallet_balance(&self, wallet: WalletBalance) {
+        self.inner
+            .lock()
+            .unwrap()
+            .wallets
+            .insert((wallet.chain_id, wallet.address), wallet.balance_wei);
+    }
+
+    /// Records that an attempt at relaying the message `id` failed with `error`.
+    pub fn record_error(&self, id: MessageId, error: String) {
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let mut inner = self.inner.lock().unwrap();
+        if inner.recent_errors.len() == RECENT_ERRORS {
+            inner.recent_errors.pop_front();
+        }
+        inner.recent_errors.push_back(RelayError { at, id, error });
+    }
+
+    /// Records a request to the endpoint at `host` that took `latency`, and whether it failed.
+    pub fn record_endpoint_request(&self, kind: &str, host: &str, latency: Duration, failed: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        let totals = inner
+            .endpoints
+            .entry((kind.to_string(), host.to_string()))
+            .or_default();
+        totals.requests += 1;
+        if failed {
+            totals.errors += 1;
+        }
+        if totals.latencies.len() == ENDPOINT_LATENCY_WINDOW {
+            totals.latencies.pop_front();
+        }
+        totals.latencies.push_back(latency);
+    }
+
+    /// The requests made to each endpoint.
+    pub fn endpoint_stats(&self) -> Vec<EndpointStats> {
+        self.inner
+            .lock()
+            .unwrap()
+            .endpoints
+            .iter()
+            .map(|((kind, endpoint), totals)| EndpointStats {
+                kind: kind.clone(),
+                endpoint: endpoint.clone(),
+                requests: totals.requests,
+                errors: totals.errors,
+                latency: LatencyPercentiles::of(totals.latencies.iter().copied()),
+            })
+            .collect()
+    }
+
+    pub fn breaker_tripped(&self) -> bool {
+        self.inner.lock().unwrap().breaker_tripped
+    }
+
+    /// The last seen balances of the wallets paying for deliveries.
+    pub fn wallet_balances(&self) -> Vec<WalletBalance> {
+        self.inner
+            .lock()
+            .unwrap()
+            .wallets
+            .iter()
+            .map(|(&(chain_id, address), &balance_wei)| WalletBalance {
+                chain_id,
+                address,
+                balance_wei,
+            })
+            .collect()
+    }
+
+    /// The most recent relay errors, oldest first.
+    pub fn recent_errors(&self) -> Vec<RelayError> {
+        self.inner
+            .lock()
+            .unwrap()
+            .recent_errors
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Latency percentiles over the most recent deliveries.
+    pub fn latency_percentiles(&self) -> LatencyPercentiles {
+        LatencyPercentiles::of(self.inner.lock().unwrap().latencies.iter().copied())
+    }
+
+    /// The most recent proofs, oldest first.
+    pub fn recent_proofs(&self) -> Vec<ProofMetrics> {
+        self.inner
+            .lock()
+            .unwrap()
+            .recent_proofs
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format.
+    pub fn render_prometheus(&self) -> String {
+        let percentiles = self.latency_percentiles();
+        let endpoints = self.endpoint_stats();
+        let inner = self.inner.lock().unwrap();
+        let mut out = String::new();
+
+        let counters: [(&str, &str, fn(&BackendTotals) -> f64); 5] = [
+            ("relay_proofs_total", "Number of proofs produced", |t| {
+                t.proofs as f64
+            }),
+            ("relay_proof_cycles_total", "Total cycles proven", |t| {
+                t.total_cycles as f64
+            }),
+            ("relay_proof_segments_total", "Total segments proven", |t| {
+                t.segments as f64
+            }),
+            (
+                "relay_proving_seconds_total",
+                "Wall-clock time spent producing succinct receipts",
+                |t| t.proving_seconds,
+            ),
+            (
+                "relay_compression_seconds_total",
+                "Wall-clock time spent compressing receipts into SNARKs",
+                |t| t.compression_seconds,
+            ),
+        ];
+        for (name, help, value) in counters {
+            writeln!(out, "# HELP {name} {help}").unwrap();
+            writeln!(out, "# TYPE {name} counter").unwrap();
+            for (backend, totals) in &inner.backends {
+                writeln!(out, "{name}{{backend=\"{backend}\"}} {}", value(totals)).unwrap();
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP relay_delivery_latency_seconds Time from source finalization to destination confirmation"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_delivery_latency_seconds summary").unwrap();
+        for (quantile, latency) in [
+            ("0.5", percentiles.p50),
+            ("0.9", percentiles.p90),
+            ("0.99", percentiles.p99),
+        ] {
+            writeln!(
+                out,
+                "relay_delivery_latency_seconds{{quantile=\"{quantile}\"}} {}",
+                latency.as_secs_f64()
+            )
+            .unwrap();
+        }
+        writeln!(
+            out,
+            "relay_delivery_latency_seconds_count {}",
+            percentiles.samples
+        )
+        .unwrap();
+
+        writeln!(
+            out,
+            "# HELP relay_sla_breaches_total Messages delivered later than the configured SLA"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_sla_breaches_total counter").unwrap();
+        writeln!(out, "relay_sla_breaches_total {}", inner.sla_breaches).unwrap();
+
+        writeln!(
+            out,
+            "# HELP relay_stale_messages Finalized messages undelivered past the stale threshold"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_stale_messages gauge").unwrap();
+        writeln!(out, "relay_stale_messages {}", inner.stale_messages).unwrap();
+
+        writeln!(
+            out,
+            "# HELP relay_stale_alerts_total Alerts fired for messages becoming stale"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_stale_alerts_total counter").unwrap();
+        writeln!(out, "relay_stale_alerts_total {}", inner.stale_alerts).unwrap();
+
+        writeln!(
+            out,
+            "# HELP relay_circuit_breaker_tripped Whether submissions are halted by the circuit breaker"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_circuit_breaker_tripped gauge").unwrap();
+        writeln!(
+            out,
+            "relay_circuit_breaker_tripped {}",
+            u8::from(inner.breaker_tripped)
+        )
+        .unwrap();
+
+        writeln!(
+            out,
+            "# HELP relay_circuit_breaker_trips_total Times the circuit breaker tripped"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_circuit_breaker_trips_total counter").unwrap();
+        writeln!(
+            out,
+            "relay_circuit_breaker_trips_total {}",
+            inner.breaker_trips
+        )
+        .unwrap();
+
+        writeln!(
+            out,
+            "# HELP relay_tenant_messages_total Messages relayed per tenant by outcome"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_tenant_messages_total counter").unwrap();
+        for (tenant, (delivered, failed)) in &inner.tenants {
+            for (outcome, count) in [("delivered", delivered), ("failed", failed)] {
+                writeln!(
+                    out,
+                    "relay_tenant_messages_total{{tenant=\"{tenant}\",outcome=\"{outcome}\"}} {count}"
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP relay_wallet_balance_wei Last seen balance of the wallets paying for deliveries"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_wallet_balance_wei gauge").unwrap();
+        for ((chain_id, address), balance) in &inner.wallets {
+            writeln!(
+                out,
+                "relay_wallet_balance_wei{{chain_id=\"{chain_id}\",address=\"{address}\"}} {}",
+                *balance as f64
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "# HELP relay_endpoint_requests_total Requests to source chain endpoints by outcome"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_endpoint_requests_total counter").unwrap();
+        for stats in &endpoints {
+            let (kind, endpoint) = (&stats.kind, &stats.endpoint);
+            for (outcome, count) in [
+                ("ok", stats.requests - stats.errors),
+                ("error", stats.errors),
+            ] {
+                writeln!(
+                    out,
+                    "relay_endpoint_requests_total{{kind=\"{kind}\",endpoint=\"{endpoint}\",\
+                     outcome=\"{outcome}\"}} {count}"
+                )
+                .unwrap();
+            }
+        }
+
+        writeln!(
+            out,
+            "# HELP relay_endpoint_latency_seconds Latency of requests to source chain endpoints"
+        )
+        .unwrap();
+        writeln!(out, "# TYPE relay_endpoint_latency_seconds summary").unwrap();
+        for stats in &endpoints {
+            let (kind, endpoint) = (&stats.kind, &stats.endpoint);
+            for (quantile, latency) in [
+                ("0.5", stats.latency.p50),
+                ("0.9", stats.latency.p90),
+                ("0.99", stats.latency.p99),
+            ] {
+                writeln!(
+                    out,
+                    "relay_endpoint_latency_seconds{{kind=\"{kind}\",endpoint=\"{endpoint}\",\
+                     quantile=\"{quantile}\"}} {}",
+                    latency.as_secs_f64()
+                )
+                .unwrap();
+            }
+            writeln!(
+                out,
+                "relay_endpoint_latency_seconds_count{{kind=\"{kind}\",\
+                 endpoint=\"{endpoint}\"}} {}",
+                stats.latency.samples
+            )
+            .unwrap();
+        }
+
+        out
+    }
+}
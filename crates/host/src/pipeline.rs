@@ -0,0 +1,449 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The stages a message goes through once the daemon picked it up, each running as its own task:
+//!
+//! ```text
+//! intake → finality → preflight → prove → submit → confirm
+//! ```
+//!
+//! The finality stage holds a message until its commitment block is final and starts its relay.
+//! Preflight selects the guest and builds its input, which takes most of the RPC requests of a
+//! relay, so proving capacity is only held while actually proving.
+//!
+//! Stages are connected by bounded channels: a stage that falls behind fills the channel feeding
+//! it, which holds up the stages before it down to intake, where the daemon stops scanning for new
+//! messages until there is room again. A slow prover thus never lets messages pile up in memory.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use alloy::network::Ethereum;
+use alloy::providers::PendingTransactionBuilder;
+use alloy_primitives::{Address, B256};
+use anyhow::{Result, bail, ensure};
+use tokio::sync::{Semaphore, mpsc};
+use tracing::log;
+use zkvm::GuestProgram;
+
+use crate::admin::DaemonControl;
+use crate::config::Finality;
+use crate::deliveries::Deliveries;
+use crate::destination::Destination;
+use crate::job::MessageId;
+use crate::proof::DeliveryProof;
+use crate::registry::RegisteredChain;
+use crate::relayer::{Relay, Relayer};
+use crate::scheduler::Scheduler;
+use crate::vaa::VaaRoute;
+use crate::watcher::finalized_block;
+
+/// How a message is delivered.
+pub struct Route {
+    /// The guest to prove the message with, or `None` to use the guest matching the image ID the
+    /// destination has for the source chain of the message.
+    pub guest: Option<&'static GuestProgram>,
+    pub destination: Destination,
+    /// Name of the chain of the destination in the config.
+    pub chain: String,
+    /// The Wormhole transceivers of the message, if its VAA is delivered alongside the proof.
+    pub vaa: Option<VaaRoute>,
+    /// Digest the destination NTT manager identifies the message by, if it can be computed.
+    pub digest: Option<B256>,
+    /// Priority of the message when waiting for proving capacity.
+    pub priority: u64,
+    /// The source chain of the message, if it is in the chain registry.
+    pub source_chain: Option<RegisteredChain>,
+    /// Wormhole chain ID of the source chain, if the message carries it.
+    pub source_chain_id: Option<u16>,
+}
+
+impl Route {
+    /// The guest proving the message `id`: the one set, or the one the destination verifies
+    /// proofs of messages from its source chain with.
+    pub async fn select_guest(&self, id: MessageId) -> Result<&'static GuestProgram> {
+        match (self.guest, self.source_chain_id) {
+            (Some(guest), _) => Ok(guest),
+            (None, Some(chain)) => self.destination.guest(chain).await,
+            (None, None) => bail!(
+                "message {id} does not carry its source chain, so its transceiver must set a guest"
+            ),
+        }
+    }
+}
+
+/// A message picked up by the daemon.
+pub struct Intake {
+    pub id: MessageId,
+    /// The source transceiver that emitted the message.
+    pub source: Address,
+    /// Name of the tenant the message belongs to.
+    pub tenant: String,
+    /// Queue of the tenant's messages waiting for proving capacity.
+    pub jobs: Arc<Scheduler>,
+    pub route: Route,
+    pub commitment_block: u64,
+}
+
+/// A message whose commitment block is final, waiting for preflight.
+struct Finalized {
+    relay: Relay,
+    tenant: String,
+    jobs: Arc<Scheduler>,
+    route: Route,
+}
+
+/// A message past preflight, along with what the later stages need to deliver it.
+struct Relaying {
+    relay: Relay,
+    tenant: String,
+    jobs: Arc<Scheduler>,
+    priority: u64,
+    destination: Destination,
+    guest: &'static GuestProgram,
+    vaa: Option<VaaRoute>,
+    digest: Option<B256>,
+    /// The input built by preflight, taken once proving starts.
+    input: Option<Vec<u8>>,
+}
+
+/// The state shared by the stages.
+struct Stages {
+    relayer: Arc<Relayer>,
+    deliveries: Deliveries,
+    control: Arc<DaemonControl>,
+    finality: Finality,
+    /// How long to wait before checking again for a commitment block that is not final yet.
+    poll_interval: Duration,
+}
+
+/// Entry point of the running stages.
+pub struct Pipeline {
+    intake: mpsc::Sender<Intake>,
+}
+
+impl Pipeline {
+    /// Starts the stages. Each stage buffers and works on at most `capacity` messages at once,
+    /// except for finality, which waits for one commitment block after the other, and
+    /// submission, which sends one transaction after the other.
+    pub fn spawn(
+        relayer: Arc<Relayer>,
+        deliveries: Deliveries,
+        control: Arc<DaemonControl>,
+        capacity: usize,
+        finality: Finality,
+        poll_interval: Duration,
+    ) -> Self {
+        let stages = Arc::new(Stages {
+            relayer,
+            deliveries,
+            control,
+            finality,
+            poll_interval,
+        });
+        let (intake, intake_rx) = mpsc::channel(capacity);
+        let (preflight_tx, preflight_rx) = mpsc::channel(capacity);
+        let (prove_tx, prove_rx) = mpsc::channel(capacity);
+        let (submit_tx, submit_rx) = mpsc::channel(capacity);
+        let (confirm_tx, confirm_rx) = mpsc::channel(capacity);
+        tokio::spawn(stages.clone().finality(intake_rx, preflight_tx));
+        tokio::spawn(stages.clone().preflight(preflight_rx, prove_tx, capacity));
+        tokio::spawn(stages.clone().prove(prove_rx, submit_tx, capacity));
+        tokio::spawn(stages.clone().submit(submit_rx, confirm_tx));
+        tokio::spawn(stages.confirm(confirm_rx, capacity));
+        Self { intake }
+    }
+
+    /// Hands a message to the pipeline, waiting while the pipeline is full.
+    pub async fn send(&self, intake: Intake) {
+        if self.intake.send(intake).await.is_err() {
+            log::error!("Relay pipeline stopped");
+        }
+    }
+}
+
+impl Stages {
+    /// Waits for the commitment block of each message to be final and starts its relay.
+    async fn finality(
+        self: Arc<Self>,
+        mut intake: mpsc::Receiver<Intake>,
+        preflight: mpsc::Sender<Finalized>,
+    ) {
+        // The latest final block seen and when it was seen final, only refreshed for messages
+        // committing to a later block
+        let (mut finalized, mut finalized_at) = (0, SystemTime::now());
+        while let Some(message) = intake.recv().await {
+            while message.commitment_block > finalized {
+                match finalized_block(&self.relayer.rpc, self.finality).await {
+                    Ok(block) if block > finalized => {
+                        finalized = block;
+                        finalized_at = SystemTime::now();
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::warn!("Failed to fetch the final block: {e:#}"),
+                }
+                if message.commitment_block > finalized {
+                    tokio::time::sleep(self.poll_interval).await;
+                }
+            }
+            let Intake {
+                id,
+                source,
+                tenant,
+                jobs,
+                route,
+                commitment_block,
+            } = message;
+            match self
+                .relayer
+                .begin(id, source, commitment_block, finalized_at)
+                .await
+            {
+                Ok(relay) => {
+                    let finalized = Finalized {
+                        relay,
+                        tenant,
+                        jobs,
+                        route,
+                    };
+                    if preflight.send(finalized).await.is_err() {
+                        break;
+                    }
+                }
+                Err(e) => self.done(id, &tenant, Err(e)),
+            }
+        }
+    }
+
+    /// Selects the guest of each message and builds its input.
+    async fn preflight(
+        self: Arc<Self>,
+        mut messages: mpsc::Receiver<Finalized>,
+        prove: mpsc::Sender<Relaying>,
+        capacity: usize,
+    ) {
+        let slots = Arc::new(Semaphore::new(capacity));
+        loop {
+            let slot = slots.clone().acquire_owned().await.unwrap();
+            let Some(message) = messages.recv().await else {
+                break;
+            };
+            let stages = self.clone();
+            let prove = prove.clone();
+            tokio::spawn(async move {
+                let _slot = slot;
+                let Finalized {
+                    relay,
+                    tenant,
+                    jobs,
+                    route,
+                } = message;
+                match stages.preflight_message(&relay, &route).await {
+                    Ok((guest, input)) => {
+                        let relaying = Relaying {
+                            relay,
+                            tenant,
+                            jobs,
+                            priority: route.priority,
+                            destination: route.destination,
+                            guest,
+                            vaa: route.vaa,
+                            digest: route.digest,
+                            input: Some(input),
+                        };
+                        // Errors only once the pipeline stopped
+                        let _ = prove.send(relaying).await;
+                    }
+                    Err(e) => stages.fail(relay, &tenant, e).await,
+                }
+            });
+        }
+    }
+
+    /// Selects the guest of the message of `relay` and builds its input.
+    async fn preflight_message(
+        &self,
+        relay: &Relay,
+        route: &Route,
+    ) -> Result<(&'static GuestProgram, Vec<u8>)> {
+        let guest = route.select_guest(relay.id()).await?;
+        if let Some((chain, spec)) = route
+            .source_chain
+            .as_ref()
+            .and_then(|chain| Some((chain, chain.chain_spec?)))
+        {
+            ensure!(
+                guest
+                    .chain_id
+                    .is_none_or(|chain_id| chain_id == spec.chain_id),
+                "guest {} is built for chain {}, but messages from {} use chain {}",
+                guest.name,
+                guest.chain_id.unwrap_or_default(),
+                chain.name,
+                spec.chain_id
+            );
+        }
+        let input = self.relayer.preflight(relay, guest).await?;
+        Ok((guest, input))
+    }
+
+    /// Proves messages as the queue of their tenant admits them, in order of priority.
+    async fn prove(
+        self: Arc<Self>,
+        mut messages: mpsc::Receiver<Relaying>,
+        submit: mpsc::Sender<(Relaying, DeliveryProof)>,
+        capacity: usize,
+    ) {
+        let slots = Arc::new(Semaphore::new(capacity));
+        loop {
+            // Only take on messages while there is room, so the channel fills up when proving
+            // falls behind
+            let slot = slots.clone().acquire_owned().await.unwrap();
+            let Some(mut relaying) = messages.recv().await else {
+                break;
+            };
+            let stages = self.clone();
+            let submit = submit.clone();
+            tokio::spawn(async move {
+                let _slot = slot;
+                let proof = {
+                    let _permit = relaying.jobs.acquire(relaying.priority).await;
+                    stages.prove_message(&mut relaying).await
+                };
+                // Compression waits with the permit released, so the next message can be proven
+                let proof = match proof {
+                    Ok(Some(proof)) => stages
+                        .relayer
+                        .compress(&mut relaying.relay, proof)
+                        .await
+                        .map(Some),
+                    other => other,
+                };
+                match proof {
+                    Ok(Some(proof)) => {
+                        // Errors only once the pipeline stopped
+                        let _ = submit.send((relaying, proof)).await;
+                    }
+                    Ok(None) => stages.finish(relaying).await,
+                    Err(e) => stages.fail(relaying.relay, &relaying.tenant, e).await,
+                }
+            });
+        }
+    }
+
+    /// Proves the message of `relaying`, returning `None` if it was delivered some other way in
+    /// the meantime.
+    async fn prove_message(&self, relaying: &mut Relaying) -> Result<Option<DeliveryProof>> {
+        let transceiver = relaying.destination.address();
+        let proof = self.relayer.prove_message(
+            &mut relaying.relay,
+            &relaying.destination,
+            relaying.guest,
+            relaying.vaa,
+            relaying.input.take(),
+        );
+        // Stop proving once the message was delivered some other way
+        let delivered = match relaying.digest {
+            Some(digest) => tokio::select! {
+                biased;
+                result = proof => return result,
+                tx_hash = self.deliveries.wait(transceiver, digest) => tx_hash,
+            },
+            None => return proof.await,
+        };
+        self.relayer
+            .delivered_elsewhere(&mut relaying.relay, delivered)?;
+        Ok(None)
+    }
+
+    /// Sends the proofs to their destinations one after the other, so the transactions of each
+    /// wallet get consecutive nonces.
+    async fn submit(
+        self: Arc<Self>,
+        mut proofs: mpsc::Receiver<(Relaying, DeliveryProof)>,
+        confirm: mpsc::Sender<(Relaying, PendingTransactionBuilder<Ethereum>)>,
+    ) {
+        while let Some((mut relaying, proof)) = proofs.recv().await {
+            let result = self
+                .relayer
+                .submit(
+                    &mut relaying.relay,
+                    &relaying.destination,
+                    &proof,
+                    relaying.guest,
+                )
+                .await;
+            match result {
+                Ok(Some(pending_tx)) => {
+                    if confirm.send((relaying, pending_tx)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => self.finish(relaying).await,
+                Err(e) => self.fail(relaying.relay, &relaying.tenant, e).await,
+            }
+        }
+    }
+
+    /// Waits for the submitted transactions to confirm.
+    async fn confirm(
+        self: Arc<Self>,
+        mut submitted: mpsc::Receiver<(Relaying, PendingTransactionBuilder<Ethereum>)>,
+        capacity: usize,
+    ) {
+        let slots = Arc::new(Semaphore::new(capacity));
+        loop {
+            let slot = slots.clone().acquire_owned().await.unwrap();
+            let Some((mut relaying, pending_tx)) = submitted.recv().await else {
+                break;
+            };
+            let stages = self.clone();
+            tokio::spawn(async move {
+                let _slot = slot;
+                let result = stages
+                    .relayer
+                    .confirm(&mut relaying.relay, &relaying.destination, pending_tx)
+                    .await;
+                match result {
+                    Ok(()) => stages.finish(relaying).await,
+                    Err(e) => stages.fail(relaying.relay, &relaying.tenant, e).await,
+                }
+            });
+        }
+    }
+
+    async fn finish(&self, relaying: Relaying) {
+        let id = relaying.relay.id();
+        self.relayer.finish(relaying.relay).await;
+        self.done(id, &relaying.tenant, Ok(()));
+    }
+
+    async fn fail(&self, mut relay: Relay, tenant: &str, error: anyhow::Error) {
+        if let Err(e) = self.relayer.fail(&mut relay.job, &error).await {
+            log::warn!("Failed to record failure of message {}: {e:#}", relay.id());
+        }
+        self.done(relay.id(), tenant, Err(error));
+    }
+
+    /// Releases the message `id` of `tenant` once it left the pipeline.
+    fn done(&self, id: MessageId, tenant: &str, result: Result<()>) {
+        self.control.finish(&id);
+        self.relayer
+            .metrics
+            .record_tenant_outcome(tenant, result.is_ok());
+        if let Err(e) = result {
+            log::error!("Failed to relay message {id} of {tenant}: {e:#}");
+        }
+    }
+}
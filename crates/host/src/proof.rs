@@ -0,0 +1,67 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Proofs of messages in the forms the destination's verifier router accepts.
+
+use alloy_primitives::Bytes;
+use anyhow::{Context, Result, bail};
+use risc0_ethereum_contracts::encode_seal;
+use risc0_zkvm::Receipt;
+
+/// A proof of a message, ready to be delivered with `receiveMessage`.
+#[derive(Clone, Debug)]
+pub enum DeliveryProof {
+    /// A Groth16 receipt of the guest, verified on its own.
+    Groth16(Receipt),
+    /// Inclusion of the claim of the guest in a set of claims that Boundless proved together.
+    /// The seal holds the Merkle path of the claim for the set verifier, which verifies the root
+    /// of the set once for all of its claims, making each message cheaper to verify.
+    SetInclusion { journal: Bytes, seal: Bytes },
+    /// A succinct receipt of the guest, which the compressor still has to turn into a Groth16
+    /// receipt before it can be delivered.
+    Succinct(Receipt),
+}
+
+impl DeliveryProof {
+    /// The journal committed by the guest.
+    pub fn journal(&self) -> &[u8] {
+        match self {
+            Self::Groth16(receipt) | Self::Succinct(receipt) => &receipt.journal.bytes,
+            Self::SetInclusion { journal, .. } => journal,
+        }
+    }
+
+    /// The seal as `receiveMessage` takes it, prefixed with the selector of its verifier.
+    pub fn seal(&self) -> Result<Vec<u8>> {
+        match self {
+            Self::Groth16(receipt) => encode_seal(receipt).context("invalid receipt"),
+            Self::SetInclusion { seal, .. } => Ok(seal.to_vec()),
+            Self::Succinct(_) => bail!("succinct receipts must be compressed before delivery"),
+        }
+    }
+
+    /// The Groth16 receipt, if the proof is one.
+    pub fn receipt(&self) -> Option<&Receipt> {
+        match self {
+            Self::Groth16(receipt) => Some(receipt),
+            Self::SetInclusion { .. } | Self::Succinct(_) => None,
+        }
+    }
+}
+
+impl From<Receipt> for DeliveryProof {
+    fn from(receipt: Receipt) -> Self {
+        Self::Groth16(receipt)
+    }
+}
@@ -0,0 +1,429 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Queue of proving work shared between a submitter, which holds the keys and delivers proofs, and
+//! any number of prover workers, which need neither keys nor access to any chain.
+//!
+//! The submitter builds the input of each message and pushes it. A worker claims it, proves it and
+//! completes it with the outcome, which the submitter takes to deliver the proof.
+//!
+//! The queue is kept either in a shared directory ([`DirQueue`]) or on a Redis server
+//! ([`RedisQueue`]), see [`open`].
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result, bail, ensure};
+use redis::{Commands, Script};
+use risc0_zkvm::Receipt;
+use serde::{Deserialize, Serialize};
+use tokio::task;
+use tracing::log;
+
+use crate::artifact::{self, InputArtifact, ProofArtifact};
+use crate::job::MessageId;
+
+/// Interval between checks of the queue for the outcome of a pushed input.
+const OUTCOME_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How proving a claimed input ended.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum ProofOutcome {
+    Proved(ProofArtifact),
+    Failed(String),
+}
+
+pub trait ProofQueue: Send + Sync {
+    /// Adds the input of a message to be proven.
+    fn push(&self, input: &InputArtifact) -> Result<()>;
+
+    /// Claims the oldest input no worker is proving. Claims older than `lease` are presumed
+    /// abandoned by a crashed worker and handed out again.
+    fn claim(&self, lease: Duration) -> Result<Option<InputArtifact>>;
+
+    /// Records the outcome of proving the claimed input of the message `id`.
+    fn complete(&self, id: &MessageId, outcome: &ProofOutcome) -> Result<()>;
+
+    /// Takes the outcome of proving the message `id`, if available.
+    fn take(&self, id: &MessageId) -> Result<Option<ProofOutcome>>;
+}
+
+/// Opens the queue at `location`: a Redis server for a `redis://` URL, otherwise a directory.
+pub fn open(location: &str) -> Result<Arc<dyn ProofQueue>> {
+    if location.starts_with("redis://") || location.starts_with("redis+unix://") {
+        Ok(Arc::new(RedisQueue::open(location)?))
+    } else {
+        Ok(Arc::new(DirQueue::open(location)?))
+    }
+}
+
+/// Pushes `input` and waits for a worker to prove it, returning the Groth16 receipt. Fails if no
+/// worker completed it within `timeout`. Queues block on their storage, so they are accessed from
+/// the blocking thread pool.
+pub async fn prove_remotely(
+    queue: Arc<dyn ProofQueue>,
+    input: InputArtifact,
+    timeout: Duration,
+) -> Result<Receipt> {
+    let (id, guest) = (input.id, input.guest.clone());
+    let pushing = queue.clone();
+    task::spawn_blocking(move || pushing.push(&input)).await??;
+    log::info!("Queued message {id} for the prover workers");
+    let deadline = Instant::now() + timeout;
+    loop {
+        let taking = queue.clone();
+        match task::spawn_blocking(move || taking.take(&id)).await?? {
+            Some(ProofOutcome::Proved(proof)) => {
+                ensure!(
+                    proof.guest == guest,
+                    "message {id} was proven with guest {} instead of {guest}",
+                    proof.guest
+                );
+                return Ok(proof.receipt);
+            }
+            Some(ProofOutcome::Failed(error)) => {
+                bail!("prover worker failed to prove message {id}: {error}")
+            }
+            None if Instant::now() >= deadline => {
+                bail!(
+                    "no prover worker proved message {id} within {}s",
+                    timeout.as_secs()
+                )
+            }
+            None => tokio::time::sleep(OUTCOME_POLL_INTERVAL).await,
+        }
+    }
+}
+
+/// Keeps the queue in a directory shared by the submitter and the workers, which must be on a
+/// filesystem where renames are atomic. Workers claim inputs by moving them out of `pending`, so
+/// only one of them succeeds.
+pub struct DirQueue {
+    pending: PathBuf,
+    claimed: PathBuf,
+    done: PathBuf,
+}
+
+impl DirQueue {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        let dir = dir.as_ref();
+        let queue = Self {
+            pending: dir.join("pending"),
+            claimed: dir.join("claimed"),
+            done: dir.join("done"),
+        };
+        for dir in [&queue.pending, &queue.claimed, &queue.done] {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("failed to create proof queue {}", dir.display()))?;
+        }
+        Ok(queue)
+    }
+
+    fn file_name(id: &MessageId) -> String {
+        format!("{id}.bin")
+    }
+
+    /// Moves claims older than `lease` back to `pending`.
+    fn requeue_expired(&self, lease: Duration) -> Result<()> {
+        let now = SystemTime::now();
+        for path in entries(&self.claimed)? {
+            let claimed_at = fs::metadata(&path)?.modified()?;
+            if now.duration_since(claimed_at).unwrap_or_default() < lease {
+                continue;
+            }
+            log::warn!("Claim of {} expired, queueing it again", path.display());
+            ignore_not_found(fs::rename(
+                &path,
+                self.pending.join(path.file_name().unwrap()),
+            ))?;
+        }
+        Ok(())
+    }
+}
+
+impl ProofQueue for DirQueue {
+    fn push(&self, input: &InputArtifact) -> Result<()> {
+        // Write to a temporary file first so workers never claim a partially written input
+        let path = self.pending.join(Self::file_name(&input.id));
+        let tmp = path.with_extension("bin.tmp");
+        input.write(&tmp)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn claim(&self, lease: Duration) -> Result<Option<InputArtifact>> {
+        self.requeue_expired(lease)?;
+
+        let mut pending = Vec::new();
+        for path in entries(&self.pending)? {
+            pending.push((fs::metadata(&path)?.modified()?, path));
+        }
+        pending.sort();
+        for (_, path) in pending {
+            let claimed = self.claimed.join(path.file_name().unwrap());
+            match fs::rename(&path, &claimed) {
+                Ok(()) => {
+                    // The modification time of a claim is when it was claimed
+                    fs::File::options()
+                        .write(true)
+                        .open(&claimed)?
+                        .set_modified(SystemTime::now())?;
+                    return InputArtifact::read(&claimed).map(Some);
+                }
+                // Claimed by another worker in the meantime
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(None)
+    }
+
+    fn complete(&self, id: &MessageId, outcome: &ProofOutcome) -> Result<()> {
+        let path = self.done.join(Self::file_name(id));
+        let tmp = path.with_extension("bin.tmp");
+        artifact::write(&tmp, outcome)?;
+        fs::rename(&tmp, &path)?;
+        ignore_not_found(fs::remove_file(self.claimed.join(Self::file_name(id))))
+    }
+
+    fn take(&self, id: &MessageId) -> Result<Option<ProofOutcome>> {
+        let path = self.done.join(Self::file_name(id));
+        if !path.exists() {
+            return Ok(None);
+        }
+        let outcome = artifact::read(&path)?;
+        fs::remove_file(&path)?;
+        Ok(Some(outcome))
+    }
+}
+
+/// Keeps the queue on a Redis server shared by the submitter and the workers. Pending inputs are
+/// in a list and claims in a sorted set scored by when their lease expires; a script moves expired
+/// claims back and claims the oldest pending input in one step, so only one worker gets it.
+pub struct RedisQueue {
+    client: redis::Client,
+    /// Connection reused across calls, reopened after it failed.
+    connection: Mutex<Option<redis::Connection>>,
+    claim: Script,
+}
+
+/// Key prefix of everything the queue stores. Deployments sharing a server select a database in
+/// the URL to keep their queues apart.
+const REDIS_PREFIX: &str = "proof-queue";
+
+/// Requeues the expired claims in the sorted set `KEYS[2]`, then moves the oldest input of the
+/// list `KEYS[1]` to it with a lease expiring at `ARGV[1] + ARGV[2]` milliseconds.
+const REDIS_CLAIM: &str = r#"
+local expired = redis.call('ZRANGEBYSCORE', KEYS[2], '-inf', ARGV[1])
+for _, id in ipairs(expired) do
+    redis.call('ZREM', KEYS[2], id)
+    redis.call('RPUSH', KEYS[1], id)
+end
+local id = redis.call('RPOP', KEYS[1])
+if id then
+    redis.call('ZADD', KEYS[2], tonumber(ARGV[1]) + tonumber(ARGV[2]), id)
+end
+return id
+"#;
+
+impl RedisQueue {
+    pub fn open(url: &str) -> Result<Self> {
+        let client = redis::Client::open(url).context("invalid Redis URL")?;
+        // Fail early if the server cannot be reached
+        let connection = client
+            .get_connection()
+            .context("failed to connect to the Redis proof queue")?;
+        Ok(Self {
+            client,
+            connection: Mutex::new(Some(connection)),
+            claim: Script::new(REDIS_CLAIM),
+        })
+    }
+
+    /// Runs `f` on the connection to the server, connecting first if there is none. A connection
+    /// `f` failed on is dropped, so the next call reconnects.
+    fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&mut redis::Connection) -> redis::RedisResult<T>,
+    ) -> Result<T> {
+        let mut connection = self.connection.lock().unwrap();
+        let mut conn = match connection.take() {
+            Some(conn) => conn,
+            None => self
+                .client
+                .get_connection()
+                .context("failed to connect to the Redis proof queue")?,
+        };
+        let result = f(&mut conn);
+        if result.is_ok() {
+            *connection = Some(conn);
+        }
+        Ok(result?)
+    }
+
+    fn key(name: &str) -> String {
+        format!("{REDIS_PREFIX}:{name}")
+    }
+
+    fn input_key(id: &str) -> String {
+        Self::key(&format!("input:{id}"))
+    }
+
+    fn done_key(id: &str) -> String {
+        Self::key(&format!("done:{id}"))
+    }
+}
+
+impl ProofQueue for RedisQueue {
+    fn push(&self, input: &InputArtifact) -> Result<()> {
+        let id = input.id.to_string();
+        let bytes = bincode::serialize(input)?;
+        // Store the input before listing it so workers never claim one without its input
+        self.with_connection(|conn| {
+            redis::pipe()
+                .atomic()
+                .set(Self::input_key(&id), bytes)
+                .ignore()
+                .lpush(Self::key("pending"), &id)
+                .ignore()
+                .exec(conn)
+        })
+    }
+
+    fn claim(&self, lease: Duration) -> Result<Option<InputArtifact>> {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        let claimed = self.with_connection(|conn| {
+            loop {
+                let id: Option<String> = self
+                    .claim
+                    .key(Self::key("pending"))
+                    .key(Self::key("claimed"))
+                    .arg(now)
+                    .arg(lease.as_millis() as u64)
+                    .invoke(conn)?;
+                let Some(id) = id else {
+                    return Ok(None);
+                };
+                let bytes: Option<Vec<u8>> = conn.get(Self::input_key(&id))?;
+                match bytes {
+                    Some(bytes) => return Ok(Some((id, bytes))),
+                    // Completed by the worker whose claim expired in the meantime
+                    None => {
+                        let _: () = conn.zrem(Self::key("claimed"), &id)?;
+                    }
+                }
+            }
+        })?;
+        claimed
+            .map(|(id, bytes)| {
+                bincode::deserialize(&bytes)
+                    .with_context(|| format!("invalid input of message {id} in the queue"))
+            })
+            .transpose()
+    }
+
+    fn complete(&self, id: &MessageId, outcome: &ProofOutcome) -> Result<()> {
+        let id = id.to_string();
+        let bytes = bincode::serialize(outcome)?;
+        self.with_connection(|conn| {
+            redis::pipe()
+                .atomic()
+                .set(Self::done_key(&id), bytes)
+                .ignore()
+                .zrem(Self::key("claimed"), &id)
+                .ignore()
+                .del(Self::input_key(&id))
+                .ignore()
+                .exec(conn)
+        })
+    }
+
+    fn take(&self, id: &MessageId) -> Result<Option<ProofOutcome>> {
+        let bytes: Option<Vec<u8>> = self.with_connection(|conn| {
+            redis::cmd("GETDEL")
+                .arg(Self::done_key(&id.to_string()))
+                .query(conn)
+        })?;
+        bytes
+            .map(|bytes| bincode::deserialize(&bytes))
+            .transpose()
+            .with_context(|| format!("invalid outcome of message {id} in the queue"))
+    }
+}
+
+/// The complete files in `dir`, skipping those still being written.
+fn entries(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "bin") {
+            paths.push(path);
+        }
+    }
+    Ok(paths)
+}
+
+fn ignore_not_found(result: std::io::Result<()>) -> Result<()> {
+    match result {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::{Address, TxHash};
+    use risc0_zkvm::Digest;
+
+    #[test]
+    fn claims_each_input_once() {
+        let dir = std::env::temp_dir().join(format!("proof-queue-test-{}", std::process::id()));
+        let queue = DirQueue::open(&dir).unwrap();
+        let input = InputArtifact {
+            id: MessageId {
+                tx_hash: TxHash::ZERO,
+                index: 0,
+            },
+            source: Address::ZERO,
+            commitment_block: 1,
+            guest: "guest".to_string(),
+            image_id: Digest::ZERO,
+            input: vec![1, 2, 3],
+        };
+        queue.push(&input).unwrap();
+
+        let lease = Duration::from_secs(60);
+        let claimed = queue.claim(lease).unwrap().unwrap();
+        assert_eq!(claimed.id, input.id);
+        assert!(queue.claim(lease).unwrap().is_none());
+        // An expired claim is handed out again
+        assert!(queue.claim(Duration::ZERO).unwrap().is_some());
+
+        assert!(queue.take(&input.id).unwrap().is_none());
+        queue
+            .complete(&input.id, &ProofOutcome::Failed("boom".to_string()))
+            .unwrap();
+        assert!(matches!(
+            queue.take(&input.id).unwrap(),
+            Some(ProofOutcome::Failed(error)) if error == "boom"
+        ));
+        assert!(queue.take(&input.id).unwrap().is_none());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
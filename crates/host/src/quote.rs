@@ -0,0 +1,185 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Quotes of what proving a message costs on each backend, from the cycle count of a dry run of
+//! the guest.
+
+use alloy_primitives::U256;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use zkvm::GuestProgram;
+
+use crate::config::{OfferConfig, Pricing};
+use crate::execute;
+
+/// What proving a message costs on each backend.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CostQuote {
+    /// Cycles of the guest execution to prove.
+    pub cycles: u64,
+    /// Price the offer on the Boundless market starts at, in wei.
+    pub market_min_wei: U256,
+    /// Price the offer on the Boundless market ramps up to, in wei. Requests are usually locked
+    /// somewhere between the two.
+    pub market_max_wei: U256,
+    /// Cost of proving on Bonsai in USD.
+    pub bonsai_usd: f64,
+    /// Cost of proving on this host in USD.
+    pub local_usd: f64,
+}
+
+impl CostQuote {
+    /// Quotes proving an execution of `cycles` cycles, with the market price taken from the
+    /// offer the relay would make.
+    pub fn new(cycles: u64, pricing: &Pricing, offer: &OfferConfig) -> Self {
+        let offer = offer.offer(cycles);
+        let mcycles = cycles as f64 / 1e6;
+        Self {
+            cycles,
+            market_min_wei: offer.min_price,
+            market_max_wei: offer.max_price,
+            bonsai_usd: mcycles * pricing.bonsai_usd_per_mcycle,
+            local_usd: mcycles * pricing.local_usd_per_mcycle,
+        }
+    }
+}
+
+/// Proving throughput of each backend in cycles per second, for estimating latencies.
+#[derive(Clone, Copy, Debug)]
+pub struct Throughput {
+    pub cpu_hz: u64,
+    pub gpu_hz: u64,
+    pub bonsai_hz: u64,
+    /// Of the prover fulfilling a market request, once it locked the request.
+    pub market_hz: u64,
+}
+
+/// Seconds compressing a receipt into a Groth16 SNARK on this host takes on top of proving it.
+const COMPRESSION_SECS: u64 = 30;
+
+/// The estimated cost and latency of proving an execution on a backend.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BackendEstimate {
+    pub backend: String,
+    /// Cost in USD, for backends paid in USD.
+    pub cost_usd: Option<f64>,
+    /// Range of the cost in wei, for the market.
+    pub cost_wei: Option<(U256, U256)>,
+    /// Seconds until the Groth16 seal is available.
+    pub latency_secs: u64,
+}
+
+impl CostQuote {
+    /// Estimates the cost and latency of proving on every backend, with the latencies derived
+    /// from `throughput`. Market requests are assumed to be locked halfway through their ramp-up.
+    pub fn compare(
+        &self,
+        pricing: &Pricing,
+        offer: &OfferConfig,
+        throughput: &Throughput,
+    ) -> Vec<BackendEstimate> {
+        let mcycles = self.cycles as f64 / 1e6;
+        let proving_secs = |hz: u64| self.cycles.div_ceil(hz.max(1));
+        let estimate = |backend: &str, cost_usd, latency_secs| BackendEstimate {
+            backend: backend.to_string(),
+            cost_usd: Some(cost_usd),
+            cost_wei: None,
+            latency_secs,
+        };
+        let ramp_up_period = offer.offer(self.cycles).ramp_up_period;
+        vec![
+            estimate(
+                "local-cpu",
+                mcycles * pricing.cpu_usd_per_mcycle,
+                proving_secs(throughput.cpu_hz) + COMPRESSION_SECS,
+            ),
+            estimate(
+                "local-gpu",
+                self.local_usd,
+                proving_secs(throughput.gpu_hz) + COMPRESSION_SECS,
+            ),
+            estimate(
+                "bonsai",
+                self.bonsai_usd,
+                proving_secs(throughput.bonsai_hz),
+            ),
+            BackendEstimate {
+                backend: "market".to_string(),
+                cost_usd: None,
+                cost_wei: Some((self.market_min_wei, self.market_max_wei)),
+                latency_secs: u64::from(ramp_up_period / 2) + proving_secs(throughput.market_hz),
+            },
+        ]
+    }
+}
+
+/// Executes `guest` on `env_input` without proving it, quoting what proving the execution costs.
+pub async fn quote(
+    env_input: Vec<u8>,
+    guest: &'static GuestProgram,
+    pricing: &Pricing,
+    offer: &OfferConfig,
+) -> Result<CostQuote> {
+    let session = execute(env_input, guest).await?;
+    Ok(CostQuote::new(session.cycles(), pricing, offer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_with_cycles() {
+        let pricing = Pricing {
+            bonsai_usd_per_mcycle: 0.02,
+            local_usd_per_mcycle: 0.01,
+            cpu_usd_per_mcycle: 0.1,
+        };
+        let offer = OfferConfig {
+            min_price_per_mcycle_wei: Some(10),
+            max_price_per_mcycle_wei: Some(100),
+            ..Default::default()
+        };
+        let quote = CostQuote::new(50_000_000, &pricing, &offer);
+        assert_eq!(quote.market_min_wei, U256::from(500));
+        assert_eq!(quote.market_max_wei, U256::from(5000));
+        assert!((quote.bonsai_usd - 1.0).abs() < 1e-9);
+        assert!((quote.local_usd - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn compares_backends() {
+        let (pricing, offer) = (Pricing::default(), OfferConfig::default());
+        let throughput = Throughput {
+            cpu_hz: 100_000,
+            gpu_hz: 1_000_000,
+            bonsai_hz: 2_000_000,
+            market_hz: 2_000_000,
+        };
+        let estimates =
+            CostQuote::new(10_000_000, &pricing, &offer).compare(&pricing, &offer, &throughput);
+        let latency = |backend: &str| {
+            estimates
+                .iter()
+                .find(|estimate| estimate.backend == backend)
+                .unwrap()
+                .latency_secs
+        };
+        assert_eq!(latency("local-cpu"), 100 + COMPRESSION_SECS);
+        assert_eq!(latency("local-gpu"), 10 + COMPRESSION_SECS);
+        assert_eq!(latency("bonsai"), 5);
+        // Locked halfway through the ramp-up of a fifth of the default lock timeout
+        assert_eq!(latency("market"), 33 + 5);
+    }
+}
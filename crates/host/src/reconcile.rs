@@ -0,0 +1,157 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Reconciliation of the messages emitted by source transceivers against their deliveries.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::{Provider, ProviderBuilder};
+use alloy_primitives::{Address, B256, Bytes};
+use anyhow::{Context, Result};
+use common::from_wormhole_address;
+use common::ntt::TransceiverMessage;
+use serde::Serialize;
+use tracing::log;
+
+use crate::config::DaemonConfig;
+use crate::destination::DeliveryCheck;
+use crate::job::MessageId;
+use crate::routing::RoutingTable;
+use crate::rpc::EndpointPool;
+use crate::watcher::scan;
+
+/// A message that has not been delivered to its destination.
+#[derive(Clone, Debug, Serialize)]
+pub struct UndeliveredMessage {
+    pub id: MessageId,
+    /// The source transceiver that emitted the message.
+    pub source: Address,
+    pub block_number: u64,
+    /// Wormhole chain ID the message is sent to.
+    pub recipient_chain: u16,
+    /// Name of the destination chain.
+    pub chain: String,
+    /// The destination transceiver the message is routed to.
+    pub destination: Address,
+    /// Digest the destination NTT manager identifies the message by.
+    pub digest: B256,
+    /// Seconds since the block containing the message.
+    pub age_secs: u64,
+    /// The encoded transceiver message.
+    #[serde(skip)]
+    pub encoded_message: Bytes,
+}
+
+/// Scans the source transceivers of `config` for messages emitted between `from_block` and
+/// `to_block` inclusive, returning those that have not been delivered to their destination.
+///
+/// Messages without a route, and messages of standard WormholeTransceivers, which do not carry
+/// their source chain, are skipped.
+pub async fn find_undelivered(
+    rpc: &EndpointPool,
+    config: &DaemonConfig,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<UndeliveredMessage>> {
+    let sources: Vec<Address> = config
+        .transceivers
+        .iter()
+        .map(|transceiver| transceiver.source)
+        .collect();
+    let messages = scan(rpc, &sources, from_block, to_block).await?;
+    log::info!(
+        "Found {} messages in blocks {from_block}..={to_block}",
+        messages.len()
+    );
+
+    let routing = RoutingTable::new(config);
+    let mut checks: HashMap<(String, Address), DeliveryCheck> = HashMap::new();
+    let mut timestamps: HashMap<u64, u64> = HashMap::new();
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+
+    let mut undelivered = Vec::new();
+    for message in messages {
+        let (manager, digest) = match TransceiverMessage::parse(&message.encoded_message)
+            .and_then(|parsed| Ok((parsed.source_chain()?, parsed)))
+        {
+            Ok((source_chain, parsed)) => (
+                from_wormhole_address(parsed.source_ntt_manager),
+                parsed.digest(source_chain),
+            ),
+            Err(e) => {
+                log::warn!("Skipping message {}: {e}", message.id);
+                continue;
+            }
+        };
+        let Some(destination) = routing.resolve(message.source, manager, message.recipient_chain)
+        else {
+            log::debug!(
+                "Skipping message {}: no route to chain {}",
+                message.id,
+                message.recipient_chain
+            );
+            continue;
+        };
+
+        let key = (destination.chain.clone(), destination.destination);
+        if !checks.contains_key(&key) {
+            let provider = ProviderBuilder::new()
+                .connect_http(config.chains[&destination.chain].rpc_url.clone())
+                .erased();
+            let check = DeliveryCheck::new(destination.destination, provider).await?;
+            checks.insert(key.clone(), check);
+        }
+        if checks[&key].delivered(digest).await? {
+            continue;
+        }
+
+        let emitted_at = match timestamps.get(&message.block_number) {
+            Some(&timestamp) => timestamp,
+            None => {
+                let timestamp = block_timestamp(rpc, message.block_number).await?;
+                timestamps.insert(message.block_number, timestamp);
+                timestamp
+            }
+        };
+        undelivered.push(UndeliveredMessage {
+            id: message.id,
+            source: message.source,
+            block_number: message.block_number,
+            recipient_chain: message.recipient_chain,
+            chain: destination.chain.clone(),
+            destination: destination.destination,
+            digest,
+            age_secs: now.saturating_sub(emitted_at),
+            encoded_message: message.encoded_message,
+        });
+    }
+    Ok(undelivered)
+}
+
+async fn block_timestamp(rpc: &EndpointPool, number: u64) -> Result<u64> {
+    rpc.run(|endpoint| async move {
+        let block = endpoint
+            .provider()
+            .get_block_by_number(BlockNumberOrTag::Number(number))
+            .await?
+            .with_context(|| format!("block {number} not found"))?;
+        Ok(block.header.timestamp)
+    })
+    .await
+}
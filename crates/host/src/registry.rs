@@ -0,0 +1,94 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Registry of the chains a relayer serves, resolving Wormhole chain IDs to everything needed to
+//! prove messages from a chain and deliver messages to it.
+
+use std::collections::HashMap;
+
+use alloy_primitives::Address;
+use reqwest::Url;
+use risc0_steel::ethereum::EthChainSpec;
+
+use crate::config::{CommitmentStrategy, DaemonConfig, DestinationConfig};
+use crate::register_chain_spec;
+
+/// A chain of the registry.
+#[derive(Clone, Debug)]
+pub struct RegisteredChain {
+    /// Name of the chain in the config.
+    pub name: String,
+    pub rpc_url: Url,
+    /// Chain spec proofs of messages from the chain are built with, if its EVM chain ID is known.
+    pub chain_spec: Option<&'static EthChainSpec>,
+    /// The BoundlessTransceiver on the chain, if any.
+    pub transceiver: Option<Address>,
+    /// How the commitment block of messages from the chain is chosen.
+    pub commitment: CommitmentStrategy,
+}
+
+impl RegisteredChain {
+    /// The destination delivering messages sent to this chain to its transceiver, if it has one.
+    pub fn destination(&self) -> Option<DestinationConfig> {
+        Some(DestinationConfig {
+            destination: self.transceiver?,
+            chain: self.name.clone(),
+            wormhole_transceiver: None,
+        })
+    }
+}
+
+/// The chains of a config that have a Wormhole chain ID, by that ID.
+#[derive(Clone, Debug, Default)]
+pub struct ChainRegistry {
+    chains: HashMap<u16, RegisteredChain>,
+}
+
+impl ChainRegistry {
+    pub fn new(config: &DaemonConfig) -> Self {
+        let chains = config
+            .chains
+            .iter()
+            .filter_map(|(name, profile)| {
+                let chain = RegisteredChain {
+                    name: name.clone(),
+                    rpc_url: profile.rpc_url.clone(),
+                    chain_spec: profile.chain_id.map(register_chain_spec),
+                    transceiver: profile.transceiver,
+                    commitment: profile.commitment,
+                };
+                Some((profile.wormhole_chain_id?, chain))
+            })
+            .collect();
+        Self { chains }
+    }
+
+    pub fn resolve(&self, wormhole_chain_id: u16) -> Option<&RegisteredChain> {
+        self.chains.get(&wormhole_chain_id)
+    }
+
+    /// The Wormhole chain ID of the chain named `name`, if registered.
+    pub fn chain_id(&self, name: &str) -> Option<u16> {
+        self.chains
+            .iter()
+            .find(|(_, chain)| chain.name == name)
+            .map(|(&chain_id, _)| chain_id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (u16, &RegisteredChain)> {
+        self.chains
+            .iter()
+            .map(|(&chain_id, chain)| (chain_id, chain))
+    }
+}
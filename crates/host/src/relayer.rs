@@ -0,0 +1,779 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The pipeline relaying a single message: proving it from the source chain and delivering the
+//! proof to its destination.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use alloy::network::Ethereum;
+use alloy::providers::{PendingTransactionBuilder, Provider};
+use alloy::rpc::types::TransactionReceipt;
+use alloy_primitives::{Address, TxHash};
+use anyhow::{Context, Result, ensure};
+use risc0_zkvm::Digest;
+use risc0_zkvm::sha::{Impl, Sha256};
+use tracing::log;
+use zkvm::GuestProgram;
+
+use crate::artifact::InputArtifact;
+use crate::audit::AuditBundle;
+use crate::breaker::{Anomaly, CircuitBreaker};
+use crate::budget::{ProvingBudget, Spend};
+use crate::compressor::Compressor;
+use crate::config::{OfferConfig, Pricing};
+use crate::destination::Destination;
+use crate::job::{Job, MessageId, MessageState};
+use crate::market::{MarketProver, MarketTimeout};
+use crate::metrics::{Metrics, ProofMetrics};
+use crate::proof::DeliveryProof;
+use crate::queue::{ProofQueue, prove_remotely};
+use crate::quote::{CostQuote, quote};
+use crate::rpc::EndpointPool;
+use crate::sla::SlaMonitor;
+use crate::snark::SnarkBackend;
+use crate::stale::StaleMonitor;
+use crate::store::{JobStore, TrackedJob};
+use crate::vaa::{GuardianClient, HybridMode, VaaRoute};
+use crate::{
+    InputOptions, SentMessage, compress, fetch_input, profile, prove_groth16, prove_succinct,
+    transaction_messages,
+};
+
+/// Where messages are proven.
+pub enum Prover {
+    /// With the backend producing Groth16 seals.
+    Backend(SnarkBackend),
+    /// By prover workers claiming the inputs from a shared queue, waiting at most the given time
+    /// for each proof.
+    Workers(Arc<dyn ProofQueue>, Duration),
+    /// By the Boundless market, delivering set-inclusion proofs of its aggregated proofs.
+    Market(MarketProver),
+}
+
+impl Prover {
+    /// Whether proofs are paid for, on the market or Bonsai, rather than made by this host or the
+    /// prover workers.
+    fn is_paid(&self) -> bool {
+        match self {
+            Self::Backend(backend) => *backend == SnarkBackend::Bonsai,
+            Self::Workers(..) => false,
+            Self::Market(_) => true,
+        }
+    }
+}
+
+/// Everything shared by the messages a relayer delivers.
+pub struct Relayer {
+    pub rpc: Arc<EndpointPool>,
+    pub beacon: Arc<EndpointPool>,
+    pub input: InputOptions,
+    pub prover: Prover,
+    /// Compresses receipts of the local backend as a separate task, if set. Otherwise each message
+    /// is compressed right after it is proven.
+    pub compressor: Option<Compressor>,
+    pub store: Option<Arc<dyn JobStore>>,
+    /// Number of failed attempts after which a message is moved to the dead-letter queue.
+    pub max_attempts: usize,
+    pub metrics: Arc<Metrics>,
+    pub sla: SlaMonitor,
+    /// Tracks messages until they are delivered, alerting on those that take too long.
+    pub stale: Arc<StaleMonitor>,
+    /// Halts submissions on anomalies until an operator resumes.
+    pub breaker: Arc<CircuitBreaker>,
+    /// Writes a pprof profile of the guest execution to this path before proving, if set.
+    pub pprof_out: Option<PathBuf>,
+    /// Also delivers the Wormhole VAA of messages with a [VaaRoute], if set.
+    pub guardian: Option<GuardianClient>,
+    /// Writes an [AuditBundle] of each delivered message to this directory, if set.
+    pub audit_dir: Option<PathBuf>,
+    /// Prices of the proving backends other than the market, for [Relayer::quote].
+    pub pricing: Pricing,
+    /// The offers made on the market, for [Relayer::quote].
+    pub offer: OfferConfig,
+    /// Caps the spend on proofs from the market and Bonsai, as quoted by [Relayer::quote].
+    pub budget: ProvingBudget,
+}
+
+/// The attestation that delivered a message first in [HybridMode::First].
+enum Delivery {
+    Proof(Result<DeliveryProof>),
+    Vaa(Result<TxHash>),
+}
+
+/// A proof produced by [Relayer::prove_measured].
+struct Proof {
+    proof: DeliveryProof,
+    metrics: Option<ProofMetrics>,
+    input_digest: Digest,
+}
+
+/// A message being relayed, handed from one step of its relay to the next.
+pub struct Relay {
+    pub job: TrackedJob,
+    /// When the commitment block was finalized, which is when the message became relayable.
+    finalized_at: SystemTime,
+    commitment_block: u64,
+    /// The submitted proof and the guest that produced it, kept for the audit bundle.
+    proof: Option<(DeliveryProof, &'static GuestProgram)>,
+}
+
+impl Relay {
+    pub fn id(&self) -> MessageId {
+        self.job.job.id
+    }
+}
+
+impl Relayer {
+    /// Relays the message `id` emitted by the source transceiver `source` to `destination`,
+    /// recording its progress in the store. The commitment block must already be final. Messages
+    /// that were already delivered or are dead-lettered are rejected.
+    ///
+    /// With a `vaa` route and a guardian client configured, the Wormhole VAA of the message is
+    /// delivered alongside the proof.
+    pub async fn relay(
+        &self,
+        id: MessageId,
+        source: Address,
+        destination: &Destination,
+        guest: &'static GuestProgram,
+        commitment_block: u64,
+        vaa: Option<VaaRoute>,
+    ) -> Result<()> {
+        let mut relay = self
+            .begin(id, source, commitment_block, SystemTime::now())
+            .await?;
+        let result = async {
+            let Some(proof) = self
+                .prove_message(&mut relay, destination, guest, vaa, None)
+                .await?
+            else {
+                return Ok(());
+            };
+            let proof = self.compress(&mut relay, proof).await?;
+            if let Some(pending_tx) = self.submit(&mut relay, destination, &proof, guest).await? {
+                self.confirm(&mut relay, destination, pending_tx).await?;
+            }
+            anyhow::Ok(())
+        }
+        .await;
+        match result {
+            Ok(()) => {
+                self.finish(relay).await;
+                Ok(())
+            }
+            Err(e) => {
+                self.fail(&mut relay.job, &e).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Starts relaying the message `id` emitted by `source`, committing to `commitment_block`,
+    /// which was seen final at `finalized_at`. Messages that were already delivered or are
+    /// dead-lettered are rejected.
+    pub async fn begin(
+        &self,
+        id: MessageId,
+        source: Address,
+        commitment_block: u64,
+        finalized_at: SystemTime,
+    ) -> Result<Relay> {
+        let mut job = Job::new(id, source);
+        if let Some(store) = &self.store {
+            if let Some(previous) = store.load(&id)? {
+                ensure!(
+                    previous.state != MessageState::Confirmed,
+                    "Message {id} has already been delivered"
+                );
+                ensure!(
+                    !previous.dead_lettered,
+                    "Message {id} is dead-lettered, replay it once the cause of its failures is fixed"
+                );
+                job.failures = previous.failures;
+                job.market_request = previous.market_request;
+                job.gas_used = previous.gas_used;
+                job.gas_cost_wei = previous.gas_cost_wei;
+            }
+        }
+        let mut job = TrackedJob::new(job, self.store.clone())?;
+
+        let result = async {
+            self.breaker.check()?;
+
+            // The proof only verifies on the destination once the commitment block is final, so
+            // the message became relayable when the block was seen final
+            self.stale.track(id, finalized_at);
+            job.job.commitment_block = Some(commitment_block);
+            job.advance(
+                MessageState::Finalized,
+                Some(format!("commitment block {commitment_block}")),
+            )
+        }
+        .await;
+        match result {
+            Ok(()) => Ok(Relay {
+                job,
+                finalized_at,
+                commitment_block,
+                proof: None,
+            }),
+            Err(e) => {
+                self.fail(&mut job, &e).await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Builds the input of `guest` for the message of `relay`, so it can be proven without
+    /// further RPC requests.
+    pub async fn preflight(&self, relay: &Relay, guest: &'static GuestProgram) -> Result<Vec<u8>> {
+        fetch_input(
+            relay.id(),
+            relay.job.job.contract_addr,
+            &self.rpc,
+            &self.beacon,
+            relay.commitment_block,
+            guest,
+            &self.input,
+        )
+        .await
+    }
+
+    /// Proves the message of `relay` from `input`, as built by [Relayer::preflight], building
+    /// the input first if there is none. With a `vaa` route and a guardian client configured, the
+    /// Wormhole VAA of the message is delivered alongside.
+    ///
+    /// Returns `None` if the VAA delivered the message first in [HybridMode::First], making the
+    /// proof unnecessary.
+    pub async fn prove_message(
+        &self,
+        relay: &mut Relay,
+        destination: &Destination,
+        guest: &'static GuestProgram,
+        vaa: Option<VaaRoute>,
+        input: Option<Vec<u8>>,
+    ) -> Result<Option<DeliveryProof>> {
+        let commitment_block = relay.commitment_block;
+        let job = &mut relay.job;
+        let (Some(guardian), Some(route)) = (&self.guardian, vaa) else {
+            return self
+                .prove_job(job, guest, commitment_block, input)
+                .await
+                .map(Some);
+        };
+
+        let id = job.job.id;
+        let source = job.job.contract_addr;
+        let vaa = guardian.deliver(&self.rpc, id.tx_hash, source, route, destination);
+        let proof = self.prove_job(job, guest, commitment_block, input);
+        match guardian.mode {
+            HybridMode::Both => {
+                let (proof, vaa) = tokio::join!(proof, vaa);
+                match vaa {
+                    Ok(tx_hash) => {
+                        log::info!("Message {id} VAA delivered in transaction {tx_hash}");
+                        job.job.vaa_tx_hash = Some(tx_hash);
+                        job.save()?;
+                    }
+                    Err(e) => log::warn!("Failed to deliver VAA of message {id}: {e:#}"),
+                }
+                proof.map(Some)
+            }
+            HybridMode::First => {
+                let mut proof = Box::pin(proof);
+                let mut vaa = Box::pin(vaa);
+                let first = tokio::select! {
+                    result = &mut proof => Delivery::Proof(result),
+                    result = &mut vaa => Delivery::Vaa(result),
+                };
+                match first {
+                    Delivery::Proof(result) => result.map(Some),
+                    Delivery::Vaa(Ok(tx_hash)) => {
+                        // Proving is no longer needed once the VAA delivered the message
+                        drop(proof);
+                        job.job.vaa_tx_hash = Some(tx_hash);
+                        job.advance(
+                            MessageState::Confirmed,
+                            Some(format!("VAA delivered in transaction {tx_hash}")),
+                        )?;
+                        Ok(None)
+                    }
+                    Delivery::Vaa(Err(e)) => {
+                        log::warn!("Failed to deliver VAA of message {id}: {e:#}");
+                        proof.await.map(Some)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Compresses `proof` if proving left it succinct for the [Compressor], recording the time
+    /// compression took with the job of `relay`. Other proofs are returned as they are.
+    pub async fn compress(&self, relay: &mut Relay, proof: DeliveryProof) -> Result<DeliveryProof> {
+        if !matches!(proof, DeliveryProof::Succinct(_)) {
+            return Ok(proof);
+        }
+        let proof = self
+            .compress_deferred(proof, relay.job.job.proof.as_mut())
+            .await?;
+        relay.job.save()?;
+        Ok(proof)
+    }
+
+    /// Compresses a succinct `proof` on the [Compressor], completing and recording its
+    /// `metrics`.
+    async fn compress_deferred(
+        &self,
+        proof: DeliveryProof,
+        metrics: Option<&mut ProofMetrics>,
+    ) -> Result<DeliveryProof> {
+        let DeliveryProof::Succinct(receipt) = proof else {
+            return Ok(proof);
+        };
+        let compressor = self
+            .compressor
+            .as_ref()
+            .context("succinct proofs require a compressor")?;
+        let (receipt, compression_time) = compressor.compress(receipt).await?;
+        log::info!("Compressed proof in {compression_time:?}");
+        if let Some(metrics) = metrics {
+            metrics.compression_time = Some(compression_time);
+            self.metrics.record_proof(metrics.clone());
+        }
+        Ok(receipt.into())
+    }
+
+    async fn prove_job(
+        &self,
+        job: &mut TrackedJob,
+        guest: &'static GuestProgram,
+        commitment_block: u64,
+        input: Option<Vec<u8>>,
+    ) -> Result<DeliveryProof> {
+        job.advance(MessageState::Proving, None)?;
+        let proof = self
+            .prove_measured(
+                job.job.id,
+                job.job.contract_addr,
+                guest,
+                commitment_block,
+                input,
+                Some(&mut *job),
+            )
+            .await?;
+        job.job.proof = proof.metrics;
+        job.job.input_digest = Some(proof.input_digest);
+        job.advance(MessageState::Proved, None)?;
+        Ok(proof.proof)
+    }
+
+    /// Sends the proof of the message of `relay` to `destination`, returning the pending
+    /// transaction, or `None` if the destination already had the message.
+    pub async fn submit(
+        &self,
+        relay: &mut Relay,
+        destination: &Destination,
+        proof: &DeliveryProof,
+        guest: &'static GuestProgram,
+    ) -> Result<Option<PendingTransactionBuilder<Ethereum>>> {
+        if self.audit_dir.is_some() {
+            relay.proof = Some((proof.clone(), guest));
+        }
+        destination
+            .send(proof, guest, &mut relay.job, &self.breaker)
+            .await
+    }
+
+    /// Waits for the transaction sent by [Relayer::submit] to confirm.
+    pub async fn confirm(
+        &self,
+        relay: &mut Relay,
+        destination: &Destination,
+        pending_tx: PendingTransactionBuilder<Ethereum>,
+    ) -> Result<()> {
+        let tx_receipt = destination
+            .confirm(pending_tx, &mut relay.job, &self.breaker)
+            .await?;
+        self.record_wallet_balance(destination).await;
+        if let Some(dir) = &self.audit_dir {
+            // The message was delivered regardless, so a missing bundle is only worth a warning
+            if let Err(e) = self
+                .write_audit_bundle(relay, destination, tx_receipt, dir)
+                .await
+            {
+                log::warn!(
+                    "Failed to write audit bundle of message {}: {e:#}",
+                    relay.id()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    async fn write_audit_bundle(
+        &self,
+        relay: &Relay,
+        destination: &Destination,
+        destination_receipt: TransactionReceipt,
+        dir: &Path,
+    ) -> Result<()> {
+        let job = &relay.job.job;
+        let (proof, guest) = relay.proof.as_ref().context("proof was not kept")?;
+        let receipt = proof
+            .receipt()
+            .cloned()
+            .context("audit bundles require a Groth16 receipt")?;
+        let input_digest = job.input_digest.context("input digest was not recorded")?;
+        let tx_hash = job.id.tx_hash;
+        let source_receipt = self
+            .rpc
+            .run(|endpoint| async move {
+                endpoint
+                    .provider()
+                    .get_transaction_receipt(tx_hash)
+                    .await?
+                    .context("source transaction receipt not found")
+            })
+            .await?;
+        let bundle = AuditBundle::new(
+            job.id,
+            job.contract_addr,
+            destination.address(),
+            guest.name,
+            Digest::from(guest.image_id),
+            input_digest,
+            receipt,
+            source_receipt,
+            destination_receipt,
+        )?;
+        let path = bundle.write(dir)?;
+        log::info!(
+            "Wrote audit bundle of message {} to {}",
+            job.id,
+            path.display()
+        );
+        Ok(())
+    }
+
+    /// Records the balance of the wallet paying for deliveries to `destination`.
+    pub async fn record_wallet_balance(&self, destination: &Destination) {
+        match destination.wallet_balance().await {
+            Ok(Some(balance)) => self.metrics.set_wallet_balance(balance),
+            Ok(None) => {}
+            Err(e) => log::warn!(
+                "Failed to fetch the wallet balance for {}: {e:#}",
+                destination.address()
+            ),
+        }
+    }
+
+    /// Records that the message of `relay` was delivered in `tx_hash` by someone else, such as
+    /// another relayer, making the rest of its relay unnecessary.
+    pub fn delivered_elsewhere(&self, relay: &mut Relay, tx_hash: TxHash) -> Result<()> {
+        let job = &mut relay.job;
+        log::info!(
+            "Message {} was delivered in transaction {tx_hash}",
+            job.job.id
+        );
+        if job.job.state.can_transition_to(MessageState::Confirmed) {
+            job.job.dest_tx_hash.get_or_insert(tx_hash);
+            job.advance(
+                MessageState::Confirmed,
+                Some(format!("delivered in transaction {tx_hash}")),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Completes the relay of a delivered message.
+    pub async fn finish(&self, relay: Relay) {
+        let id = relay.id();
+        let latency = self
+            .sla
+            .record_delivery(&id.to_string(), relay.finalized_at, SystemTime::now())
+            .await;
+        self.stale.delivered(&id);
+        log::info!("Message {id} delivered {latency:?} after finalization");
+    }
+
+    /// Records that relaying the message of `job` failed with `error`, tripping the circuit
+    /// breaker on anomalies and moving the message to the dead-letter queue once it has no
+    /// attempts left.
+    pub async fn fail(&self, job: &mut TrackedJob, error: &anyhow::Error) -> Result<()> {
+        if let Some(anomaly) = error.downcast_ref::<Anomaly>() {
+            self.breaker.trip(anomaly.clone()).await;
+        }
+        job.job.record_failure(format!("{error:#}"));
+        self.metrics.record_error(job.job.id, format!("{error:#}"));
+        if self.store.is_some() && job.job.failures.len() >= self.max_attempts {
+            log::error!(
+                "Message {} failed {} times, moving it to the dead-letter queue",
+                job.job.id,
+                job.job.failures.len()
+            );
+            job.job.dead_lettered = true;
+        }
+        job.advance(MessageState::Failed, Some(format!("{error:#}")))
+    }
+
+    /// Whether the message `id` is in the dead-letter queue.
+    pub fn is_dead_lettered(&self, id: &MessageId) -> Result<bool> {
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        Ok(store.load(id)?.is_some_and(|job| job.dead_lettered))
+    }
+
+    /// Takes the message `id` out of the dead-letter queue with a fresh set of attempts, so it
+    /// can be relayed again once the cause of its failures is fixed.
+    pub fn release(&self, id: &MessageId) -> Result<()> {
+        let store = self
+            .store
+            .as_ref()
+            .context("the dead-letter queue requires a job store")?;
+        let mut job = store
+            .load(id)?
+            .filter(|job| job.dead_lettered)
+            .with_context(|| format!("message {id} is not dead-lettered"))?;
+        log::info!(
+            "Releasing message {id} from the dead-letter queue after {} failures",
+            job.failures.len()
+        );
+        job.dead_lettered = false;
+        job.failures.clear();
+        store.save(&job)
+    }
+
+    /// Proves the message `id` emitted by `source`.
+    pub async fn prove(
+        &self,
+        id: MessageId,
+        source: Address,
+        guest: &'static GuestProgram,
+        commitment_block: u64,
+    ) -> Result<DeliveryProof> {
+        let mut proof = self
+            .prove_measured(id, source, guest, commitment_block, None, None)
+            .await?;
+        self.compress_deferred(proof.proof, proof.metrics.as_mut())
+            .await
+    }
+
+    /// Proves every message `source` sent in `tx_hash` on its own, returning each message, with
+    /// the index of its log, along with its proof.
+    pub async fn prove_transaction(
+        &self,
+        tx_hash: TxHash,
+        source: Address,
+        guest: &'static GuestProgram,
+        commitment_block: u64,
+    ) -> Result<Vec<(SentMessage, DeliveryProof)>> {
+        let messages = self
+            .rpc
+            .run(|endpoint| async move {
+                transaction_messages(&endpoint.provider(), tx_hash, source, guest.transceiver).await
+            })
+            .await?;
+        let mut proofs = Vec::with_capacity(messages.len());
+        for message in messages {
+            let proof = self
+                .prove(message.id, source, guest, commitment_block)
+                .await?;
+            proofs.push((message, proof));
+        }
+        Ok(proofs)
+    }
+
+    /// Quotes what proving the message `id` emitted by `source` costs on each backend, from a dry
+    /// run of `guest` on its input.
+    pub async fn quote(
+        &self,
+        id: MessageId,
+        source: Address,
+        guest: &'static GuestProgram,
+        commitment_block: u64,
+    ) -> Result<CostQuote> {
+        let env_input = fetch_input(
+            id,
+            source,
+            &self.rpc,
+            &self.beacon,
+            commitment_block,
+            guest,
+            &self.input,
+        )
+        .await?;
+        quote(env_input, guest, &self.pricing, &self.offer).await
+    }
+
+    /// Like [Relayer::prove], also returning the digest of the input and the measurements of the
+    /// proof if it was produced on this host. The input is built unless given in `input`. Proof
+    /// requests made on the market are recorded in `job`, if any.
+    async fn prove_measured(
+        &self,
+        id: MessageId,
+        source: Address,
+        guest: &'static GuestProgram,
+        commitment_block: u64,
+        input: Option<Vec<u8>>,
+        job: Option<&mut TrackedJob>,
+    ) -> Result<Proof> {
+        let env_input = match input {
+            Some(input) => input,
+            None => {
+                fetch_input(
+                    id,
+                    source,
+                    &self.rpc,
+                    &self.beacon,
+                    commitment_block,
+                    guest,
+                    &self.input,
+                )
+                .await?
+            }
+        };
+        let input_digest = *Impl::hash_bytes(&env_input);
+        if let Some(pprof_out) = &self.pprof_out {
+            profile(env_input.clone(), guest, pprof_out.clone()).await?;
+        }
+        // Only paid proofs are budgeted, which takes a dry run to quote them
+        let quote = match self.prover.is_paid() && self.budget.limits().is_limited() {
+            true => Some(quote(env_input.clone(), guest, &self.pricing, &self.offer).await?),
+            false => None,
+        };
+
+        let (proof, metrics) = match &self.prover {
+            Prover::Backend(backend) => {
+                self.prove_on(id, *backend, env_input, guest, quote.as_ref())
+                    .await?
+            }
+            Prover::Workers(queue, timeout) => {
+                let input = InputArtifact {
+                    id,
+                    source,
+                    commitment_block,
+                    guest: guest.name.to_string(),
+                    image_id: Digest::from(guest.image_id),
+                    input: env_input,
+                };
+                return Ok(Proof {
+                    proof: prove_remotely(queue.clone(), input, *timeout).await?.into(),
+                    metrics: None,
+                    input_digest,
+                });
+            }
+            Prover::Market(market) => {
+                if let Some(quote) = &quote {
+                    self.budget.reserve(id, Spend::market(quote)).await?;
+                }
+                let result = market
+                    .prove(guest, env_input.clone(), input_digest, job)
+                    .await;
+                let timeout = match result {
+                    Ok(proof) => {
+                        return Ok(Proof {
+                            proof,
+                            metrics: None,
+                            input_digest,
+                        });
+                    }
+                    Err(e) => e.downcast::<MarketTimeout>()?,
+                };
+                // An expired request is not paid for, while one that was not locked in time may
+                // still be fulfilled and stays reserved
+                if let (MarketTimeout::Expired { .. }, Some(quote)) = (&timeout, &quote) {
+                    self.budget.refund(id, Spend::market(quote))?;
+                }
+                if market.fallback().is_empty() {
+                    return Err(timeout.into());
+                }
+                log::warn!("{timeout}, falling back to {:?}", market.fallback());
+                let mut result = Err(timeout.into());
+                for &backend in market.fallback() {
+                    result = self
+                        .prove_on(id, backend, env_input.clone(), guest, quote.as_ref())
+                        .await;
+                    match &result {
+                        Ok(_) => break,
+                        Err(e) => log::warn!("Failed to prove on {backend:?}: {e:#}"),
+                    }
+                }
+                result?
+            }
+        };
+        // Proofs left to the compressor are recorded once compressed
+        if !matches!(proof, DeliveryProof::Succinct(_)) {
+            self.metrics.record_proof(metrics.clone());
+        }
+
+        Ok(Proof {
+            proof,
+            metrics: Some(metrics),
+            input_digest,
+        })
+    }
+
+    /// Proves `env_input` of the message `id` with `guest` on `backend`, returning a Groth16
+    /// receipt and the measurements of the proof. With a compressor, local proofs are returned
+    /// succinct instead, so the prover can move on while [Relayer::compress] waits for their
+    /// compression. Proofs from Bonsai are paid for out of the budget at the price of `quote`, if
+    /// quoted.
+    async fn prove_on(
+        &self,
+        id: MessageId,
+        backend: SnarkBackend,
+        env_input: Vec<u8>,
+        guest: &'static GuestProgram,
+        quote: Option<&CostQuote>,
+    ) -> Result<(DeliveryProof, ProofMetrics)> {
+        if let (SnarkBackend::Bonsai, Some(quote)) = (backend, quote) {
+            self.budget.reserve(id, Spend::bonsai(quote)).await?;
+        }
+        match backend {
+            SnarkBackend::Local => {
+                let (prove_info, mut metrics) = prove_succinct(env_input, guest).await?;
+                log::info!(
+                    "Proved {} cycles in {} segments using {} in {:?}",
+                    metrics.total_cycles,
+                    metrics.segments,
+                    metrics.backend,
+                    metrics.proving_time,
+                );
+
+                if self.compressor.is_some() {
+                    return Ok((DeliveryProof::Succinct(prove_info.receipt), metrics));
+                }
+                let (receipt, compression_time) = compress(prove_info.receipt).await?;
+                log::info!("Compressed proof in {compression_time:?}");
+                metrics.compression_time = Some(compression_time);
+                Ok((receipt.into(), metrics))
+            }
+            SnarkBackend::Bonsai => {
+                let (prove_info, metrics) = prove_groth16(env_input, guest).await?;
+                log::info!(
+                    "Proved {} cycles in {} segments using {} in {:?}",
+                    metrics.total_cycles,
+                    metrics.segments,
+                    metrics.backend,
+                    metrics.proving_time,
+                );
+                Ok((prove_info.receipt.into(), metrics))
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of the destination each observed message must be delivered to.
+
+use std::collections::HashMap;
+
+use alloy_primitives::Address;
+
+use crate::config::{DaemonConfig, DestinationConfig, RouteConfig};
+use crate::registry::ChainRegistry;
+
+pub struct RoutingTable {
+    /// Destinations by source NTT manager and recipient Wormhole chain.
+    routes: HashMap<(Address, u16), DestinationConfig>,
+    /// Default destinations by source transceiver.
+    defaults: HashMap<Address, DestinationConfig>,
+    /// Destinations of the chains in the registry that have a transceiver, by Wormhole chain ID.
+    registered: HashMap<u16, DestinationConfig>,
+    registry: ChainRegistry,
+}
+
+impl RoutingTable {
+    pub fn new(config: &DaemonConfig) -> Self {
+        let registry = ChainRegistry::new(config);
+        Self {
+            routes: config
+                .routes
+                .iter()
+                .map(|route| {
+                    (
+                        (route.manager, route.recipient_chain),
+                        route.destination.clone(),
+                    )
+                })
+                .collect(),
+            defaults: config
+                .transceivers
+                .iter()
+                .filter_map(|transceiver| {
+                    let destination = transceiver.destination.clone()?;
+                    Some((transceiver.source, destination))
+                })
+                .collect(),
+            registered: registry
+                .iter()
+                .filter_map(|(chain_id, chain)| Some((chain_id, chain.destination()?)))
+                .collect(),
+            registry,
+        }
+    }
+
+    /// Adds or replaces the route of the messages `route.manager` sends to
+    /// `route.recipient_chain`.
+    pub fn add_route(&mut self, route: &RouteConfig) {
+        self.routes.insert(
+            (route.manager, route.recipient_chain),
+            route.destination.clone(),
+        );
+    }
+
+    pub fn remove_route(
+        &mut self,
+        manager: Address,
+        recipient_chain: u16,
+    ) -> Option<DestinationConfig> {
+        self.routes.remove(&(manager, recipient_chain))
+    }
+
+    pub fn registry(&self) -> &ChainRegistry {
+        &self.registry
+    }
+
+    /// Returns where a message sent by `manager` to `recipient_chain` through the source
+    /// transceiver `source` must be delivered, if anywhere. Default destinations on a chain with a
+    /// different Wormhole chain ID than `recipient_chain` are not considered. Without a route or
+    /// default destination, messages go to the transceiver registered for `recipient_chain`.
+    pub fn resolve(
+        &self,
+        source: Address,
+        manager: Address,
+        recipient_chain: u16,
+    ) -> Option<&DestinationConfig> {
+        self.routes
+            .get(&(manager, recipient_chain))
+            .or_else(|| {
+                self.defaults.get(&source).filter(|destination| {
+                    self.registry
+                        .chain_id(&destination.chain)
+                        .is_none_or(|chain_id| chain_id == recipient_chain)
+                })
+            })
+            .or_else(|| self.registered.get(&recipient_chain))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+        [chains.arbitrum]
+        rpc_url = "http://localhost:8545"
+
+        [chains.base]
+        rpc_url = "http://localhost:8546"
+
+        [[transceivers]]
+        source = "0x1111111111111111111111111111111111111111"
+        destination = "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+        chain = "arbitrum"
+
+        [[transceivers]]
+        source = "0x2222222222222222222222222222222222222222"
+
+        [[routes]]
+        manager = "0x3333333333333333333333333333333333333333"
+        recipient_chain = 30
+        destination = "0xbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb"
+        chain = "base"
+    "#;
+
+    #[test]
+    fn routes_take_precedence_over_defaults() {
+        let config: DaemonConfig = toml::from_str(CONFIG).unwrap();
+        let table = RoutingTable::new(&config);
+        let with_default = Address::repeat_byte(0x11);
+        let without_default = Address::repeat_byte(0x22);
+        let manager = Address::repeat_byte(0x33);
+
+        let routed = table.resolve(without_default, manager, 30).unwrap();
+        assert_eq!(routed.chain, "base");
+        assert_eq!(table.resolve(with_default, manager, 30).unwrap(), routed);
+
+        let default = table.resolve(with_default, manager, 23).unwrap();
+        assert_eq!(default.chain, "arbitrum");
+        assert_eq!(default.destination, Address::repeat_byte(0xaa));
+
+        assert!(table.resolve(without_default, manager, 23).is_none());
+    }
+
+    #[test]
+    fn defaults_only_serve_their_chain() {
+        let config = CONFIG.replace(
+            "rpc_url = \"http://localhost:8545\"",
+            "rpc_url = \"http://localhost:8545\"\nwormhole_chain_id = 23",
+        );
+        let config: DaemonConfig = toml::from_str(&config).unwrap();
+        let table = RoutingTable::new(&config);
+        let source = Address::repeat_byte(0x11);
+        let manager = Address::repeat_byte(0x44);
+
+        assert_eq!(
+            table.resolve(source, manager, 23).unwrap().chain,
+            "arbitrum"
+        );
+        assert!(table.resolve(source, manager, 24).is_none());
+    }
+
+    #[test]
+    fn falls_back_to_registered_transceivers() {
+        let config = CONFIG.replace(
+            "rpc_url = \"http://localhost:8546\"",
+            "rpc_url = \"http://localhost:8546\"\nwormhole_chain_id = 30\ntransceiver = \"0xcccccccccccccccccccccccccccccccccccccccc\"",
+        );
+        let config: DaemonConfig = toml::from_str(&config).unwrap();
+        let table = RoutingTable::new(&config);
+        let without_default = Address::repeat_byte(0x22);
+        let manager = Address::repeat_byte(0x44);
+
+        let registered = table.resolve(without_default, manager, 30).unwrap();
+        assert_eq!(registered.chain, "base");
+        assert_eq!(registered.destination, Address::repeat_byte(0xcc));
+        assert!(table.resolve(without_default, manager, 23).is_none());
+    }
+}
@@ -0,0 +1,395 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Failover between redundant source chain endpoints.
+//!
+//! A single flaky RPC or beacon provider would otherwise halt relaying, so each is configured as
+//! a pool of interchangeable URLs. Requests rotate over the healthy endpoints and fail over to the
+//! next one on error, while a periodic health check takes failing endpoints out of rotation and
+//! puts recovered ones back.
+//!
+//! Requests time out rather than hang on a dead endpoint, so that failover kicks in. Steel's beacon
+//! API client only takes a URL, so beacon endpoints are served through a local proxy whose client
+//! applies the timeouts, along with any credentials of the endpoint.
+
+use std::future::Future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use alloy::providers::{Provider, RootProvider};
+use alloy::rpc::client::ClientBuilder;
+use alloy::transports::http::Http;
+use alloy::transports::utils::guess_local_url;
+use anyhow::{Context, Result, ensure};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use reqwest::{Client, Url};
+use tracing::log;
+
+use crate::auth::AuthConfig;
+use crate::metrics::Metrics;
+use crate::throttle::{RpcBudget, ThrottleLayer};
+
+/// The API served by the endpoints of a pool, which determines how their health is checked.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndpointKind {
+    /// Execution layer JSON-RPC, healthy if it returns the latest block number.
+    ExecutionRpc,
+    /// Consensus layer beacon API, healthy if its node health endpoint reports it synced.
+    BeaconApi,
+}
+
+impl EndpointKind {
+    /// Name of the kind in metrics labels.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::ExecutionRpc => "execution_rpc",
+            Self::BeaconApi => "beacon_api",
+        }
+    }
+}
+
+/// Settings of the HTTP connections to the endpoints of a pool.
+#[derive(Clone, Debug)]
+pub struct TransportOptions {
+    /// Time allowed for establishing a connection.
+    pub connect_timeout: Duration,
+    /// Time allowed for each read of a response before the request fails.
+    pub read_timeout: Duration,
+    /// Interval of TCP keep-alive probes, or `None` to not send any.
+    pub tcp_keepalive: Option<Duration>,
+    /// Time after which idle pooled connections are closed.
+    pub pool_idle_timeout: Duration,
+    /// Maximum number of idle connections kept open to each host.
+    pub pool_max_idle_per_host: usize,
+}
+
+impl Default for TransportOptions {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_secs(10),
+            read_timeout: Duration::from_secs(60),
+            tcp_keepalive: Some(Duration::from_secs(60)),
+            pool_idle_timeout: Duration::from_secs(90),
+            pool_max_idle_per_host: 16,
+        }
+    }
+}
+
+impl TransportOptions {
+    /// Builds a client with these settings, sending `headers` with every request.
+    fn client(&self, headers: HeaderMap) -> Result<Client> {
+        Ok(Client::builder()
+            .default_headers(headers)
+            .connect_timeout(self.connect_timeout)
+            .read_timeout(self.read_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .build()?)
+    }
+}
+
+/// An endpoint of a pool, along with the request budget of its provider.
+#[derive(Clone)]
+pub struct Endpoint {
+    /// The URL requests are sent to, which is a local proxy for authenticated beacon endpoints.
+    pub url: Url,
+    /// Host of the endpoint itself, identifying it in metrics.
+    host: String,
+    /// HTTP client of the provider, with the transport settings of the pool and any headers the
+    /// endpoint requires.
+    client: Client,
+    throttle: Option<ThrottleLayer>,
+}
+
+impl Endpoint {
+    fn new(url: Url) -> Self {
+        Self {
+            host: url.host_str().unwrap_or("-").to_string(),
+            url,
+            client: Client::new(),
+            throttle: None,
+        }
+    }
+
+    /// Connects a provider to the endpoint, throttled to its budget if any.
+    pub fn provider(&self) -> RootProvider {
+        let is_local = guess_local_url(&self.url);
+        let http = Http::with_client(self.client.clone(), self.url.clone());
+        match &self.throttle {
+            Some(throttle) => RootProvider::new(
+                ClientBuilder::default()
+                    .layer(throttle.clone())
+                    .transport(http, is_local),
+            ),
+            None => RootProvider::new(ClientBuilder::default().transport(http, is_local)),
+        }
+    }
+}
+
+pub struct EndpointPool {
+    kind: EndpointKind,
+    endpoints: Vec<Endpoint>,
+    healthy: Vec<AtomicBool>,
+    next: AtomicUsize,
+    client: Client,
+    metrics: Option<Arc<Metrics>>,
+}
+
+impl EndpointPool {
+    pub fn new(kind: EndpointKind, urls: Vec<Url>) -> Result<Self> {
+        ensure!(
+            !urls.is_empty(),
+            "at least one {kind:?} endpoint is required"
+        );
+        Ok(Self {
+            kind,
+            healthy: urls.iter().map(|_| AtomicBool::new(true)).collect(),
+            endpoints: urls.into_iter().map(Endpoint::new).collect(),
+            next: AtomicUsize::new(0),
+            client: Client::new(),
+            metrics: None,
+        })
+    }
+
+    /// Connects to the endpoints with the `transport` settings, sending the headers and
+    /// credentials `auth` configures for each endpoint along with its requests. Must be called
+    /// from within a Tokio runtime, which serves the proxies of beacon endpoints.
+    pub fn with_transport(
+        mut self,
+        transport: &TransportOptions,
+        auth: Option<&AuthConfig>,
+    ) -> Result<Self> {
+        for endpoint in &mut self.endpoints {
+            let headers = match auth {
+                Some(auth) => auth.headers(&endpoint.url)?,
+                None => HeaderMap::new(),
+            };
+            let client = transport.client(headers)?;
+            match self.kind {
+                EndpointKind::ExecutionRpc => endpoint.client = client,
+                EndpointKind::BeaconApi => {
+                    endpoint.url = serve_through(endpoint.url.clone(), client)?;
+                }
+            }
+        }
+        // Health checks of beacon endpoints go through their proxies, which apply the credentials
+        self.client = transport.client(HeaderMap::new())?;
+        Ok(self)
+    }
+
+    /// Records the outcome and latency of every request and health check in `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    fn record(&self, endpoint: &Endpoint, started: Instant, failed: bool) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_endpoint_request(
+                self.kind.label(),
+                &endpoint.host,
+                started.elapsed(),
+                failed,
+            );
+        }
+    }
+
+    /// Throttles the requests to each endpoint to `budget`, which is applied to every endpoint
+    /// separately as they are typically served by different providers.
+    pub fn with_budget(mut self, budget: RpcBudget) -> Self {
+        for endpoint in &mut self.endpoints {
+            endpoint.throttle = Some(ThrottleLayer::new(budget.clone()));
+        }
+        self
+    }
+
+    /// Returns the endpoints in the order they should be tried: healthy ones first, starting
+    /// from the next one in rotation, then the unhealthy ones as a last resort.
+    fn candidates(&self) -> Vec<usize> {
+        let len = self.endpoints.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed) % len;
+        let mut rotated: Vec<usize> = (start..len).chain(0..start).collect();
+        rotated.sort_by_key(|&i| !self.healthy[i].load(Ordering::Relaxed));
+        rotated
+    }
+
+    /// Runs `f` against each endpoint in turn until it succeeds, marking endpoints it fails
+    /// against as unhealthy. Returns the last error if it fails against all of them.
+    pub async fn run<T, F, Fut>(&self, mut f: F) -> Result<T>
+    where
+        F: FnMut(Endpoint) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let mut last_err = None;
+        for i in self.candidates() {
+            let endpoint = &self.endpoints[i];
+            let started = Instant::now();
+            let result = f(endpoint.clone()).await;
+            self.record(endpoint, started, result.is_err());
+            match result {
+                Ok(value) => {
+                    self.healthy[i].store(true, Ordering::Relaxed);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    log::warn!("{:?} endpoint {} failed: {e:#}", self.kind, endpoint.url);
+                    self.healthy[i].store(false, Ordering::Relaxed);
+                    last_err = Some(e);
+                }
+            }
+        }
+        Err(last_err.unwrap()).context(format!("all {:?} endpoints failed", self.kind))
+    }
+
+    /// Checks the health of every endpoint, updating which ones are in rotation.
+    pub async fn check_health(&self) {
+        for (endpoint, is_healthy) in self.endpoints.iter().zip(&self.healthy) {
+            let started = Instant::now();
+            let result = match self.kind {
+                EndpointKind::ExecutionRpc => self.check_execution(endpoint).await,
+                EndpointKind::BeaconApi => self.check_beacon(&endpoint.url).await,
+            };
+            self.record(endpoint, started, result.is_err());
+            let healthy = match result {
+                Ok(()) => true,
+                Err(e) => {
+                    log::warn!("{:?} endpoint {} unhealthy: {e:#}", self.kind, endpoint.url);
+                    false
+                }
+            };
+            if is_healthy.swap(healthy, Ordering::Relaxed) != healthy && healthy {
+                log::info!("{:?} endpoint {} recovered", self.kind, endpoint.url);
+            }
+        }
+    }
+
+    /// Checks every endpoint once without taking any out of rotation, returning the host of each
+    /// along with the outcome.
+    pub async fn probe(&self) -> Vec<(String, Result<()>)> {
+        let mut outcomes = Vec::with_capacity(self.endpoints.len());
+        for endpoint in &self.endpoints {
+            let result = match self.kind {
+                EndpointKind::ExecutionRpc => self.check_execution(endpoint).await,
+                EndpointKind::BeaconApi => self.check_beacon(&endpoint.url).await,
+            };
+            outcomes.push((endpoint.host.clone(), result));
+        }
+        outcomes
+    }
+
+    async fn check_execution(&self, endpoint: &Endpoint) -> Result<()> {
+        endpoint.provider().get_block_number().await?;
+        Ok(())
+    }
+
+    async fn check_beacon(&self, url: &Url) -> Result<()> {
+        // The node health endpoint responds 200 when synced and 206 while syncing
+        let response = self
+            .client
+            .get(url.join("eth/v1/node/health")?)
+            .send()
+            .await?
+            .error_for_status()?;
+        ensure!(
+            response.status() == reqwest::StatusCode::OK,
+            "node is syncing"
+        );
+        Ok(())
+    }
+
+    /// Spawns a task checking the health of the endpoints every `interval`.
+    pub fn spawn_health_checks(self: &Arc<Self>, interval: Duration) {
+        let pool = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                pool.check_health().await;
+            }
+        });
+    }
+}
+
+/// Serves `upstream` from a local proxy forwarding every request with `client`, returning the
+/// URL to use instead of `upstream`.
+fn serve_through(upstream: Url, client: Client) -> Result<Url> {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+    listener.set_nonblocking(true)?;
+    let addr = listener.local_addr()?;
+    let listener = tokio::net::TcpListener::from_std(listener)?;
+
+    let proxy = Arc::new(Proxy { upstream, client });
+    let router = Router::new().fallback(handle).with_state(proxy);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, router).await {
+            log::error!("Beacon API proxy failed: {e}");
+        }
+    });
+    Ok(format!("http://{addr}/").parse()?)
+}
+
+/// The proxy in front of a beacon API endpoint.
+struct Proxy {
+    upstream: Url,
+    client: Client,
+}
+
+impl Proxy {
+    async fn forward(
+        &self,
+        method: Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: Bytes,
+    ) -> Result<Response> {
+        let path = uri.path_and_query().map_or("/", |path| path.as_str());
+        let url: Url = match path {
+            "/" => self.upstream.clone(),
+            path => format!("{}{path}", self.upstream.as_str().trim_end_matches('/')).parse()?,
+        };
+        let mut request = self.client.request(method, url).body(body);
+        for name in [header::CONTENT_TYPE, header::ACCEPT] {
+            if let Some(value) = headers.get(&name) {
+                request = request.header(name, value);
+            }
+        }
+        let response = request.send().await?;
+        let mut forwarded = Response::builder().status(response.status());
+        if let Some(content_type) = response.headers().get(header::CONTENT_TYPE) {
+            forwarded = forwarded.header(header::CONTENT_TYPE, content_type);
+        }
+        Ok(forwarded.body(response.bytes().await?.into())?)
+    }
+}
+
+async fn handle(
+    State(proxy): State<Arc<Proxy>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    match proxy.forward(method, &uri, &headers, body).await {
+        Ok(response) => response,
+        Err(e) => (StatusCode::BAD_GATEWAY, format!("{e:#}")).into_response(),
+    }
+}
@@ -0,0 +1,159 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Admission of messages into the relay pipeline. When more messages are waiting than can be
+//! relayed at once, those with the highest priority are admitted first, ties in arrival order.
+
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::oneshot;
+
+struct Waiter {
+    priority: u64,
+    seq: u64,
+    wake: oneshot::Sender<()>,
+}
+
+impl Ord for Waiter {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority
+            .cmp(&other.priority)
+            .then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for Waiter {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Waiter {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Waiter {}
+
+struct State {
+    available: usize,
+    waiting: BinaryHeap<Waiter>,
+    next_seq: u64,
+}
+
+pub struct Scheduler {
+    state: Mutex<State>,
+}
+
+/// Admission of a message, handed to the next waiting message when dropped.
+pub struct Permit {
+    scheduler: Arc<Scheduler>,
+}
+
+impl Scheduler {
+    /// Creates a scheduler admitting at most `max_concurrent` messages at once.
+    pub fn new(max_concurrent: usize) -> Arc<Self> {
+        Arc::new(Self {
+            state: Mutex::new(State {
+                available: max_concurrent.max(1),
+                waiting: BinaryHeap::new(),
+                next_seq: 0,
+            }),
+        })
+    }
+
+    /// Waits until a message with `priority` may be relayed. Cancelling the wait after it was
+    /// granted loses the permit, so callers must not drop the future once polled.
+    pub async fn acquire(self: &Arc<Self>, priority: u64) -> Permit {
+        let woken = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 {
+                state.available -= 1;
+                return Permit {
+                    scheduler: self.clone(),
+                };
+            }
+            let (wake, woken) = oneshot::channel();
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            state.waiting.push(Waiter {
+                priority,
+                seq,
+                wake,
+            });
+            woken
+        };
+        // The permit of the releasing message is handed over rather than returned to the pool
+        woken.await.ok();
+        Permit {
+            scheduler: self.clone(),
+        }
+    }
+
+    /// Number of messages waiting to be admitted.
+    pub fn waiting(&self) -> usize {
+        self.state.lock().unwrap().waiting.len()
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        while let Some(waiter) = state.waiting.pop() {
+            if waiter.wake.send(()).is_ok() {
+                return;
+            }
+        }
+        state.available += 1;
+    }
+}
+
+impl Drop for Permit {
+    fn drop(&mut self) {
+        self.scheduler.release();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn admits_highest_priority_first() {
+        let scheduler = Scheduler::new(1);
+        let held = scheduler.acquire(0).await;
+
+        let admitted = Arc::new(Mutex::new(Vec::new()));
+        let mut tasks = Vec::new();
+        for priority in [1, 5, 3, 5] {
+            let scheduler = scheduler.clone();
+            let admitted = admitted.clone();
+            tasks.push(tokio::spawn(async move {
+                let _permit = scheduler.acquire(priority).await;
+                admitted.lock().unwrap().push(priority);
+            }));
+            // Let the task register before the next one, so ties are ordered
+            while scheduler.waiting() < tasks.len() {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        drop(held);
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(*admitted.lock().unwrap(), vec![5, 5, 3, 1]);
+    }
+}
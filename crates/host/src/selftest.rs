@@ -0,0 +1,197 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Self-test of the relay before it processes real messages. The guest is executed without
+//! proving, as in dev mode, on the input of a synthetic message in a synthetic block, and the
+//! resulting mock proof is delivered to a simulated destination, so a broken guest build,
+//! toolchain or chain spec fails at startup rather than on the first message.
+
+use std::time::{Duration, Instant};
+
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, address};
+use alloy_sol_types::SolCall;
+use anyhow::{Context, Result, ensure};
+use common::ntt::TransceiverMessage;
+use common::{GuestInput, IBoundlessTransceiver, Journal, to_wormhole_address};
+use risc0_zkvm::sha::{Digestible, Impl, Sha256};
+use risc0_zkvm::{Digest, FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+use serde::Serialize;
+use synthetic::SyntheticBlock;
+use tracing::log;
+use zkvm::{GuestMode, GuestProgram, Transceiver};
+
+use crate::config::DaemonConfig;
+use crate::proof::DeliveryProof;
+use crate::vectors::{SyntheticMessage, TestVector};
+use crate::{chain_spec, execute, frame};
+
+/// Transceiver emitting the synthetic message.
+const EMITTER: Address = address!("0x00000000000000000000000000000000000000e1");
+
+/// Chain the synthetic block is on for guests taking the chain from their input.
+const DEFAULT_CHAIN_ID: u64 = 1;
+
+/// Outcome of the self-test of a guest.
+#[derive(Clone, Debug, Serialize)]
+pub struct SelfTest {
+    pub guest: String,
+    /// Chain ID of the synthetic block.
+    pub chain_id: u64,
+    pub cycles: u64,
+    /// SHA-256 digest of the journal the guest committed.
+    pub journal_digest: B256,
+    pub elapsed: Duration,
+}
+
+/// Whether the self-test can check `guest`, which takes a guest committing a [Journal] for an
+/// input claiming a single message of a BoundlessTransceiver.
+pub fn supports(guest: &GuestProgram) -> bool {
+    guest.mode == GuestMode::Message && guest.transceiver == Transceiver::Boundless
+}
+
+/// Self-tests the guests a daemon running `config` proves with: those set for its transceivers,
+/// and the default guest for transceivers selecting theirs by the image ID of the destination.
+/// Guests the self-test cannot check are skipped.
+pub async fn run_all(config: &DaemonConfig) -> Result<Vec<SelfTest>> {
+    let mut guests: Vec<&'static GuestProgram> = Vec::new();
+    for transceiver in &config.transceivers {
+        let guest = match &transceiver.guest {
+            Some(name) => GuestProgram::by_name(name)
+                .with_context(|| format!("Unknown guest program: {name}"))?,
+            None => &zkvm::NTT_MESSAGE_INCLUSION,
+        };
+        if !guests.iter().any(|other| other.name == guest.name) {
+            guests.push(guest);
+        }
+    }
+
+    let mut outcomes = Vec::with_capacity(guests.len());
+    for guest in guests {
+        if !supports(guest) {
+            log::warn!("Guest {} has no self-test, skipping it", guest.name);
+            continue;
+        }
+        let outcome = run(guest)
+            .await
+            .with_context(|| format!("self-test of guest {} failed", guest.name))?;
+        log::info!(
+            "Self-test of guest {} passed in {:?}, {} cycles",
+            guest.name,
+            outcome.elapsed,
+            outcome.cycles
+        );
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+/// Executes `guest` on a synthetic message and delivers the mock proof of its journal to a
+/// simulated destination.
+pub async fn run(guest: &'static GuestProgram) -> Result<SelfTest> {
+    ensure!(supports(guest), "guest {} has no self-test", guest.name);
+    let started = Instant::now();
+    let chain_id = guest.chain_id.unwrap_or(DEFAULT_CHAIN_ID);
+    let image_id = Digest::from(guest.image_id);
+    let emitter = to_wormhole_address(EMITTER);
+    let message = SyntheticMessage {
+        emitter,
+        ..Default::default()
+    };
+    let encoded_message = TestVector::new(&message, image_id, FixedBytes::ZERO)?.encoded_message;
+
+    let evm_input = SyntheticBlock::new()
+        .chain_spec(chain_spec(chain_id)?)
+        .message(EMITTER, message.recipient_chain, encoded_message.clone())
+        .preflight_messages(EMITTER)
+        .await
+        .context("failed to build the input of the synthetic message")?;
+    let input =
+        GuestInput::single(evm_input, encoded_message.clone(), emitter).with_chain_id(chain_id);
+    let env_input = frame(&input.serialize().map_err(anyhow::Error::msg)?);
+    let session = execute(env_input, guest).await?;
+
+    let journal_bytes = session.journal.bytes.clone();
+    let journal = Journal::decode_any(&journal_bytes)
+        .map_err(anyhow::Error::msg)
+        .context("the guest committed an invalid journal")?;
+    ensure!(
+        journal.encodedMessage == encoded_message && journal.emitterContract == emitter,
+        "the guest committed another message than the synthetic one"
+    );
+
+    let claim = ReceiptClaim::ok(image_id, journal_bytes.clone());
+    let receipt = Receipt::new(
+        InnerReceipt::Fake(FakeReceipt::new(claim)),
+        journal_bytes.clone(),
+    );
+    simulate_delivery(&receipt.into(), image_id)?;
+
+    Ok(SelfTest {
+        guest: guest.name.to_string(),
+        chain_id,
+        cycles: session
+            .segments
+            .iter()
+            .map(|segment| u64::from(segment.cycles))
+            .sum(),
+        journal_digest: B256::from_slice(Impl::hash_bytes(&journal_bytes).as_bytes()),
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Delivers `proof` to a simulated destination, which decodes the `receiveMessage` calldata, the
+/// journal and the message as the contract does, and verifies the seal as a mock verifier of the
+/// guest with `image_id` does.
+fn simulate_delivery(proof: &DeliveryProof, image_id: Digest) -> Result<()> {
+    let calldata = IBoundlessTransceiver::receiveMessageCall {
+        journalData: Bytes::copy_from_slice(proof.journal()),
+        seal: proof.seal()?.into(),
+    }
+    .abi_encode();
+
+    let call = IBoundlessTransceiver::receiveMessageCall::abi_decode(&calldata)
+        .context("the destination cannot decode the calldata")?;
+    let journal = Journal::decode_any(&call.journalData)
+        .map_err(anyhow::Error::msg)
+        .context("the destination cannot decode the journal")?;
+    TransceiverMessage::parse(&journal.encodedMessage)
+        .and_then(|message| message.source_chain())
+        .map_err(anyhow::Error::msg)
+        .context("the destination cannot decode the message")?;
+    let claim_digest = ReceiptClaim::ok(image_id, call.journalData.to_vec()).digest();
+    ensure!(
+        call.seal.get(4..) == Some(claim_digest.as_bytes()),
+        "the seal does not verify against image ID {image_id}"
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn simulates_delivery_of_mock_proofs() {
+        let image_id = Digest::from([7u32; 8]);
+        let vector =
+            TestVector::new(&SyntheticMessage::default(), image_id, FixedBytes::ZERO).unwrap();
+        let journal = vector.journal.to_vec();
+        let claim = ReceiptClaim::ok(image_id, journal.clone());
+        let proof: DeliveryProof =
+            Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal).into();
+
+        simulate_delivery(&proof, image_id).unwrap();
+        assert!(simulate_delivery(&proof, Digest::from([8u32; 8])).is_err());
+    }
+}
@@ -0,0 +1,620 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Size breakdown of guest inputs, to tell why some blocks are much more expensive to prove than
+//! others. The input is re-encoded with bincode, as for the guest, and every byte of the encoding
+//! is attributed to the field it encodes. The fields of the Steel input are private, so they are
+//! recognized by their serialized names.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{Context, Result, ensure};
+use common::GuestInput;
+use serde::Serialize;
+use serde::ser;
+
+/// What the bytes of an input hold.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// The header of the committed block.
+    Headers,
+    /// The headers linking the committed block to the block the commitment is verified against.
+    Ancestors,
+    Receipts,
+    /// Nodes of the account state trie.
+    State,
+    /// Nodes of the storage tries of the accounts queried.
+    Storage,
+    /// Code of the contracts called.
+    Contracts,
+    /// The proof of the commitment, e.g. the beacon block root of the committed block.
+    Commitment,
+    /// The claimed messages.
+    Messages,
+    Other,
+}
+
+impl Category {
+    /// The category of a field named `name`, if its name tells.
+    fn of(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let category = match name.as_str() {
+            "commit" => Self::Commitment,
+            "claims" => Self::Messages,
+            _ if name.contains("ancestor") => Self::Ancestors,
+            _ if name.contains("receipt") => Self::Receipts,
+            _ if name.contains("header") => Self::Headers,
+            _ if name.contains("storage") => Self::Storage,
+            _ if name.contains("state") => Self::State,
+            _ if name.contains("contract") => Self::Contracts,
+            _ => return None,
+        };
+        Some(category)
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizeReport {
+    pub total_bytes: u64,
+    /// Bytes by what they hold, largest first.
+    pub categories: Vec<CategorySize>,
+    /// Bytes by field, largest first. Each field only counts the bytes that no deeper field listed
+    /// counts.
+    pub fields: Vec<FieldSize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CategorySize {
+    pub category: Category,
+    pub bytes: u64,
+    pub percent: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FieldSize {
+    /// Names of the field and the fields and enum variants it is nested in, dot separated.
+    pub path: String,
+    pub bytes: u64,
+    /// Number of elements of the sequences and maps the field holds, such as trie nodes.
+    pub entries: u64,
+}
+
+impl SizeReport {
+    /// Breaks down the size of a framed guest input of a message guest, listing fields nested at
+    /// most `depth` levels deep.
+    pub fn of_input(framed: &[u8], depth: usize) -> Result<Self> {
+        let (len, input) = framed
+            .split_first_chunk::<8>()
+            .context("input is shorter than its frame")?;
+        ensure!(
+            u64::from_le_bytes(*len) == input.len() as u64,
+            "input does not match the length of its frame"
+        );
+        let input = GuestInput::deserialize(input)
+            .map_err(anyhow::Error::msg)
+            .context("not the input of a message guest")?;
+        Self::of(&input, depth)
+    }
+
+    /// Breaks down the size of the bincode encoding of `value`, listing fields nested at most
+    /// `depth` levels deep.
+    pub fn of<T: Serialize>(value: &T, depth: usize) -> Result<Self> {
+        let mut counter = Counter::new(depth);
+        value.serialize(&mut counter)?;
+        Ok(counter.report())
+    }
+}
+
+#[derive(Debug)]
+struct SizeError(String);
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+impl ser::Error for SizeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self(msg.to_string())
+    }
+}
+
+/// A field being serialized.
+#[derive(Clone, Copy)]
+struct Frame {
+    /// The category of the field or of the first field it is nested in that has one.
+    category: Option<Category>,
+    /// Index of the listed field its bytes are counted towards.
+    field: usize,
+    level: usize,
+}
+
+/// A serializer counting the bytes bincode encodes a value to, by field.
+struct Counter {
+    depth: usize,
+    /// The fields being serialized, innermost last.
+    stack: Vec<Frame>,
+    categories: BTreeMap<Category, u64>,
+    fields: Vec<FieldSize>,
+}
+
+impl Counter {
+    fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            stack: vec![Frame {
+                category: None,
+                field: 0,
+                level: 0,
+            }],
+            categories: BTreeMap::new(),
+            fields: vec![FieldSize {
+                path: String::new(),
+                bytes: 0,
+                entries: 0,
+            }],
+        }
+    }
+
+    fn frame(&self) -> Frame {
+        *self.stack.last().expect("the root frame is never left")
+    }
+
+    fn enter(&mut self, name: &'static str) {
+        let parent = self.frame();
+        let level = parent.level + 1;
+        let field = if level > self.depth {
+            parent.field
+        } else {
+            let path = match parent.level {
+                0 => name.to_string(),
+                _ => format!("{}.{name}", self.fields[parent.field].path),
+            };
+            match self.fields.iter().position(|field| field.path == path) {
+                Some(index) => index,
+                None => {
+                    self.fields.push(FieldSize {
+                        path,
+                        bytes: 0,
+                        entries: 0,
+                    });
+                    self.fields.len() - 1
+                }
+            }
+        };
+        self.stack.push(Frame {
+            category: parent.category.or_else(|| Category::of(name)),
+            field,
+            level,
+        });
+    }
+
+    fn leave(&mut self) {
+        self.stack.pop();
+    }
+
+    fn add(&mut self, bytes: usize) -> Result<(), SizeError> {
+        let frame = self.frame();
+        *self
+            .categories
+            .entry(frame.category.unwrap_or(Category::Other))
+            .or_default() += bytes as u64;
+        self.fields[frame.field].bytes += bytes as u64;
+        Ok(())
+    }
+
+    /// Counts the length prefix of a sequence or map of `len` elements.
+    fn add_len(&mut self, len: Option<usize>) -> Result<(), SizeError> {
+        let len = len.ok_or_else(|| SizeError("sequence of unknown length".to_string()))?;
+        let frame = self.frame();
+        // Elements of deeper sequences are not entries of the listed field itself
+        if frame.level <= self.depth {
+            self.fields[frame.field].entries += len as u64;
+        }
+        self.add(8)
+    }
+
+    fn report(self) -> SizeReport {
+        let total_bytes = self.categories.values().sum();
+        let mut categories: Vec<_> = self
+            .categories
+            .into_iter()
+            .map(|(category, bytes)| CategorySize {
+                category,
+                bytes,
+                percent: (bytes as f64 * 1000.0 / total_bytes as f64).round() / 10.0,
+            })
+            .collect();
+        categories.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        let mut fields: Vec<_> = self
+            .fields
+            .into_iter()
+            .filter(|field| field.bytes > 0)
+            .collect();
+        fields.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+        SizeReport {
+            total_bytes,
+            categories,
+            fields,
+        }
+    }
+}
+
+// Sizes follow the default encoding of bincode 1: fixed size integers, u64 lengths and u32 enum
+// variant indices
+impl ser::Serializer for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, _: bool) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_i8(self, _: i8) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_i16(self, _: i16) -> Result<(), SizeError> {
+        self.add(2)
+    }
+
+    fn serialize_i32(self, _: i32) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_i64(self, _: i64) -> Result<(), SizeError> {
+        self.add(8)
+    }
+
+    fn serialize_i128(self, _: i128) -> Result<(), SizeError> {
+        self.add(16)
+    }
+
+    fn serialize_u8(self, _: u8) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_u16(self, _: u16) -> Result<(), SizeError> {
+        self.add(2)
+    }
+
+    fn serialize_u32(self, _: u32) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_u64(self, _: u64) -> Result<(), SizeError> {
+        self.add(8)
+    }
+
+    fn serialize_u128(self, _: u128) -> Result<(), SizeError> {
+        self.add(16)
+    }
+
+    fn serialize_f32(self, _: f32) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_f64(self, _: f64) -> Result<(), SizeError> {
+        self.add(8)
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), SizeError> {
+        self.add(v.len_utf8())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), SizeError> {
+        self.add(8 + v.len())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), SizeError> {
+        self.add(8 + v.len())
+    }
+
+    fn serialize_none(self) -> Result<(), SizeError> {
+        self.add(1)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), SizeError> {
+        self.add(1)?;
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), SizeError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+    ) -> Result<(), SizeError> {
+        self.add(4)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        self.add(4)?;
+        self.enter(variant);
+        value.serialize(&mut *self)?;
+        self.leave();
+        Ok(())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, SizeError> {
+        self.add_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _: &'static str, _: usize) -> Result<Self, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self, SizeError> {
+        self.add(4)?;
+        self.enter(variant);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, SizeError> {
+        self.add_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, SizeError> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self, SizeError> {
+        self.add(4)?;
+        self.enter(variant);
+        Ok(self)
+    }
+}
+
+impl ser::SerializeSeq for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleStruct for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTupleVariant for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        self.leave();
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), SizeError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), SizeError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        self.enter(name);
+        value.serialize(&mut **self)?;
+        self.leave();
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for &mut Counter {
+    type Ok = ();
+    type Error = SizeError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), SizeError> {
+        self.enter(name);
+        value.serialize(&mut **self)?;
+        self.leave();
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), SizeError> {
+        self.leave();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Block {
+        header: Vec<u8>,
+        state_trie: Vec<Vec<u8>>,
+        ancestors: Vec<[u64; 2]>,
+        receipts: Option<Vec<String>>,
+    }
+
+    #[derive(Serialize)]
+    enum Commitment {
+        Beacon { input: Block, commit: (u32, char) },
+    }
+
+    #[derive(Serialize)]
+    struct Input {
+        commitment: Commitment,
+        claims: Vec<String>,
+        chain_id: Option<u64>,
+    }
+
+    #[test]
+    fn counts_encoded_bytes_by_field() {
+        let input = Input {
+            commitment: Commitment::Beacon {
+                input: Block {
+                    header: vec![0; 10],
+                    state_trie: vec![vec![1; 32], vec![2; 64]],
+                    ancestors: vec![[3, 4]],
+                    receipts: Some(vec!["receipt".to_string()]),
+                },
+                commit: (5, 'é'),
+            },
+            claims: vec!["message".to_string()],
+            chain_id: None,
+        };
+        let report = SizeReport::of(&input, 4).unwrap();
+        assert_eq!(
+            report.total_bytes,
+            bincode::serialize(&input).unwrap().len() as u64
+        );
+        let bytes = |category| {
+            report
+                .categories
+                .iter()
+                .find(|size| size.category == category)
+                .map(|size| size.bytes)
+        };
+        assert_eq!(bytes(Category::Headers), Some(8 + 10));
+        assert_eq!(bytes(Category::State), Some(8 + 8 + 32 + 8 + 64));
+        assert_eq!(bytes(Category::Ancestors), Some(8 + 16));
+        assert_eq!(bytes(Category::Receipts), Some(1 + 8 + 8 + 7));
+        assert_eq!(bytes(Category::Commitment), Some(4 + 2));
+        assert_eq!(bytes(Category::Messages), Some(8 + 8 + 7));
+
+        let field = |report: &SizeReport, path: &str| {
+            let field = report.fields.iter().find(|field| field.path == path);
+            field.map(|field| (field.bytes, field.entries))
+        };
+        assert_eq!(
+            field(&report, "commitment.Beacon.input.state_trie"),
+            Some((8 + 8 + 32 + 8 + 64, 2))
+        );
+        // Deeper fields count towards the deepest listed field they are nested in
+        let report = SizeReport::of(&input, 3).unwrap();
+        assert_eq!(field(&report, "commitment.Beacon.input.state_trie"), None);
+        assert_eq!(
+            field(&report, "commitment.Beacon.input"),
+            Some((18 + 120 + 24 + 24, 0))
+        );
+    }
+}
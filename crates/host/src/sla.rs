@@ -0,0 +1,65 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Tracking of the end-to-end delivery latency of messages against a configured SLA.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use crate::alert::{Alert, Alerter};
+use crate::metrics::Metrics;
+
+pub struct SlaMonitor {
+    sla: Duration,
+    metrics: Arc<Metrics>,
+    alerter: Alerter,
+}
+
+impl SlaMonitor {
+    pub fn new(sla: Duration, metrics: Arc<Metrics>, alerter: Alerter) -> Self {
+        Self {
+            sla,
+            metrics,
+            alerter,
+        }
+    }
+
+    /// Records the delivery of `message`, whose source event finalized at `finalized_at` and
+    /// which was confirmed on the destination at `confirmed_at`, alerting if it exceeded the SLA.
+    pub async fn record_delivery(
+        &self,
+        message: &str,
+        finalized_at: SystemTime,
+        confirmed_at: SystemTime,
+    ) -> Duration {
+        let latency = confirmed_at
+            .duration_since(finalized_at)
+            .unwrap_or_default();
+        let breached = latency > self.sla;
+        self.metrics.record_latency(latency, breached);
+
+        if breached {
+            self.alerter
+                .fire(Alert {
+                    kind: "sla_breach",
+                    message: format!(
+                        "Message {message} took {latency:?} from finalization to confirmation, exceeding the SLA of {:?}",
+                        self.sla
+                    ),
+                })
+                .await;
+        }
+        latency
+    }
+}
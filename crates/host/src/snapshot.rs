@@ -0,0 +1,176 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Guest inputs of historical source chain blocks with real messages, checked in together with the
+//! journal the guest committed for them. Re-executing them catches upgrades of Steel or of the
+//! chain specs that change what the guest accepts or commits.
+//!
+//! A snapshot named `name` is stored as `<name>.json`, holding where the input came from and the
+//! expected journal, and `<name>.input`, holding the framed guest input.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use alloy_primitives::{Address, Bytes, TxHash};
+use anyhow::{Context, Result, bail};
+use risc0_zkvm::{ExecutorEnv, default_executor};
+use serde::{Deserialize, Serialize};
+use zkvm::GuestProgram;
+
+use crate::error::HostError;
+
+/// The directory of the snapshots checked in with this crate.
+pub const SNAPSHOT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots");
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Transaction on the source chain that sent the message.
+    pub tx_hash: TxHash,
+    /// The source transceiver contract that emitted the message.
+    pub source: Address,
+    pub commitment_block: u64,
+    /// Name of the embedded guest program the input is for.
+    pub guest: String,
+    /// The journal the guest committed when the snapshot was captured.
+    pub journal: Bytes,
+}
+
+impl Snapshot {
+    /// Executes `guest` on `input` and writes the snapshot of it as `name` into `dir`.
+    pub fn capture(
+        dir: &Path,
+        name: &str,
+        tx_hash: TxHash,
+        source: Address,
+        commitment_block: u64,
+        guest: &GuestProgram,
+        input: &[u8],
+    ) -> Result<Self> {
+        let snapshot = Self {
+            tx_hash,
+            source,
+            commitment_block,
+            guest: guest.name.to_string(),
+            journal: execute(input, guest)?.into(),
+        };
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let (json, input_path) = paths(dir, name);
+        fs::write(&input_path, input)
+            .with_context(|| format!("failed to write {}", input_path.display()))?;
+        fs::write(&json, serde_json::to_string_pretty(&snapshot)? + "\n")
+            .with_context(|| format!("failed to write {}", json.display()))?;
+        Ok(snapshot)
+    }
+
+    /// Re-executes the input of the snapshot named `name` in `dir`, failing if the guest rejects
+    /// it or commits a different journal than when it was captured.
+    pub fn check(dir: &Path, name: &str) -> Result<()> {
+        let (snapshot, input) = Self::load(dir, name)?;
+        let guest = snapshot.guest()?;
+
+        let journal = execute(&input, guest)?;
+        if journal != snapshot.journal {
+            bail!(
+                "Guest {} committed {} instead of {}",
+                guest.name,
+                Bytes::from(journal),
+                snapshot.journal
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the snapshot named `name` in `dir` along with its framed guest input.
+    pub fn load(dir: &Path, name: &str) -> Result<(Self, Vec<u8>)> {
+        let (json, input_path) = paths(dir, name);
+        let snapshot: Self = serde_json::from_slice(
+            &fs::read(&json).with_context(|| format!("failed to read {}", json.display()))?,
+        )
+        .with_context(|| format!("invalid snapshot {}", json.display()))?;
+        let input = fs::read(&input_path)
+            .with_context(|| format!("failed to read {}", input_path.display()))?;
+        Ok((snapshot, input))
+    }
+
+    /// The embedded guest program the input is for.
+    pub fn guest(&self) -> Result<&'static GuestProgram> {
+        GuestProgram::by_name(&self.guest)
+            .with_context(|| format!("Unknown guest program: {}", self.guest))
+    }
+}
+
+/// The names of the snapshots in `dir`, sorted.
+pub fn names(dir: &Path) -> Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(dir).with_context(|| format!("failed to read {}", dir.display()))? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            names.extend(
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().into_owned()),
+            );
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+/// Checks every snapshot in `dir`, returning how many there are or an error naming those that
+/// failed.
+pub fn check_all(dir: &Path) -> Result<usize> {
+    let names = names(dir)?;
+    let failures: Vec<String> = names
+        .iter()
+        .filter_map(|name| {
+            Snapshot::check(dir, name)
+                .err()
+                .map(|e| format!("{name}: {e:#}"))
+        })
+        .collect();
+    if !failures.is_empty() {
+        bail!(
+            "{} snapshots failed:\n{}",
+            failures.len(),
+            failures.join("\n")
+        );
+    }
+    Ok(names.len())
+}
+
+fn paths(dir: &Path, name: &str) -> (PathBuf, PathBuf) {
+    (
+        dir.join(format!("{name}.json")),
+        dir.join(format!("{name}.input")),
+    )
+}
+
+fn execute(input: &[u8], guest: &GuestProgram) -> Result<Vec<u8>> {
+    let env = ExecutorEnv::builder().write_slice(input).build()?;
+    let session = default_executor()
+        .execute(env, guest.elf)
+        .map_err(HostError::classify)
+        .with_context(|| format!("guest {} rejected the input", guest.name))?;
+    Ok(session.journal.bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshots_match() {
+        let count = check_all(Path::new(SNAPSHOT_DIR)).unwrap();
+        assert!(count > 0, "no snapshots in {SNAPSHOT_DIR}");
+    }
+}
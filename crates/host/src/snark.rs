@@ -0,0 +1,119 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Selection of where Groth16 seals are produced.
+//!
+//! Compressing a receipt into a Groth16 SNARK locally needs an x86_64 host with Docker, which is
+//! otherwise only discovered once the first proof reaches compression. Checking this up front
+//! lets the relay fail with an actionable error or prove remotely instead.
+
+use std::env;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use anyhow::{Result, anyhow, bail, ensure};
+use risc0_zkvm::ProverOpts;
+use tracing::log;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SnarkBackend {
+    /// Prove succinct receipts and compress them on this host.
+    Local,
+    /// Prove straight to Groth16 receipts on Bonsai, which does not compress existing receipts.
+    Bonsai,
+}
+
+impl SnarkBackend {
+    /// Selects the backend to use: Bonsai if it is configured through `BONSAI_API_URL` and
+    /// `BONSAI_API_KEY`, otherwise the local host, provided it is able to produce Groth16 seals.
+    pub fn detect() -> Result<Self> {
+        // Compression of dev mode receipts is a no-op
+        if ProverOpts::default().is_dev_mode() {
+            return Ok(Self::Local);
+        }
+        if bonsai_configured() {
+            log::info!("Producing Groth16 seals on Bonsai");
+            return Ok(Self::Bonsai);
+        }
+        if let Err(reason) = check_local_groth16() {
+            bail!(
+                "Cannot produce Groth16 seals on this host: {reason}. Run the relay on an x86_64 \
+                 host with Docker available, or set BONSAI_API_URL and BONSAI_API_KEY to prove on \
+                 Bonsai instead"
+            );
+        }
+        Ok(Self::Local)
+    }
+
+    /// Checks this backend is able to produce Groth16 seals, for backends that are chosen rather
+    /// than detected.
+    pub fn check(self) -> Result<()> {
+        if ProverOpts::default().is_dev_mode() {
+            return Ok(());
+        }
+        match self {
+            Self::Local => check_local_groth16()
+                .map_err(|reason| anyhow!("Cannot produce Groth16 seals on this host: {reason}")),
+            Self::Bonsai => {
+                ensure!(
+                    bonsai_configured(),
+                    "Proving on Bonsai requires BONSAI_API_URL and BONSAI_API_KEY"
+                );
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for SnarkBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "local" => Ok(Self::Local),
+            "bonsai" => Ok(Self::Bonsai),
+            other => Err(format!(
+                "unknown proving backend {other}, expected local or bonsai"
+            )),
+        }
+    }
+}
+
+fn bonsai_configured() -> bool {
+    match env::var("RISC0_PROVER") {
+        Ok(prover) if !prover.is_empty() => prover.eq_ignore_ascii_case("bonsai"),
+        _ => env::var("BONSAI_API_URL").is_ok() && env::var("BONSAI_API_KEY").is_ok(),
+    }
+}
+
+/// Checks whether the local host can compress receipts into Groth16 SNARKs, which runs the
+/// Groth16 prover in an x86_64 Docker container.
+pub fn check_local_groth16() -> Result<(), String> {
+    if !cfg!(target_arch = "x86_64") {
+        return Err(format!(
+            "local Groth16 proving requires x86_64, this host is {}",
+            env::consts::ARCH
+        ));
+    }
+    let docker = Command::new("docker")
+        .arg("info")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+    match docker {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err("the Docker daemon is not running or not accessible".to_string()),
+        Err(e) => Err(format!("Docker is not installed: {e}")),
+    }
+}
@@ -0,0 +1,149 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Detection of finalized messages that remain undelivered for too long. A relay gap that goes
+//! unnoticed is the worst failure mode of a bridge, so these are alerted on rather than only
+//! logged.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use crate::alert::{Alert, Alerter};
+use crate::job::MessageId;
+use crate::metrics::Metrics;
+
+struct Pending {
+    finalized_at: SystemTime,
+    alerted: bool,
+}
+
+pub struct StaleMonitor {
+    max_age: Duration,
+    pending: Mutex<HashMap<MessageId, Pending>>,
+    metrics: Arc<Metrics>,
+    alerter: Alerter,
+}
+
+impl StaleMonitor {
+    pub fn new(max_age: Duration, metrics: Arc<Metrics>, alerter: Alerter) -> Self {
+        Self {
+            max_age,
+            pending: Mutex::default(),
+            metrics,
+            alerter,
+        }
+    }
+
+    /// Starts tracking the message `id`, which became relayable at `finalized_at`, until it is
+    /// delivered. Tracking a message again keeps its original finalization time.
+    pub fn track(&self, id: MessageId, finalized_at: SystemTime) {
+        self.pending.lock().unwrap().entry(id).or_insert(Pending {
+            finalized_at,
+            alerted: false,
+        });
+    }
+
+    /// Stops tracking the message `id` once it was delivered.
+    pub fn delivered(&self, id: &MessageId) {
+        self.pending.lock().unwrap().remove(id);
+    }
+
+    /// Alerts once on every message that became stale since the last check and returns the
+    /// number of stale messages.
+    pub async fn check(&self, now: SystemTime) -> usize {
+        let mut stale = 0;
+        let mut newly_stale = Vec::new();
+        for (id, pending) in self.pending.lock().unwrap().iter_mut() {
+            let age = now.duration_since(pending.finalized_at).unwrap_or_default();
+            if age <= self.max_age {
+                continue;
+            }
+            stale += 1;
+            if !pending.alerted {
+                pending.alerted = true;
+                newly_stale.push((*id, age));
+            }
+        }
+        self.metrics.set_stale_messages(stale);
+
+        for (id, age) in newly_stale {
+            self.metrics.record_stale_alert();
+            self.alerter
+                .fire(Alert {
+                    kind: "stale_message",
+                    message: format!(
+                        "Message {id} is still undelivered {age:?} after finalization, exceeding {:?}",
+                        self.max_age
+                    ),
+                })
+                .await;
+        }
+        stale
+    }
+
+    /// Checks for stale messages every `interval` in the background.
+    pub fn spawn_checks(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                self.check(SystemTime::now()).await;
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_primitives::TxHash;
+
+    #[tokio::test]
+    async fn alerts_once_per_stale_message() {
+        let metrics = Arc::new(Metrics::default());
+        let monitor =
+            StaleMonitor::new(Duration::from_secs(60), metrics.clone(), Alerter::default());
+        let id = MessageId {
+            tx_hash: TxHash::ZERO,
+            index: 0,
+        };
+        let finalized_at = SystemTime::UNIX_EPOCH;
+        monitor.track(id, finalized_at);
+
+        assert_eq!(
+            monitor.check(finalized_at + Duration::from_secs(30)).await,
+            0
+        );
+        assert_eq!(
+            monitor.check(finalized_at + Duration::from_secs(90)).await,
+            1
+        );
+        assert_eq!(
+            monitor.check(finalized_at + Duration::from_secs(120)).await,
+            1
+        );
+        assert!(
+            metrics
+                .render_prometheus()
+                .contains("relay_stale_alerts_total 1\n")
+        );
+
+        monitor.delivered(&id);
+        assert_eq!(
+            monitor.check(finalized_at + Duration::from_secs(150)).await,
+            0
+        );
+    }
+}
@@ -0,0 +1,189 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Persistent storage of relay jobs.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::budget::BudgetState;
+use crate::fees::UnpaidMessage;
+use crate::job::{Job, MessageId, MessageState};
+use crate::watcher::Checkpoint;
+
+pub trait JobStore: Send + Sync {
+    fn load(&self, id: &MessageId) -> Result<Option<Job>>;
+
+    fn save(&self, job: &Job) -> Result<()>;
+
+    fn list(&self) -> Result<Vec<Job>>;
+
+    /// The position of the watcher last saved, if any.
+    fn load_checkpoint(&self) -> Result<Option<Checkpoint>>;
+
+    fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()>;
+
+    /// The messages parked until their relay is paid for, as last saved.
+    fn load_unpaid(&self) -> Result<Vec<UnpaidMessage>>;
+
+    fn save_unpaid(&self, unpaid: &[UnpaidMessage]) -> Result<()>;
+
+    /// The spend on proving of the day it was last saved, if any.
+    fn load_budget(&self) -> Result<Option<BudgetState>>;
+
+    fn save_budget(&self, budget: &BudgetState) -> Result<()>;
+}
+
+/// Stores each job as a JSON file in a directory, alongside the watcher checkpoint.
+pub struct FileJobStore {
+    dir: PathBuf,
+}
+
+impl FileJobStore {
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir)
+            .with_context(|| format!("failed to create job store {}", dir.display()))?;
+        Ok(Self { dir })
+    }
+
+    fn path(&self, id: &MessageId) -> PathBuf {
+        self.dir.join(format!("{id}.json"))
+    }
+
+    fn checkpoint_path(&self) -> PathBuf {
+        // Not a `.json` file, which would be listed as a job
+        self.dir.join("watcher.checkpoint")
+    }
+
+    fn unpaid_path(&self) -> PathBuf {
+        self.dir.join("unpaid.parked")
+    }
+
+    fn budget_path(&self) -> PathBuf {
+        self.dir.join("proving.budget")
+    }
+}
+
+/// Writes to a temporary file first so a crash never leaves a truncated file behind.
+fn write_atomically(path: &Path, data: &[u8]) -> Result<()> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    fs::write(&tmp, data)?;
+    fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+impl JobStore for FileJobStore {
+    fn load(&self, id: &MessageId) -> Result<Option<Job>> {
+        match fs::read(self.path(id)) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data)?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self, job: &Job) -> Result<()> {
+        write_atomically(&self.path(&job.id), &serde_json::to_vec_pretty(job)?)
+    }
+
+    fn list(&self) -> Result<Vec<Job>> {
+        let mut jobs = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let data = fs::read(&path)?;
+                jobs.push(
+                    serde_json::from_slice(&data)
+                        .with_context(|| format!("invalid job file {}", path.display()))?,
+                );
+            }
+        }
+        Ok(jobs)
+    }
+
+    fn load_checkpoint(&self) -> Result<Option<Checkpoint>> {
+        let path = self.checkpoint_path();
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data).with_context(|| {
+                format!("invalid watcher checkpoint {}", path.display())
+            })?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_checkpoint(&self, checkpoint: &Checkpoint) -> Result<()> {
+        write_atomically(&self.checkpoint_path(), &serde_json::to_vec(checkpoint)?)
+    }
+
+    fn load_unpaid(&self) -> Result<Vec<UnpaidMessage>> {
+        let path = self.unpaid_path();
+        match fs::read(&path) {
+            Ok(data) => serde_json::from_slice(&data)
+                .with_context(|| format!("invalid parked messages {}", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Vec::new()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_unpaid(&self, unpaid: &[UnpaidMessage]) -> Result<()> {
+        write_atomically(&self.unpaid_path(), &serde_json::to_vec(unpaid)?)
+    }
+
+    fn load_budget(&self) -> Result<Option<BudgetState>> {
+        let path = self.budget_path();
+        match fs::read(&path) {
+            Ok(data) => Ok(Some(serde_json::from_slice(&data).with_context(|| {
+                format!("invalid proving budget {}", path.display())
+            })?)),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_budget(&self, budget: &BudgetState) -> Result<()> {
+        write_atomically(&self.budget_path(), &serde_json::to_vec(budget)?)
+    }
+}
+
+/// A job whose transitions are persisted to a store, if any, as they happen.
+pub struct TrackedJob {
+    pub job: Job,
+    store: Option<Arc<dyn JobStore>>,
+}
+
+impl TrackedJob {
+    pub fn new(job: Job, store: Option<Arc<dyn JobStore>>) -> Result<Self> {
+        let tracked = Self { job, store };
+        tracked.save()?;
+        Ok(tracked)
+    }
+
+    pub fn advance(&mut self, to: MessageState, reason: Option<String>) -> Result<()> {
+        self.job.transition(to, reason)?;
+        self.save()
+    }
+
+    pub fn save(&self) -> Result<()> {
+        match &self.store {
+            Some(store) => store.save(&self.job),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,185 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Client side rate limiting of RPC requests.
+//!
+//! Preflighting a large block issues a burst of storage proof and receipt requests which quickly
+//! exceeds the rate limits of hosted providers, getting requests rejected or the relayer banned.
+//! [ThrottleLayer] spreads requests out to stay within a budget instead.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::{TransportError, TransportFut};
+use tokio::sync::Semaphore;
+use tower::{Layer, Service};
+
+/// The request budget of an RPC provider.
+#[derive(Clone, Debug)]
+pub struct RpcBudget {
+    /// Weighted requests per second, unlimited if `None`.
+    pub requests_per_second: Option<u32>,
+    /// Maximum number of requests in flight at once, unlimited if `None`.
+    pub max_concurrent: Option<usize>,
+    /// Weight of each method, in requests. Methods not listed weigh one request.
+    pub weights: HashMap<String, u32>,
+}
+
+impl Default for RpcBudget {
+    fn default() -> Self {
+        // Providers typically price proofs and bulk receipt queries well above simple lookups
+        let weights = [
+            ("eth_getProof", 10),
+            ("eth_getBlockReceipts", 10),
+            ("eth_getLogs", 5),
+            ("debug_traceTransaction", 10),
+        ]
+        .into_iter()
+        .map(|(method, weight)| (method.to_string(), weight))
+        .collect();
+        Self {
+            requests_per_second: None,
+            max_concurrent: None,
+            weights,
+        }
+    }
+}
+
+impl RpcBudget {
+    fn weight(&self, request: &RequestPacket) -> u32 {
+        let weight = |method: &str| self.weights.get(method).copied().unwrap_or(1);
+        match request {
+            RequestPacket::Single(request) => weight(request.method()),
+            RequestPacket::Batch(requests) => requests.iter().map(|r| weight(r.method())).sum(),
+        }
+    }
+}
+
+/// Token bucket refilled at the budgeted rate.
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+struct Limiter {
+    budget: RpcBudget,
+    bucket: Mutex<Bucket>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+impl Limiter {
+    /// Waits until `weight` requests fit in the rate budget and takes them from it.
+    async fn take(&self, weight: u32) {
+        let Some(rate) = self.budget.requests_per_second else {
+            return;
+        };
+        let rate = f64::from(rate);
+        // A single request heavier than a second's worth of budget must still go through
+        let capacity = rate.max(f64::from(weight));
+        loop {
+            let wait = {
+                let mut bucket = self.bucket.lock().unwrap();
+                let now = Instant::now();
+                let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+                bucket.tokens = (bucket.tokens + elapsed * rate).min(capacity);
+                bucket.refilled_at = now;
+                if bucket.tokens >= f64::from(weight) {
+                    bucket.tokens -= f64::from(weight);
+                    return;
+                }
+                Duration::from_secs_f64((f64::from(weight) - bucket.tokens) / rate)
+            };
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Transport layer delaying requests so they stay within an [RpcBudget]. Clones share the budget,
+/// so a single layer should be used for all clients of the same provider.
+#[derive(Clone)]
+pub struct ThrottleLayer {
+    limiter: Arc<Limiter>,
+}
+
+impl ThrottleLayer {
+    pub fn new(budget: RpcBudget) -> Self {
+        let bucket = Bucket {
+            tokens: budget
+                .requests_per_second
+                .map(f64::from)
+                .unwrap_or_default(),
+            refilled_at: Instant::now(),
+        };
+        let in_flight = budget
+            .max_concurrent
+            .map(|permits| Arc::new(Semaphore::new(permits)));
+        Self {
+            limiter: Arc::new(Limiter {
+                budget,
+                bucket: Mutex::new(bucket),
+                in_flight,
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for ThrottleLayer {
+    type Service = ThrottleService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ThrottleService {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ThrottleService<S> {
+    inner: S,
+    limiter: Arc<Limiter>,
+}
+
+impl<S> Service<RequestPacket> for ThrottleService<S>
+where
+    S: Service<RequestPacket, Response = ResponsePacket, Error = TransportError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        let limiter = self.limiter.clone();
+        let mut inner = self.inner.clone();
+        Box::pin(async move {
+            let _permit = match &limiter.in_flight {
+                Some(semaphore) => Some(semaphore.clone().acquire_owned().await.unwrap()),
+                None => None,
+            };
+            limiter.take(limiter.budget.weight(&request)).await;
+            inner.call(request).await
+        })
+    }
+}
@@ -0,0 +1,378 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Terminal dashboard of a running relay, built from its status API. Meant for operators
+//! debugging a relay on a remote machine over SSH.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use ratatui::{
+    DefaultTerminal, Frame,
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Style, Stylize},
+    text::Line,
+    widgets::{Block, Paragraph, Row, Table},
+};
+use reqwest::{StatusCode, Url};
+
+use crate::api::Status;
+use crate::job::{Job, MessageState};
+
+/// Interval between checks for key presses.
+const INPUT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Non-terminal states, in the order messages move through them.
+const QUEUES: [MessageState; 6] = [
+    MessageState::Observed,
+    MessageState::Finalized,
+    MessageState::Proving,
+    MessageState::Proved,
+    MessageState::Cleared,
+    MessageState::Submitted,
+];
+
+/// What was last fetched from the API.
+#[derive(Default)]
+struct Snapshot {
+    status: Option<Status>,
+    /// The persisted jobs, if the relay has a job store.
+    jobs: Option<Vec<Job>>,
+    error: Option<String>,
+}
+
+/// Shows the dashboard of the relay serving its API at `api_url`, refreshing it every `refresh`
+/// until `q` or `Esc` is pressed.
+pub async fn run(api_url: Url, refresh: Duration) -> Result<()> {
+    let client = reqwest::Client::new();
+    let mut terminal = ratatui::init();
+    let result = run_loop(&mut terminal, &client, &api_url, refresh).await;
+    ratatui::restore();
+    result
+}
+
+async fn run_loop(
+    terminal: &mut DefaultTerminal,
+    client: &reqwest::Client,
+    api_url: &Url,
+    refresh: Duration,
+) -> Result<()> {
+    let mut snapshot = Snapshot::default();
+    let mut fetched_at: Option<Instant> = None;
+    loop {
+        if fetched_at.is_none_or(|at| at.elapsed() >= refresh) {
+            snapshot = fetch(client, api_url).await;
+            fetched_at = Some(Instant::now());
+        }
+        terminal.draw(|frame| draw(frame, api_url, &snapshot))?;
+
+        if !event::poll(INPUT_POLL_INTERVAL)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('r') => fetched_at = None,
+            _ => {}
+        }
+    }
+}
+
+async fn fetch(client: &reqwest::Client, api_url: &Url) -> Snapshot {
+    let status = async {
+        let url = api_url.join("status")?;
+        anyhow::Ok(
+            client
+                .get(url)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<Status>()
+                .await?,
+        )
+    };
+    let jobs = async {
+        let response = client.get(api_url.join("jobs")?).send().await?;
+        // Served only when the relay persists its jobs
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        anyhow::Ok(Some(response.error_for_status()?.json::<Vec<Job>>().await?))
+    };
+    match tokio::try_join!(status, jobs).context("failed to fetch the relay status") {
+        Ok((status, jobs)) => Snapshot {
+            status: Some(status),
+            jobs,
+            error: None,
+        },
+        Err(e) => Snapshot {
+            error: Some(format!("{e:#}")),
+            ..Default::default()
+        },
+    }
+}
+
+fn draw(frame: &mut Frame, api_url: &Url, snapshot: &Snapshot) {
+    let [header, top, proofs, submissions, errors, footer] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(QUEUES.len() as u16 + 4),
+        Constraint::Min(5),
+        Constraint::Min(5),
+        Constraint::Min(5),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [queues, wallets] =
+        Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)]).areas(top);
+
+    draw_header(frame, header, api_url, snapshot);
+    draw_queues(frame, queues, snapshot.jobs.as_deref());
+    draw_wallets(frame, wallets, snapshot.status.as_ref());
+    draw_proofs(frame, proofs, snapshot);
+    draw_submissions(frame, submissions, snapshot.jobs.as_deref());
+    draw_errors(frame, errors, snapshot.status.as_ref());
+    frame.render_widget(
+        Paragraph::new("q: quit  r: refresh").style(Style::new().dim()),
+        footer,
+    );
+}
+
+fn draw_header(frame: &mut Frame, area: Rect, api_url: &Url, snapshot: &Snapshot) {
+    let line = match (&snapshot.status, &snapshot.error) {
+        (_, Some(error)) => Line::from(format!("{api_url}: {error}")).red(),
+        (Some(status), None) => {
+            let breaker = if status.breaker_tripped {
+                "HALTED by circuit breaker".red().bold()
+            } else {
+                "running".green()
+            };
+            let latency = &status.delivery_latency;
+            Line::from(vec![
+                format!("{api_url}  ").into(),
+                breaker,
+                format!(
+                    "  latency p50 {} p90 {} p99 {}  dead letters {}",
+                    duration(latency.p50),
+                    duration(latency.p90),
+                    duration(latency.p99),
+                    status
+                        .dead_letters
+                        .map_or("-".to_string(), |count| count.to_string()),
+                )
+                .into(),
+            ])
+        }
+        (None, None) => Line::from(format!("{api_url}: connecting")),
+    };
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+fn draw_queues(frame: &mut Frame, area: Rect, jobs: Option<&[Job]>) {
+    let block = Block::bordered().title(" Queues ");
+    let Some(jobs) = jobs else {
+        frame.render_widget(
+            Paragraph::new("Jobs are not persisted by this relay").block(block),
+            area,
+        );
+        return;
+    };
+    let count = |state: MessageState| jobs.iter().filter(|job| job.state == state).count();
+    let mut rows: Vec<Row> = QUEUES
+        .iter()
+        .map(|&state| Row::new([state.to_string(), count(state).to_string()]))
+        .collect();
+    let dead_letters = jobs.iter().filter(|job| job.dead_lettered).count();
+    rows.push(Row::new(["Dead-lettered".to_string(), dead_letters.to_string()]).red());
+    let table = Table::new(rows, [Constraint::Fill(1), Constraint::Length(8)]).block(block);
+    frame.render_widget(table, area);
+}
+
+fn draw_wallets(frame: &mut Frame, area: Rect, status: Option<&Status>) {
+    let rows = status
+        .into_iter()
+        .flat_map(|status| &status.wallets)
+        .map(|wallet| {
+            Row::new([
+                wallet.chain_id.to_string(),
+                wallet.address.to_string(),
+                format!("{:.6}", wallet.balance_wei as f64 / 1e18),
+            ])
+        });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(42),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["Chain ID", "Wallet", "Balance (ETH)"]).bold())
+    .block(Block::bordered().title(" Wallets "));
+    frame.render_widget(table, area);
+}
+
+/// Shows the messages being proven, with their progress estimated from the proving time of
+/// recent proofs since the provers do not report any.
+fn draw_proofs(frame: &mut Frame, area: Rect, snapshot: &Snapshot) {
+    let recent = snapshot
+        .status
+        .as_ref()
+        .map(|status| status.recent_proofs.as_slice())
+        .unwrap_or_default();
+    let expected = (!recent.is_empty()).then(|| {
+        recent
+            .iter()
+            .map(|proof| proof.proving_time + proof.compression_time.unwrap_or_default())
+            .sum::<Duration>()
+            / recent.len() as u32
+    });
+    let now = unix_now();
+    let rows = snapshot
+        .jobs
+        .iter()
+        .flatten()
+        .filter(|job| job.state == MessageState::Proving)
+        .map(|job| {
+            let elapsed =
+                Duration::from_secs(now.saturating_sub(job.entered_at(job.state).unwrap_or(now)));
+            let progress = match expected {
+                Some(expected) if !expected.is_zero() => {
+                    progress_bar(elapsed.as_secs_f64() / expected.as_secs_f64())
+                }
+                _ => "-".to_string(),
+            };
+            Row::new([job.id.to_string(), duration(elapsed), progress])
+        });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(70),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["Message", "Elapsed", "Progress (estimated)"]).bold())
+    .block(Block::bordered().title(" In-flight proofs "));
+    frame.render_widget(table, area);
+}
+
+fn draw_submissions(frame: &mut Frame, area: Rect, jobs: Option<&[Job]>) {
+    let mut submitted: Vec<_> = jobs
+        .into_iter()
+        .flatten()
+        .filter_map(|job| {
+            Some((
+                job.entered_at(MessageState::Submitted)?,
+                job.dest_tx_hash?,
+                job,
+            ))
+        })
+        .collect();
+    submitted.sort_by_key(|(at, _, _)| std::cmp::Reverse(*at));
+    let now = unix_now();
+    let rows = submitted.into_iter().map(|(at, tx_hash, job)| {
+        let style = match job.state {
+            MessageState::Confirmed => Style::new().green(),
+            MessageState::Failed | MessageState::Expired => Style::new().red(),
+            _ => Style::new(),
+        };
+        Row::new([
+            job.id.to_string(),
+            tx_hash.to_string(),
+            job.state.to_string(),
+            format!(
+                "{} ago",
+                duration(Duration::from_secs(now.saturating_sub(at)))
+            ),
+        ])
+        .style(style)
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(70),
+            Constraint::Length(68),
+            Constraint::Length(10),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["Message", "Transaction", "State", "Submitted"]).bold())
+    .block(Block::bordered().title(" Recent submissions "));
+    frame.render_widget(table, area);
+}
+
+fn draw_errors(frame: &mut Frame, area: Rect, status: Option<&Status>) {
+    let now = unix_now();
+    let rows = status
+        .into_iter()
+        .flat_map(|status| status.recent_errors.iter().rev())
+        .map(|error| {
+            Row::new([
+                format!(
+                    "{} ago",
+                    duration(Duration::from_secs(now.saturating_sub(error.at)))
+                ),
+                error.id.to_string(),
+                error.error.clone(),
+            ])
+        });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(10),
+            Constraint::Length(70),
+            Constraint::Fill(1),
+        ],
+    )
+    .header(Row::new(["When", "Message", "Error"]).bold())
+    .style(Style::new().fg(Color::LightRed))
+    .block(Block::bordered().title(" Errors "));
+    frame.render_widget(table, area);
+}
+
+/// Renders `ratio` as a text progress bar, capped below completion since proofs routinely take
+/// longer than the estimate.
+fn progress_bar(ratio: f64) -> String {
+    const WIDTH: usize = 20;
+    let ratio = ratio.clamp(0.0, 0.99);
+    let filled = (ratio * WIDTH as f64) as usize;
+    format!(
+        "{}{} {:>3.0}%",
+        "█".repeat(filled),
+        "░".repeat(WIDTH - filled),
+        ratio * 100.0
+    )
+}
+
+fn duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..60 => format!("{secs}s"),
+        60..3600 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
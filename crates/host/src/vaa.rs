@@ -0,0 +1,221 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Delivery of messages through the Wormhole guardians alongside the ZK proof.
+//!
+//! NTT managers with several transceivers accept a message once enough of them attested to it.
+//! Where a destination also runs the standard WormholeTransceiver, the relayer can fetch the VAA
+//! the guardians signed for the message and submit it too, so delivery does not hinge on a
+//! single attestation path.
+
+use std::str::FromStr;
+use std::time::Duration;
+
+use alloy::providers::Provider;
+use alloy::rpc::types::TransactionReceipt;
+use alloy::sol;
+use alloy_primitives::{Address, Bytes, TxHash, hex};
+use alloy_sol_types::SolEvent;
+use anyhow::{Context, Result, bail, ensure};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use common::IBoundlessTransceiver::SendTransceiverMessage;
+use common::ntt::TransceiverMessage;
+use common::to_wormhole_address;
+use reqwest::{Client, StatusCode, Url};
+use serde::Deserialize;
+use tokio::time::{Instant, sleep};
+use tracing::log;
+
+use crate::destination::Destination;
+use crate::rpc::EndpointPool;
+
+sol! {
+    /// Emitted by the Wormhole core bridge for every published message.
+    event LogMessagePublished(
+        address indexed sender, uint64 sequence, uint32 nonce, bytes payload, uint8 consistencyLevel
+    );
+
+    #[sol(rpc)]
+    interface IWormholeTransceiver {
+        /// @notice Receives an attested message from the Wormhole core bridge.
+        function receiveMessage(bytes memory encodedMessage) external;
+    }
+}
+
+/// How the VAA is used alongside the ZK proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HybridMode {
+    /// Submit both attestations.
+    Both,
+    /// Stop at whichever attestation delivers the message first.
+    First,
+}
+
+impl FromStr for HybridMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, String> {
+        match s {
+            "both" => Ok(Self::Both),
+            "first" => Ok(Self::First),
+            other => Err(format!(
+                "unknown hybrid mode {other}, expected both or first"
+            )),
+        }
+    }
+}
+
+/// The Wormhole transceivers of a message, alongside the BoundlessTransceivers.
+#[derive(Clone, Copy, Debug)]
+pub struct VaaRoute {
+    /// The WormholeTransceiver on the source chain, which emits the message to the guardians.
+    pub emitter: Address,
+    /// The WormholeTransceiver on the destination chain.
+    pub wormhole_transceiver: Address,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedVaa {
+    vaa_bytes: String,
+}
+
+/// Client of the guardian REST API serving signed VAAs.
+pub struct GuardianClient {
+    api_url: Url,
+    client: Client,
+    pub mode: HybridMode,
+    poll_interval: Duration,
+    timeout: Duration,
+}
+
+impl GuardianClient {
+    pub fn new(api_url: Url, mode: HybridMode, poll_interval: Duration, timeout: Duration) -> Self {
+        Self {
+            api_url,
+            client: Client::new(),
+            mode,
+            poll_interval,
+            timeout,
+        }
+    }
+
+    /// Fetches the VAA of a message, returning `None` if the guardians have not signed it yet.
+    pub async fn fetch(
+        &self,
+        chain: u16,
+        emitter: Address,
+        sequence: u64,
+    ) -> Result<Option<Bytes>> {
+        let url = self.api_url.join(&format!(
+            "v1/signed_vaa/{chain}/{}/{sequence}",
+            hex::encode(to_wormhole_address(emitter))
+        ))?;
+        let response = self.client.get(url).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let vaa: SignedVaa = response.error_for_status()?.json().await?;
+        let vaa = STANDARD
+            .decode(vaa.vaa_bytes)
+            .context("invalid VAA encoding")?;
+        Ok(Some(vaa.into()))
+    }
+
+    /// Waits until the guardians signed the VAA of a message.
+    pub async fn wait(&self, chain: u16, emitter: Address, sequence: u64) -> Result<Bytes> {
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match self.fetch(chain, emitter, sequence).await {
+                Ok(Some(vaa)) => return Ok(vaa),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to fetch VAA {chain}/{emitter}/{sequence}: {e:#}"),
+            }
+            if Instant::now() >= deadline {
+                bail!(
+                    "VAA {chain}/{emitter}/{sequence} not signed within {:?}",
+                    self.timeout
+                );
+            }
+            sleep(self.poll_interval).await;
+        }
+    }
+
+    /// Fetches the VAA of the message sent in `tx_hash` by the BoundlessTransceiver `source` and
+    /// delivers it to the destination WormholeTransceiver, returning the delivering transaction.
+    pub async fn deliver(
+        &self,
+        rpc: &EndpointPool,
+        tx_hash: TxHash,
+        source: Address,
+        route: VaaRoute,
+        destination: &Destination,
+    ) -> Result<TxHash> {
+        let (chain, sequence) = rpc
+            .run(|endpoint| async move {
+                let receipt = endpoint
+                    .provider()
+                    .get_transaction_receipt(tx_hash)
+                    .await?
+                    .context("No transaction found with given tx_hash")?;
+                message_sequence(&receipt, source, route.emitter)
+            })
+            .await?;
+
+        log::info!("Waiting for VAA {chain}/{}/{sequence}", route.emitter);
+        let vaa = self.wait(chain, route.emitter, sequence).await?;
+
+        let transceiver =
+            IWormholeTransceiver::new(route.wormhole_transceiver, destination.provider());
+        let pending_tx = transceiver.receiveMessage(vaa).send().await?;
+        let dest_tx_hash = *pending_tx.tx_hash();
+        let receipt = pending_tx
+            .get_receipt()
+            .await
+            .with_context(|| format!("transaction did not confirm: {dest_tx_hash}"))?;
+        ensure!(receipt.status(), "transaction failed: {dest_tx_hash}");
+        Ok(dest_tx_hash)
+    }
+}
+
+/// Finds the source Wormhole chain and the core bridge sequence number of the message `emitter`
+/// published in the transaction, alongside the message of the BoundlessTransceiver `source`.
+fn message_sequence(
+    receipt: &TransactionReceipt,
+    source: Address,
+    emitter: Address,
+) -> Result<(u16, u64)> {
+    let encoded_message = receipt
+        .logs()
+        .iter()
+        .filter(|log| log.address() == source)
+        .find_map(|log| SendTransceiverMessage::decode_log(&log.inner).ok())
+        .context("No SendTransceiverMessage event found in transaction receipt")?
+        .data
+        .encodedMessage;
+    // The BoundlessTransceiver puts the Wormhole chain ID of the source in its payload
+    let message = TransceiverMessage::parse(&encoded_message).map_err(anyhow::Error::msg)?;
+    let chain = message.source_chain().map_err(anyhow::Error::msg)?;
+
+    let sequence = receipt
+        .logs()
+        .iter()
+        .filter_map(|log| LogMessagePublished::decode_log(&log.inner).ok())
+        .find(|event| event.sender == emitter)
+        .with_context(|| format!("No message published by {emitter} in transaction"))?
+        .sequence;
+
+    Ok((chain, sequence))
+}
@@ -0,0 +1,356 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Record and replay of the responses of source chain endpoints, VCR style, so that building
+//! inputs and preflighting can be tested deterministically without live endpoints or anvil.
+//!
+//! Each endpoint is put behind a local HTTP proxy. While recording, the proxy forwards requests to
+//! the endpoint and writes every response to a cassette file. While replaying, it answers from the
+//! cassette alone. The execution RPC and the beacon API are both plain HTTP, so the same proxy
+//! serves either.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use anyhow::{Context, Result, bail};
+use axum::{
+    Router,
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, Method, StatusCode, Uri, header},
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::log;
+
+/// A response as recorded.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Recorded {
+    status: u16,
+    content_type: Option<String>,
+    /// The body, base64 encoded as beacon API responses may be binary.
+    body: String,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Tape {
+    /// The responses to each request, in the order they were received.
+    interactions: BTreeMap<String, Vec<Recorded>>,
+    /// Number of responses replayed for each request.
+    #[serde(skip)]
+    replayed: HashMap<String, usize>,
+}
+
+/// A cassette file the responses of endpoints are recorded to or replayed from.
+pub struct Cassette {
+    path: PathBuf,
+    recording: bool,
+    tape: Mutex<Tape>,
+    client: reqwest::Client,
+}
+
+impl Cassette {
+    /// Starts recording to a new cassette at `path`, replacing any existing one.
+    pub fn record(path: impl AsRef<Path>) -> Result<Arc<Self>> {
+        let cassette = Self {
+            path: path.as_ref().to_path_buf(),
+            recording: true,
+            tape: Default::default(),
+            client: reqwest::Client::new(),
+        };
+        cassette.save(&cassette.tape.lock().unwrap())?;
+        Ok(Arc::new(cassette))
+    }
+
+    /// Loads the cassette at `path` to replay it.
+    pub fn replay(path: impl AsRef<Path>) -> Result<Arc<Self>> {
+        let path = path.as_ref();
+        let json = fs::read(path)
+            .with_context(|| format!("failed to read cassette {}", path.display()))?;
+        let tape = serde_json::from_slice(&json)
+            .with_context(|| format!("invalid cassette {}", path.display()))?;
+        Ok(Arc::new(Self {
+            path: path.to_path_buf(),
+            recording: false,
+            tape: Mutex::new(tape),
+            client: reqwest::Client::new(),
+        }))
+    }
+
+    /// Serves the endpoint `name` from a local proxy, returning the URL to use instead of the
+    /// endpoint. Recording requires the `upstream` URL of the endpoint, replaying ignores it.
+    ///
+    /// Endpoints sharing a name share their responses, so all endpoints of a failover pool should
+    /// be given the same name.
+    pub fn serve(self: &Arc<Self>, name: &str, upstream: Option<Url>) -> Result<Url> {
+        if self.recording && upstream.is_none() {
+            bail!("recording endpoint {name} requires its URL");
+        }
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+
+        let proxy = Arc::new(Proxy {
+            cassette: self.clone(),
+            name: name.to_string(),
+            upstream,
+        });
+        let router = Router::new().fallback(handle).with_state(proxy);
+        tokio::spawn(async move {
+            if let Err(e) = axum::serve(listener, router).await {
+                log::error!("Cassette proxy failed: {e}");
+            }
+        });
+        Ok(format!("http://{addr}/").parse()?)
+    }
+
+    fn save(&self, tape: &Tape) -> Result<()> {
+        let tmp = self.path.with_extension("tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(tape)?)
+            .with_context(|| format!("failed to write cassette {}", self.path.display()))?;
+        fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+
+    /// Replays the next response recorded for `key`. Once they are exhausted, the last one is
+    /// repeated.
+    fn next(&self, key: &str) -> Option<Recorded> {
+        let mut tape = self.tape.lock().unwrap();
+        let count = tape.interactions.get(key)?.len();
+        let replayed = tape.replayed.entry(key.to_string()).or_default();
+        let index = (*replayed).min(count - 1);
+        *replayed += 1;
+        Some(tape.interactions[key][index].clone())
+    }
+
+    fn append(&self, key: String, recorded: Recorded) -> Result<()> {
+        let mut tape = self.tape.lock().unwrap();
+        tape.interactions.entry(key).or_default().push(recorded);
+        self.save(&tape)
+    }
+}
+
+/// The proxy serving a single endpoint.
+struct Proxy {
+    cassette: Arc<Cassette>,
+    name: String,
+    upstream: Option<Url>,
+}
+
+impl Proxy {
+    async fn forward(
+        &self,
+        method: Method,
+        uri: &Uri,
+        headers: &HeaderMap,
+        body: Bytes,
+    ) -> Result<Recorded> {
+        let upstream = self.upstream.as_ref().unwrap();
+        let path = uri.path_and_query().map_or("/", |path| path.as_str());
+        // The URL of JSON-RPC endpoints often ends in an API key, which must not get a slash
+        let url = match path {
+            "/" => upstream.clone(),
+            path => format!("{}{path}", upstream.as_str().trim_end_matches('/')).parse()?,
+        };
+        let mut request = self.cassette.client.request(method, url).body(body);
+        for name in [header::CONTENT_TYPE, header::ACCEPT] {
+            if let Some(value) = headers.get(&name) {
+                request = request.header(name, value);
+            }
+        }
+        let response = request.send().await?;
+        let status = response.status().as_u16();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let body = response.bytes().await?;
+        Ok(Recorded {
+            status,
+            content_type,
+            body: STANDARD.encode(body),
+        })
+    }
+}
+
+async fn handle(
+    State(proxy): State<Arc<Proxy>>,
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response {
+    let path = uri.path_and_query().map_or("/", |path| path.as_str());
+    // The IDs of JSON-RPC requests differ from run to run, so requests are matched without them
+    let mut request: Option<Value> = serde_json::from_slice(&body).ok();
+    let ids = request.as_mut().map(strip_ids).unwrap_or_default();
+    let key = match &request {
+        Some(request) => format!("{} {method} {path} {request}", proxy.name),
+        None => format!("{} {method} {path}", proxy.name),
+    };
+
+    let recorded = if proxy.cassette.recording {
+        let result = async {
+            let mut recorded = proxy.forward(method, &uri, &headers, body).await?;
+            recorded.body = map_json_body(&recorded.body, |response| number_ids(response, &ids))?;
+            proxy.cassette.append(key.clone(), recorded.clone())?;
+            anyhow::Ok(recorded)
+        }
+        .await;
+        match result {
+            Ok(recorded) => recorded,
+            Err(e) => return (StatusCode::BAD_GATEWAY, format!("{e:#}")).into_response(),
+        }
+    } else {
+        match proxy.cassette.next(&key) {
+            Some(recorded) => recorded,
+            None => {
+                log::warn!("No recorded response to {key}");
+                return (
+                    StatusCode::NOT_FOUND,
+                    format!("no recorded response to {key}"),
+                )
+                    .into_response();
+            }
+        }
+    };
+
+    let body = match response_body(&recorded, &ids) {
+        Ok(body) => body,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, format!("{e:#}")).into_response(),
+    };
+    let mut response = Response::builder()
+        .status(StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::BAD_GATEWAY));
+    if let Some(content_type) = &recorded.content_type {
+        response = response.header(header::CONTENT_TYPE, content_type);
+    }
+    response
+        .body(body.into())
+        .unwrap_or_else(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response())
+}
+
+/// The body of `recorded` as the response to the request with `ids`.
+fn response_body(recorded: &Recorded, ids: &[Value]) -> Result<Vec<u8>> {
+    let body = map_json_body(&recorded.body, |response| restore_ids(response, ids))?;
+    Ok(STANDARD.decode(body)?)
+}
+
+/// Applies `f` to a base64 encoded body if it is JSON, returning it unchanged otherwise.
+fn map_json_body(body: &str, f: impl FnOnce(&mut Value)) -> Result<String> {
+    let bytes = STANDARD.decode(body)?;
+    let Ok(mut json) = serde_json::from_slice::<Value>(&bytes) else {
+        return Ok(body.to_string());
+    };
+    f(&mut json);
+    Ok(STANDARD.encode(serde_json::to_vec(&json)?))
+}
+
+/// The JSON-RPC requests of a single request or a batch.
+fn messages(json: &mut Value) -> Vec<&mut serde_json::Map<String, Value>> {
+    match json {
+        Value::Object(message) => vec![message],
+        Value::Array(batch) => batch.iter_mut().filter_map(Value::as_object_mut).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Removes the IDs of a JSON-RPC request, returning them in order.
+fn strip_ids(request: &mut Value) -> Vec<Value> {
+    messages(request)
+        .into_iter()
+        .filter_map(|message| message.remove("id"))
+        .collect()
+}
+
+/// Replaces the IDs of a JSON-RPC response by the position of the request they answer among
+/// `ids`, which stays the same from run to run.
+fn number_ids(response: &mut Value, ids: &[Value]) {
+    for message in messages(response) {
+        let position = message
+            .get("id")
+            .and_then(|id| ids.iter().position(|request_id| request_id == id));
+        if let Some(position) = position {
+            message.insert("id".to_string(), position.into());
+        }
+    }
+}
+
+/// Reverses [number_ids] for the request with `ids`.
+fn restore_ids(response: &mut Value, ids: &[Value]) {
+    for message in messages(response) {
+        let request_id = message
+            .get("id")
+            .and_then(Value::as_u64)
+            .and_then(|position| ids.get(position as usize));
+        if let Some(request_id) = request_id {
+            message.insert("id".to_string(), request_id.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, routing::post};
+    use serde_json::json;
+
+    async fn call(url: &Url, id: u64) -> Value {
+        reqwest::Client::new()
+            .post(url.clone())
+            .json(&json!({"jsonrpc": "2.0", "id": id, "method": "eth_chainId", "params": []}))
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_responses() {
+        let upstream = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let upstream_url: Url = format!("http://{}/", upstream.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let router = Router::new().route(
+            "/",
+            post(|Json(request): Json<Value>| async move {
+                Json(json!({"jsonrpc": "2.0", "id": request["id"], "result": "0x1"}))
+            }),
+        );
+        tokio::spawn(async move { axum::serve(upstream, router).await });
+
+        let path = std::env::temp_dir().join(format!("cassette-test-{}.json", std::process::id()));
+        let recorder = Cassette::record(&path).unwrap();
+        let url = recorder.serve("rpc", Some(upstream_url)).unwrap();
+        assert_eq!(call(&url, 7).await["id"], 7);
+
+        // The upstream is not needed anymore, and the response answers the new ID
+        let player = Cassette::replay(&path).unwrap();
+        let url = player.serve("rpc", None).unwrap();
+        let response = call(&url, 42).await;
+        assert_eq!(response["id"], 42);
+        assert_eq!(response["result"], "0x1");
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Deterministic fixtures of a synthetic message for the Foundry tests of the destination
+//! contract, keeping them in sync with the encoding of the transceiver message, the journal and the
+//! seal the relayer actually delivers.
+//!
+//! The seal is the one `RiscZeroMockVerifier.mockProve` produces: the selector of the mock
+//! verifier followed by the digest of the claim that the guest ran successfully on the journal.
+
+use std::fmt::Write;
+
+use alloy_primitives::{Address, B256, Bytes, FixedBytes, U256};
+use alloy_sol_types::{SolCall, SolValue};
+use anyhow::Result;
+use common::ntt::{
+    BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX, NativeTokenTransfer, NttManagerMessage,
+    TransceiverMessage,
+};
+use common::{IBoundlessTransceiver, Journal, to_wormhole_address};
+use risc0_steel::Commitment;
+use risc0_zkvm::sha::{Digestible, Impl, Sha256};
+use risc0_zkvm::{Digest, ReceiptClaim};
+use serde::Serialize;
+
+/// Version of Steel commitments identifying a beacon block root by its slot.
+const CONSENSUS_COMMITMENT_VERSION: u16 = 2;
+
+/// A token transfer sent through a BoundlessTransceiver, committed to by a beacon block root. The
+/// defaults match the constants of `test/BoundlessTransceiver.t.sol`.
+#[derive(Clone, Debug)]
+pub struct SyntheticMessage {
+    /// Wormhole chain ID of the source chain.
+    pub source_chain: u16,
+    /// Wormhole chain ID of the destination chain.
+    pub recipient_chain: u16,
+    /// Wormhole format address of the NTT manager on the source chain.
+    pub source_manager: B256,
+    /// Wormhole format address of the NTT manager on the destination chain.
+    pub recipient_manager: B256,
+    /// Wormhole format address of the transceiver that emitted the message.
+    pub emitter: B256,
+    /// Sequence number of the message on the source manager.
+    pub sequence: B256,
+    /// Wormhole format address of the account that initiated the transfer.
+    pub sender: B256,
+    /// Amount in units of `10^-decimals` tokens.
+    pub amount: u64,
+    pub decimals: u8,
+    /// Wormhole format address of the token on the source chain.
+    pub source_token: B256,
+    pub recipient: Address,
+    /// Slot of the beacon block the message is committed to.
+    pub slot: u64,
+    /// Root of the beacon block the message is committed to.
+    pub block_root: B256,
+}
+
+impl Default for SyntheticMessage {
+    fn default() -> Self {
+        Self {
+            source_chain: 666,
+            recipient_chain: 333,
+            source_manager: B256::ZERO,
+            recipient_manager: B256::ZERO,
+            emitter: B256::ZERO,
+            sequence: B256::left_padding_from(&[25]),
+            sender: B256::left_padding_from(&[1]),
+            amount: 1_234_567,
+            decimals: 8,
+            source_token: B256::left_padding_from(&1022u16.to_be_bytes()),
+            recipient: Address::left_padding_from(&1234u16.to_be_bytes()),
+            slot: 1_234_567_890,
+            block_root: B256::left_padding_from(&1022u16.to_be_bytes()),
+        }
+    }
+}
+
+/// Fixtures of a message delivered with a mock proof.
+#[derive(Clone, Debug, Serialize)]
+pub struct TestVector {
+    pub image_id: B256,
+    /// The encoded transceiver message.
+    pub encoded_message: Bytes,
+    /// Digest the NTT manager on the destination identifies the message by.
+    pub message_digest: B256,
+    /// Versioned ID of the Steel commitment, encoding the slot of the beacon block.
+    pub commitment_id: U256,
+    pub block_root: B256,
+    /// The ABI encoded journal.
+    pub journal: Bytes,
+    /// SHA-256 digest of the journal.
+    pub journal_digest: B256,
+    /// Selector the mock verifier is deployed with.
+    pub selector: FixedBytes<4>,
+    pub seal: Bytes,
+    /// Calldata of `receiveMessage(journal, seal)`.
+    pub calldata: Bytes,
+}
+
+impl TestVector {
+    /// Derives the fixtures of `message` proven by the guest with `image_id`, for a mock verifier
+    /// deployed with `selector`.
+    pub fn new(
+        message: &SyntheticMessage,
+        image_id: Digest,
+        selector: FixedBytes<4>,
+    ) -> Result<Self> {
+        let transfer = NativeTokenTransfer {
+            amount: message.amount,
+            decimals: message.decimals,
+            source_token: message.source_token,
+            to: to_wormhole_address(message.recipient),
+            to_chain: message.recipient_chain,
+        };
+        let manager_message = NttManagerMessage {
+            id: message.sequence,
+            sender: message.sender,
+            payload: transfer.encode(),
+        };
+        let transceiver_message = TransceiverMessage {
+            source_ntt_manager: message.source_manager,
+            recipient_ntt_manager: message.recipient_manager,
+            ntt_manager_payload: manager_message.encode().map_err(anyhow::Error::msg)?,
+            transceiver_payload: message.source_chain.to_be_bytes().to_vec().into(),
+        };
+        let encoded_message = transceiver_message
+            .encode(BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX)
+            .map_err(anyhow::Error::msg)?;
+
+        let commitment_id =
+            (U256::from(CONSENSUS_COMMITMENT_VERSION) << 240) | U256::from(message.slot);
+        let journal = Journal {
+            commitment: Commitment {
+                id: commitment_id,
+                digest: message.block_root,
+                configID: B256::ZERO,
+            },
+            encodedMessage: encoded_message.clone(),
+            emitterContract: message.emitter,
+        }
+        .abi_encode();
+        let journal_digest = B256::from_slice(Impl::hash_bytes(&journal).as_bytes());
+
+        let claim_digest = ReceiptClaim::ok(image_id, journal.clone()).digest();
+        let seal = [selector.as_slice(), claim_digest.as_bytes()].concat();
+        let calldata = IBoundlessTransceiver::receiveMessageCall {
+            journalData: journal.clone().into(),
+            seal: seal.clone().into(),
+        }
+        .abi_encode();
+
+        Ok(Self {
+            image_id: B256::from_slice(image_id.as_bytes()),
+            message_digest: transceiver_message.digest(message.source_chain),
+            encoded_message,
+            commitment_id,
+            block_root: message.block_root,
+            journal: journal.into(),
+            journal_digest,
+            selector,
+            seal: seal.into(),
+            calldata: calldata.into(),
+        })
+    }
+
+    /// Renders the fixtures as a Solidity library of constants.
+    pub fn to_solidity(&self, library: &str) -> String {
+        let mut out = String::new();
+        writeln!(out, "// SPDX-License-Identifier: Apache-2.0").unwrap();
+        writeln!(out, "// Generated by `relay test-vectors`. Do not edit.").unwrap();
+        writeln!(out, "pragma solidity >=0.8.8 <0.9.0;").unwrap();
+        writeln!(out).unwrap();
+        writeln!(out, "library {library} {{").unwrap();
+        let words = [
+            ("IMAGE_ID", self.image_id),
+            ("MESSAGE_DIGEST", self.message_digest),
+            ("COMMITMENT_ID", self.commitment_id.into()),
+            ("BLOCK_ROOT", self.block_root),
+            ("JOURNAL_DIGEST", self.journal_digest),
+        ];
+        for (name, value) in words {
+            writeln!(out, "    bytes32 internal constant {name} = {value};").unwrap();
+        }
+        writeln!(
+            out,
+            "    bytes4 internal constant SELECTOR = {};",
+            self.selector
+        )
+        .unwrap();
+        let bytes = [
+            ("ENCODED_MESSAGE", &self.encoded_message),
+            ("JOURNAL", &self.journal),
+            ("SEAL", &self.seal),
+            ("CALLDATA", &self.calldata),
+        ];
+        for (name, value) in bytes {
+            writeln!(
+                out,
+                "    bytes internal constant {name} = hex\"{}\";",
+                alloy_primitives::hex::encode(value)
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}").unwrap();
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_consistent_fixtures() {
+        let image_id = Digest::from([7u32; 8]);
+        let selector = FixedBytes([0xff; 4]);
+        let vector = TestVector::new(&SyntheticMessage::default(), image_id, selector).unwrap();
+
+        let journal = Journal::abi_decode(&vector.journal).unwrap();
+        assert_eq!(journal.encodedMessage, vector.encoded_message);
+        assert_eq!(
+            journal.commitment.decode_id(),
+            (U256::from(1_234_567_890), 2)
+        );
+
+        let message = TransceiverMessage::parse(&vector.encoded_message).unwrap();
+        assert_eq!(message.source_chain(), Ok(666));
+        assert_eq!(message.transfer().unwrap().to_chain, 333);
+
+        assert_eq!(&vector.seal[..4], selector.as_slice());
+        assert_eq!(
+            &vector.seal[4..],
+            ReceiptClaim::ok(image_id, vector.journal.to_vec())
+                .digest()
+                .as_bytes()
+        );
+        let call = IBoundlessTransceiver::receiveMessageCall::abi_decode(&vector.calldata).unwrap();
+        assert_eq!(call.seal, vector.seal);
+
+        let solidity = vector.to_solidity("TestVectors");
+        assert!(solidity.contains(&format!(
+            "bytes32 internal constant IMAGE_ID = {};",
+            vector.image_id
+        )));
+    }
+}
@@ -0,0 +1,97 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of the destination chain's RISC Zero verifier deployment.
+
+use alloy::{providers::Provider, sol};
+use alloy_primitives::{Address, B256, FixedBytes};
+use anyhow::{Context, Result, ensure};
+use risc0_zkvm::Digest;
+use tracing::log;
+
+use crate::breaker::Anomaly;
+
+sol! {
+    #[sol(rpc)]
+    interface IRiscZeroVerifierRouter {
+        /// @notice Get the associated verifier, reverting if the selector is unknown or removed.
+        function getVerifier(bytes4 selector) external view returns (address);
+    }
+
+    #[sol(rpc)]
+    interface IRiscZeroVerifier {
+        /// @notice Verify that the given seal is a valid RISC Zero proof of execution with the
+        /// given image ID and journal digest. Reverts on failure.
+        function verify(bytes calldata seal, bytes32 imageId, bytes32 journalDigest) external view;
+    }
+
+    #[sol(rpc)]
+    interface IRiscZeroVerifierEmergencyStop {
+        /// @notice Returns true if the verifier has been stopped.
+        function paused() external view returns (bool);
+    }
+}
+
+/// Checks that the verifier router at `router` will route `seal` to a verifier that has not been
+/// removed or paused, so proofs are not submitted to a disabled verifier.
+pub async fn check_verifier(provider: impl Provider, router: Address, seal: &[u8]) -> Result<()> {
+    ensure!(seal.len() >= 4, "Seal is too short to contain a selector");
+    let selector = FixedBytes::<4>::from_slice(&seal[..4]);
+
+    let router = IRiscZeroVerifierRouter::new(router, &provider);
+    let verifier = router.getVerifier(selector).call().await.with_context(|| {
+        format!(
+            "Verifier disabled: router {} has no verifier for selector {selector}",
+            router.address()
+        )
+    })?;
+
+    // Verifiers registered with a router are wrapped in an emergency stop. If the call fails the
+    // verifier is not wrapped and can't be stopped.
+    let estop = IRiscZeroVerifierEmergencyStop::new(verifier, &provider);
+    match estop.paused().call().await {
+        Ok(true) => return Err(Anomaly::VerifierStopped { verifier }.into()),
+        Ok(false) => {}
+        Err(e) => log::warn!("Could not query emergency stop status of {verifier}: {e}"),
+    }
+
+    Ok(())
+}
+
+/// Verifies `seal` as a proof of `image_id` committing a journal with `journal_digest` through an
+/// `eth_call` to `verifier`, which is typically a router. Run after [check_verifier], a failure
+/// means the proof is bad rather than the verifier deployment.
+pub async fn verify_seal(
+    provider: impl Provider,
+    verifier: Address,
+    seal: &[u8],
+    image_id: Digest,
+    journal_digest: Digest,
+) -> Result<()> {
+    IRiscZeroVerifier::new(verifier, &provider)
+        .verify(
+            seal.to_vec().into(),
+            B256::from_slice(image_id.as_bytes()),
+            B256::from_slice(journal_digest.as_bytes()),
+        )
+        .call()
+        .await
+        .with_context(|| {
+            format!(
+                "Bad proof: verifier {verifier} rejects the seal for image ID {image_id} and \
+                 journal digest {journal_digest}"
+            )
+        })?;
+    Ok(())
+}
@@ -0,0 +1,153 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! What a relay binary was built from, so support can identify exactly what an operator is
+//! running.
+
+use std::fmt;
+
+use risc0_zkvm::Digest;
+use serde::Serialize;
+use zkvm::{GUEST_PROGRAMS, NTT_GUEST_CHAIN_ID, Transceiver};
+
+/// Version of the relay crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Commit the relay was built from, suffixed with `-dirty` for uncommitted changes, or `unknown`
+/// if it was built outside of a git checkout without `RELAY_GIT_COMMIT`.
+pub const GIT_COMMIT: &str = env!("RELAY_GIT_COMMIT");
+
+#[derive(Clone, Debug, Serialize)]
+pub struct VersionInfo {
+    pub version: &'static str,
+    pub git_commit: &'static str,
+    /// Version of the RISC Zero zkVM the guests were built and are proven with.
+    pub zkvm_version: &'static str,
+    /// Chain ID of the source chain selected with NTT_GUEST_CHAIN when the guests were built.
+    pub guest_chain_id: u64,
+    pub guests: Vec<GuestInfo>,
+    /// Chain specs inputs can be built with.
+    pub chain_specs: Vec<ChainSpecInfo>,
+}
+
+/// An embedded guest program.
+#[derive(Clone, Debug, Serialize)]
+pub struct GuestInfo {
+    pub name: &'static str,
+    /// Hex encoded image ID, as accepted by `--expected-image-id`.
+    pub image_id: String,
+    /// Chain ID of the source chain the guest was built for, or `None` if it takes the chain from
+    /// its input.
+    pub chain_id: Option<u64>,
+    /// Kind of source transceiver whose messages the guest proves.
+    pub transceiver: &'static str,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ChainSpecInfo {
+    pub name: &'static str,
+    /// `None` for the spec used for every other chain ID.
+    pub chain_id: Option<u64>,
+}
+
+impl VersionInfo {
+    /// Describes this binary.
+    pub fn current() -> Self {
+        let guests = GUEST_PROGRAMS
+            .iter()
+            .map(|guest| GuestInfo {
+                name: guest.name,
+                image_id: Digest::from(guest.image_id).to_string(),
+                chain_id: guest.chain_id,
+                transceiver: match guest.transceiver {
+                    Transceiver::Boundless => "boundless",
+                    Transceiver::Wormhole => "wormhole",
+                },
+            })
+            .collect();
+        // Mirrors the chains of `crate::chain_spec`.
+        let chain_specs = vec![
+            ChainSpecInfo {
+                name: "mainnet",
+                chain_id: Some(1),
+            },
+            ChainSpecInfo {
+                name: "sepolia",
+                chain_id: Some(11155111),
+            },
+            ChainSpecInfo {
+                name: "custom-spec",
+                chain_id: None,
+            },
+        ];
+        Self {
+            version: VERSION,
+            git_commit: GIT_COMMIT,
+            zkvm_version: risc0_zkvm::VERSION,
+            guest_chain_id: NTT_GUEST_CHAIN_ID,
+            guests,
+            chain_specs,
+        }
+    }
+
+    /// The one line summary printed by `relay version` without `--verbose`.
+    pub fn summary(&self) -> String {
+        format!("relay {} ({})", self.version, self.git_commit)
+    }
+}
+
+impl fmt::Display for VersionInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.summary())?;
+        writeln!(f, "zkVM: {}", self.zkvm_version)?;
+        writeln!(f, "Guest chain ID: {}", self.guest_chain_id)?;
+        writeln!(f, "Guests:")?;
+        for guest in &self.guests {
+            let chain = guest
+                .chain_id
+                .map_or("from input".to_string(), |id| id.to_string());
+            writeln!(
+                f,
+                "  {} {} (chain {chain}, {} transceiver)",
+                guest.name, guest.image_id, guest.transceiver
+            )?;
+        }
+        writeln!(f, "Chain specs:")?;
+        for spec in &self.chain_specs {
+            match spec.chain_id {
+                Some(chain_id) => writeln!(f, "  {} ({chain_id})", spec.name)?,
+                None => writeln!(
+                    f,
+                    "  {} (chains of the config or of guests built for them, single fork)",
+                    spec.name
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_embedded_guests() {
+        let info = VersionInfo::current();
+        assert_eq!(info.guests.len(), GUEST_PROGRAMS.len());
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["version"], VERSION);
+        assert!(info.to_string().contains(zkvm::NTT_MESSAGE_INCLUSION.name));
+    }
+}
@@ -0,0 +1,396 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Discovery of messages sent by source transceivers.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+use alloy::eips::BlockNumberOrTag;
+use alloy::providers::Provider;
+use alloy::rpc::types::Filter;
+use alloy_primitives::{Address, B256, Bytes, Log, TxHash};
+use alloy_sol_types::SolEvent;
+use anyhow::{Context, Result, ensure};
+use common::IBoundlessTransceiver::SendTransceiverMessage;
+use common::ntt::{IWormholeTransceiver, MessageEvent};
+use serde::{Deserialize, Serialize};
+use tracing::log;
+
+use crate::config::{Finality, WatchFilter};
+use crate::job::MessageId;
+use crate::rpc::EndpointPool;
+use crate::store::JobStore;
+
+/// Largest block range of a single `eth_getLogs` request. Providers limiting ranges further are
+/// queried in smaller chunks.
+const MAX_LOG_RANGE_BLOCKS: u64 = 10_000;
+
+/// Number of scanned blocks whose hashes are kept to find where to resume after a reorg.
+const SCANNED_BLOCKS: usize = 256;
+
+/// A message found in a final block.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ObservedMessage {
+    pub id: MessageId,
+    /// The source transceiver that emitted the message.
+    pub source: Address,
+    pub block_number: u64,
+    /// Wormhole chain ID the message is sent to.
+    pub recipient_chain: u16,
+    /// The encoded transceiver message.
+    pub encoded_message: Bytes,
+}
+
+/// Position of a watcher on the source chain, persisted so a restarted watcher resumes where
+/// it stopped.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The next block to scan.
+    pub next_block: u64,
+    /// Number and hash of the last block of each recent scan, oldest first.
+    pub scanned: VecDeque<(u64, B256)>,
+}
+
+/// Scans final source chain blocks for `SendTransceiverMessage` events of a set of source
+/// transceivers, which may be BoundlessTransceivers or standard WormholeTransceivers.
+pub struct Watcher {
+    rpc: Arc<EndpointPool>,
+    sources: Vec<Address>,
+    finality: Finality,
+    filter: WatchFilter,
+    checkpoint: Checkpoint,
+    store: Option<Arc<dyn JobStore>>,
+}
+
+impl Watcher {
+    /// Creates a watcher scanning blocks once they are final under `finality`, from
+    /// `start_block`, or from the latest final block if `None`.
+    pub async fn new(
+        rpc: Arc<EndpointPool>,
+        sources: Vec<Address>,
+        finality: Finality,
+        start_block: Option<u64>,
+    ) -> Result<Self> {
+        let next_block = match start_block {
+            Some(block) => block,
+            None => finalized_block(&rpc, finality).await?,
+        };
+        Ok(Self {
+            rpc,
+            sources,
+            finality,
+            filter: WatchFilter::default(),
+            checkpoint: Checkpoint {
+                next_block,
+                scanned: VecDeque::new(),
+            },
+            store: None,
+        })
+    }
+
+    /// Persists the position of the watcher to `store` on every [`Self::save`], resuming from the
+    /// position last saved there, if any, instead of the start block.
+    pub fn with_checkpoints(mut self, store: Arc<dyn JobStore>) -> Result<Self> {
+        if let Some(checkpoint) = store.load_checkpoint()? {
+            log::info!(
+                "Resuming from checkpoint at block {}",
+                checkpoint.next_block
+            );
+            self.checkpoint = checkpoint;
+        }
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Only yields the messages matching `filter`.
+    pub fn with_filter(mut self, filter: WatchFilter) -> Self {
+        self.sources
+            .retain(|source| filter.sources.is_empty() || filter.sources.contains(source));
+        self.filter = filter;
+        self
+    }
+
+    /// The next block that will be scanned.
+    pub fn next_block(&self) -> u64 {
+        self.checkpoint.next_block
+    }
+
+    /// Returns the messages in blocks that became final since the last poll, along with the
+    /// latest final block, which can be used as their commitment block. Blocks of earlier polls
+    /// that were reorged out are scanned again once their replacements are final.
+    pub async fn poll(&mut self) -> Result<(Vec<ObservedMessage>, u64)> {
+        let finalized = finalized_block(&self.rpc, self.finality).await?;
+        self.rewind_reorged().await?;
+        if finalized < self.checkpoint.next_block {
+            if finalized + 1 < self.checkpoint.next_block {
+                log::warn!(
+                    "Final block {finalized} is behind the scanned blocks up to {}",
+                    self.checkpoint.next_block - 1
+                );
+            }
+            return Ok((Vec::new(), finalized));
+        }
+
+        let hash = block_hash(&self.rpc, finalized).await?;
+        let messages = self
+            .scan_range(self.checkpoint.next_block, finalized)
+            .await?;
+        // The logs may be of a block reorged out while scanning, whose messages would be missed
+        ensure!(
+            block_hash(&self.rpc, finalized).await? == hash,
+            "block {finalized} was reorged while scanning"
+        );
+        self.checkpoint.next_block = finalized + 1;
+        if let Some(hash) = hash {
+            if self.checkpoint.scanned.len() == SCANNED_BLOCKS {
+                self.checkpoint.scanned.pop_front();
+            }
+            self.checkpoint.scanned.push_back((finalized, hash));
+        }
+        Ok((messages, finalized))
+    }
+
+    /// Persists the position of the watcher, if it has a store. Called once the messages of a
+    /// poll are handed on, so that a restart neither skips nor scans again any block.
+    pub fn save(&self) -> Result<()> {
+        match &self.store {
+            Some(store) => store.save_checkpoint(&self.checkpoint),
+            None => Ok(()),
+        }
+    }
+
+    /// Moves back to the block after the last scanned block still on the canonical chain, if
+    /// any scanned block was reorged out.
+    async fn rewind_reorged(&mut self) -> Result<()> {
+        let mut rewound = None;
+        while let Some(&(number, hash)) = self.checkpoint.scanned.back() {
+            if block_hash(&self.rpc, number).await? == Some(hash) {
+                break;
+            }
+            self.checkpoint.scanned.pop_back();
+            rewound = Some(number);
+        }
+        let Some(reorged) = rewound else {
+            return Ok(());
+        };
+        self.checkpoint.next_block = match self.checkpoint.scanned.back() {
+            Some(&(number, _)) => number + 1,
+            None => {
+                // Blocks before the oldest known scan are assumed intact
+                log::error!(
+                    "Reorg deeper than the last {SCANNED_BLOCKS} scans, rescanning from block \
+                     {reorged}; use a catch-up range to rescan earlier blocks"
+                );
+                reorged
+            }
+        };
+        log::warn!(
+            "Scanned block {reorged} was reorged, rescanning from block {}",
+            self.checkpoint.next_block
+        );
+        Ok(())
+    }
+
+    /// Returns the matching messages between `from_block` and `to_block` inclusive, without
+    /// moving the watcher.
+    pub async fn scan_range(&self, from_block: u64, to_block: u64) -> Result<Vec<ObservedMessage>> {
+        let mut messages = scan(&self.rpc, &self.sources, from_block, to_block).await?;
+        messages.retain(|message| self.filter.matches(message.source, message.recipient_chain));
+        Ok(messages)
+    }
+}
+
+/// Returns the messages the `sources` emitted between `from_block` and `to_block` inclusive.
+pub async fn scan(
+    rpc: &EndpointPool,
+    sources: &[Address],
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<ObservedMessage>> {
+    let filter = Filter::new()
+        .address(sources.to_vec())
+        .event_signature(vec![
+            SendTransceiverMessage::SIGNATURE_HASH,
+            IWormholeTransceiver::SendTransceiverMessage::SIGNATURE_HASH,
+        ]);
+    let logs = get_logs(rpc, &filter, from_block, to_block).await?;
+
+    // Number the messages of each transaction per source transceiver, as `build_input` finds
+    // them in the transaction receipt
+    let mut counts: HashMap<(TxHash, Address), usize> = HashMap::new();
+    let mut messages = Vec::with_capacity(logs.len());
+    for log in logs {
+        let tx_hash = log
+            .transaction_hash
+            .context("log without transaction hash")?;
+        let block_number = log.block_number.context("log without block number")?;
+        let source = log.address();
+        let (recipient_chain, encoded_message) = match decode_message(&log.inner) {
+            Ok(message) => message,
+            Err(e) => {
+                log::warn!("Skipping invalid SendTransceiverMessage event in {tx_hash}: {e}");
+                continue;
+            }
+        };
+        let index = counts.entry((tx_hash, source)).or_default();
+        messages.push(ObservedMessage {
+            id: MessageId {
+                tx_hash,
+                index: *index,
+            },
+            source,
+            block_number,
+            recipient_chain,
+            encoded_message,
+        });
+        *index += 1;
+    }
+
+    Ok(messages)
+}
+
+/// Fetches the logs matching `filter` between `from_block` and `to_block` inclusive. The range is
+/// queried in chunks, halving a chunk the provider refuses as too large and doubling the next
+/// one after a success, so that large ranges can be scanned through providers capping the range
+/// or the number of results of `eth_getLogs`.
+pub(crate) async fn get_logs(
+    rpc: &EndpointPool,
+    filter: &Filter,
+    from_block: u64,
+    to_block: u64,
+) -> Result<Vec<alloy::rpc::types::Log>> {
+    let mut logs = Vec::new();
+    let mut chunk = MAX_LOG_RANGE_BLOCKS;
+    let mut from = from_block;
+    while from <= to_block {
+        let to = to_block.min(from.saturating_add(chunk - 1));
+        let filter = filter.clone().from_block(from).to_block(to);
+        // A refused range is not a failure of the endpoint, so it does not count against its
+        // health
+        let page = rpc
+            .run(|endpoint| {
+                let filter = filter.clone();
+                async move {
+                    match endpoint.provider().get_logs(&filter).await {
+                        Ok(logs) => Ok(Some(logs)),
+                        Err(e) if is_range_limited(&e.to_string()) => Ok(None),
+                        Err(e) => Err(e.into()),
+                    }
+                }
+            })
+            .await?;
+        match page {
+            Some(page) => {
+                logs.extend(page);
+                from = to + 1;
+                chunk = MAX_LOG_RANGE_BLOCKS.min(chunk * 2);
+            }
+            None => {
+                ensure!(
+                    to > from,
+                    "provider refuses the logs of block {from} as too many"
+                );
+                chunk = (to - from).div_ceil(2);
+                log::debug!("Log range {from}-{to} refused, retrying with {chunk} blocks");
+            }
+        }
+    }
+    Ok(logs)
+}
+
+/// Whether an `eth_getLogs` error is a provider refusing the block range or the number of logs
+/// as too large, rather than a failure.
+fn is_range_limited(error: &str) -> bool {
+    const MESSAGES: [&str; 8] = [
+        "more than",
+        "too many",
+        "response size",
+        "block range",
+        "range too",
+        "limit exceeded",
+        "is limited to",
+        "exceed maximum",
+    ];
+    let error = error.to_lowercase();
+    MESSAGES.iter().any(|message| error.contains(message))
+}
+
+/// Decodes the recipient chain and encoded message of either transceiver's event.
+pub(crate) fn decode_message(log: &Log) -> alloy_sol_types::Result<(u16, Bytes)> {
+    fn decode<E: MessageEvent>(log: &Log) -> alloy_sol_types::Result<(u16, Bytes)> {
+        let event = E::decode_log(log)?;
+        Ok((event.recipient_chain(), event.encoded_message()))
+    }
+
+    if log.topics().first() == Some(&SendTransceiverMessage::SIGNATURE_HASH) {
+        decode::<SendTransceiverMessage>(log)
+    } else {
+        decode::<IWormholeTransceiver::SendTransceiverMessage>(log)
+    }
+}
+
+/// The hash of block `number`, or `None` if the chain does not have that block.
+async fn block_hash(rpc: &EndpointPool, number: u64) -> Result<Option<B256>> {
+    rpc.run(|endpoint| async move {
+        let block = endpoint
+            .provider()
+            .get_block_by_number(BlockNumberOrTag::Number(number))
+            .await?;
+        Ok(block.map(|block| block.header.hash))
+    })
+    .await
+}
+
+/// The number of the latest block that is final under `finality`.
+pub async fn finalized_block(rpc: &EndpointPool, finality: Finality) -> Result<u64> {
+    let tag = match finality {
+        Finality::Finalized => BlockNumberOrTag::Finalized,
+        Finality::Safe => BlockNumberOrTag::Safe,
+        Finality::Confirmations(_) => BlockNumberOrTag::Latest,
+    };
+    let number = rpc
+        .run(|endpoint| async move {
+            let block = endpoint
+                .provider()
+                .get_block_by_number(tag)
+                .await?
+                .with_context(|| format!("no {tag} block"))?;
+            Ok(block.header.number)
+        })
+        .await?;
+    Ok(match finality {
+        Finality::Confirmations(confirmations) => number.saturating_sub(confirmations),
+        _ => number,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_range_limits() {
+        assert!(is_range_limited(
+            "server returned an error response: error code -32005: query returned more than 10000 \
+             results"
+        ));
+        assert!(is_range_limited(
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block \
+             range"
+        ));
+        assert!(is_range_limited("eth_getLogs is limited to a 10,000 range"));
+        assert!(!is_range_limited("error sending request for url"));
+    }
+}
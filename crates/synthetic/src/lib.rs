@@ -0,0 +1,327 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Synthetic blocks served by an in-process JSON-RPC stub, for fast guest tests of edge cases
+//! without spinning up a node, and for the self-test of the relay at startup.
+//!
+//! A [SyntheticBlock] holds chosen logs in the receipts of its transactions, with a header whose
+//! receipts root commits to them, so the inputs Steel builds from it verify in the guest like
+//! those of a real chain.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use alloy::{
+    consensus::{
+        EMPTY_ROOT_HASH, Eip658Value, Header, Receipt, ReceiptEnvelope, ReceiptWithBloom,
+        TxReceipt, proofs,
+    },
+    eips::eip7685::EMPTY_REQUESTS_HASH,
+    primitives::{Address, B256, Bytes, KECCAK256_EMPTY, Log, U256, keccak256, logs_bloom},
+    sol,
+    sol_types::SolEvent,
+    transports::http::reqwest::Url,
+};
+use anyhow::Result;
+use axum::{Json, Router, extract::State, routing::post};
+use risc0_steel::{
+    Event,
+    ethereum::{ETH_MAINNET_CHAIN_SPEC, EthChainSpec, EthEvmEnv, EthEvmInput},
+};
+use serde_json::{Value, json};
+
+sol! {
+    /// The event of a BoundlessTransceiver sending a message.
+    event SendTransceiverMessage(uint16 indexed recipientChain, bytes encodedMessage);
+}
+
+/// Gas each synthetic transaction is accounted to use.
+const GAS_PER_TRANSACTION: u64 = 50_000;
+
+/// A block with chosen logs, on mainnet unless another chain spec is chosen.
+#[derive(Clone, Debug)]
+pub struct SyntheticBlock {
+    /// Chain spec inputs are built with, whose chain ID the stub reports.
+    chain_spec: &'static EthChainSpec,
+    number: u64,
+    timestamp: u64,
+    /// The logs emitted by each transaction of the block.
+    transactions: Vec<Vec<Log>>,
+}
+
+impl Default for SyntheticBlock {
+    fn default() -> Self {
+        Self {
+            chain_spec: &ETH_MAINNET_CHAIN_SPEC,
+            number: 1,
+            // After the Prague upgrade of mainnet
+            timestamp: 1_750_000_000,
+            transactions: Vec::new(),
+        }
+    }
+}
+
+impl SyntheticBlock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds inputs with `chain_spec`. The timestamp must fall after its Prague upgrade.
+    pub fn chain_spec(mut self, chain_spec: &'static EthChainSpec) -> Self {
+        self.chain_spec = chain_spec;
+        self
+    }
+
+    pub fn number(mut self, number: u64) -> Self {
+        self.number = number;
+        self
+    }
+
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// Adds a transaction emitting `logs`.
+    pub fn transaction(mut self, logs: Vec<Log>) -> Self {
+        self.transactions.push(logs);
+        self
+    }
+
+    /// Adds a transaction in which `emitter` emits `SendTransceiverMessage` with `message`.
+    pub fn message(self, emitter: Address, recipient_chain: u16, message: Bytes) -> Self {
+        let event = SendTransceiverMessage {
+            recipientChain: recipient_chain,
+            encodedMessage: message,
+        };
+        self.transaction(vec![Log {
+            address: emitter,
+            data: event.encode_log_data(),
+        }])
+    }
+
+    /// Builds the Steel input of the block with the `SendTransceiverMessage` events of `emitter`
+    /// preflighted, as `TestChain::preflight_messages` of the testkit does for a deployed
+    /// emitter.
+    pub async fn preflight_messages(&self, emitter: Address) -> Result<EthEvmInput> {
+        let mut env = EthEvmEnv::builder()
+            .rpc(self.serve().await?)
+            .chain_spec(self.chain_spec)
+            .build()
+            .await?;
+        Event::preflight::<SendTransceiverMessage>(&mut env)
+            .address(emitter)
+            .query()
+            .await?;
+        Ok(env.into_input().await?)
+    }
+
+    /// Serves the block from a JSON-RPC stub on a local port for as long as the process runs,
+    /// returning its URL. The stub answers the requests Steel makes to build inputs.
+    pub async fn serve(&self) -> Result<Url> {
+        let listener = tokio::net::TcpListener::bind(SocketAddr::from(([127, 0, 0, 1], 0))).await?;
+        let url = format!("http://{}/", listener.local_addr()?).parse()?;
+        let router = Router::new()
+            .route("/", post(handle))
+            .with_state(Arc::new(Stub::new(self)?));
+        tokio::spawn(async move { axum::serve(listener, router).await });
+        Ok(url)
+    }
+
+    fn receipts(&self) -> Vec<ReceiptEnvelope> {
+        self.transactions
+            .iter()
+            .enumerate()
+            .map(|(i, logs)| {
+                let receipt = Receipt {
+                    status: Eip658Value::Eip658(true),
+                    cumulative_gas_used: GAS_PER_TRANSACTION * (i as u64 + 1),
+                    logs: logs.clone(),
+                };
+                ReceiptEnvelope::Eip1559(ReceiptWithBloom::new(receipt, logs_bloom(logs)))
+            })
+            .collect()
+    }
+
+    fn header(&self, receipts: &[ReceiptEnvelope]) -> Header {
+        Header {
+            number: self.number,
+            timestamp: self.timestamp,
+            parent_hash: keccak256(self.number.saturating_sub(1).to_be_bytes()),
+            state_root: EMPTY_ROOT_HASH,
+            receipts_root: proofs::calculate_receipt_root(receipts),
+            logs_bloom: logs_bloom(self.transactions.iter().flatten()),
+            gas_limit: 30_000_000,
+            gas_used: GAS_PER_TRANSACTION * self.transactions.len() as u64,
+            base_fee_per_gas: Some(1),
+            withdrawals_root: Some(EMPTY_ROOT_HASH),
+            blob_gas_used: Some(0),
+            excess_blob_gas: Some(0),
+            parent_beacon_block_root: Some(B256::ZERO),
+            requests_hash: Some(EMPTY_REQUESTS_HASH),
+            ..Default::default()
+        }
+    }
+}
+
+/// The JSON-RPC responses describing a [SyntheticBlock].
+struct Stub {
+    chain_id: u64,
+    number: u64,
+    block: Value,
+    receipts: Value,
+}
+
+impl Stub {
+    fn new(synthetic: &SyntheticBlock) -> Result<Self> {
+        let receipts = synthetic.receipts();
+        let header = synthetic.header(&receipts);
+        let hash = header.hash_slow();
+        let tx_hashes: Vec<B256> = (0..receipts.len())
+            .map(|i| keccak256([hash.as_slice(), &(i as u64).to_be_bytes()].concat()))
+            .collect();
+
+        let mut block = serde_json::to_value(&header)?;
+        block["hash"] = json!(hash);
+        block["transactions"] = json!(tx_hashes);
+        block["uncles"] = json!([]);
+        block["withdrawals"] = json!([]);
+        block["size"] = json!("0x0");
+
+        let mut log_index = 0u64;
+        let receipts = receipts
+            .iter()
+            .zip(&tx_hashes)
+            .enumerate()
+            .map(|(i, (receipt, tx_hash))| {
+                let logs: Vec<Value> = receipt
+                    .logs()
+                    .iter()
+                    .map(|log| {
+                        let log = json!({
+                            "address": log.address,
+                            "topics": log.topics(),
+                            "data": log.data.data,
+                            "blockHash": hash,
+                            "blockNumber": format!("{:#x}", synthetic.number),
+                            "blockTimestamp": format!("{:#x}", synthetic.timestamp),
+                            "transactionHash": tx_hash,
+                            "transactionIndex": format!("{i:#x}"),
+                            "logIndex": format!("{log_index:#x}"),
+                            "removed": false,
+                        });
+                        log_index += 1;
+                        log
+                    })
+                    .collect();
+                json!({
+                    "type": "0x2",
+                    "status": "0x1",
+                    "cumulativeGasUsed": format!("{:#x}", receipt.cumulative_gas_used()),
+                    "logs": logs,
+                    "logsBloom": receipt.bloom(),
+                    "transactionHash": tx_hash,
+                    "transactionIndex": format!("{i:#x}"),
+                    "blockHash": hash,
+                    "blockNumber": format!("{:#x}", synthetic.number),
+                    "gasUsed": format!("{GAS_PER_TRANSACTION:#x}"),
+                    "effectiveGasPrice": "0x1",
+                    "from": Address::ZERO,
+                    "to": Address::ZERO,
+                    "contractAddress": null,
+                })
+            })
+            .collect();
+
+        Ok(Self {
+            chain_id: synthetic.chain_spec.chain_id,
+            number: synthetic.number,
+            block,
+            receipts: Value::Array(receipts),
+        })
+    }
+
+    /// The logs matching an `eth_getLogs` filter, by address and first topic only.
+    fn logs(&self, filter: &Value) -> Value {
+        let matches = |criterion: &Value, value: &Value| match criterion {
+            Value::Null => true,
+            Value::Array(options) => options.is_empty() || options.contains(value),
+            criterion => criterion == value,
+        };
+        let topic0 = &filter["topics"][0];
+        let logs = self
+            .receipts
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|receipt| receipt["logs"].as_array().into_iter().flatten())
+            .filter(|log| {
+                matches(&filter["address"], &log["address"]) && matches(topic0, &log["topics"][0])
+            })
+            .cloned()
+            .collect();
+        Value::Array(logs)
+    }
+
+    fn answer(&self, method: &str, params: &Value) -> Result<Value, String> {
+        match method {
+            "eth_chainId" => Ok(json!(format!("{:#x}", self.chain_id))),
+            "eth_blockNumber" => Ok(json!(format!("{:#x}", self.number))),
+            "eth_getBlockByNumber" | "eth_getBlockByHash" => Ok(self.block.clone()),
+            "eth_getBlockReceipts" => Ok(self.receipts.clone()),
+            "eth_getLogs" => Ok(self.logs(&params[0])),
+            // Every account is absent from the empty state
+            "eth_getProof" => Ok(json!({
+                "address": params[0],
+                "balance": "0x0",
+                "codeHash": KECCAK256_EMPTY,
+                "nonce": "0x0",
+                "storageHash": EMPTY_ROOT_HASH,
+                "accountProof": [],
+                "storageProof": params[1]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|key| json!({"key": key, "value": "0x0", "proof": []}))
+                    .collect::<Vec<_>>(),
+            })),
+            "eth_getCode" => Ok(json!("0x")),
+            "eth_getStorageAt" => Ok(json!(B256::ZERO)),
+            "eth_getBalance" => Ok(json!(U256::ZERO)),
+            method => Err(format!(
+                "method {method} is not supported by the synthetic block"
+            )),
+        }
+    }
+
+    fn respond(&self, request: &Value) -> Value {
+        let id = request["id"].clone();
+        let method = request["method"].as_str().unwrap_or_default();
+        match self.answer(method, &request["params"]) {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+            Err(message) => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": -32601, "message": message},
+            }),
+        }
+    }
+}
+
+async fn handle(State(stub): State<Arc<Stub>>, Json(request): Json<Value>) -> Json<Value> {
+    Json(match &request {
+        Value::Array(batch) => batch.iter().map(|request| stub.respond(request)).collect(),
+        request => stub.respond(request),
+    })
+}
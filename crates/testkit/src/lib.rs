@@ -0,0 +1,356 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fixtures for tests against a local anvil chain: spawning the chain, deploying a contract
+//! emitting `SendTransceiverMessage` events like a source transceiver, emitting messages and
+//! building the Steel input that proves them.
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! let chain = testkit::TestChain::spawn();
+//! let emitter = chain.deploy_emitter().await?;
+//! emitter.emit(3, "Some message".into()).await?;
+//! let input = chain.preflight_messages(emitter.address()).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Guest tests of edge cases that need no node build their inputs from a
+//! [synthetic::SyntheticBlock] instead.
+
+pub use synthetic;
+
+use alloy::{
+    network::EthereumWallet,
+    node_bindings::{Anvil, AnvilInstance},
+    primitives::{Address, B256, Bytes},
+    providers::{DynProvider, Provider, ProviderBuilder},
+    rpc::types::TransactionReceipt,
+    signers::local::PrivateKeySigner,
+    sol,
+    transports::http::reqwest::Url,
+};
+use anyhow::Result;
+use risc0_steel::{
+    Contract, Event,
+    ethereum::{ETH_MAINNET_CHAIN_SPEC, EthEvmEnv, EthEvmInput},
+};
+
+// A minimal contract that emits a `SendTransceiverMessage` event when `emitEvent` is called, with
+// the recipient chain indexed as the BoundlessTransceiver does. Hand-assembled: it copies the
+// encoded message from the calldata, which is laid out as the data of the event but for the offset
+// of the message, and emits it, whatever function is called.
+sol! {
+#[sol(rpc, bytecode="603a80600b6000396000f360206000526044360360446020376004357f0d4a24add37c1972207e3dcfa8359764948caf868db363ee8fa1cb7f55f0a74c602436036000a200")]
+contract SendTransceiverMessageEmitter {
+      event SendTransceiverMessage(
+          uint16 indexed recipientChain, bytes encodedMessage
+      );
+
+      function emitEvent(uint16 recipientChain, bytes calldata encodedMessage) external;
+    }
+}
+
+/// Next message sequence reported by the NTT manager of [TestChain::deploy_ntt_manager].
+pub const MOCK_NEXT_MESSAGE_SEQUENCE: u64 = 42;
+
+/// Outbound capacity reported by the NTT manager of [TestChain::deploy_ntt_manager].
+pub const MOCK_OUTBOUND_CAPACITY: u64 = 1000;
+
+/// Balance of the NTT manager of [TestChain::deploy_ntt_manager] in its token.
+pub const MOCK_MANAGER_BALANCE: u64 = 5000;
+
+/// Total supply of the token of the NTT manager of [TestChain::deploy_ntt_manager].
+pub const MOCK_TOTAL_SUPPLY: u64 = 1_000_000;
+
+/// Peer of the NTT manager of [TestChain::deploy_ntt_manager] on every chain.
+pub const MOCK_PEER: B256 = B256::repeat_byte(0x01);
+
+/// Token decimals of [MOCK_PEER].
+pub const MOCK_PEER_DECIMALS: u8 = 8;
+
+// An NTT manager in locking mode answering the queries of the manager state, token backing and
+// peer registration guests with the constants above. It is its own token, so a single contract
+// serves all of them, and its only state is the single transceiver registered with
+// `registerTransceiver`. Hand-assembled, as it does little more than return constants: it
+// dispatches on the selector, stores the result in memory and returns it, reverting for any other
+// call.
+sol! {
+#[sol(rpc, bytecode="60ff80600b6000396000f360003560e01c806323d75e311461006d578063f5cfec1814610078578063fc0c546a146100845780634b4fd03b1461008e57806370a082311461009957806318160ddd146100a5578063cce72edd146100b2578063b4d591bb146100ba578063c128d170146100d057600080fd5b602a60005260206000f35b6103e860005260206000f35b3060005260206000f35b600060005260206000f35b61138860005260206000f35b620f424060005260206000f35b600435600055005b6020600052600160205260005460405260606000f35b7f0101010101010101010101010101010101010101010101010101010101010101600052600860205260406000f3")]
+contract MockNttManager {
+      function nextMessageSequence() external view returns (uint64);
+
+      function getCurrentOutboundCapacity() external view returns (uint256);
+
+      function token() external view returns (address);
+
+      function getMode() external view returns (uint8);
+
+      function balanceOf(address account) external view returns (uint256);
+
+      function totalSupply() external view returns (uint256);
+
+      function registerTransceiver(address transceiver) external;
+
+      function getTransceivers() external view returns (address[] memory);
+
+      struct NttManagerPeer {
+          bytes32 peerAddress;
+          uint8 tokenDecimals;
+      }
+
+      function getPeer(uint16 chainId) external view returns (NttManagerPeer memory);
+}
+}
+
+/// A local anvil chain along with a provider paying from its first funded account. The chain ID
+/// is 1, so inputs are built with the mainnet chain spec the default guest expects.
+pub struct TestChain {
+    anvil: AnvilInstance,
+    provider: DynProvider,
+}
+
+impl TestChain {
+    /// Spawns anvil, which must be installed.
+    pub fn spawn() -> Self {
+        let anvil = Anvil::new().chain_id(1).spawn();
+        let signer = PrivateKeySigner::from(anvil.keys()[0].clone());
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_http(anvil.endpoint_url())
+            .erased();
+        Self { anvil, provider }
+    }
+
+    pub fn endpoint_url(&self) -> Url {
+        self.anvil.endpoint_url()
+    }
+
+    pub fn provider(&self) -> &DynProvider {
+        &self.provider
+    }
+
+    /// Deploys a fresh emitter, whose events are the only ones at its address.
+    pub async fn deploy_emitter(&self) -> Result<Emitter> {
+        let contract = SendTransceiverMessageEmitter::deploy(self.provider.clone()).await?;
+        Ok(Emitter { contract })
+    }
+
+    /// Deploys an NTT manager reporting [MOCK_NEXT_MESSAGE_SEQUENCE] and [MOCK_OUTBOUND_CAPACITY],
+    /// returning its address, which is also the address of its token.
+    pub async fn deploy_ntt_manager(&self) -> Result<Address> {
+        let contract = MockNttManager::deploy(self.provider.clone()).await?;
+        Ok(*contract.address())
+    }
+
+    /// Makes `transceiver` the transceiver enabled on the NTT `manager` of
+    /// [Self::deploy_ntt_manager].
+    pub async fn register_transceiver(&self, manager: Address, transceiver: Address) -> Result<()> {
+        MockNttManager::new(manager, self.provider.clone())
+            .registerTransceiver(transceiver)
+            .send()
+            .await?
+            .get_receipt()
+            .await?;
+        Ok(())
+    }
+
+    /// Builds the Steel input of the latest block with the `SendTransceiverMessage` events of
+    /// `emitter` in it preflighted, as the host does for a source transceiver.
+    pub async fn preflight_messages(&self, emitter: Address) -> Result<EthEvmInput> {
+        let mut env = EthEvmEnv::builder()
+            .rpc(self.endpoint_url())
+            .chain_spec(&ETH_MAINNET_CHAIN_SPEC)
+            .build()
+            .await?;
+        Event::preflight::<SendTransceiverMessageEmitter::SendTransceiverMessage>(&mut env)
+            .address(emitter)
+            .query()
+            .await?;
+        Ok(env.into_input().await?)
+    }
+
+    /// Like [Self::preflight_messages], also preflighting the state queries of the NTT `manager`,
+    /// as the host does for the manager state guest.
+    pub async fn preflight_manager_state(
+        &self,
+        emitter: Address,
+        manager: Address,
+    ) -> Result<EthEvmInput> {
+        let mut env = EthEvmEnv::builder()
+            .rpc(self.endpoint_url())
+            .chain_spec(&ETH_MAINNET_CHAIN_SPEC)
+            .build()
+            .await?;
+        Event::preflight::<SendTransceiverMessageEmitter::SendTransceiverMessage>(&mut env)
+            .address(emitter)
+            .query()
+            .await?;
+        let mut contract = Contract::preflight(manager, &mut env);
+        contract
+            .call_builder(&MockNttManager::nextMessageSequenceCall {})
+            .call()
+            .await?;
+        contract
+            .call_builder(&MockNttManager::getCurrentOutboundCapacityCall {})
+            .call()
+            .await?;
+        Ok(env.into_input().await?)
+    }
+
+    /// Like [Self::preflight_messages], also preflighting the queries of the token holdings of
+    /// the NTT `manager`, as the host does for the token backing guest.
+    pub async fn preflight_token_backing(
+        &self,
+        emitter: Address,
+        manager: Address,
+    ) -> Result<EthEvmInput> {
+        let mut env = EthEvmEnv::builder()
+            .rpc(self.endpoint_url())
+            .chain_spec(&ETH_MAINNET_CHAIN_SPEC)
+            .build()
+            .await?;
+        Event::preflight::<SendTransceiverMessageEmitter::SendTransceiverMessage>(&mut env)
+            .address(emitter)
+            .query()
+            .await?;
+        let mut contract = Contract::preflight(manager, &mut env);
+        contract
+            .call_builder(&MockNttManager::tokenCall {})
+            .call()
+            .await?;
+        contract
+            .call_builder(&MockNttManager::getModeCall {})
+            .call()
+            .await?;
+        contract
+            .call_builder(&MockNttManager::balanceOfCall { account: manager })
+            .call()
+            .await?;
+        contract
+            .call_builder(&MockNttManager::totalSupplyCall {})
+            .call()
+            .await?;
+        Ok(env.into_input().await?)
+    }
+
+    /// Like [Self::preflight_messages], also preflighting the queries of the registration of the
+    /// NTT `manager`, as the host does for the peer registration guest.
+    pub async fn preflight_peer_registration(
+        &self,
+        emitter: Address,
+        manager: Address,
+        recipient_chain: u16,
+    ) -> Result<EthEvmInput> {
+        let mut env = EthEvmEnv::builder()
+            .rpc(self.endpoint_url())
+            .chain_spec(&ETH_MAINNET_CHAIN_SPEC)
+            .build()
+            .await?;
+        Event::preflight::<SendTransceiverMessageEmitter::SendTransceiverMessage>(&mut env)
+            .address(emitter)
+            .query()
+            .await?;
+        let mut contract = Contract::preflight(manager, &mut env);
+        contract
+            .call_builder(&MockNttManager::getTransceiversCall {})
+            .call()
+            .await?;
+        contract
+            .call_builder(&MockNttManager::getPeerCall {
+                chainId: recipient_chain,
+            })
+            .call()
+            .await?;
+        Ok(env.into_input().await?)
+    }
+
+    /// Builds the Steel inputs of the latest `blocks` blocks with the `SendTransceiverMessage`
+    /// events of `emitter` preflighted, as the host does for the block range guest: the input of
+    /// the latest block and those of its ancestors, newest first.
+    pub async fn preflight_range(
+        &self,
+        emitter: Address,
+        blocks: u64,
+    ) -> Result<(EthEvmInput, Vec<EthEvmInput>)> {
+        let latest = self.provider.get_block_number().await?;
+        let mut inputs = Vec::new();
+        for number in (latest + 1 - blocks..=latest).rev() {
+            let mut env = EthEvmEnv::builder()
+                .rpc(self.endpoint_url())
+                .block_number(number)
+                .chain_spec(&ETH_MAINNET_CHAIN_SPEC)
+                .build()
+                .await?;
+            Event::preflight::<SendTransceiverMessageEmitter::SendTransceiverMessage>(&mut env)
+                .address(emitter)
+                .query()
+                .await?;
+            inputs.push(env.into_input().await?);
+        }
+        let latest = inputs.remove(0);
+        Ok((latest, inputs))
+    }
+}
+
+/// An emitter contract deployed to a [TestChain].
+pub struct Emitter {
+    contract: SendTransceiverMessageEmitter::SendTransceiverMessageEmitterInstance<DynProvider>,
+}
+
+impl Emitter {
+    pub fn address(&self) -> Address {
+        *self.contract.address()
+    }
+
+    /// Emits `message` for `recipient_chain` in a transaction of its own.
+    pub async fn emit(&self, recipient_chain: u16, message: Bytes) -> Result<TransactionReceipt> {
+        Ok(self
+            .contract
+            .emitEvent(recipient_chain, message)
+            .send()
+            .await?
+            .get_receipt()
+            .await?)
+    }
+
+    /// Emits each of `messages` for its recipient chain in a transaction of its own, all mined in
+    /// the same block rather than a block each, as anvil mines them by default.
+    pub async fn emit_in_one_block(
+        &self,
+        messages: &[(u16, Bytes)],
+    ) -> Result<Vec<TransactionReceipt>> {
+        let provider = self.contract.provider();
+        provider
+            .raw_request::<_, ()>("evm_setAutomine".into(), (false,))
+            .await?;
+        let mut pending = Vec::new();
+        for (recipient_chain, message) in messages {
+            let tx = self.contract.emitEvent(*recipient_chain, message.clone());
+            pending.push(tx.send().await?);
+        }
+        provider
+            .raw_request::<_, serde_json::Value>("evm_mine".into(), ())
+            .await?;
+        provider
+            .raw_request::<_, ()>("evm_setAutomine".into(), (true,))
+            .await?;
+
+        let mut receipts = Vec::new();
+        for tx in pending {
+            receipts.push(tx.get_receipt().await?);
+        }
+        Ok(receipts)
+    }
+}
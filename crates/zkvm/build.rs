@@ -12,40 +12,330 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use std::{collections::HashMap, env, path::PathBuf};
+use std::{
+    collections::{BTreeMap, HashMap},
+    env, fs,
+    path::PathBuf,
+};
 
-use risc0_build::{embed_methods_with_options, DockerOptionsBuilder, GuestOptionsBuilder};
+use alloy_sol_types::SolStruct;
+use common::{
+    BackingJournal, BatchJournal, BatchedMessage, ChainJournal, DigestJournal, EventJournal,
+    Journal, JournalV2, ManagerState, MerkleBatchJournal, MessageLeaf, PeerJournal,
+    PeerRegistration, StateJournal, TokenBacking, JOURNAL_V2, JOURNAL_V2_TAG,
+};
+use risc0_build::{
+    embed_methods_with_options, DockerOptionsBuilder, GuestListEntry, GuestOptionsBuilder,
+};
 use risc0_build_ethereum::generate_solidity_files;
 
 // Paths where the generated Solidity files will be written.
 const SOLIDITY_IMAGE_ID_PATH: &str = "../../test/ImageID.sol";
 const SOLIDITY_ELF_PATH: &str = "../../test/Elf.sol";
+const SOLIDITY_JOURNAL_PATH: &str = "../../test/Journal.sol";
+
+// Image IDs expected from the reproducible docker build, one `<guest name> <chain id> <image id>`
+// per line, as the chain the guest is built for changes its image ID.
+const PINNED_IMAGE_IDS_PATH: &str = "image-ids.txt";
 
 fn main() {
     // Builds can be made deterministic, and thereby reproducible, by using Docker to build the
     // guest. Check the RISC0_USE_DOCKER variable and use Docker to build the guest if set.
     println!("cargo:rerun-if-env-changed=RISC0_USE_DOCKER");
+    // RISC0_ASSERT_IMAGE_ID fails the build if a guest's image ID differs from the pinned value,
+    // RISC0_UPDATE_IMAGE_ID rewrites the pinned values instead. Both imply a docker build since
+    // only those are reproducible.
+    println!("cargo:rerun-if-env-changed=RISC0_ASSERT_IMAGE_ID");
+    println!("cargo:rerun-if-env-changed=RISC0_UPDATE_IMAGE_ID");
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed={PINNED_IMAGE_IDS_PATH}");
+
+    // NTT_GUEST_CHAIN selects the guest feature choosing its chain spec: `mainnet` (the default),
+    // `sepolia` or `custom-spec`, which additionally requires NTT_GUEST_CHAIN_ID.
+    println!("cargo:rerun-if-env-changed=NTT_GUEST_CHAIN");
+    println!("cargo:rerun-if-env-changed=NTT_GUEST_CHAIN_ID");
+
+    let assert_image_id = env::var("RISC0_ASSERT_IMAGE_ID").is_ok();
+    let update_image_id = env::var("RISC0_UPDATE_IMAGE_ID").is_ok();
+
+    let chain = env::var("NTT_GUEST_CHAIN").unwrap_or_else(|_| "mainnet".to_string());
+    let chain_id: u64 = match chain.as_str() {
+        "mainnet" => 1,
+        "sepolia" => 11155111,
+        "custom-spec" => env::var("NTT_GUEST_CHAIN_ID")
+            .expect("NTT_GUEST_CHAIN_ID must be set for the custom-spec guest")
+            .parse()
+            .expect("NTT_GUEST_CHAIN_ID must be a chain ID"),
+        other => panic!("unsupported NTT_GUEST_CHAIN: {other}"),
+    };
 
     let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
     let mut builder = GuestOptionsBuilder::default();
-    if env::var("RISC0_USE_DOCKER").is_ok() {
+    builder.features(vec![chain]);
+    if env::var("RISC0_USE_DOCKER").is_ok() || assert_image_id || update_image_id {
         let docker_options = DockerOptionsBuilder::default()
             .root_dir(manifest_dir.join(".."))
+            .env(vec![(
+                "NTT_GUEST_CHAIN_ID".to_string(),
+                chain_id.to_string(),
+            )])
             .build()
             .unwrap();
         builder.use_docker(docker_options);
     }
     let guest_options = builder.build().unwrap();
 
+    // Expose the chain ID the guest was built for to the methods crate.
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    fs::write(
+        out_dir.join("chain.rs"),
+        format!("/// Chain ID of the source chain the guests were built for.\npub const NTT_GUEST_CHAIN_ID: u64 = {chain_id};\n"),
+    )
+    .unwrap();
+
     // Generate Rust source files for the methods crate.
     let guests =
         embed_methods_with_options(HashMap::from([("ntt_message_inclusion", guest_options)]));
 
+    if update_image_id {
+        write_pinned_image_ids(&guests, chain_id);
+    } else if assert_image_id {
+        assert_pinned_image_ids(&guests, chain_id);
+    }
+
     // Generate Solidity source files for use with Forge.
     let solidity_opts = risc0_build_ethereum::Options::default()
         .with_image_id_sol_path(SOLIDITY_IMAGE_ID_PATH)
         .with_elf_sol_path(SOLIDITY_ELF_PATH);
 
     let _ = generate_solidity_files(guests.as_slice(), &solidity_opts);
+
+    if let Err(e) = fs::write(SOLIDITY_JOURNAL_PATH, journal_library()) {
+        println!("cargo:warning=Failed to write {SOLIDITY_JOURNAL_PATH}: {e}");
+    }
+}
+
+/// Reads the pinned image IDs by guest name and chain ID, skipping the comments of the file.
+fn read_pinned_image_ids() -> BTreeMap<(String, u64), String> {
+    let pinned = fs::read_to_string(PINNED_IMAGE_IDS_PATH)
+        .unwrap_or_else(|e| panic!("failed to read {PINNED_IMAGE_IDS_PATH}: {e}"));
+    pinned
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let [name, chain_id, image_id] = fields[..] else {
+                panic!("malformed line in {PINNED_IMAGE_IDS_PATH}: {line}");
+            };
+            let chain_id = chain_id.parse().unwrap_or_else(|_| {
+                panic!("malformed chain ID in {PINNED_IMAGE_IDS_PATH}: {line}")
+            });
+            ((name.to_string(), chain_id), image_id.to_string())
+        })
+        .collect()
+}
+
+/// Pins the image IDs of the guests built for `chain_id`, keeping the pins of other chains.
+fn write_pinned_image_ids(guests: &[GuestListEntry], chain_id: u64) {
+    let header: String = fs::read_to_string(PINNED_IMAGE_IDS_PATH)
+        .unwrap_or_default()
+        .lines()
+        .take_while(|line| line.starts_with('#'))
+        .map(|line| format!("{line}\n"))
+        .collect();
+    let mut pinned = read_pinned_image_ids();
+    for guest in guests {
+        pinned.insert(
+            (guest.name.to_string(), chain_id),
+            guest.image_id.to_string(),
+        );
+    }
+    let pins: String = pinned
+        .iter()
+        .map(|((name, chain_id), image_id)| format!("{name} {chain_id} {image_id}\n"))
+        .collect();
+    fs::write(PINNED_IMAGE_IDS_PATH, header + &pins).expect("failed to write pinned image IDs");
+}
+
+fn assert_pinned_image_ids(guests: &[GuestListEntry], chain_id: u64) {
+    let pinned = read_pinned_image_ids();
+    for guest in guests {
+        let expected = pinned
+            .get(&(guest.name.to_string(), chain_id))
+            .unwrap_or_else(|| {
+                panic!(
+                    "no pinned image ID for guest {} built for chain {chain_id}, run with \
+                     RISC0_UPDATE_IMAGE_ID to pin it",
+                    guest.name
+                )
+            });
+        let actual = guest.image_id.to_string();
+        assert_eq!(
+            actual, *expected,
+            "image ID of guest {} built for chain {chain_id} changed, if this is intended run \
+             with RISC0_UPDATE_IMAGE_ID",
+            guest.name
+        );
+    }
+}
+
+/// Renders a Solidity library declaring the journal structs committed by the guest, derived
+/// from the `sol!` definitions in the common crate so the two can never drift.
+fn journal_library() -> String {
+    let structs = [
+        solidity_struct(&Journal::eip712_root_type()),
+        solidity_struct(&JournalV2::eip712_root_type()),
+        solidity_struct(&BatchedMessage::eip712_root_type()),
+        solidity_struct(&BatchJournal::eip712_root_type()),
+        solidity_struct(&DigestJournal::eip712_root_type()),
+        solidity_struct(&ChainJournal::eip712_root_type()),
+        solidity_struct(&EventJournal::eip712_root_type()),
+        solidity_struct(&ManagerState::eip712_root_type()),
+        solidity_struct(&StateJournal::eip712_root_type()),
+        solidity_struct(&TokenBacking::eip712_root_type()),
+        solidity_struct(&BackingJournal::eip712_root_type()),
+        solidity_struct(&PeerRegistration::eip712_root_type()),
+        solidity_struct(&PeerJournal::eip712_root_type()),
+        solidity_struct(&MessageLeaf::eip712_root_type()),
+        solidity_struct(&MerkleBatchJournal::eip712_root_type()),
+    ]
+    .join("\n\n");
+    let journal_v2_tag: String = JOURNAL_V2_TAG.iter().map(|b| format!("{b:02x}")).collect();
+
+    format!(
+        r#"// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//
+// SPDX-License-Identifier: Apache-2.0
+
+// This file is automatically generated by crates/zkvm/build.rs
+
+pragma solidity ^0.8.20;
+
+import {{Steel}} from "@steel/contracts/Steel.sol";
+
+/// @notice Journal types committed to by the NTT message inclusion guest.
+library JournalLib {{
+    /// @notice Version of JournalV2. The unversioned Journal is version 1.
+    uint16 internal constant JOURNAL_V2 = {JOURNAL_V2};
+
+    /// @notice Tag a JournalV2 is prefixed with, bytes4(keccak256("JournalV2")).
+    bytes4 internal constant JOURNAL_V2_TAG = 0x{journal_v2_tag};
+
+{structs}
+
+    /// @notice Decodes the journal of a guest execution attesting to a single message.
+    function decode(bytes calldata journalData) internal pure returns (Journal memory) {{
+        return abi.decode(journalData, (Journal));
+    }}
+
+    /// @notice Decodes the journal of a single message of either version, so contracts can accept
+    /// @notice both while guests migrate to the versioned journal.
+    /// @dev A Journal starts with the offset of its struct, whose first four bytes are zero, so
+    /// @dev only a JournalV2 starts with the tag.
+    function decodeAny(bytes calldata journalData) internal pure returns (Journal memory) {{
+        if (journalData.length >= 4 && bytes4(journalData[:4]) == JOURNAL_V2_TAG) {{
+            JournalV2 memory journal = abi.decode(journalData[4:], (JournalV2));
+            return Journal(journal.commitment, journal.encodedMessage, journal.emitterContract);
+        }}
+        return abi.decode(journalData, (Journal));
+    }}
+
+    /// @notice Decodes the journal of a guest execution attesting to several messages.
+    function decodeBatch(bytes calldata journalData) internal pure returns (BatchJournal memory) {{
+        return abi.decode(journalData, (BatchJournal));
+    }}
+
+    /// @notice Decodes the journal of a hash commit mode guest execution.
+    function decodeDigest(bytes calldata journalData) internal pure returns (DigestJournal memory) {{
+        return abi.decode(journalData, (DigestJournal));
+    }}
+
+    /// @notice Decodes the journal of a multichain guest execution.
+    function decodeChain(bytes calldata journalData) internal pure returns (ChainJournal memory) {{
+        return abi.decode(journalData, (ChainJournal));
+    }}
+
+    /// @notice Decodes the journal of an event inclusion guest execution.
+    function decodeEvent(bytes calldata journalData) internal pure returns (EventJournal memory) {{
+        return abi.decode(journalData, (EventJournal));
+    }}
+
+    /// @notice Decodes the journal of a manager state guest execution.
+    function decodeState(bytes calldata journalData) internal pure returns (StateJournal memory) {{
+        return abi.decode(journalData, (StateJournal));
+    }}
+
+    /// @notice Decodes the journal of a token backing guest execution.
+    function decodeBacking(bytes calldata journalData) internal pure returns (BackingJournal memory) {{
+        return abi.decode(journalData, (BackingJournal));
+    }}
+
+    /// @notice Decodes the journal of a peer registration guest execution.
+    function decodePeer(bytes calldata journalData) internal pure returns (PeerJournal memory) {{
+        return abi.decode(journalData, (PeerJournal));
+    }}
+
+    /// @notice Decodes the journal of a Merkle batch guest execution.
+    function decodeMerkleBatch(bytes calldata journalData) internal pure returns (MerkleBatchJournal memory) {{
+        return abi.decode(journalData, (MerkleBatchJournal));
+    }}
+
+    /// @notice Whether the message is in a batch committed to by a Merkle batch journal.
+    /// @param branch Sibling hashes from the leaf of the message up to the root, from the host.
+    function verifyMessage(
+        MerkleBatchJournal memory journal,
+        bytes calldata encodedMessage,
+        bytes32 emitterContract,
+        uint16 recipientChain,
+        bytes32[] calldata branch
+    ) internal pure returns (bool) {{
+        MessageLeaf memory leaf =
+            MessageLeaf(keccak256(encodedMessage), emitterContract, recipientChain);
+        bytes32 node = keccak256(abi.encode(leaf));
+        for (uint256 i = 0; i < branch.length; i++) {{
+            bytes32 sibling = branch[i];
+            node = node <= sibling
+                ? keccak256(abi.encodePacked(node, sibling))
+                : keccak256(abi.encodePacked(sibling, node));
+        }}
+        return node == journal.messagesRoot;
+    }}
+}}
+"#
+    )
+}
+
+/// Converts an EIP-712 root type such as `Journal(Commitment commitment,bytes encodedMessage)`
+/// into an indented Solidity struct declaration.
+fn solidity_struct(root_type: &str) -> String {
+    let (name, fields) = root_type
+        .strip_suffix(')')
+        .and_then(|s| s.split_once('('))
+        .expect("malformed EIP-712 type");
+    let fields: String = fields
+        .split(',')
+        .map(|field| {
+            let (ty, name) = field.split_once(' ').expect("malformed EIP-712 field");
+            // The Steel commitment is declared by the Steel library rather than this file
+            let ty = if ty == "Commitment" {
+                "Steel.Commitment"
+            } else {
+                ty
+            };
+            format!("        {ty} {name};\n")
+        })
+        .collect();
+    format!("    struct {name} {{\n{fields}    }}")
 }
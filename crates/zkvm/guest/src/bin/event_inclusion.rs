@@ -0,0 +1,35 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves inclusion of an event in a block of the source chain selected by the crate features,
+//! committing its signature and data in an `EventJournal` rather than the NTT specific journal.
+//!
+//! This instance proves `SendTransceiverMessage` events. Guests for events of other protocols
+//! only differ in the event type passed to `verify_event`.
+
+use alloy_sol_types::SolValue;
+use common::IBoundlessTransceiver;
+use ntt_message_inclusion::{read_event_input, verify_event, CHAIN_SPEC};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let journal = verify_event::<IBoundlessTransceiver::SendTransceiverMessage>(
+        read_event_input(),
+        &CHAIN_SPEC,
+    );
+    env::commit_slice(&journal.abi_encode());
+}
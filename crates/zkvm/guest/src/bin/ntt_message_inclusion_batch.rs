@@ -0,0 +1,26 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves inclusion of NTT messages in a block of the source chain selected by the crate
+//! features, always committing a `BatchJournal` so batch-verifying contracts decode a single
+//! journal shape.
+
+use ntt_message_inclusion::{commit_batch, read_input, verify_claims, CHAIN_SPEC};
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    commit_batch(verify_claims(read_input(), &CHAIN_SPEC));
+}
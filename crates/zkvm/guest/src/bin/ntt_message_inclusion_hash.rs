@@ -0,0 +1,33 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves inclusion of a single NTT message in a block of the source chain selected by the
+//! crate features, committing only the keccak256 digest of the message so the journal size is
+//! independent of the message size.
+
+use alloy_sol_types::SolValue;
+use ntt_message_inclusion::{read_input, verify_claims, CHAIN_SPEC};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let journals = verify_claims(read_input(), &CHAIN_SPEC);
+    assert!(
+        journals.len() == 1,
+        "Hash commit mode supports a single message claim"
+    );
+    env::commit_slice(&journals[0].to_digest_journal().abi_encode());
+}
@@ -0,0 +1,38 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves inclusion of a single NTT message in a block of any supported source chain. The chain
+//! is taken from the input and committed to the journal, so one image ID serves every source
+//! chain while the destination checks the claimed chain.
+
+use alloy_sol_types::SolValue;
+use ntt_message_inclusion::{chain_spec, read_input, verify_claims};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let input = read_input();
+    let chain_id = input
+        .chain_id
+        .expect("Input does not specify a source chain ID");
+
+    let journals = verify_claims(input, chain_spec(chain_id));
+    assert!(
+        journals.len() == 1,
+        "Multichain mode supports a single message claim"
+    );
+    env::commit_slice(&journals[0].to_chain_journal(chain_id).abi_encode());
+}
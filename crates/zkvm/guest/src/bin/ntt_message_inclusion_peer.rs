@@ -0,0 +1,35 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves inclusion of a single NTT message in a block of the source chain selected by the crate
+//! features, and that it was sent through a transceiver enabled on its NTT manager to a registered
+//! peer of the manager, committing a `PeerJournal` with the registration that was checked.
+
+use alloy_sol_types::SolValue;
+use common::IBoundlessTransceiver::SendTransceiverMessage;
+use ntt_message_inclusion::{read_input, verify_message_claims_with_peer, CHAIN_SPEC};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let journals =
+        verify_message_claims_with_peer::<SendTransceiverMessage>(read_input(), &CHAIN_SPEC);
+    assert!(
+        journals.len() == 1,
+        "Peer registration mode supports a single message claim"
+    );
+    env::commit_slice(&journals[0].abi_encode());
+}
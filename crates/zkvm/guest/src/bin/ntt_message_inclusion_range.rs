@@ -0,0 +1,30 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves that an NTT message was emitted in one of a contiguous range of blocks of the source
+//! chain selected by the crate features, for when the exact block is unknown. The journal has the
+//! same shape as that of the default guest, with the commitment of the newest block of the range.
+
+use alloy_sol_types::SolValue;
+use common::IBoundlessTransceiver::SendTransceiverMessage;
+use ntt_message_inclusion::{read_range_input, verify_claim_in_range, CHAIN_SPEC};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let journal = verify_claim_in_range::<SendTransceiverMessage>(read_range_input(), &CHAIN_SPEC);
+    env::commit_slice(&journal.abi_encode());
+}
@@ -0,0 +1,35 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+#![no_main]
+
+//! Proves inclusion of a single NTT message in a block of the source chain selected by the crate
+//! features, committing a `StateJournal` that also carries the sequence counter and outbound rate
+//! limit capacity of the NTT manager that sent it, read in the same block.
+
+use alloy_sol_types::SolValue;
+use common::IBoundlessTransceiver::SendTransceiverMessage;
+use ntt_message_inclusion::{read_input, verify_message_claims_with_state, CHAIN_SPEC};
+use risc0_zkvm::guest::env;
+
+risc0_zkvm::guest::entry!(main);
+
+fn main() {
+    let journals =
+        verify_message_claims_with_state::<SendTransceiverMessage>(read_input(), &CHAIN_SPEC);
+    assert!(
+        journals.len() == 1,
+        "Manager state mode supports a single message claim"
+    );
+    env::commit_slice(&journals[0].abi_encode());
+}
@@ -0,0 +1,467 @@
+// Copyright 2025 Boundless, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Logic shared by the NTT message inclusion guest programs. Each binary in this package is
+//! embedded as a separate guest with its own image ID, differing only in the chain spec used
+//! and the shape of the committed journal. [CHAIN_SPEC] is selected at compile time by the
+//! `mainnet`, `sepolia` and `custom-spec` features.
+
+use std::collections::BTreeMap;
+
+use alloy_primitives::{Address, Bytes, B256};
+use alloy_sol_types::{SolEvent, SolValue};
+use common::{
+    from_wormhole_address, merkle,
+    ntt::{INttManager, MessageEvent, TransceiverMessage, IERC20},
+    BackingJournal, BatchJournal, EventInput, EventJournal, GuestInput, IBoundlessTransceiver,
+    Journal, JournalV2, ManagerState, MerkleBatchJournal, MessageClaim, MessageLeaf, PeerJournal,
+    PeerRegistration, RangeInput, StateJournal, TokenBacking, JOURNAL_V2, MAX_ENCODED_MESSAGE_SIZE,
+};
+use risc0_steel::{
+    ethereum::{EthChainSpec, EthEvmFactory, ETH_MAINNET_CHAIN_SPEC, ETH_SEPOLIA_CHAIN_SPEC},
+    Contract, Event, EvmBlockHeader, GuestEvmEnv,
+};
+use risc0_zkvm::guest::env;
+
+#[cfg(not(any(feature = "sepolia", feature = "custom-spec")))]
+pub use risc0_steel::ethereum::ETH_MAINNET_CHAIN_SPEC as CHAIN_SPEC;
+#[cfg(all(feature = "sepolia", not(feature = "custom-spec")))]
+pub use risc0_steel::ethereum::ETH_SEPOLIA_CHAIN_SPEC as CHAIN_SPEC;
+
+/// Chain spec of a custom source chain, treating every block as Prague.
+#[cfg(feature = "custom-spec")]
+pub static CHAIN_SPEC: std::sync::LazyLock<EthChainSpec> = std::sync::LazyLock::new(|| {
+    let chain_id = env!("NTT_GUEST_CHAIN_ID")
+        .parse()
+        .expect("NTT_GUEST_CHAIN_ID must be a chain ID");
+    EthChainSpec::new_single(
+        chain_id,
+        risc0_steel::revm::primitives::hardfork::SpecId::PRAGUE,
+    )
+});
+
+/// Returns the chain spec of a source chain supported by the multichain guest.
+pub fn chain_spec(chain_id: u64) -> &'static EthChainSpec {
+    match chain_id {
+        1 => &ETH_MAINNET_CHAIN_SPEC,
+        11155111 => &ETH_SEPOLIA_CHAIN_SPEC,
+        _ => panic!("Unsupported source chain ID"),
+    }
+}
+
+/// Reads the framed [GuestInput] written by the host. Only the Steel commitment goes through
+/// bincode, the claims are sliced out of the frame.
+pub fn read_input() -> GuestInput {
+    let input_bytes: Vec<u8> = env::read_frame();
+    GuestInput::deserialize(&input_bytes).expect("Failed to deserialize input")
+}
+
+/// Reads the framed [EventInput] written by the host for the event inclusion guest.
+pub fn read_event_input() -> EventInput {
+    let input_bytes: Vec<u8> = env::read_frame();
+    EventInput::deserialize(&input_bytes).expect("Failed to deserialize input")
+}
+
+/// Reads the framed [RangeInput] written by the host for the block range guest.
+pub fn read_range_input() -> RangeInput {
+    let input_bytes: Vec<u8> = env::read_frame();
+    RangeInput::deserialize(&input_bytes).expect("Failed to deserialize input")
+}
+
+/// Verifies that the emitter claimed by the input emitted an event of type `E` with the claimed
+/// data in the block committed to by the input. Protocols other than NTT can prove their own
+/// events by building a guest instantiating this with their event type.
+pub fn verify_event<E: SolEvent>(input: EventInput, chain_spec: &EthChainSpec) -> EventJournal {
+    assert!(
+        input.event_signature == E::SIGNATURE_HASH,
+        "Event signature does not match the event proven by this guest"
+    );
+    assert!(
+        input.data.len() <= MAX_ENCODED_MESSAGE_SIZE,
+        "Event data exceeds maximum size",
+    );
+
+    let env = input.commitment.into_env(chain_spec);
+    let logs = Event::new::<E>(&env)
+        .address(from_wormhole_address(input.emitter_contract))
+        .query();
+    assert!(
+        logs.iter()
+            .any(|log| log.data.encode_data() == input.data.as_ref()),
+        "Event with given data not contained in logs for this block",
+    );
+
+    EventJournal {
+        commitment: env.into_commitment(),
+        emitterContract: input.emitter_contract,
+        eventSignature: input.event_signature,
+        data: input.data,
+    }
+}
+
+/// Verifies that every claimed message was emitted by its BoundlessTransceiver contract in the
+/// block committed to by the input, returning one journal per claim in input order.
+pub fn verify_claims(input: GuestInput, chain_spec: &EthChainSpec) -> Vec<Journal> {
+    verify_message_claims::<IBoundlessTransceiver::SendTransceiverMessage>(input, chain_spec)
+}
+
+/// Like [verify_claims], returning versioned journals that also commit to the execution block
+/// the messages were emitted in.
+pub fn verify_claims_v2(input: GuestInput, chain_spec: &EthChainSpec) -> Vec<JournalV2> {
+    verify_message_claims_v2::<IBoundlessTransceiver::SendTransceiverMessage>(input, chain_spec)
+}
+
+/// Verifies that every claimed message was emitted as an `E` event by its transceiver contract
+/// in the block committed to by the input, returning one journal per claim in input order.
+pub fn verify_message_claims<E: MessageEvent>(
+    input: GuestInput,
+    chain_spec: &EthChainSpec,
+) -> Vec<Journal> {
+    verify_message_claims_v2::<E>(input, chain_spec)
+        .into_iter()
+        .map(Journal::from)
+        .collect()
+}
+
+/// Like [verify_message_claims], returning versioned journals that also commit to the execution
+/// block the messages were emitted in.
+pub fn verify_message_claims_v2<E: MessageEvent>(
+    input: GuestInput,
+    chain_spec: &EthChainSpec,
+) -> Vec<JournalV2> {
+    // Converts the input into a `EvmEnv` for execution.
+    let env = input.commitment.into_env(chain_spec);
+    check_claims::<E>(&env, &input.claims);
+
+    // Commit to each message as being emitted by its transceiver contract in the block committed to by the env commitment
+    let block_number = env.header().number();
+    let block_hash = env.header().seal();
+    let commitment = env.into_commitment();
+    input
+        .claims
+        .into_iter()
+        .map(|claim| JournalV2 {
+            version: JOURNAL_V2,
+            commitment: commitment.clone(),
+            blockNumber: block_number,
+            blockHash: block_hash,
+            encodedMessage: claim.encoded_message,
+            emitterContract: claim.contract_addr,
+        })
+        .collect()
+}
+
+/// Like [verify_message_claims], extending each journal with the state of the NTT manager named
+/// as the source of the message, read in the block committed to by the input. The manager is
+/// taken from the message itself, which the transceiver vouches for by emitting it, so the input
+/// cannot substitute the state of another manager.
+pub fn verify_message_claims_with_state<E: MessageEvent>(
+    input: GuestInput,
+    chain_spec: &EthChainSpec,
+) -> Vec<StateJournal> {
+    let env = input.commitment.into_env(chain_spec);
+    check_claims::<E>(&env, &input.claims);
+
+    // Messages of a batch are usually sent by the same manager, whose state is only read once
+    let mut states: BTreeMap<Address, ManagerState> = BTreeMap::new();
+    let manager_states: Vec<_> = input
+        .claims
+        .iter()
+        .map(|claim| {
+            let manager = source_manager(&claim.encoded_message);
+            states
+                .entry(manager)
+                .or_insert_with(|| {
+                    let contract = Contract::new(manager, &env);
+                    ManagerState {
+                        nttManager: manager,
+                        nextMessageSequence: contract
+                            .call_builder(&INttManager::nextMessageSequenceCall {})
+                            .call(),
+                        currentOutboundCapacity: contract
+                            .call_builder(&INttManager::getCurrentOutboundCapacityCall {})
+                            .call(),
+                    }
+                })
+                .clone()
+        })
+        .collect();
+
+    let commitment = env.into_commitment();
+    input
+        .claims
+        .into_iter()
+        .zip(manager_states)
+        .map(|(claim, manager_state)| StateJournal {
+            commitment: commitment.clone(),
+            encodedMessage: claim.encoded_message,
+            emitterContract: claim.contract_addr,
+            managerState: manager_state,
+        })
+        .collect()
+}
+
+/// Like [verify_message_claims] for token transfers, extending each journal with the holdings of
+/// the transferred token at the end of the block committed to by the input: the balance of the
+/// sending NTT manager, which holds the tokens it locked, and the total supply, which the tokens
+/// it burned are taken out of. The token is the one the manager reports, which the transfer must
+/// name as its source token.
+pub fn verify_message_claims_with_backing<E: MessageEvent>(
+    input: GuestInput,
+    chain_spec: &EthChainSpec,
+) -> Vec<BackingJournal> {
+    let env = input.commitment.into_env(chain_spec);
+    check_claims::<E>(&env, &input.claims);
+
+    let mut backings: BTreeMap<Address, TokenBacking> = BTreeMap::new();
+    let token_backings: Vec<_> = input
+        .claims
+        .iter()
+        .map(|claim| {
+            let message = TransceiverMessage::parse(&claim.encoded_message)
+                .expect("Failed to parse transceiver message");
+            let transfer = message.transfer().expect("Message is not a token transfer");
+            let manager = from_wormhole_address(message.source_ntt_manager);
+            let backing = backings
+                .entry(manager)
+                .or_insert_with(|| {
+                    let contract = Contract::new(manager, &env);
+                    let token = contract.call_builder(&INttManager::tokenCall {}).call();
+                    let mode = contract.call_builder(&INttManager::getModeCall {}).call();
+                    let erc20 = Contract::new(token, &env);
+                    TokenBacking {
+                        token,
+                        mode,
+                        managerBalance: erc20
+                            .call_builder(&IERC20::balanceOfCall { account: manager })
+                            .call(),
+                        totalSupply: erc20.call_builder(&IERC20::totalSupplyCall {}).call(),
+                    }
+                })
+                .clone();
+            assert!(
+                from_wormhole_address(transfer.source_token) == backing.token,
+                "Transferred token is not the token of the NTT manager"
+            );
+            backing
+        })
+        .collect();
+
+    let commitment = env.into_commitment();
+    input
+        .claims
+        .into_iter()
+        .zip(token_backings)
+        .map(|(claim, token_backing)| BackingJournal {
+            commitment: commitment.clone(),
+            encodedMessage: claim.encoded_message,
+            emitterContract: claim.contract_addr,
+            tokenBacking: token_backing,
+        })
+        .collect()
+}
+
+/// Like [verify_message_claims], additionally verifying that each message was sent through the
+/// NTT manager it names as its source to a registered peer: the emitting transceiver must be
+/// enabled on the manager, and the recipient manager must be the manager's peer on the chain the
+/// message was sent to. Each journal is extended with the registration that was checked.
+pub fn verify_message_claims_with_peer<E: MessageEvent>(
+    input: GuestInput,
+    chain_spec: &EthChainSpec,
+) -> Vec<PeerJournal> {
+    let env = input.commitment.into_env(chain_spec);
+    let recipient_chains = check_claims::<E>(&env, &input.claims);
+
+    let mut transceivers: BTreeMap<Address, Vec<Address>> = BTreeMap::new();
+    let registrations: Vec<_> = input
+        .claims
+        .iter()
+        .zip(recipient_chains)
+        .map(|(claim, recipient_chain)| {
+            let message = TransceiverMessage::parse(&claim.encoded_message)
+                .expect("Failed to parse transceiver message");
+            let manager = from_wormhole_address(message.source_ntt_manager);
+            let contract = Contract::new(manager, &env);
+            let enabled = transceivers.entry(manager).or_insert_with(|| {
+                contract
+                    .call_builder(&INttManager::getTransceiversCall {})
+                    .call()
+            });
+            assert!(
+                enabled.contains(&from_wormhole_address(claim.contract_addr)),
+                "Transceiver is not enabled on its NTT manager"
+            );
+            let peer = contract
+                .call_builder(&INttManager::getPeerCall {
+                    chainId: recipient_chain,
+                })
+                .call();
+            assert!(
+                peer.peerAddress == message.recipient_ntt_manager,
+                "Recipient manager is not a peer of the source manager"
+            );
+            PeerRegistration {
+                nttManager: manager,
+                recipientChain: recipient_chain,
+                peerAddress: peer.peerAddress,
+                peerDecimals: peer.tokenDecimals,
+            }
+        })
+        .collect();
+
+    let commitment = env.into_commitment();
+    input
+        .claims
+        .into_iter()
+        .zip(registrations)
+        .map(|(claim, peer_registration)| PeerJournal {
+            commitment: commitment.clone(),
+            encodedMessage: claim.encoded_message,
+            emitterContract: claim.contract_addr,
+            peerRegistration: peer_registration,
+        })
+        .collect()
+}
+
+/// Like [verify_message_claims], committing to the messages through the root of the Merkle tree
+/// over their leaves rather than one by one, so the journal has the same size for any number of
+/// claims.
+pub fn verify_message_claims_merkle<E: MessageEvent>(
+    input: GuestInput,
+    chain_spec: &EthChainSpec,
+) -> MerkleBatchJournal {
+    let env = input.commitment.into_env(chain_spec);
+    let recipient_chains = check_claims::<E>(&env, &input.claims);
+
+    let leaves: Vec<B256> = input
+        .claims
+        .iter()
+        .zip(recipient_chains)
+        .map(|(claim, recipient_chain)| {
+            MessageLeaf::new(&claim.encoded_message, claim.contract_addr, recipient_chain).hash()
+        })
+        .collect();
+    MerkleBatchJournal {
+        commitment: env.into_commitment(),
+        messagesRoot: merkle::root(&leaves),
+        messageCount: leaves.len().try_into().expect("Too many message claims"),
+    }
+}
+
+/// The NTT manager named as the source of an encoded transceiver message.
+fn source_manager(encoded_message: &[u8]) -> Address {
+    let message =
+        TransceiverMessage::parse(encoded_message).expect("Failed to parse transceiver message");
+    from_wormhole_address(message.source_ntt_manager)
+}
+
+/// Asserts that every claimed message was emitted as an `E` event by its transceiver contract in
+/// the block of `env`, returning the Wormhole chain ID each message was sent to.
+fn check_claims<E: MessageEvent>(
+    env: &GuestEvmEnv<EthEvmFactory>,
+    claims: &[MessageClaim],
+) -> Vec<u16> {
+    assert!(!claims.is_empty(), "No message claims provided");
+
+    // Query the `SendTransceiverMessage` events of each claimed emitter and ensure they contain
+    // the claimed message. Steel matches logs by emitter and topic0 before ABI decoding them, so
+    // only the events of the claimed emitters are decoded, and each emitter is queried once
+    // however many messages of the batch it sent.
+    let mut emitted: BTreeMap<B256, Vec<(Bytes, u16)>> = BTreeMap::new();
+    claims
+        .iter()
+        .map(|claim| {
+            assert!(
+                claim.encoded_message.len() <= MAX_ENCODED_MESSAGE_SIZE,
+                "Encoded message exceeds maximum size",
+            );
+            let messages = emitted.entry(claim.contract_addr).or_insert_with(|| {
+                Event::new::<E>(env)
+                    .address(from_wormhole_address(claim.contract_addr))
+                    .query()
+                    .iter()
+                    .map(|log| (log.data.encoded_message(), log.data.recipient_chain()))
+                    .collect()
+            });
+            messages
+                .iter()
+                .find(|(message, _)| *message == claim.encoded_message)
+                .map(|(_, recipient_chain)| *recipient_chain)
+                .expect("Event for given message not contained in logs for this block")
+        })
+        .collect()
+}
+
+/// Verifies that the claimed message was emitted as an `E` event by its transceiver contract in
+/// one of the blocks of the input, returning the journal of the message with the commitment of the
+/// newest block. The older blocks are linked to it through their parent hashes, so the journal
+/// attests to the message being emitted at or before that block.
+pub fn verify_claim_in_range<E: MessageEvent>(
+    input: RangeInput,
+    chain_spec: &EthChainSpec,
+) -> Journal {
+    assert!(
+        input.claim.encoded_message.len() <= MAX_ENCODED_MESSAGE_SIZE,
+        "Encoded message exceeds maximum size",
+    );
+
+    let env = input.commitment.into_env(chain_spec);
+    let ancestors: Vec<_> = input
+        .ancestors
+        .into_iter()
+        .map(|ancestor| ancestor.into_env(chain_spec))
+        .collect();
+    let mut parent_hash = *env.header().parent_hash();
+    for ancestor in &ancestors {
+        assert!(
+            ancestor.header().seal() == parent_hash,
+            "Blocks of the range are not contiguous"
+        );
+        parent_hash = *ancestor.header().parent_hash();
+    }
+
+    let contract = from_wormhole_address(input.claim.contract_addr);
+    let emitted = std::iter::once(&env).chain(&ancestors).any(|env| {
+        Event::new::<E>(env)
+            .address(contract)
+            .query()
+            .iter()
+            .any(|log| log.data.encoded_message() == input.claim.encoded_message)
+    });
+    assert!(
+        emitted,
+        "Event for given message not contained in logs of the block range"
+    );
+
+    Journal {
+        commitment: env.into_commitment(),
+        encodedMessage: input.claim.encoded_message,
+        emitterContract: input.claim.contract_addr,
+    }
+}
+
+/// Commits a plain `Journal` for a single claim so existing destination contracts can decode it,
+/// and a `BatchJournal` sharing the one commitment for several claims.
+pub fn commit_journals(journals: Vec<Journal>) {
+    if journals.len() == 1 {
+        env::commit_slice(&journals[0].abi_encode());
+    } else {
+        commit_batch(journals);
+    }
+}
+
+/// Commits a `BatchJournal` regardless of the number of claims.
+pub fn commit_batch(journals: Vec<Journal>) {
+    let batch = BatchJournal::from_journals(journals).expect("Failed to build batch journal");
+    env::commit_slice(&batch.encode());
+}
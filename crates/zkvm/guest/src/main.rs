@@ -13,36 +13,12 @@
 // limitations under the License.
 #![no_main]
 
-use alloy_sol_types::SolValue;
-use common::{from_wormhole_address, GuestInput, IBoundlessTransceiver, Journal};
-use risc0_steel::{ethereum::ETH_MAINNET_CHAIN_SPEC, Event};
-use risc0_zkvm::guest::env;
+//! Proves inclusion of NTT messages in a block of the source chain selected by the crate features.
+
+use ntt_message_inclusion::{commit_journals, read_input, verify_claims, CHAIN_SPEC};
 
 risc0_zkvm::guest::entry!(main);
 
 fn main() {
-    let input_bytes: Vec<u8> = env::read_frame();
-    let input = GuestInput::deserialize(&input_bytes).expect("Failed to deserialize input");
-
-    // Converts the input into a `EvmEnv` for execution.
-    let env = input.commitment.into_env(&ETH_MAINNET_CHAIN_SPEC);
-
-    // Query the `SendTransceiverMessage` events of the contract and ensure it contains the expected message digest
-    let event = Event::new::<IBoundlessTransceiver::SendTransceiverMessage>(&env);
-    let logs = &event
-        .address(from_wormhole_address(input.contract_addr))
-        .query();
-    assert!(
-        logs.iter()
-            .any(|log| log.encodedMessage == input.encoded_message),
-        "Event for given message not contained in logs for this block",
-    );
-
-    // Commit to this message as being emitted by the transceiver contract in the block committed to by the env commitment
-    let journal = Journal {
-        commitment: env.into_commitment(),
-        encodedMessage: input.encoded_message,
-        emitterContract: input.contract_addr,
-    };
-    env::commit_slice(&journal.abi_encode());
+    commit_journals(verify_claims(read_input(), &CHAIN_SPEC));
 }
@@ -13,35 +13,290 @@
 // limitations under the License.
 
 include!(concat!(env!("OUT_DIR"), "/methods.rs"));
+include!(concat!(env!("OUT_DIR"), "/chain.rs"));
+
+use std::str::FromStr;
+use std::sync::RwLock;
+
+/// The kind of source transceiver whose `SendTransceiverMessage` events a guest proves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Transceiver {
+    /// The BoundlessTransceiver, emitting encoded messages.
+    Boundless,
+    /// The standard WormholeTransceiver, emitting message structs.
+    Wormhole,
+}
+
+/// The input a guest reads and the journal it commits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuestMode {
+    /// Reads a `GuestInput` and commits a `Journal` or `JournalV2` per claimed message, as the
+    /// BoundlessTransceiver accepts.
+    Message,
+    /// Reads a `RangeInput` and commits the `Journal` of a message emitted in a range of blocks.
+    Range,
+    /// Reads a `GuestInput` and commits a `BatchJournal` of the claimed messages.
+    Batch,
+    /// Reads a `GuestInput` and commits the `DigestJournal` of a single claimed message.
+    Digest,
+    /// Reads a `GuestInput` naming its chain and commits the `ChainJournal` of a single claimed
+    /// message.
+    Chain,
+    /// Reads a `GuestInput` and commits the `StateJournal` of a single claimed message.
+    State,
+    /// Reads a `GuestInput` and commits the `BackingJournal` of a single claimed message.
+    Backing,
+    /// Reads a `GuestInput` and commits the `PeerJournal` of a single claimed message.
+    Peer,
+    /// Reads a `GuestInput` and commits a `MerkleBatchJournal` of the claimed messages.
+    MerkleBatch,
+    /// Reads an `EventInput` and commits an `EventJournal`.
+    Event,
+}
+
+impl GuestMode {
+    pub const ALL: [GuestMode; 10] = [
+        Self::Message,
+        Self::Range,
+        Self::Batch,
+        Self::Digest,
+        Self::Chain,
+        Self::State,
+        Self::Backing,
+        Self::Peer,
+        Self::MerkleBatch,
+        Self::Event,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Message => "message",
+            Self::Range => "range",
+            Self::Batch => "batch",
+            Self::Digest => "digest",
+            Self::Chain => "chain",
+            Self::State => "state",
+            Self::Backing => "backing",
+            Self::Peer => "peer",
+            Self::MerkleBatch => "merkle_batch",
+            Self::Event => "event",
+        }
+    }
+}
+
+impl FromStr for GuestMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| format!("unknown guest mode {s}"))
+    }
+}
+
+/// A guest program, embedded in this crate or loaded at runtime.
+#[derive(Clone, Copy, Debug)]
+pub struct GuestProgram {
+    pub name: &'static str,
+    pub elf: &'static [u8],
+    pub image_id: [u32; 8],
+    /// Chain ID of the source chain whose chain spec the guest was built for, or `None` if the
+    /// guest takes the chain from its input.
+    pub chain_id: Option<u64>,
+    pub transceiver: Transceiver,
+    pub mode: GuestMode,
+}
+
+/// The default guest, proving messages from the chain selected by NTT_GUEST_CHAIN at build time.
+pub const NTT_MESSAGE_INCLUSION: GuestProgram = GuestProgram {
+    name: "ntt_message_inclusion",
+    elf: NTT_MESSAGE_INCLUSION_ELF,
+    image_id: NTT_MESSAGE_INCLUSION_ID,
+    chain_id: Some(NTT_GUEST_CHAIN_ID),
+    transceiver: Transceiver::Boundless,
+    mode: GuestMode::Message,
+};
+
+/// Every guest program embedded in this crate.
+pub const GUEST_PROGRAMS: &[GuestProgram] = &[
+    NTT_MESSAGE_INCLUSION,
+    GuestProgram {
+        name: "ntt_message_inclusion_sepolia",
+        elf: NTT_MESSAGE_INCLUSION_SEPOLIA_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_SEPOLIA_ID,
+        chain_id: Some(11155111),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Message,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_batch",
+        elf: NTT_MESSAGE_INCLUSION_BATCH_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_BATCH_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Batch,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_hash",
+        elf: NTT_MESSAGE_INCLUSION_HASH_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_HASH_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Digest,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_multichain",
+        elf: NTT_MESSAGE_INCLUSION_MULTICHAIN_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_MULTICHAIN_ID,
+        chain_id: None,
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Chain,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_v2",
+        elf: NTT_MESSAGE_INCLUSION_V2_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_V2_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Message,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_range",
+        elf: NTT_MESSAGE_INCLUSION_RANGE_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_RANGE_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Range,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_state",
+        elf: NTT_MESSAGE_INCLUSION_STATE_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_STATE_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::State,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_backing",
+        elf: NTT_MESSAGE_INCLUSION_BACKING_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_BACKING_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Backing,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_peer",
+        elf: NTT_MESSAGE_INCLUSION_PEER_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_PEER_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Peer,
+    },
+    GuestProgram {
+        name: "ntt_message_inclusion_merkle",
+        elf: NTT_MESSAGE_INCLUSION_MERKLE_ELF,
+        image_id: NTT_MESSAGE_INCLUSION_MERKLE_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::MerkleBatch,
+    },
+    GuestProgram {
+        name: "event_inclusion",
+        elf: EVENT_INCLUSION_ELF,
+        image_id: EVENT_INCLUSION_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Boundless,
+        mode: GuestMode::Event,
+    },
+    GuestProgram {
+        name: "wormhole_message_inclusion",
+        elf: WORMHOLE_MESSAGE_INCLUSION_ELF,
+        image_id: WORMHOLE_MESSAGE_INCLUSION_ID,
+        chain_id: Some(NTT_GUEST_CHAIN_ID),
+        transceiver: Transceiver::Wormhole,
+        mode: GuestMode::Message,
+    },
+];
+
+/// Guest programs loaded at runtime, such as released builds that deployed contracts still verify
+/// proofs of.
+static LOADED_PROGRAMS: RwLock<Vec<&'static GuestProgram>> = RwLock::new(Vec::new());
+
+impl GuestProgram {
+    /// Looks up a guest program by name, whether embedded or loaded.
+    pub fn by_name(name: &str) -> Option<&'static GuestProgram> {
+        Self::all().into_iter().find(|guest| guest.name == name)
+    }
+
+    /// Every guest program, the embedded ones first.
+    pub fn all() -> Vec<&'static GuestProgram> {
+        let loaded = LOADED_PROGRAMS.read().unwrap();
+        GUEST_PROGRAMS
+            .iter()
+            .chain(loaded.iter().copied())
+            .collect()
+    }
+
+    /// Makes a guest program loaded at runtime available to lookups for the rest of the process,
+    /// failing if another guest has its name. Registering the same guest again is a no-op.
+    pub fn register(
+        name: String,
+        elf: Vec<u8>,
+        image_id: [u32; 8],
+        chain_id: Option<u64>,
+        transceiver: Transceiver,
+        mode: GuestMode,
+    ) -> Result<&'static GuestProgram, String> {
+        let mut loaded = LOADED_PROGRAMS.write().unwrap();
+        let existing = GUEST_PROGRAMS
+            .iter()
+            .chain(loaded.iter().copied())
+            .find(|other| other.name == name);
+        if let Some(existing) = existing {
+            return if existing.image_id == image_id {
+                Ok(existing)
+            } else {
+                Err(format!("a different guest is named {name}"))
+            };
+        }
+        // Guests are loaded once at startup, so leaking them is bounded
+        let guest: &'static GuestProgram = Box::leak(Box::new(GuestProgram {
+            name: Box::leak(name.into_boxed_str()),
+            elf: Box::leak(elf.into_boxed_slice()),
+            image_id,
+            chain_id,
+            transceiver,
+            mode,
+        }));
+        loaded.push(guest);
+        Ok(guest)
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use alloy::{
-        dyn_abi::SolType, network::EthereumWallet, node_bindings::Anvil, primitives::Bytes,
-        providers::ProviderBuilder, signers::local::PrivateKeySigner, sol,
+        dyn_abi::SolType,
+        primitives::{Address, B256, Bytes, U256, address},
     };
-    use common::{GuestInput, Journal, from_wormhole_address, to_wormhole_address};
-    use risc0_steel::{
-        Event,
-        ethereum::{ETH_MAINNET_CHAIN_SPEC, EthEvmEnv},
+    use common::{
+        BackingJournal, BatchJournal, GuestInput, JOURNAL_V2, Journal, JournalV2,
+        MerkleBatchJournal, MessageClaim, MessageLeaf, PeerJournal, RangeInput, StateJournal,
+        from_wormhole_address, merkle,
+        ntt::{
+            BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX, NativeTokenTransfer, NttManagerMessage,
+            TransceiverMessage,
+        },
+        to_wormhole_address,
     };
-    use risc0_zkvm::{ExecutorEnv, default_executor};
+    use risc0_zkvm::{ExecutorEnv, SessionInfo, default_executor};
     use std::sync::LazyLock;
-
-    // A minimal contract that emits a `SendTransceiverMessage` events when `emitEvent` is called.
-    sol! {
-    #[sol(rpc, bytecode="6080604052348015600e575f5ffd5b5061016c8061001c5f395ff3fe608060405234801561000f575f5ffd5b5060043610610029575f3560e01c80631e08b77e1461002d575b5f5ffd5b61004061003b366004610082565b610042565b005b7f0d4a24add37c1972207e3dcfa8359764948caf868db363ee8fa1cb7f55f0a74c83838360405161007593929190610108565b60405180910390a1505050565b5f5f5f60408486031215610094575f5ffd5b833561ffff811681146100a5575f5ffd5b9250602084013567ffffffffffffffff8111156100c0575f5ffd5b8401601f810186136100d0575f5ffd5b803567ffffffffffffffff8111156100e6575f5ffd5b8660208284010111156100f7575f5ffd5b939660209190910195509293505050565b61ffff8416815260406020820152816040820152818360608301375f818301606090810191909152601f9092017fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffe01601019291505056fea164736f6c634300081e000a")]
-    contract SendTransceiverMessageEmitter {
-          event SendTransceiverMessage(
-              uint16 recipientChain, bytes encodedMessage
-          );
-
-          function emitEvent(uint16 recipientChain, bytes calldata encodedMessage) external {
-              emit SendTransceiverMessage(recipientChain, encodedMessage);
-          }
-        }
-    }
+    use testkit::{
+        MOCK_MANAGER_BALANCE, MOCK_NEXT_MESSAGE_SEQUENCE, MOCK_OUTBOUND_CAPACITY, MOCK_PEER,
+        MOCK_PEER_DECIMALS, MOCK_TOTAL_SUPPLY, TestChain, synthetic::SyntheticBlock,
+    };
 
     fn expected_message() -> Bytes {
         Bytes::from("Some message")
@@ -69,54 +324,36 @@ mod tests {
         ]
     });
 
+    async fn execute(input: GuestInput) -> anyhow::Result<anyhow::Result<SessionInfo>> {
+        Ok(tokio::task::spawn_blocking(move || {
+            let env = ExecutorEnv::builder()
+                .write_frame(&input.serialize().unwrap())
+                .build()
+                .unwrap();
+
+            default_executor().execute(env, NTT_MESSAGE_INCLUSION_ELF)
+        })
+        .await?)
+    }
+
     #[tokio::test]
     async fn run_tests() -> anyhow::Result<()> {
-        let anvil = Anvil::new().chain_id(1).spawn();
-        let private_key = anvil.keys()[0].clone();
-        let signer = PrivateKeySigner::from(private_key);
-        let wallet = EthereumWallet::from(signer);
-
-        let provider = ProviderBuilder::new()
-            .wallet(wallet)
-            .connect_http(anvil.endpoint_url());
+        let chain = TestChain::spawn();
 
         for (i, (messages, expected)) in TEST_CASES.iter().enumerate() {
-            let contract = SendTransceiverMessageEmitter::deploy(&provider).await?;
+            let emitter = chain.deploy_emitter().await?;
             for msg in messages {
-                contract
-                    .emitEvent(3, msg.clone())
-                    .send()
-                    .await?
-                    .get_receipt()
-                    .await?;
+                emitter.emit(3, msg.clone()).await?;
             }
-            let mut env = EthEvmEnv::builder()
-                .rpc(anvil.endpoint_url())
-                .chain_spec(&ETH_MAINNET_CHAIN_SPEC)
-                .build()
-                .await?;
+            let evm_input = chain.preflight_messages(emitter.address()).await?;
 
-            let event =
-                Event::preflight::<SendTransceiverMessageEmitter::SendTransceiverMessage>(&mut env);
-            let _logs = event.address(*contract.address()).query().await?;
+            let input = GuestInput::single(
+                evm_input,
+                expected_message(),
+                to_wormhole_address(emitter.address()),
+            );
 
-            let evm_input = env.into_input().await?;
-
-            let input = GuestInput {
-                commitment: evm_input,
-                contract_addr: to_wormhole_address(contract.address().clone()),
-                encoded_message: expected_message(),
-            };
-
-            let result = tokio::task::spawn_blocking(move || {
-                let env = ExecutorEnv::builder()
-                    .write_frame(&input.serialize().unwrap())
-                    .build()
-                    .unwrap();
-
-                default_executor().execute(env, NTT_MESSAGE_INCLUSION_ELF)
-            })
-            .await?;
+            let result = execute(input).await?;
 
             match result {
                 Ok(info) => {
@@ -128,7 +365,7 @@ mod tests {
                     let journal = Journal::abi_decode(&info.journal.bytes)?;
                     assert_eq!(
                         from_wormhole_address(journal.emitterContract),
-                        *contract.address()
+                        emitter.address()
                     );
                     assert_eq!(journal.encodedMessage, expected_message());
                 }
@@ -148,4 +385,417 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn multiple_claims() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        let messages = vec![Bytes::from("first message"), Bytes::from("second message")];
+        let sent: Vec<_> = messages.iter().map(|msg| (3, msg.clone())).collect();
+        emitter.emit_in_one_block(&sent).await?;
+
+        let input = GuestInput {
+            commitment: chain.preflight_messages(emitter.address()).await?,
+            claims: messages
+                .iter()
+                .map(|msg| MessageClaim {
+                    encoded_message: msg.clone(),
+                    contract_addr: to_wormhole_address(emitter.address()),
+                })
+                .collect(),
+            chain_id: None,
+        };
+
+        let info = execute(input).await??;
+        let journals = BatchJournal::decode(&info.journal.bytes)
+            .map_err(anyhow::Error::msg)?
+            .journals();
+        assert_eq!(journals.len(), messages.len());
+        for (journal, msg) in journals.iter().zip(&messages) {
+            assert_eq!(journal.encodedMessage, *msg);
+            assert_eq!(
+                from_wormhole_address(journal.emitterContract),
+                emitter.address()
+            );
+            assert_eq!(journal.commitment, journals[0].commitment);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn merkle_batch() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        let messages = [
+            (3, Bytes::from("first message")),
+            (4, Bytes::from("second message")),
+            (3, Bytes::from("third message")),
+        ];
+        emitter.emit_in_one_block(&messages).await?;
+        let emitter_contract = to_wormhole_address(emitter.address());
+        let input = GuestInput {
+            commitment: chain.preflight_messages(emitter.address()).await?,
+            claims: messages
+                .iter()
+                .map(|(_, msg)| MessageClaim {
+                    encoded_message: msg.clone(),
+                    contract_addr: emitter_contract,
+                })
+                .collect(),
+            chain_id: None,
+        };
+
+        let info = tokio::task::spawn_blocking(move || {
+            let env = ExecutorEnv::builder()
+                .write_frame(&input.serialize().unwrap())
+                .build()
+                .unwrap();
+            default_executor().execute(env, NTT_MESSAGE_INCLUSION_MERKLE_ELF)
+        })
+        .await??;
+        let journal = MerkleBatchJournal::abi_decode(&info.journal.bytes)?;
+        assert_eq!(journal.messageCount, 3);
+        let leaves: Vec<_> = messages
+            .iter()
+            .map(|(recipient_chain, msg)| {
+                MessageLeaf::new(msg, emitter_contract, *recipient_chain).hash()
+            })
+            .collect();
+        assert_eq!(journal.messagesRoot, merkle::root(&leaves));
+        for (index, leaf) in leaves.iter().enumerate() {
+            let branch = merkle::branch(&leaves, index);
+            assert!(merkle::verify(*leaf, &branch, journal.messagesRoot));
+        }
+
+        // A leaf claiming another recipient chain is not in the tree
+        let forged = MessageLeaf::new(&messages[0].1, emitter_contract, 5).hash();
+        assert!(!merkle::verify(
+            forged,
+            &merkle::branch(&leaves, 0),
+            journal.messagesRoot
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn versioned_journal() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        emitter.emit(3, expected_message()).await?;
+        let input = GuestInput::single(
+            chain.preflight_messages(emitter.address()).await?,
+            expected_message(),
+            to_wormhole_address(emitter.address()),
+        );
+
+        let info = tokio::task::spawn_blocking(move || {
+            let env = ExecutorEnv::builder()
+                .write_frame(&input.serialize().unwrap())
+                .build()
+                .unwrap();
+            default_executor().execute(env, NTT_MESSAGE_INCLUSION_V2_ELF)
+        })
+        .await??;
+        let journal = JournalV2::decode(&info.journal.bytes).map_err(anyhow::Error::msg)?;
+        assert_eq!(journal.version, JOURNAL_V2);
+        assert!(journal.blockNumber > 0);
+        assert_eq!(journal.encodedMessage, expected_message());
+
+        let unversioned = Journal::decode_any(&info.journal.bytes).map_err(anyhow::Error::msg)?;
+        assert_eq!(unversioned.commitment, journal.commitment);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn block_range() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        emitter.emit(3, expected_message()).await?;
+        // Each transaction is mined in a block of its own
+        for _ in 0..2 {
+            emitter.emit(3, Bytes::from("later message")).await?;
+        }
+
+        for (blocks, contained) in [(3, true), (2, false)] {
+            let (commitment, ancestors) = chain.preflight_range(emitter.address(), blocks).await?;
+            let input = RangeInput {
+                commitment,
+                ancestors,
+                claim: MessageClaim {
+                    encoded_message: expected_message(),
+                    contract_addr: to_wormhole_address(emitter.address()),
+                },
+            };
+            let result = tokio::task::spawn_blocking(move || {
+                let env = ExecutorEnv::builder()
+                    .write_frame(&input.serialize().unwrap())
+                    .build()
+                    .unwrap();
+                default_executor().execute(env, NTT_MESSAGE_INCLUSION_RANGE_ELF)
+            })
+            .await?;
+            match result {
+                Ok(info) => {
+                    assert!(
+                        contained,
+                        "Expected an error for a range of {blocks} blocks"
+                    );
+                    let journal = Journal::abi_decode(&info.journal.bytes)?;
+                    assert_eq!(journal.encodedMessage, expected_message());
+                }
+                Err(e) => assert!(
+                    !contained && e.to_string().contains("not contained in logs"),
+                    "Unexpected error for a range of {blocks} blocks: {e}"
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn manager_state() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        let manager = chain.deploy_ntt_manager().await?;
+        let message = TransceiverMessage {
+            source_ntt_manager: to_wormhole_address(manager),
+            recipient_ntt_manager: B256::repeat_byte(0x01),
+            ntt_manager_payload: Bytes::from("manager payload"),
+            transceiver_payload: Bytes::new(),
+        }
+        .encode(BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX)
+        .map_err(anyhow::Error::msg)?;
+        emitter.emit(3, message.clone()).await?;
+        let input = GuestInput::single(
+            chain
+                .preflight_manager_state(emitter.address(), manager)
+                .await?,
+            message.clone(),
+            to_wormhole_address(emitter.address()),
+        );
+
+        let info = tokio::task::spawn_blocking(move || {
+            let env = ExecutorEnv::builder()
+                .write_frame(&input.serialize().unwrap())
+                .build()
+                .unwrap();
+            default_executor().execute(env, NTT_MESSAGE_INCLUSION_STATE_ELF)
+        })
+        .await??;
+        let journal = StateJournal::abi_decode(&info.journal.bytes)?;
+        assert_eq!(journal.encodedMessage, message);
+        assert_eq!(journal.managerState.nttManager, manager);
+        assert_eq!(
+            journal.managerState.nextMessageSequence,
+            MOCK_NEXT_MESSAGE_SEQUENCE
+        );
+        assert_eq!(
+            journal.managerState.currentOutboundCapacity,
+            U256::from(MOCK_OUTBOUND_CAPACITY)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn token_backing() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        let manager = chain.deploy_ntt_manager().await?;
+        let transfer = NativeTokenTransfer {
+            amount: 100,
+            decimals: 8,
+            source_token: to_wormhole_address(manager),
+            to: B256::repeat_byte(0x02),
+            to_chain: 3,
+        };
+        let manager_message = NttManagerMessage {
+            id: B256::ZERO,
+            sender: B256::repeat_byte(0x03),
+            payload: transfer.encode(),
+        };
+        let message = TransceiverMessage {
+            source_ntt_manager: to_wormhole_address(manager),
+            recipient_ntt_manager: B256::repeat_byte(0x01),
+            ntt_manager_payload: manager_message.encode().map_err(anyhow::Error::msg)?,
+            transceiver_payload: Bytes::new(),
+        }
+        .encode(BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX)
+        .map_err(anyhow::Error::msg)?;
+        emitter.emit(3, message.clone()).await?;
+        let input = GuestInput::single(
+            chain
+                .preflight_token_backing(emitter.address(), manager)
+                .await?,
+            message.clone(),
+            to_wormhole_address(emitter.address()),
+        );
+
+        let info = tokio::task::spawn_blocking(move || {
+            let env = ExecutorEnv::builder()
+                .write_frame(&input.serialize().unwrap())
+                .build()
+                .unwrap();
+            default_executor().execute(env, NTT_MESSAGE_INCLUSION_BACKING_ELF)
+        })
+        .await??;
+        let journal = BackingJournal::abi_decode(&info.journal.bytes)?;
+        assert_eq!(journal.encodedMessage, message);
+        assert_eq!(journal.tokenBacking.token, manager);
+        assert_eq!(journal.tokenBacking.mode, 0);
+        assert_eq!(
+            journal.tokenBacking.managerBalance,
+            U256::from(MOCK_MANAGER_BALANCE)
+        );
+        assert_eq!(
+            journal.tokenBacking.totalSupply,
+            U256::from(MOCK_TOTAL_SUPPLY)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn peer_registration() -> anyhow::Result<()> {
+        let chain = TestChain::spawn();
+        let emitter = chain.deploy_emitter().await?;
+        let manager = chain.deploy_ntt_manager().await?;
+        let message_to = |recipient_ntt_manager| {
+            TransceiverMessage {
+                source_ntt_manager: to_wormhole_address(manager),
+                recipient_ntt_manager,
+                ntt_manager_payload: Bytes::from("manager payload"),
+                transceiver_payload: Bytes::new(),
+            }
+            .encode(BOUNDLESS_TRANSCEIVER_PAYLOAD_PREFIX)
+            .unwrap()
+        };
+
+        // (enable the emitter on the manager, recipient manager, expected error)
+        let cases = [
+            (true, MOCK_PEER, None),
+            (true, B256::repeat_byte(0x02), Some("not a peer")),
+            (false, MOCK_PEER, Some("not enabled")),
+        ];
+        for (registered, recipient, expected_error) in cases {
+            let transceiver = if registered {
+                emitter.address()
+            } else {
+                Address::repeat_byte(0x04)
+            };
+            chain.register_transceiver(manager, transceiver).await?;
+            let message = message_to(recipient);
+            emitter.emit(3, message.clone()).await?;
+            let input = GuestInput::single(
+                chain
+                    .preflight_peer_registration(emitter.address(), manager, 3)
+                    .await?,
+                message.clone(),
+                to_wormhole_address(emitter.address()),
+            );
+
+            let result = tokio::task::spawn_blocking(move || {
+                let env = ExecutorEnv::builder()
+                    .write_frame(&input.serialize().unwrap())
+                    .build()
+                    .unwrap();
+                default_executor().execute(env, NTT_MESSAGE_INCLUSION_PEER_ELF)
+            })
+            .await?;
+            match (result, expected_error) {
+                (Ok(info), None) => {
+                    let journal = PeerJournal::abi_decode(&info.journal.bytes)?;
+                    let registration = journal.peerRegistration;
+                    assert_eq!(journal.encodedMessage, message);
+                    assert_eq!(registration.nttManager, manager);
+                    assert_eq!(registration.recipientChain, 3);
+                    assert_eq!(registration.peerAddress, MOCK_PEER);
+                    assert_eq!(registration.peerDecimals, MOCK_PEER_DECIMALS);
+                }
+                (Err(e), Some(expected)) => assert!(
+                    e.to_string().contains(expected),
+                    "Expected error containing {expected:?}, got {e}"
+                ),
+                (result, _) => panic!(
+                    "Unexpected result {:?} for expected error {expected_error:?}",
+                    result.map(|info| info.journal.bytes)
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn synthetic_edge_cases() -> anyhow::Result<()> {
+        const EMITTER: Address = address!("0x00000000000000000000000000000000000000e1");
+        const OTHER: Address = address!("0x00000000000000000000000000000000000000e2");
+        let huge_message = Bytes::from(vec![0xab; 64 * 1024]);
+
+        let cases = [
+            (SyntheticBlock::new(), expected_message(), false),
+            (
+                SyntheticBlock::new().message(EMITTER, 3, huge_message.clone()),
+                huge_message,
+                true,
+            ),
+            (
+                SyntheticBlock::new().message(OTHER, 3, expected_message()),
+                expected_message(),
+                false,
+            ),
+        ];
+        for (i, (block, message, valid)) in cases.into_iter().enumerate() {
+            let input = GuestInput::single(
+                block.preflight_messages(EMITTER).await?,
+                message.clone(),
+                to_wormhole_address(EMITTER),
+            );
+            match execute(input).await? {
+                Ok(info) => {
+                    assert!(valid, "Test case {i}: Expected an error, but got success");
+                    let journal = Journal::abi_decode(&info.journal.bytes)?;
+                    assert_eq!(journal.encodedMessage, message);
+                }
+                Err(e) => assert!(
+                    !valid && e.to_string().contains("not contained in logs"),
+                    "Test case {i}: Unexpected error: {e}"
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn registers_loaded_guests() {
+        let register = |name: &str, image_id| {
+            GuestProgram::register(
+                name.to_string(),
+                NTT_MESSAGE_INCLUSION.elf.to_vec(),
+                image_id,
+                NTT_MESSAGE_INCLUSION.chain_id,
+                NTT_MESSAGE_INCLUSION.transceiver,
+                NTT_MESSAGE_INCLUSION.mode,
+            )
+        };
+        let release = "ntt_message_inclusion_release";
+        let registered = register(release, [7; 8]).unwrap();
+        assert_eq!(registered.name, release);
+        assert_eq!(GuestProgram::by_name(release).unwrap().image_id, [7; 8]);
+        assert!(
+            GuestProgram::all()
+                .iter()
+                .any(|guest| std::ptr::eq(*guest, registered))
+        );
+
+        // The same guest again is accepted, a different one under a taken name is not
+        assert!(std::ptr::eq(register(release, [7; 8]).unwrap(), registered));
+        assert!(register(NTT_MESSAGE_INCLUSION.name, NTT_MESSAGE_INCLUSION.image_id).is_ok());
+        assert!(register(release, [8; 8]).is_err());
+    }
 }
@@ -0,0 +1,13 @@
+// SPDX-License-Identifier: Apache-2.0
+
+pragma solidity ^0.8.0;
+
+/// @notice Payments to the relayer for proving and delivering NTT messages, which the relayer
+/// checks before relaying a message.
+interface IRelayFee {
+    /// @notice Emitted when the relay of a message is paid for
+    /// @param nttManager The NTT manager that sent the message
+    /// @param messageId The ID the NTT manager assigned the message
+    /// @param amount The fee paid, in wei of the source chain's native token
+    event RelayFeePaid(address indexed nttManager, bytes32 indexed messageId, uint256 amount);
+}